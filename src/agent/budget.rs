@@ -0,0 +1,161 @@
+//! Interceptor that enforces spend budgets (see `gateway::budgets`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use synaptic::core::{
+    ChatModel, ChatRequest, ChatResponse, ChatStream, Message, RunContext, SynapticError,
+};
+use synaptic::middleware::{Interceptor, ModelCaller, ModelRequest, ModelResponse};
+
+use crate::config::{BudgetAction, SynapseConfig};
+use crate::gateway::budgets::{BudgetDecision, BudgetGuard};
+
+use super::model::build_model_by_name;
+
+tokio::task_local! {
+    /// Canonical name of the model a downgraded call uses instead of the
+    /// agent's own.
+    static DOWNGRADE: String;
+}
+
+/// Checks the budgets before every model call of one agent run and charges
+/// the call's usage to them afterwards.
+///
+/// Over budget, a call is made anyway (`warn`), sent to a cheaper catalog
+/// model (`downgrade`), or answered with the budget's message (`refuse`).
+/// A downgraded call still goes down the rest of the interceptor stack;
+/// only the model at its end, see [`BudgetMiddleware::wrap`], is swapped.
+pub(crate) struct BudgetMiddleware {
+    guard: BudgetGuard,
+    /// The agent's own model.
+    model: String,
+    /// `downgrade_to` targets by canonical name.
+    downgrades: Arc<HashMap<String, Arc<dyn ChatModel>>>,
+}
+
+impl BudgetMiddleware {
+    pub fn new(guard: BudgetGuard, config: &SynapseConfig) -> Self {
+        let mut downgrades = HashMap::new();
+        for budget in &config.budgets {
+            let Some(ref name) = budget.downgrade_to else {
+                continue;
+            };
+            if budget.action != BudgetAction::Downgrade {
+                continue;
+            }
+            let canonical = guard.budgets.canonical(name).to_string();
+            if downgrades.contains_key(&canonical) {
+                continue;
+            }
+            match build_model_by_name(config, name) {
+                Ok(model) => {
+                    downgrades.insert(canonical, model);
+                }
+                Err(e) => {
+                    tracing::warn!(model = %name, error = %e, "Failed to build budget downgrade model")
+                }
+            }
+        }
+        Self {
+            model: guard
+                .budgets
+                .canonical(&config.model_config().model)
+                .to_string(),
+            guard,
+            downgrades: Arc::new(downgrades),
+        }
+    }
+
+    /// The agent's model, switched to the downgrade target while a
+    /// downgraded call is in flight.  Every interceptor that talks to the
+    /// model (streaming included) must be given this one.
+    pub fn wrap(&self, model: Arc<dyn ChatModel>) -> Arc<dyn ChatModel> {
+        Arc::new(BudgetedModel {
+            primary: model,
+            downgrades: self.downgrades.clone(),
+        })
+    }
+}
+
+struct BudgetedModel {
+    primary: Arc<dyn ChatModel>,
+    downgrades: Arc<HashMap<String, Arc<dyn ChatModel>>>,
+}
+
+impl BudgetedModel {
+    fn current(&self) -> &Arc<dyn ChatModel> {
+        DOWNGRADE
+            .try_with(|name| self.downgrades.get(name))
+            .ok()
+            .flatten()
+            .unwrap_or(&self.primary)
+    }
+}
+
+#[async_trait]
+impl ChatModel for BudgetedModel {
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, SynapticError> {
+        self.current().chat(request).await
+    }
+
+    fn stream_chat(&self, request: ChatRequest) -> ChatStream<'_> {
+        self.current().stream_chat(request)
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[async_trait]
+impl Interceptor for BudgetMiddleware {
+    async fn wrap_model_call(
+        &self,
+        request: ModelRequest,
+        ctx: &RunContext,
+        next: &dyn ModelCaller,
+    ) -> Result<ModelResponse, SynapticError> {
+        let budgets = &self.guard.budgets;
+        let scope = &self.guard.scope;
+        let (model, response) = match budgets.check(scope, &self.model, now_ms()) {
+            BudgetDecision::Refuse { budget, message } => {
+                tracing::warn!(budget = %budget, agent = %scope.agent, sender = ?scope.sender, "Budget exhausted, refusing model call");
+                return Ok(ModelResponse {
+                    message: Message::ai(message),
+                    usage: None,
+                });
+            }
+            BudgetDecision::Downgrade { budget, model } if self.downgrades.contains_key(&model) => {
+                tracing::info!(budget = %budget, model = %model, "Budget exhausted, downgrading model");
+                let response = DOWNGRADE
+                    .scope(model.clone(), next.call(request, ctx))
+                    .await?;
+                (model, response)
+            }
+            BudgetDecision::Downgrade { budget, model } => {
+                tracing::warn!(budget = %budget, model = %model, "Budget downgrade model unavailable, using the configured model");
+                (self.model.clone(), next.call(request, ctx).await?)
+            }
+            BudgetDecision::Warn { budget } => {
+                tracing::warn!(budget = %budget, agent = %scope.agent, sender = ?scope.sender, "Budget exhausted, continuing (warn only)");
+                (self.model.clone(), next.call(request, ctx).await?)
+            }
+            BudgetDecision::Allow => (self.model.clone(), next.call(request, ctx).await?),
+        };
+        if let Some(ref usage) = response.usage {
+            budgets.charge(
+                scope,
+                &model,
+                usage.input_tokens as u64,
+                usage.output_tokens as u64,
+                now_ms(),
+            );
+        }
+        Ok(response)
+    }
+}
@@ -81,8 +81,11 @@ pub async fn build_deep_agent(
         None,
         None,
         None,
+        None,
+        None,
         session_kind,
         &[],
+        None,
     )
     .await
 }
@@ -105,8 +108,11 @@ pub async fn build_deep_agent_with_callback(
     event_bus: Option<Arc<EventBus>>,
     plugin_registry: Option<Arc<tokio::sync::RwLock<synaptic::plugin::PluginRegistry>>>,
     channel_registry: Option<Arc<tokio::sync::RwLock<crate::gateway::messages::ChannelRegistry>>>,
+    schedule_origin: Option<crate::tools::ScheduleOrigin>,
+    memory_scope: Option<crate::memory::MemoryScope>,
     session_kind: SessionKind,
     extra_skills_dirs: &[std::path::PathBuf],
+    budget: Option<crate::gateway::budgets::BudgetGuard>,
 ) -> Result<CompiledGraph<MessageState>, SynapticError> {
     // --- Backend selection ---
     #[cfg(feature = "sandbox")]
@@ -236,13 +242,14 @@ pub async fn build_deep_agent_with_callback(
         }
     };
 
-    // Inject user profile from memory provider (if available)
-    {
-        let user_id = if channel == "web" || channel == "unknown" {
-            "default"
-        } else {
-            channel
-        };
+    // Inject the profile of the user this session is with (if any). Operator
+    // surfaces (web, CLI) share the "default" profile.
+    let profile_user = match memory_scope.as_ref() {
+        _ if channel == "web" || channel == "unknown" => Some("default".to_string()),
+        Some(scope) => crate::memory::profile::user_id(scope),
+        None => None,
+    };
+    if let Some(ref user_id) = profile_user {
         match memory_provider_arc.get_profile(user_id).await {
             Ok(Some(profile)) if !profile.is_empty() => {
                 let base = options
@@ -326,15 +333,27 @@ pub async fn build_deep_agent_with_callback(
     // --- Tools ---
     tools_setup::register_tools(
         &mut options,
+        config,
         cwd,
         mcp_tools,
         session_mgr.as_ref(),
         plugin_registry.as_ref(),
         channel_registry.as_ref(),
+        schedule_origin,
+        memory_scope.clone(),
     )
     .await;
 
     // --- Middleware stack ---
+    // Budget downgrades swap the model behind every interceptor, so the
+    // agent and its streaming interceptor get the budget's wrapper.
+    let budget = budget
+        .filter(|g| !g.budgets.is_empty())
+        .map(|guard| super::budget::BudgetMiddleware::new(guard, config));
+    let model = match budget {
+        Some(ref budget) => budget.wrap(model),
+        None => model,
+    };
     middleware_setup::setup_middleware(
         &mut options,
         config,
@@ -343,6 +362,7 @@ pub async fn build_deep_agent_with_callback(
         security_callback,
         session_overrides.as_ref(),
         cost_tracker,
+        budget,
     )
     .await;
 
@@ -370,6 +390,15 @@ pub async fn build_deep_agent_with_callback(
             )));
     }
 
+    // Plugin auto-recall recalls for this conversation
+    if let Some(scope) = memory_scope {
+        options
+            .interceptors
+            .push(Arc::new(crate::plugins::memory_recall::RecallScope::new(
+                scope,
+            )));
+    }
+
     // Inject plugin-registered interceptors
     if let Some(ref registry) = plugin_registry {
         let reg = registry.read().await;
@@ -0,0 +1,307 @@
+//! Headless agent runs — an agent from `agents.list` executed with nobody in
+//! the loop, for scheduled jobs and webhook triggers.
+//!
+//! A run gets the agent's model, tools, workspace and long-term memory,
+//! records its conversation in a session, and can post its answer to a
+//! channel through the outbound delivery queue.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use synaptic::core::{ChatModel, MemoryStore, Message};
+use synaptic::session::SessionManager;
+
+use super::runtime::{AgentRuntime, InvokeRuntime};
+use crate::config::{AgentDef, SynapseConfig};
+use crate::cron::{DeliverTarget, RunUsage};
+use crate::gateway::budgets::{BudgetGuard, BudgetScope, Budgets};
+use crate::gateway::messages::{DeliveryService, QueuedDelivery};
+use crate::memory::{LongTermMemory, MemoryScope};
+
+/// Look up `agent_id` in `agents.list`.  `None` means the built-in default
+/// agent.
+pub fn resolve_agent<'a>(
+    config: &'a SynapseConfig,
+    agent_id: &str,
+) -> Result<Option<&'a AgentDef>, String> {
+    let def = config
+        .agents
+        .as_ref()
+        .and_then(|a| a.list.iter().find(|d| d.id == agent_id));
+    match def {
+        Some(def) => Ok(Some(def)),
+        None if agent_id == "default" => Ok(None),
+        None => Err(format!("unknown agent '{}'", agent_id)),
+    }
+}
+
+/// Whether a schedule or webhook may name `agent_id`.
+pub fn agent_exists(config: &SynapseConfig, agent_id: &str) -> bool {
+    resolve_agent(config, agent_id).is_ok()
+}
+
+/// Copy of `config` with `def`'s tool allow/deny lists applied on top of the
+/// global tool policy.
+fn agent_scoped_config(config: &SynapseConfig, def: &AgentDef) -> SynapseConfig {
+    let mut scoped = config.clone();
+    if !def.tool_allow.is_empty() {
+        scoped.tool_policy.tool_allow = def.tool_allow.clone();
+    }
+    scoped
+        .tool_policy
+        .tool_deny
+        .extend(def.tool_deny.iter().cloned());
+    scoped
+}
+
+/// Sum the token usage reported on the AI messages of a finished run.
+fn usage_of(messages: &[Message]) -> Option<RunUsage> {
+    let mut usage = RunUsage::default();
+    let mut reported = false;
+    for msg in messages.iter().filter(|m| m.is_ai()) {
+        if let Some(u) = msg.response_metadata().get("usage") {
+            reported = true;
+            usage.input_tokens += u["input_tokens"].as_u64().unwrap_or(0);
+            usage.output_tokens += u["output_tokens"].as_u64().unwrap_or(0);
+        }
+    }
+    usage.total_tokens = usage.input_tokens + usage.output_tokens;
+    reported.then_some(usage)
+}
+
+/// Where a headless run's conversation is recorded.
+pub struct RunSession<'a> {
+    /// Session key, e.g. `agent:{agent}:cron:{run_id}`.
+    pub key: String,
+    /// Channel the session is attributed to ("cron", "webhook", ...).
+    pub channel: &'a str,
+    pub display_name: String,
+    /// Continue an existing session with the same key instead of always
+    /// starting a new one.
+    pub reuse: bool,
+}
+
+impl RunSession<'_> {
+    /// Find or create the session; returns its id.
+    pub async fn open(&self, sessions: &SessionManager) -> Result<String, String> {
+        if self.reuse {
+            if let Ok(list) = sessions.list_sessions().await {
+                if let Some(info) = list
+                    .into_iter()
+                    .find(|s| s.session_key.as_deref() == Some(self.key.as_str()))
+                {
+                    return Ok(info.session_id);
+                }
+            }
+        }
+        let session_id = sessions
+            .create_session()
+            .await
+            .map_err(|e| format!("failed to create session: {}", e))?;
+        if let Ok(Some(mut info)) = sessions.get_session(&session_id).await {
+            info.session_key = Some(self.key.clone());
+            info.channel = Some(self.channel.to_string());
+            info.display_name = Some(self.display_name.clone());
+            let _ = sessions.update_session(&info).await;
+        }
+        Ok(session_id)
+    }
+}
+
+/// Memories a run may see: those of the chat a job was created from (with
+/// its creator as the sender), or only agent-wide ones for runs nobody
+/// asked for from a chat, keyed by the run's own session.  Run keys are
+/// built from templates and caller input, so they are never parsed for a
+/// peer or group.
+pub fn memory_scope(
+    origin_session: Option<&str>,
+    owner: Option<String>,
+    run_session: &str,
+) -> MemoryScope {
+    match origin_session {
+        Some(key) => MemoryScope::from_session_key(key).with_sender(owner),
+        None => MemoryScope::session_only(run_session),
+    }
+}
+
+/// Budget scope of a run: the agent, plus the user who created the job and
+/// the chat its result goes to, if any.  `channel` ("cron", "webhook") is
+/// used when there is no delivery target.
+pub fn budget_guard(
+    budgets: &Arc<Budgets>,
+    agent_id: &str,
+    channel: &str,
+    owner: Option<String>,
+    deliver: Option<&DeliverTarget>,
+) -> Option<BudgetGuard> {
+    if budgets.is_empty() {
+        return None;
+    }
+    let (channel, chat) = match deliver {
+        Some(target) => (
+            target.channel.clone(),
+            target
+                .to
+                .split_once(':')
+                .map(|(_, id)| format!("{}:{}", target.channel, id)),
+        ),
+        None => (channel.to_string(), None),
+    };
+    Some(BudgetGuard {
+        budgets: budgets.clone(),
+        scope: BudgetScope {
+            agent: agent_id.to_string(),
+            sender: owner,
+            channel,
+            chat,
+        },
+    })
+}
+
+/// Run `prompt` through `agent_id` as a full deep agent, continuing the
+/// conversation in `session_id` and saving the new messages to it.
+///
+/// `model` is used for agents that don't override it, `scope` (see
+/// [`memory_scope`]) limits what the run recalls, and `budget` (see
+/// [`budget_guard`]) is checked and charged for every model call.  Returns
+/// the response text and the token usage, if the model reported any.
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    base: &SynapseConfig,
+    model: Arc<dyn ChatModel>,
+    sessions: &SessionManager,
+    session_id: &str,
+    channel: &str,
+    agent_id: &str,
+    prompt: &str,
+    kind: super::SessionKind,
+    scope: MemoryScope,
+    budget: Option<BudgetGuard>,
+) -> Result<(String, Option<RunUsage>), String> {
+    let def = resolve_agent(base, agent_id)?;
+    let model = match def.and_then(|d| d.model.as_deref()) {
+        Some(name) => super::build_model_by_name(base, name).map_err(|e| e.to_string())?,
+        None => model,
+    };
+    let scoped = def.map(|d| agent_scoped_config(base, d));
+    let config = scoped.as_ref().unwrap_or(base);
+    let cwd = match def {
+        Some(d) => crate::config::agent_workspace_dir(d),
+        None => config.workspace_dir(),
+    };
+    std::fs::create_dir_all(&cwd).map_err(|e| format!("failed to create workspace: {}", e))?;
+
+    let mut messages = Vec::new();
+    if config.memory.ltm_enabled {
+        let ltm_dir = match def {
+            Some(d) => crate::config::agent_memory_dir(&d.id),
+            None => PathBuf::from(config.sessions_dir()).join("long_term_memory"),
+        };
+        let ltm = LongTermMemory::new(ltm_dir, config.memory.clone());
+        ltm.load().await.ok();
+        let recalled = ltm
+            .recall_scoped(prompt, config.memory.ltm_recall_limit, &scope)
+            .await;
+        if !recalled.is_empty() {
+            messages.push(Message::system(format!(
+                "Relevant memories from past sessions:\n- {}",
+                recalled.join("\n- ")
+            )));
+        }
+    }
+    let memory = sessions.memory();
+    messages.extend(memory.load(session_id).await.unwrap_or_default());
+    let human = Message::human(prompt);
+    memory
+        .append(session_id, human.clone())
+        .await
+        .map_err(|e| format!("failed to save message: {}", e))?;
+    messages.push(human);
+    let initial = messages.len();
+
+    let agent = super::build_deep_agent_with_callback(
+        model,
+        config,
+        &cwd,
+        Arc::new(sessions.checkpointer()),
+        super::load_mcp_tools(config).await,
+        def.and_then(|d| d.system_prompt.as_deref()),
+        // Nobody is around to confirm risky tool calls.
+        Some(Arc::new(super::BotSafetyCallback)),
+        None,
+        None,
+        None,
+        channel,
+        Some(agent_id),
+        None,
+        None,
+        None,
+        None,
+        Some(scope),
+        kind,
+        &[],
+        budget,
+    )
+    .await
+    .map_err(|e| format!("failed to build agent: {}", e))?;
+
+    let result = InvokeRuntime
+        .run(&agent, messages)
+        .await
+        .map_err(|e| format!("agent error: {}", e))?;
+    for msg in result.messages.iter().skip(initial) {
+        memory.append(session_id, msg.clone()).await.ok();
+    }
+
+    Ok((result.response_text, usage_of(&result.messages)))
+}
+
+/// Queue `content` for `target`; returns the delivery id.
+pub async fn deliver(
+    delivery: &DeliveryService,
+    target: &DeliverTarget,
+    content: &str,
+) -> Result<String, String> {
+    let queued = QueuedDelivery::text_chunks(
+        target.channel.clone(),
+        target.to.clone(),
+        vec![content.to_string()],
+    )
+    .with_account_id(target.account_id.clone())
+    .with_thread_id(target.thread_id.clone());
+    let id = queued.id.clone();
+    match delivery.submit(queued).await {
+        Ok(true) => {}
+        Ok(false) => tracing::warn!(
+            channel = %target.channel,
+            to = %target.to,
+            "headless run result queued for retry"
+        ),
+        Err(e) => return Err(format!("failed to queue delivery: {}", e)),
+    }
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_only_see_their_origin_chat() {
+        let scope = memory_scope(
+            Some("agent:default:slack:grp:C1"),
+            Some("U1".into()),
+            "agent:default:cron:r1",
+        );
+        assert_eq!(scope.group.as_deref(), Some("slack:grp:C1"));
+        assert_eq!(scope.sender.as_deref(), Some("U1"));
+
+        let scope = memory_scope(None, None, "agent:default:webhook:deploy:r2");
+        assert!(!scope.is_unrestricted());
+        assert_eq!((scope.peer, scope.group), (None, None));
+
+        // A run key carrying caller text that looks like a DM key.
+        let scope = memory_scope(None, None, "agent:default:webhook:x:dm:ou_alice");
+        assert_eq!((scope.channel, scope.peer, scope.group), (None, None, None));
+    }
+}
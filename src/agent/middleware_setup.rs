@@ -16,6 +16,7 @@ use synaptic::secrets::SecretRegistry;
 
 use crate::config::SynapseConfig;
 
+use super::budget::BudgetMiddleware;
 use super::builder::SessionOverrides;
 use super::callbacks::AutoApproveCallback;
 use super::middleware::{build_fallback_interceptor, LoopDetectionMiddleware};
@@ -53,9 +54,10 @@ impl Interceptor for CostTrackingInterceptor {
     }
 }
 
-/// Set up the full interceptor stack on `options`, including tracing, secret masking,
-/// SSRF guard, security, tool policy, circuit breaker, loop detection, thinking,
-/// verbose, auto-compaction, deep summarization, cost tracking, OTel, and fallback.
+/// Set up the full interceptor stack on `options`, including tracing, spend budgets,
+/// secret masking, SSRF guard, security, tool policy, circuit breaker, loop detection,
+/// thinking, verbose, auto-compaction, deep summarization, cost tracking, OTel, and
+/// fallback.
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn setup_middleware(
     options: &mut DeepAgentOptions,
@@ -65,12 +67,19 @@ pub(crate) async fn setup_middleware(
     security_callback: Option<Arc<dyn SecurityConfirmationCallback>>,
     session_overrides: Option<&SessionOverrides>,
     cost_tracker: Option<Arc<CostTrackingCallback>>,
+    budget: Option<BudgetMiddleware>,
 ) {
     // Agent tracing middleware (first, so it captures the full picture)
     options
         .interceptors
         .push(Arc::new(AgentTracingMiddleware::new()));
 
+    // Spend budgets — ahead of streaming, so a refused call never reaches the model
+    if let Some(budget) = budget {
+        options.interceptors.push(Arc::new(budget));
+        tracing::info!("Budget enforcement enabled");
+    }
+
     // Streaming interceptor — if RunContext contains a StreamingOutputHandle,
     // intercepts model calls to use stream_chat() and forward token deltas.
     options
@@ -1,9 +1,11 @@
 pub mod bootstrap;
+mod budget;
 mod builder;
 pub mod callbacks;
 pub mod context_engine;
 pub mod context_resolver;
 pub mod discovery;
+pub mod headless;
 pub(crate) mod mcp;
 pub(crate) mod middleware;
 mod middleware_setup;
@@ -51,6 +51,14 @@ fn builtin_groups() -> HashMap<String, Vec<String>> {
             "memory_get".into(),
         ],
     );
+    m.insert(
+        "@scheduling".into(),
+        vec![
+            "schedule_create".into(),
+            "schedule_list".into(),
+            "schedule_cancel".into(),
+        ],
+    );
     m.insert(
         "@readonly".into(),
         vec![
@@ -5,25 +5,39 @@ use synaptic::core::Tool;
 use synaptic::deep::DeepAgentOptions;
 use synaptic::session::SessionManager;
 
-/// Register all built-in tools, MCP tools, plugin tools, and session tools on
-/// the `DeepAgentOptions`.
+use crate::config::SynapseConfig;
+use crate::memory::MemoryScope;
+use crate::tools::ScheduleOrigin;
+
+/// Register all built-in tools, MCP tools, plugin tools, session tools and,
+/// for chat turns, the schedule tools on the `DeepAgentOptions`.
+///
+/// `memory_scope` limits the memory tools to what the conversation may see;
+/// `None` leaves them unrestricted.
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn register_tools(
     options: &mut DeepAgentOptions,
+    config: &SynapseConfig,
     cwd: &Path,
     mcp_tools: Vec<Arc<dyn Tool>>,
     session_mgr: Option<&Arc<SessionManager>>,
     plugin_registry: Option<&Arc<tokio::sync::RwLock<synaptic::plugin::PluginRegistry>>>,
     channel_registry: Option<&Arc<tokio::sync::RwLock<crate::gateway::messages::ChannelRegistry>>>,
+    schedule_origin: Option<ScheduleOrigin>,
+    memory_scope: Option<MemoryScope>,
 ) {
     // Add MCP tools
     options.tools.extend(mcp_tools);
 
-    // Add plugin-registered tools
+    // Add plugin-registered tools, binding memory tools to the conversation
     if let Some(registry) = plugin_registry {
         let reg = registry.read().await;
         for tool in reg.tools() {
-            options.tools.push(tool.clone());
+            let tool = match memory_scope {
+                Some(ref scope) => crate::tools::scope_memory_tool(tool.clone(), scope),
+                None => tool.clone(),
+            };
+            options.tools.push(tool);
         }
         tracing::debug!(count = reg.tools().len(), "Plugin tools merged into agent");
     }
@@ -73,6 +87,15 @@ pub(crate) async fn register_tools(
             .push(crate::tools::SessionsSpawnTool::new(mgr.clone()));
     }
 
+    // Add schedule tools when the turn comes from a chat results can be
+    // delivered back to
+    if let Some(origin) = schedule_origin {
+        options.tools.extend(crate::tools::schedule_tools(
+            origin,
+            config.schedule_tool.clone(),
+        ));
+    }
+
     // Add platform action tool (channel registry wired when running as gateway)
     {
         let tool = if let Some(reg) = channel_registry {
@@ -0,0 +1,621 @@
+//! Tamper-evident audit log.
+//!
+//! An append-only JSONL file in which every entry carries the SHA-256 hash of
+//! the entry before it, so editing, deleting or reordering lines breaks the
+//! chain.  `synapse audit verify` and the `audit.verify` RPC walk the chain
+//! and report the first entry that no longer matches.
+//!
+//! Entries record what agents executed (every tool call, from the event bus)
+//! and privileged operator actions (RPC methods outside the chat and read
+//! scopes, mutating dashboard requests, device pairing, web terminal
+//! sessions, spend budget alerts).  Arguments are redacted: values under keys that look like
+//! credentials are never written.
+
+// Only the CLI's verify/query are used without the gateway.
+#![cfg_attr(not(feature = "web"), allow(dead_code))]
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use synaptic::core::SynapticError;
+use synaptic::events::{Event, EventAction, EventFilter, EventKind, EventSubscriber};
+
+use crate::config::AuditConfig;
+
+/// `prev_hash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+/// Longest string kept in entry details; longer ones are cut.
+const MAX_DETAIL_CHARS: usize = 2000;
+/// Key fragments whose values are replaced by `[redacted]`.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+    "credential",
+];
+
+/// One audit log line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the chain, from 1.
+    pub seq: u64,
+    /// When it happened (ms since epoch).
+    pub ts: u64,
+    /// `tool`, `rpc`, `http`, `pairing`, ...
+    pub category: String,
+    /// Tool name, RPC method or request line.
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_key: Option<String>,
+    /// `ok`, `error: ...`, `forbidden: ...`; absent for actions not yet finished.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub detail: Value,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Hash of the entry with `hash` itself left empty.
+    fn compute_hash(&self) -> String {
+        let mut unhashed = self.clone();
+        unhashed.hash = String::new();
+        let bytes = serde_json::to_vec(&unhashed).unwrap_or_default();
+        format!("{:x}", Sha256::digest(&bytes))
+    }
+}
+
+/// Something to record; [`AuditLog::record`] adds the chain fields.
+#[derive(Debug, Clone, Default)]
+pub struct AuditRecord {
+    pub category: String,
+    pub action: String,
+    pub actor: Option<String>,
+    pub session_key: Option<String>,
+    pub outcome: Option<String>,
+    pub detail: Value,
+}
+
+impl AuditRecord {
+    pub fn new(category: impl Into<String>, action: impl Into<String>) -> Self {
+        Self {
+            category: category.into(),
+            action: action.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    pub fn with_session(mut self, session_key: Option<String>) -> Self {
+        self.session_key = session_key;
+        self
+    }
+
+    pub fn with_outcome(mut self, outcome: impl Into<String>) -> Self {
+        self.outcome = Some(outcome.into());
+        self
+    }
+
+    /// Attach details; credentials are redacted and long strings cut.
+    pub fn with_detail(mut self, detail: Value) -> Self {
+        self.detail = redact(detail);
+        self
+    }
+}
+
+/// Filters for [`AuditLog::query`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Exact action, or a prefix ending in `*` (e.g. `config.*`).
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub actor: Option<String>,
+    #[serde(default)]
+    pub session_key: Option<String>,
+    /// Only entries at or after this time (ms since epoch).
+    #[serde(default)]
+    pub since: Option<u64>,
+    #[serde(default)]
+    pub until: Option<u64>,
+    /// Newest entries returned (default 100).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, e: &AuditEntry) -> bool {
+        let action_ok = match self.action.as_deref() {
+            None => true,
+            Some(a) => match a.strip_suffix('*') {
+                Some(prefix) => e.action.starts_with(prefix),
+                None => e.action == a,
+            },
+        };
+        action_ok
+            && self.category.as_ref().is_none_or(|c| &e.category == c)
+            && self
+                .actor
+                .as_ref()
+                .is_none_or(|a| e.actor.as_ref() == Some(a))
+            && self
+                .session_key
+                .as_ref()
+                .is_none_or(|k| e.session_key.as_ref() == Some(k))
+            && self.since.is_none_or(|t| e.ts >= t)
+            && self.until.is_none_or(|t| e.ts <= t)
+    }
+}
+
+/// Result of checking a log's hash chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VerifyReport {
+    /// Entries checked before stopping.
+    pub entries: u64,
+    pub ok: bool,
+    /// Line (from 1) of the first broken entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub broken_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Hash of the last entry; keep it elsewhere to detect truncation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+}
+
+/// Hash-chained audit log file.
+///
+/// The gateway and CLI commands may append to the same file, so every
+/// append locks the file and continues from the entry last on disk.  The
+/// file I/O runs on a writer thread of its own, so recording from async code
+/// never blocks a runtime worker.
+pub struct AuditLog {
+    /// `None` when auditing is disabled.
+    path: Option<PathBuf>,
+    /// Queue of the writer thread, started by the first record.
+    writer: Mutex<Option<mpsc::Sender<WriteJob>>>,
+}
+
+/// Work for the writer thread, handled in order.
+enum WriteJob {
+    /// A record and when it was made (ms since epoch).
+    Append(AuditRecord, u64),
+    /// Signalled once every earlier job is done.
+    Flush(mpsc::SyncSender<()>),
+}
+
+impl AuditLog {
+    /// Open (or create) the log at `path`, continuing its chain.
+    pub fn open(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            writer: Mutex::new(None),
+        }
+    }
+
+    /// A log that records nothing.
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            writer: Mutex::new(None),
+        }
+    }
+
+    pub fn from_config(config: &AuditConfig) -> Self {
+        if config.enabled {
+            Self::open(config.path())
+        } else {
+            Self::disabled()
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Queue `record` for appending to the chain.  Failures are logged, not
+    /// returned, so auditing never breaks the action being audited.
+    pub fn record(&self, record: AuditRecord) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = writer.get_or_insert_with(|| spawn_writer(path.clone()));
+        if queue.send(WriteJob::Append(record, now_ms())).is_err() {
+            tracing::error!(path = %path.display(), "audit writer is gone, entry dropped");
+        }
+    }
+
+    /// Block until every record made so far is on disk.
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::sync_channel(1);
+        let queued = self
+            .writer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .is_some_and(|queue| queue.send(WriteJob::Flush(done)).is_ok());
+        if queued {
+            let _ = wait.recv();
+        }
+    }
+
+    /// Entries matching `query`, oldest first.
+    pub fn query(&self, query: &AuditQuery) -> Vec<AuditEntry> {
+        let Some(text) = self
+            .path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+        else {
+            return Vec::new();
+        };
+        let mut entries: Vec<AuditEntry> = text
+            .lines()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .filter(|e| query.matches(e))
+            .collect();
+        let limit = query.limit.unwrap_or(100);
+        if entries.len() > limit {
+            entries.drain(..entries.len() - limit);
+        }
+        entries
+    }
+
+    /// Verify this log's chain.
+    pub fn verify(&self) -> VerifyReport {
+        match self.path {
+            Some(ref path) => verify(path),
+            None => VerifyReport {
+                ok: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Start the thread that appends the queued records to `path`; it exits
+/// once the log is dropped.
+fn spawn_writer(path: PathBuf) -> mpsc::Sender<WriteJob> {
+    let (queue, jobs) = mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name("audit-writer".into())
+        .spawn(move || {
+            for job in jobs {
+                match job {
+                    WriteJob::Append(record, ts) => {
+                        if let Err(e) = append(&path, record, ts) {
+                            tracing::error!(
+                                path = %path.display(),
+                                error = %e,
+                                "failed to write audit entry"
+                            )
+                        }
+                    }
+                    WriteJob::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::error!(error = %e, "failed to start the audit writer");
+    }
+    queue
+}
+
+/// Append one entry under the file lock, chained to the last one on disk.
+fn append(path: &Path, record: AuditRecord, ts: u64) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).read(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // Held until `file` is dropped.
+    file.lock()?;
+    let (seq, prev_hash) = read_head(&mut file)?;
+    let mut entry = AuditEntry {
+        seq: seq + 1,
+        ts,
+        category: record.category,
+        action: record.action,
+        actor: record.actor,
+        session_key: record.session_key,
+        outcome: record.outcome,
+        detail: record.detail,
+        prev_hash,
+        hash: String::new(),
+    };
+    entry.hash = entry.compute_hash();
+    writeln!(file, "{}", serde_json::to_string(&entry)?)
+}
+
+/// Sequence number and hash of the last entry in `file`, read from its tail.
+fn read_head(file: &mut std::fs::File) -> std::io::Result<(u64, String)> {
+    let len = file.seek(SeekFrom::End(0))?;
+    let mut window = 8 * 1024u64;
+    loop {
+        let start = len.saturating_sub(window);
+        file.seek(SeekFrom::Start(start))?;
+        let mut tail = Vec::new();
+        file.by_ref().take(len - start).read_to_end(&mut tail)?;
+        let text = String::from_utf8_lossy(&tail);
+        let mut lines = text.lines().rev().filter(|l| !l.trim().is_empty());
+        match lines.next() {
+            // Unless the window reaches the start of the file, its first
+            // line may be cut, so the last line is only whole if another
+            // line precedes it.
+            Some(line) if start == 0 || lines.next().is_some() => {
+                let entry: AuditEntry = serde_json::from_str(line).map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("unparsable last entry: {}", e),
+                    )
+                })?;
+                return Ok((entry.seq, entry.hash));
+            }
+            None if start == 0 => return Ok((0, GENESIS_HASH.to_string())),
+            _ => window *= 2,
+        }
+    }
+}
+
+/// Walk the hash chain of the log at `path`.
+pub fn verify(path: &Path) -> VerifyReport {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return VerifyReport {
+                ok: true,
+                ..Default::default()
+            }
+        }
+        Err(e) => {
+            return VerifyReport {
+                error: Some(e.to_string()),
+                ..Default::default()
+            }
+        }
+    };
+    let mut report = VerifyReport::default();
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i as u64 + 1;
+        let broken = |report: &mut VerifyReport, error: String| {
+            report.broken_at = Some(line_no);
+            report.error = Some(error);
+        };
+        let entry: AuditEntry = match serde_json::from_str(line) {
+            Ok(e) => e,
+            Err(e) => {
+                broken(&mut report, format!("unparsable entry: {}", e));
+                return report;
+            }
+        };
+        if entry.seq != line_no {
+            broken(
+                &mut report,
+                format!("sequence {} where {} was expected", entry.seq, line_no),
+            );
+            return report;
+        }
+        if entry.prev_hash != prev_hash {
+            broken(&mut report, "previous hash does not match".into());
+            return report;
+        }
+        if entry.compute_hash() != entry.hash {
+            broken(&mut report, "entry hash does not match its contents".into());
+            return report;
+        }
+        prev_hash = entry.hash;
+        report.entries = line_no;
+    }
+    report.ok = true;
+    report.head = (report.entries > 0).then_some(prev_hash);
+    report
+}
+
+/// Redact credential-like keys and cut long strings.
+pub fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let lower = k.to_ascii_lowercase();
+                    if SECRET_KEYS.iter().any(|s| lower.contains(s)) && !v.is_null() {
+                        (k, Value::String("[redacted]".into()))
+                    } else {
+                        (k, redact(v))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact).collect()),
+        Value::String(s) => match s.char_indices().nth(MAX_DETAIL_CHARS) {
+            Some((end, _)) => Value::String(format!("{}...", &s[..end])),
+            None => Value::String(s),
+        },
+        other => other,
+    }
+}
+
+/// Records every tool call the agents make.
+pub struct AuditSubscriber {
+    log: Arc<AuditLog>,
+}
+
+impl AuditSubscriber {
+    pub fn new(log: Arc<AuditLog>) -> Self {
+        Self { log }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for AuditSubscriber {
+    fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![EventFilter::AnyOf(vec![
+            EventKind::BeforeToolCall,
+            EventKind::AfterToolCall,
+        ])]
+    }
+
+    async fn handle(&self, event: &mut Event) -> Result<EventAction, SynapticError> {
+        let payload = &event.payload;
+        let tool = payload["tool_name"]
+            .as_str()
+            .or_else(|| payload["tool"].as_str())
+            .unwrap_or("?");
+        let session_key = payload["session_key"].as_str().map(String::from);
+        let record = AuditRecord::new("tool", tool).with_session(session_key);
+        let record = match event.kind {
+            EventKind::BeforeToolCall => {
+                let args = if payload["arguments"].is_null() {
+                    payload["args"].clone()
+                } else {
+                    payload["arguments"].clone()
+                };
+                record.with_detail(serde_json::json!({ "phase": "call", "args": args }))
+            }
+            _ => match payload["error"].as_str() {
+                Some(error) => record
+                    .with_outcome(format!("error: {}", error))
+                    .with_detail(serde_json::json!({ "phase": "result" })),
+                None => record
+                    .with_outcome("ok")
+                    .with_detail(serde_json::json!({ "phase": "result" })),
+            },
+        };
+        let record = match event.metadata.request_id.clone() {
+            Some(request_id) => record.with_actor(format!("agent:{}", request_id)),
+            None => record.with_actor("agent"),
+        };
+        self.log.record(record);
+        Ok(EventAction::Continue)
+    }
+
+    fn name(&self) -> &str {
+        "AuditSubscriber"
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        (dir, path)
+    }
+
+    #[test]
+    fn chain_survives_reopen_and_detects_tampering() {
+        let (_dir, path) = temp_log();
+        let log = AuditLog::open(path.clone());
+        log.record(
+            AuditRecord::new("tool", "bash")
+                .with_detail(serde_json::json!({"args": {"command": "ls"}})),
+        );
+        log.record(AuditRecord::new("rpc", "config.set").with_outcome("ok"));
+        log.flush();
+        // A new handle continues the chain.
+        let reopened = AuditLog::open(path.clone());
+        reopened.record(AuditRecord::new("pairing", "device.approve"));
+        reopened.flush();
+
+        let report = verify(&path);
+        assert!(report.ok, "{:?}", report);
+        assert_eq!(report.entries, 3);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replace("config.set", "config.get")).unwrap();
+        let report = verify(&path);
+        assert!(!report.ok);
+        assert_eq!(report.broken_at, Some(2));
+
+        let lines: Vec<&str> = text.lines().collect();
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(verify(&path).broken_at, Some(2));
+    }
+
+    #[test]
+    fn handles_share_one_chain() {
+        let (_dir, path) = temp_log();
+        let (a, b) = (AuditLog::open(path.clone()), AuditLog::open(path.clone()));
+        for i in 0..3 {
+            a.record(AuditRecord::new("rpc", "config.set").with_detail(serde_json::json!(i)));
+            b.record(AuditRecord::new("cli", "audit.verify"));
+        }
+        // A last entry longer than the first tail window.
+        b.record(AuditRecord::new("cli", "audit.verify"));
+        b.flush();
+        a.record(
+            AuditRecord::new("tool", "bash")
+                .with_detail(serde_json::json!(vec!["x".repeat(1500); 8])),
+        );
+        a.record(AuditRecord::new("cli", "audit.verify"));
+        a.flush();
+        let report = verify(&path);
+        assert!(report.ok, "{:?}", report);
+        assert_eq!(report.entries, 9);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn redacts_credentials_and_filters() {
+        let detail = redact(serde_json::json!({
+            "api_key": "sk-123",
+            "nested": {"Bot_Token": "xoxb", "name": "bot"},
+        }));
+        assert_eq!(detail["api_key"], "[redacted]");
+        assert_eq!(detail["nested"]["Bot_Token"], "[redacted]");
+        assert_eq!(detail["nested"]["name"], "bot");
+
+        let (_dir, path) = temp_log();
+        let log = AuditLog::open(path.clone());
+        log.record(AuditRecord::new("rpc", "config.set").with_actor("rpc:cli"));
+        log.record(AuditRecord::new("rpc", "config.apply").with_actor("rpc:web"));
+        log.record(AuditRecord::new("tool", "apply_patch"));
+        log.flush();
+        let q = |action: &str| AuditQuery {
+            action: Some(action.into()),
+            ..Default::default()
+        };
+        assert_eq!(log.query(&q("config.*")).len(), 2);
+        assert_eq!(log.query(&q("apply_patch"))[0].seq, 3);
+    }
+}
@@ -13,6 +13,7 @@ use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
 
 use tracing;
 
@@ -21,7 +22,11 @@ use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{BotAllowlist, SynapseConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -29,11 +34,73 @@ type HmacSha256 = Hmac<Sha256>;
 #[allow(dead_code)]
 struct AppState {
     agent_session: Arc<AgentSession>,
+    delivery: Arc<DeliveryService>,
     allowlist: BotAllowlist,
     app_secret: String,
     robot_code: Option<String>,
 }
 
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the DingTalk channel.
+///
+/// Replies go to the conversation's session webhook, so delivery targets are
+/// `webhook:<url>`.
+pub struct DingTalkSender {
+    pub client: reqwest::Client,
+}
+
+#[async_trait]
+impl ChannelSender for DingTalkSender {
+    fn channel_id(&self) -> &str {
+        "dingtalk"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let webhook_url = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("webhook:"))
+            .ok_or("missing session webhook in delivery target (expected 'webhook:<url>')")?;
+
+        let body = serde_json::json!({
+            "msgtype": "text",
+            "text": {
+                "content": content,
+            }
+        });
+        let resp: serde_json::Value = self
+            .client
+            .post(webhook_url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let errcode = resp.get("errcode").and_then(|v| v.as_i64()).unwrap_or(0);
+        if errcode != 0 {
+            let err = resp
+                .get("errmsg")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown error");
+            return Err(format!("session webhook failed ({}): {}", errcode, err).into());
+        }
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
+
 /// DingTalk callback event payload (subset of fields we care about).
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -200,6 +267,7 @@ async fn handle_callback(
 
     // Process in background so we respond to DingTalk quickly
     let session = state.agent_session.clone();
+    let delivery = state.delivery.clone();
     tokio::spawn(async move {
         let channel_info = ChannelInfo {
             platform: "dingtalk".into(),
@@ -222,35 +290,20 @@ async fn handle_callback(
             chat_info,
         );
         msg.finalize();
-        match session.handle_message(msg, RunContext::default()).await {
-            Ok(reply) => {
-                let client = reqwest::Client::new();
-                let chunks = formatter::format_for_channel(&reply.content, "dingtalk", 20000);
-                for chunk in chunks {
-                    let body = serde_json::json!({
-                        "msgtype": "text",
-                        "text": {
-                            "content": chunk,
-                        }
-                    });
-
-                    if let Err(e) = client.post(&webhook_url).json(&body).send().await {
-                        tracing::error!(channel = "dingtalk", error = %e, "failed to send reply");
-                    }
-                }
-            }
+        let chunks = match session.handle_message(msg, RunContext::default()).await {
+            Ok(reply) => formatter::format_for_channel(&reply.content, "dingtalk", 20000),
             Err(e) => {
                 tracing::error!(channel = "dingtalk", error = %e, "handler error");
-                // Try to send error message back
-                let client = reqwest::Client::new();
-                let body = serde_json::json!({
-                    "msgtype": "text",
-                    "text": {
-                        "content": format!("Error: {}", e),
-                    }
-                });
-                let _ = client.post(&webhook_url).json(&body).send().await;
+                // Send the error back so the user isn't left waiting
+                vec![format!("Error: {}", e)]
             }
+        };
+        let queued =
+            QueuedDelivery::text_chunks("dingtalk", format!("webhook:{}", webhook_url), chunks);
+        match delivery.submit(queued).await {
+            Ok(true) => {}
+            Ok(false) => tracing::warn!(channel = "dingtalk", "reply queued for retry"),
+            Err(e) => tracing::error!(channel = "dingtalk", error = %e, "failed to queue reply"),
         }
     });
 
@@ -258,7 +311,14 @@ async fn handle_callback(
 }
 
 /// Run the DingTalk bot adapter.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let dt_configs: Vec<crate::config::DingTalkBotConfig> = config.channel_configs("dingtalk");
     let dt_config = dt_configs
         .first()
@@ -288,8 +348,16 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
         );
     }
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(DingTalkSender {
+            client: reqwest::Client::new(),
+        }))
+        .await;
+
     let state = Arc::new(AppState {
         agent_session,
+        delivery,
         allowlist,
         app_secret,
         robot_code: dt_config.robot_code.clone(),
@@ -24,7 +24,10 @@ use crate::channels::reactions;
 use crate::config::bots::resolve_secret;
 use crate::config::SynapseConfig;
 use crate::gateway::messages::sender::{ChannelSender, SendResult};
-use crate::gateway::messages::{Attachment, ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::{
+    Attachment, ChannelInfo, ChatInfo, DeliveryService, InboundMessage, Interaction,
+    QueuedDelivery, SenderInfo,
+};
 use crate::gateway::presence::now_ms;
 use synaptic::logging;
 
@@ -33,7 +36,6 @@ use synaptic::logging;
 // ---------------------------------------------------------------------------
 
 /// Outbound sender for the Discord channel.
-#[allow(dead_code)]
 pub struct DiscordSender {
     /// HTTP client for making Discord API calls.
     pub client: reqwest::Client,
@@ -59,6 +61,8 @@ impl ChannelSender for DiscordSender {
             .and_then(|s| s.strip_prefix("channel:"))
             .ok_or("missing or invalid channel_id in delivery target (expected 'channel:<id>')")?;
 
+        // Queued text arrives split to Discord's limit; this only splits what
+        // rendering pushed over it.
         let chunks = formatter::format_for_channel(content, "discord", 2000);
         let mut last_message_id: Option<String> = None;
         for chunk in chunks {
@@ -72,6 +76,7 @@ impl ChannelSender for DiscordSender {
                 .json(&json!({"content": chunk}))
                 .send()
                 .await?
+                .error_for_status()?
                 .json()
                 .await?;
             if let Some(msg_id) = resp.get("id").and_then(|v| v.as_str()) {
@@ -87,7 +92,14 @@ impl ChannelSender for DiscordSender {
 }
 
 /// Run the Discord bot.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let discord_configs: Vec<crate::config::DiscordBotConfig> = config.channel_configs("discord");
     let discord_config = discord_configs
         .first()
@@ -122,6 +134,14 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
     // Get gateway URL
     let client = reqwest::Client::new();
+
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(DiscordSender {
+            client: client.clone(),
+            token: token.clone(),
+        }))
+        .await;
     let gateway_resp: serde_json::Value = client
         .get("https://discord.com/api/v10/gateway/bot")
         .header("Authorization", format!("Bot {}", token))
@@ -250,6 +270,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
                 // Use AgentSession with persistent history + deep agent
                 let session = agent_session.clone();
+                let delivery = delivery.clone();
                 let http = http_client.clone();
                 let tok = token_clone.clone();
                 let sender_id = author_id.to_string();
@@ -264,6 +285,25 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
                     let _guard = span.enter();
                     tracing::info!("processing discord message");
 
+                    // Replies answering an interactive prompt (e.g. "YES <code>")
+                    // don't go to the agent.
+                    let interaction = Interaction {
+                        channel: "discord".into(),
+                        user_id: Some(sender_id.clone()),
+                        value: content.clone(),
+                    };
+                    if let Some(ack) = delivery.handle_interaction(&interaction).await {
+                        let queued = QueuedDelivery::text_chunks(
+                            "discord",
+                            format!("channel:{}", channel_id),
+                            vec![ack],
+                        );
+                        if let Err(e) = delivery.submit(queued).await {
+                            tracing::error!(channel = "discord", error = %e, "failed to queue reply");
+                        }
+                        return;
+                    }
+
                     // React with eyes to indicate processing
                     reactions::discord_react(&tok, &channel_id, &message_id, "\u{1f440}").await;
 
@@ -304,23 +344,31 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
                     match session.handle_message(msg, RunContext::default()).await {
                         Ok(reply) => {
-                            // Split long replies into chunks (Discord 2000 char limit)
-                            let chunks =
-                                formatter::format_for_channel(&reply.content, "discord", 2000);
-                            for chunk in chunks {
-                                let _ = http
-                                    .post(format!(
-                                        "https://discord.com/api/v10/channels/{}/messages",
-                                        channel_id
-                                    ))
-                                    .header("Authorization", format!("Bot {}", tok))
-                                    .json(&json!({"content": chunk}))
-                                    .send()
+                            // The delivery queue splits long replies (Discord 2000
+                            // char limit) and retries any chunk Discord rejects.
+                            let queued = QueuedDelivery::text_chunks(
+                                "discord",
+                                format!("channel:{}", channel_id),
+                                vec![reply.content],
+                            );
+                            match delivery.submit(queued).await {
+                                Ok(true) => {
+                                    // React with checkmark on success
+                                    reactions::discord_react(
+                                        &tok,
+                                        &channel_id,
+                                        &message_id,
+                                        "\u{2705}",
+                                    )
                                     .await;
+                                }
+                                Ok(false) => {
+                                    tracing::warn!(channel = "discord", "reply queued for retry");
+                                }
+                                Err(e) => {
+                                    tracing::error!(channel = "discord", error = %e, "failed to queue reply");
+                                }
                             }
-                            // React with checkmark on success
-                            reactions::discord_react(&tok, &channel_id, &message_id, "\u{2705}")
-                                .await;
                         }
                         Err(e) => {
                             eprintln!("Discord: handler error: {}", e);
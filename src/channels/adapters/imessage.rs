@@ -10,17 +10,78 @@ use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::SynapseConfig;
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the iMessage channel (BlueBubbles bridge).
+pub struct IMessageSender {
+    pub client: reqwest::Client,
+    /// BlueBubbles server URL, without a trailing slash.
+    pub api_url: String,
+    pub password: String,
+}
+
+#[async_trait]
+impl ChannelSender for IMessageSender {
+    fn channel_id(&self) -> &str {
+        "imessage"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let chat_guid = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("chat:"))
+            .ok_or("missing chat GUID in delivery target (expected 'chat:<guid>')")?;
+
+        let body = serde_json::json!({
+            "chatGuid": chat_guid,
+            "message": content,
+            "password": self.password,
+        });
+        self.client
+            .post(format!("{}/api/v1/message/text", self.api_url))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
 
 /// Run the iMessage bot adapter using the BlueBubbles REST API bridge.
 ///
 /// Polls `GET /api/v1/message?limit=10&offset=0&after=<timestamp>&password=<pw>`
 /// for incoming messages and replies via `POST /api/v1/message/text`.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let imessage_configs: Vec<crate::config::IMessageBotConfig> =
         config.channel_configs("imessage");
     let imessage_config = imessage_configs
@@ -55,6 +116,15 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
     let client = reqwest::Client::new();
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(IMessageSender {
+            client: client.clone(),
+            api_url: api_url.clone(),
+            password: password.clone(),
+        }))
+        .await;
+
     // Track the last poll timestamp in milliseconds (Unix epoch).
     // BlueBubbles uses millisecond timestamps for the `after` parameter.
     let mut last_timestamp_ms: u64 = SystemTime::now()
@@ -149,9 +219,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
             // Process message in background
             let session = agent_session.clone();
-            let http = client.clone();
-            let send_url = format!("{}/api/v1/message/text", api_url);
-            let pw = password.clone();
+            let delivery = delivery.clone();
             let reply_chat_guid = chat_guid.clone();
             let session_key = sender.clone();
 
@@ -184,14 +252,18 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
                     Ok(reply) => {
                         let chunks =
                             formatter::format_for_channel(&reply.content, "imessage", 10000);
-                        for chunk in chunks {
-                            let body = serde_json::json!({
-                                "chatGuid": reply_chat_guid,
-                                "message": chunk,
-                                "password": pw,
-                            });
-                            if let Err(e) = http.post(&send_url).json(&body).send().await {
-                                tracing::error!(channel = "imessage", error = %e, "send error");
+                        let queued = QueuedDelivery::text_chunks(
+                            "imessage",
+                            format!("chat:{}", reply_chat_guid),
+                            chunks,
+                        );
+                        match delivery.submit(queued).await {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                tracing::warn!(channel = "imessage", "reply queued for retry");
+                            }
+                            Err(e) => {
+                                tracing::error!(channel = "imessage", error = %e, "failed to queue reply");
                             }
                         }
                     }
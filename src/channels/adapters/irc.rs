@@ -1,24 +1,88 @@
 use std::sync::atomic::{AtomicU8, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
+use tokio::sync::mpsc::UnboundedSender;
 
 use crate::agent;
 use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{BotAllowlist, IrcBotConfig, SynapseConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the IRC channel.
+///
+/// Writes through the current connection; while the adapter is reconnecting
+/// sends fail and the delivery queue retries them.
+#[derive(Default)]
+pub struct IrcSender {
+    /// Line sink of the registered connection, if any.
+    conn: Mutex<Option<UnboundedSender<String>>>,
+}
+
+#[async_trait]
+impl ChannelSender for IrcSender {
+    fn channel_id(&self) -> &str {
+        "irc"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let reply_target = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("target:"))
+            .ok_or("missing IRC target in delivery target (expected 'target:<channel|nick>')")?;
+        let conn = self
+            .conn
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("not connected to the IRC server")?;
+
+        // Send line-by-line in case the agent included embedded newlines.
+        for irc_line in content.lines() {
+            conn.send(format!("PRIVMSG {} :{}", reply_target, irc_line))
+                .map_err(|_| "IRC connection closed")?;
+        }
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
 
 /// Run the IRC bot adapter using raw TCP.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so replies made while disconnected are sent after reconnecting.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let irc_configs: Vec<crate::config::IrcBotConfig> = config.channel_configs("irc");
     let irc_config = irc_configs
         .first()
@@ -38,10 +102,23 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
         );
     }
 
+    let sender = Arc::new(IrcSender::default());
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery.register_sender(sender.clone()).await;
+
     tracing::info!(channel = "irc", "adapter started (TCP mode)");
 
     loop {
-        match run_tcp(irc_config, agent_session.clone(), &allowlist).await {
+        let result = run_tcp(
+            irc_config,
+            agent_session.clone(),
+            &allowlist,
+            &sender,
+            &delivery,
+        )
+        .await;
+        *sender.conn.lock().unwrap() = None;
+        match result {
             Ok(()) => break,
             Err(e) => {
                 tracing::warn!(channel = "irc", error = %e, "connection error, reconnecting in 10s");
@@ -62,6 +139,8 @@ async fn run_tcp(
     irc_config: &IrcBotConfig,
     agent_session: Arc<AgentSession>,
     allowlist: &BotAllowlist,
+    sender: &IrcSender,
+    delivery: &Arc<DeliveryService>,
 ) -> crate::error::Result<()> {
     let port = irc_config.port.unwrap_or(6667);
     let addr = format!("{}:{}", irc_config.server, port);
@@ -134,6 +213,8 @@ async fn run_tcp(
                     send(format!("JOIN {}", channel));
                     tracing::info!(channel = "irc", irc_channel = %channel, "joined channel");
                 }
+                // Replies queued while disconnected go out on their next retry.
+                *sender.conn.lock().unwrap() = Some(tx.clone());
             }
             continue;
         }
@@ -175,7 +256,7 @@ async fn run_tcp(
 
         // Spawn agent processing in the background.
         let session = agent_session.clone();
-        let tx_clone = tx.clone();
+        let delivery = delivery.clone();
         // Use reply_target as the per-conversation session key.
         let session_key = reply_target.clone();
 
@@ -203,13 +284,18 @@ async fn run_tcp(
             msg.finalize();
             match session.handle_message(msg, RunContext::default()).await {
                 Ok(reply) => {
+                    // Each chunk is ≤400 chars; the sender splits embedded newlines.
                     let chunks = formatter::format_for_channel(&reply.content, "irc", 400);
-                    for chunk in chunks {
-                        // Each chunk is already ≤400 chars; send line-by-line
-                        // in case the agent included embedded newlines.
-                        for irc_line in chunk.lines() {
-                            let _ =
-                                tx_clone.send(format!("PRIVMSG {} :{}", reply_target, irc_line));
+                    let queued = QueuedDelivery::text_chunks(
+                        "irc",
+                        format!("target:{}", reply_target),
+                        chunks,
+                    );
+                    match delivery.submit(queued).await {
+                        Ok(true) => {}
+                        Ok(false) => tracing::warn!(channel = "irc", "reply queued for retry"),
+                        Err(e) => {
+                            tracing::error!(channel = "irc", error = %e, "failed to queue reply")
                         }
                     }
                 }
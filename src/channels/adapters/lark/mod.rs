@@ -25,7 +25,10 @@ use crate::channels::handler::{AgentSession, StreamingOutput};
 use crate::config::bots::{DmPolicy, GroupPolicy, GroupSessionScope, LarkRenderMode};
 use crate::config::BotAllowlist;
 use crate::gateway::messages::sender::{ChannelSender, SendResult};
-use crate::gateway::messages::{Attachment, ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::{
+    Attachment, ChannelInfo, ChatInfo, DeliveryService, InboundMessage, Interaction,
+    QueuedDelivery, SenderInfo,
+};
 use crate::gateway::presence::now_ms;
 
 use policy::{compute_session_key, strip_bot_mention};
@@ -38,7 +41,6 @@ pub use setup::run;
 // ---------------------------------------------------------------------------
 
 /// Outbound sender for the Lark channel.
-#[allow(dead_code)]
 pub struct LarkSender {
     /// Lark bot client for making API calls.
     pub client: LarkBotClient,
@@ -78,18 +80,61 @@ impl ChannelSender for LarkSender {
         &self,
         target: &DeliveryContext,
         content: &str,
-        _meta: Option<&serde_json::Value>,
+        meta: Option<&serde_json::Value>,
     ) -> crate::error::Result<SendResult> {
-        let chat_id = target
-            .to
-            .as_deref()
-            .and_then(|s| s.strip_prefix("chat:"))
-            .ok_or("missing or invalid chat_id in delivery target (expected 'chat:<id>')")?;
+        // Replies to a specific message (queued by the handler) carry the
+        // original message ID in `meta.reply_to_id`.
+        let reply_to = meta
+            .and_then(|m| m.get("reply_to_id"))
+            .and_then(|v| v.as_str());
+
+        // Buttons need a card; presses come back through the card callback.
+        // A collapsible panel follows up on a reply, so outside threads it
+        // goes to the chat as a card of its own.
+        let interactive = meta.and_then(|m| m.get("interactive"));
+        let buttons = interactive
+            .and_then(|interactive| build_buttons_card(content, interactive))
+            .filter(|_| reply_to.is_none());
+        let panel = interactive.and_then(build_collapsible_card).filter(|_| {
+            target.thread_id.is_none()
+                && target
+                    .to
+                    .as_deref()
+                    .is_some_and(|to| to.starts_with("chat:"))
+        });
+        if let Some(card) = buttons.or(panel) {
+            let chat_id = target
+                .to
+                .as_deref()
+                .and_then(|s| s.strip_prefix("chat:"))
+                .ok_or("missing or invalid chat_id in delivery target (expected 'chat:<id>')")?;
+            self.client
+                .send_card("chat_id", chat_id, &card)
+                .await
+                .map_err(|e| crate::error::SynapseError::Channel(e.to_string()))?;
+            return Ok(SendResult {
+                message_id: None,
+                delivered_at_ms: now_ms(),
+            });
+        }
 
-        self.client
-            .send_text("chat_id", chat_id, content)
-            .await
-            .map_err(|e| crate::error::SynapseError::Channel(e.to_string()))?;
+        let result = match reply_to {
+            Some(message_id) if target.thread_id.is_some() => {
+                self.client.reply_text_in_thread(message_id, content).await
+            }
+            Some(message_id) => self.client.reply_text(message_id, content).await,
+            None => {
+                let chat_id = target
+                    .to
+                    .as_deref()
+                    .and_then(|s| s.strip_prefix("chat:"))
+                    .ok_or(
+                        "missing or invalid chat_id in delivery target (expected 'chat:<id>')",
+                    )?;
+                self.client.send_text("chat_id", chat_id, content).await
+            }
+        };
+        result.map_err(|e| crate::error::SynapseError::Channel(e.to_string()))?;
 
         Ok(SendResult {
             message_id: None,
@@ -136,6 +181,78 @@ fn build_pair_card(setup_code: &str, gateway_url: &str, template: &str) -> serde
     })
 }
 
+/// Build a Lark interactive card for a `{"type":"buttons"}` interactive
+/// payload: the text followed by one button per entry.
+fn build_buttons_card(text: &str, interactive: &serde_json::Value) -> Option<serde_json::Value> {
+    if interactive.get("type").and_then(|v| v.as_str()) != Some("buttons") {
+        return None;
+    }
+    let actions: Vec<serde_json::Value> = interactive
+        .get("buttons")?
+        .as_array()?
+        .iter()
+        .filter_map(|b| {
+            Some(serde_json::json!({
+                "tag": "button",
+                "text": { "tag": "plain_text", "content": b.get("text")?.as_str()? },
+                "type": b.get("style").and_then(|v| v.as_str()).unwrap_or("default"),
+                "value": b.get("value")?.as_str()?,
+            }))
+        })
+        .collect();
+    Some(serde_json::json!({
+        "config": { "wide_screen_mode": true },
+        "elements": [
+            { "tag": "div", "text": { "tag": "lark_md", "content": text } },
+            { "tag": "action", "actions": actions }
+        ]
+    }))
+}
+
+/// Largest collapsible card sent; Lark rejects cards over 30 KB, and the
+/// text fallback is used instead.
+const MAX_CARD_BYTES: usize = 28 * 1024;
+
+/// Build a Lark card for a `{"type":"collapsible"}` interactive payload: the
+/// title followed by one collapsible panel per section.
+fn build_collapsible_card(interactive: &serde_json::Value) -> Option<serde_json::Value> {
+    if interactive.get("type").and_then(|v| v.as_str()) != Some("collapsible") {
+        return None;
+    }
+    let expanded = !interactive
+        .get("collapsed")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let mut elements = Vec::new();
+    if let Some(title) = interactive.get("title").and_then(|v| v.as_str()) {
+        elements.push(serde_json::json!({
+            "tag": "markdown",
+            "content": format!("**{}**", title),
+        }));
+    }
+    for section in interactive.get("sections")?.as_array()? {
+        elements.push(serde_json::json!({
+            "tag": "collapsible_panel",
+            "expanded": expanded,
+            "header": {
+                "title": {
+                    "tag": "plain_text",
+                    "content": section.get("title")?.as_str()?,
+                }
+            },
+            "elements": [
+                { "tag": "markdown", "content": section.get("text")?.as_str()? }
+            ]
+        }));
+    }
+    let card = serde_json::json!({
+        "schema": "2.0",
+        "config": { "wide_screen_mode": true },
+        "body": { "elements": elements }
+    });
+    (card.to_string().len() <= MAX_CARD_BYTES).then_some(card)
+}
+
 /// Build a Lark interactive card for pairing approval.
 #[allow(dead_code)]
 fn build_approval_card(
@@ -185,6 +302,7 @@ fn build_approval_card(
 
 pub(crate) struct LarkHandler {
     pub(crate) agent_session: Arc<AgentSession>,
+    pub(crate) delivery: Arc<DeliveryService>,
     pub(crate) config: Arc<LarkHandlerConfig>,
     pub(crate) dedup: Arc<MessageDedup>,
     pub(crate) bot_open_id: String,
@@ -215,21 +333,35 @@ pub(crate) struct LarkHandlerConfig {
 
 impl LarkHandler {
     /// Send a plain-text reply, respecting thread mode and chunk limits.
+    ///
+    /// The reply goes through the durable delivery queue, so chunks rejected
+    /// by the Lark API are retried in the background.
     async fn send_reply(
         &self,
         event: &LarkMessageEvent,
-        client: &LarkBotClient,
+        _client: &LarkBotClient,
         text: &str,
     ) -> Result<(), SynapticError> {
         let chunks = formatter::format_for_channel(text, "lark", self.config.text_chunk_limit);
-        for chunk in chunks {
-            if self.config.reply_in_thread && event.has_thread() {
-                client
-                    .reply_text_in_thread(event.message_id(), &chunk)
-                    .await?;
-            } else {
-                client.reply_text(event.message_id(), &chunk).await?;
-            }
+        let thread_id = if self.config.reply_in_thread && event.has_thread() {
+            Some(
+                event
+                    .root_id
+                    .clone()
+                    .unwrap_or_else(|| event.message_id().to_string()),
+            )
+        } else {
+            None
+        };
+        let queued =
+            QueuedDelivery::text_chunks("lark", format!("chat:{}", event.chat_id()), chunks)
+                .with_account_id(Some(self.account_id.clone()))
+                .with_thread_id(thread_id)
+                .with_reply_to(Some(event.message_id().to_string()));
+        match self.delivery.submit(queued).await {
+            Ok(true) => {}
+            Ok(false) => tracing::warn!(channel = "lark", "reply queued for retry"),
+            Err(e) => return Err(SynapticError::Tool(e.to_string())),
         }
         Ok(())
     }
@@ -544,7 +676,20 @@ impl LarkHandler {
             return Ok(());
         }
 
-        // 3.5 Command interception: /pair or 配对
+        // 3.5 Command interception: replies to interactive prompts
+        // ("YES <code>"), /pair or 配对
+        let interaction = Interaction {
+            channel: "lark".into(),
+            user_id: Some(event.sender_open_id().to_string()),
+            value: text.clone(),
+        };
+        if let Some(ack) = self.delivery.handle_interaction(&interaction).await {
+            client
+                .send_text("chat_id", event.chat_id(), &ack)
+                .await
+                .ok();
+            return Ok(());
+        }
         let text_trimmed = text.trim();
         if text_trimmed == "/pair" || text_trimmed == "\u{914d}\u{5bf9}" {
             let mut bootstrap = crate::gateway::nodes::BootstrapStore::new();
@@ -612,6 +757,19 @@ impl LarkHandler {
         event: CardActionEvent,
         client: &LarkBotClient,
     ) -> Result<(), SynapticError> {
+        // Buttons of interactive deliveries (e.g. exec approvals)
+        if let Some(value) = event.action_value.as_str() {
+            let interaction = Interaction {
+                channel: "lark".into(),
+                user_id: Some(event.operator_open_id.clone()),
+                value: value.to_string(),
+            };
+            if let Some(ack) = self.delivery.handle_interaction(&interaction).await {
+                client.send_text("chat_id", &event.chat_id, &ack).await?;
+                return Ok(());
+            }
+        }
+
         let text = event
             .action_value
             .get("text")
@@ -8,6 +8,7 @@ use crate::channels::dm::FileDmPolicyEnforcer;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::{resolve_secret, DmPolicy};
 use crate::config::SynapseConfig;
+use crate::gateway::messages::DeliveryService;
 
 use super::{LarkHandler, LarkHandlerConfig};
 
@@ -22,6 +23,7 @@ pub async fn run(
     status_handle: Option<Arc<dyn synaptic::ChannelStatusHandle>>,
     event_bus: Option<Arc<synaptic::events::EventBus>>,
     plugin_registry: Option<Arc<tokio::sync::RwLock<synaptic::plugin::PluginRegistry>>>,
+    delivery: Option<Arc<DeliveryService>>,
 ) -> crate::error::Result<()> {
     let lark_configs: Vec<crate::config::LarkBotConfig> = config.channel_configs("lark");
     let lark_config = lark_configs
@@ -60,6 +62,13 @@ pub async fn run(
     let lark = LarkConfig::new(&lark_config.app_id, &app_secret);
     let client = LarkBotClient::new(lark.clone());
 
+    let delivery = super::super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(super::LarkSender {
+            client: LarkBotClient::new(lark.clone()),
+        }))
+        .await;
+
     // Fetch bot info for mention detection
     let bot_info = client
         .get_bot_info()
@@ -109,6 +118,7 @@ pub async fn run(
 
     let msg_handler = LarkHandler {
         agent_session: agent_session.clone(),
+        delivery,
         config: handler_config,
         dedup: Arc::new(MessageDedup::new(2048)),
         bot_open_id: bot_info.open_id,
@@ -17,11 +17,16 @@ use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{BotAllowlist, SynapseConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -29,9 +34,9 @@ type HmacSha256 = Hmac<Sha256>;
 #[allow(dead_code)]
 struct AppState {
     agent_session: Arc<AgentSession>,
+    delivery: Arc<DeliveryService>,
     allowlist: BotAllowlist,
     channel_secret: String,
-    channel_token: String,
 }
 
 /// Top-level LINE webhook payload.
@@ -146,45 +151,90 @@ fn base64_encode(data: &[u8]) -> String {
     encoded
 }
 
-/// Send a reply to LINE via the Reply API.
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the LINE channel.
 ///
-/// Sends multiple text messages if the reply exceeds the 5000-character limit.
-/// Each call to this function uses a single `replyToken`; only the first message
-/// in the array uses it, so we fall back to individual reply calls for chunks
-/// beyond the first when the token allows multiple messages in one reply.
-async fn send_reply(channel_token: &str, reply_token: &str, text: &str) {
-    let client = reqwest::Client::new();
-    let chunks = formatter::format_for_channel(text, "line", 5000);
-
-    // LINE Reply API supports up to 5 messages per reply call.
-    // We bundle all chunks into a single request (LINE allows up to 5 messages
-    // in one reply), and if there are more than 5 chunks we send subsequent
-    // groups — though in practice format_for_channel keeps messages small enough that
-    // more than 5 chunks is rare.
-    let messages: Vec<serde_json::Value> = chunks
-        .iter()
-        .take(5)
-        .map(|chunk| {
-            serde_json::json!({
-                "type": "text",
-                "text": chunk,
+/// Answers with the event's reply token (free, but single-use and
+/// short-lived) when the delivery carries one, and falls back to the Push API
+/// once LINE rejects the token, e.g. on a retry.
+pub struct LineSender {
+    pub client: reqwest::Client,
+    pub channel_token: String,
+}
+
+#[async_trait]
+impl ChannelSender for LineSender {
+    fn channel_id(&self) -> &str {
+        "line"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let to = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("chat:"))
+            .ok_or("missing LINE chat in delivery target (expected 'chat:<id>')")?;
+
+        // LINE accepts up to 5 messages per call; format_for_channel keeps
+        // replies small enough that more than 5 chunks is rare.
+        let messages: Vec<serde_json::Value> = formatter::format_for_channel(content, "line", 5000)
+            .iter()
+            .take(5)
+            .map(|chunk| {
+                serde_json::json!({
+                    "type": "text",
+                    "text": chunk,
+                })
             })
-        })
-        .collect();
+            .collect();
 
-    let body = serde_json::json!({
-        "replyToken": reply_token,
-        "messages": messages,
-    });
+        if let Some(reply_token) = meta
+            .and_then(|m| m.get("reply_to_id"))
+            .and_then(|v| v.as_str())
+        {
+            let resp = self
+                .client
+                .post("https://api.line.me/v2/bot/message/reply")
+                .bearer_auth(&self.channel_token)
+                .json(&serde_json::json!({
+                    "replyToken": reply_token,
+                    "messages": messages,
+                }))
+                .send()
+                .await?;
+            if !resp.status().is_client_error() {
+                resp.error_for_status()?;
+                return Ok(SendResult {
+                    message_id: None,
+                    delivered_at_ms: now_ms(),
+                });
+            }
+            tracing::debug!(channel = "line", status = %resp.status(), "reply token rejected, pushing instead");
+        }
 
-    if let Err(e) = client
-        .post("https://api.line.me/v2/bot/message/reply")
-        .bearer_auth(channel_token)
-        .json(&body)
-        .send()
-        .await
-    {
-        tracing::error!(channel = "line", error = %e, "failed to send reply");
+        self.client
+            .post("https://api.line.me/v2/bot/message/push")
+            .bearer_auth(&self.channel_token)
+            .json(&serde_json::json!({
+                "to": to,
+                "messages": messages,
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
     }
 }
 
@@ -279,10 +329,15 @@ async fn handle_webhook(
         };
 
         let is_dm = event.source.source_type == "user";
+        let to = if !channel_id.is_empty() {
+            channel_id.clone()
+        } else {
+            user_id.clone()
+        };
 
         // Respond to LINE quickly (< 1 second) and process in the background.
         let session = state.agent_session.clone();
-        let channel_token = state.channel_token.clone();
+        let delivery = state.delivery.clone();
         tokio::spawn(async move {
             let channel_info = ChannelInfo {
                 platform: "line".into(),
@@ -313,14 +368,20 @@ async fn handle_webhook(
                 chat_info,
             );
             msg.finalize();
-            match session.handle_message(msg, RunContext::default()).await {
-                Ok(reply) => {
-                    send_reply(&channel_token, &reply_token, &reply.content).await;
-                }
+            let content = match session.handle_message(msg, RunContext::default()).await {
+                Ok(reply) => reply.content,
                 Err(e) => {
                     tracing::error!(channel = "line", error = %e, "handler error");
-                    send_reply(&channel_token, &reply_token, &format!("Error: {e}")).await;
+                    format!("Error: {e}")
                 }
+            };
+            // One payload, so the whole reply goes out in a single reply-token call.
+            let queued = QueuedDelivery::text_chunks("line", format!("chat:{}", to), vec![content])
+                .with_reply_to(Some(reply_token));
+            match delivery.submit(queued).await {
+                Ok(true) => {}
+                Ok(false) => tracing::warn!(channel = "line", "reply queued for retry"),
+                Err(e) => tracing::error!(channel = "line", error = %e, "failed to queue reply"),
             }
         });
     }
@@ -329,7 +390,14 @@ async fn handle_webhook(
 }
 
 /// Run the LINE bot adapter.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let line_configs: Vec<crate::config::LineBotConfig> = config.channel_configs("line");
     let line_config = line_configs
         .first()
@@ -365,11 +433,19 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
         );
     }
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(LineSender {
+            client: reqwest::Client::new(),
+            channel_token,
+        }))
+        .await;
+
     let state = Arc::new(AppState {
         agent_session,
+        delivery,
         allowlist,
         channel_secret,
-        channel_token,
     });
 
     let app = Router::new()
@@ -9,16 +9,73 @@ use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
 
 use crate::agent;
 use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::SynapseConfig;
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the Matrix channel.
+pub struct MatrixSender {
+    pub client: reqwest::Client,
+    /// Homeserver URL, without a trailing slash.
+    pub homeserver: String,
+    pub access_token: String,
+}
+
+#[async_trait]
+impl ChannelSender for MatrixSender {
+    fn channel_id(&self) -> &str {
+        "matrix"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let room_id = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("room:"))
+            .ok_or("missing room in delivery target (expected 'room:<id>')")?;
+        let event_id = send_message(
+            &self.client,
+            &self.homeserver,
+            &self.access_token,
+            room_id,
+            content,
+        )
+        .await?;
+
+        Ok(SendResult {
+            message_id: event_id,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
 
 /// Run the Matrix bot adapter using the Client-Server REST API (long-polling sync).
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let mx_configs: Vec<crate::config::MatrixBotConfig> = config.channel_configs("matrix");
     let mx_config = mx_configs
         .first()
@@ -56,6 +113,15 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
     tracing::info!(channel = "matrix", user_id = %user_id, "logged in");
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(MatrixSender {
+            client: client.clone(),
+            homeserver: homeserver.clone(),
+            access_token: access_token.clone(),
+        }))
+        .await;
+
     // Main sync loop
     let mut since: Option<String> = None;
 
@@ -92,9 +158,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
                     // Process in background
                     let session = agent_session.clone();
-                    let http = client.clone();
-                    let hs = homeserver.clone();
-                    let token = access_token.clone();
+                    let delivery = delivery.clone();
                     let rid = room_id.clone();
                     let sender_clone = sender.clone();
                     tokio::spawn(async move {
@@ -123,8 +187,22 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
                             Ok(reply) => {
                                 let chunks =
                                     formatter::format_for_channel(&reply.content, "matrix", 60000);
-                                for chunk in chunks {
-                                    send_message(&http, &hs, &token, &rid, &chunk).await;
+                                let queued = QueuedDelivery::text_chunks(
+                                    "matrix",
+                                    format!("room:{}", rid),
+                                    chunks,
+                                );
+                                match delivery.submit(queued).await {
+                                    Ok(true) => {}
+                                    Ok(false) => {
+                                        tracing::warn!(
+                                            channel = "matrix",
+                                            "reply queued for retry"
+                                        );
+                                    }
+                                    Err(e) => {
+                                        tracing::error!(channel = "matrix", error = %e, "failed to queue reply");
+                                    }
                                 }
                             }
                             Err(e) => {
@@ -297,13 +375,15 @@ async fn sync_once(
 
 /// Send a text message to a Matrix room via
 /// `PUT /_matrix/client/v3/rooms/{roomId}/send/m.room.message/{txnId}`.
+///
+/// Returns the new event's ID.
 async fn send_message(
     client: &reqwest::Client,
     homeserver: &str,
     access_token: &str,
     room_id: &str,
     text: &str,
-) {
+) -> crate::error::Result<Option<String>> {
     let txn_id = uuid::Uuid::new_v4().to_string();
     let encoded_room = urlencoding::encode(room_id);
     let url = format!(
@@ -316,15 +396,20 @@ async fn send_message(
         "body": text,
     });
 
-    if let Err(e) = client
+    let resp: serde_json::Value = client
         .put(&url)
         .bearer_auth(access_token)
         .json(&body)
         .send()
-        .await
-    {
-        tracing::error!(channel = "matrix", error = %e, "send error");
-    }
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(resp
+        .get("event_id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string()))
 }
 
 // ---------------------------------------------------------------------------
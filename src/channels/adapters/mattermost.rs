@@ -12,16 +12,82 @@ use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
 
 use crate::agent;
 use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{BotAllowlist, SynapseConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the Mattermost channel.
+pub struct MattermostSender {
+    pub client: reqwest::Client,
+    /// Server URL, e.g. `https://mattermost.example.com`.
+    pub api_url: String,
+    pub token: String,
+}
+
+#[async_trait]
+impl ChannelSender for MattermostSender {
+    fn channel_id(&self) -> &str {
+        "mattermost"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let channel_id = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("channel:"))
+            .ok_or("missing channel in delivery target (expected 'channel:<id>')")?;
+
+        let resp: serde_json::Value = self
+            .client
+            .post(format!("{}/api/v4/posts", self.api_url))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({
+                "channel_id": channel_id,
+                "message": content,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(SendResult {
+            message_id: resp
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
 
 /// Run the Mattermost bot adapter using WebSocket events.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let mm_configs: Vec<crate::config::MattermostBotConfig> = config.channel_configs("mattermost");
     let mm_config = mm_configs
         .first()
@@ -51,6 +117,15 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
         );
     }
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(MattermostSender {
+            client: reqwest::Client::new(),
+            api_url: mm_config.url.clone(),
+            token: token.clone(),
+        }))
+        .await;
+
     tracing::info!(channel = "mattermost", "adapter started");
 
     loop {
@@ -60,6 +135,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
             &bot_user_id,
             agent_session.clone(),
             &allowlist,
+            &delivery,
         )
         .await
         {
@@ -97,6 +173,7 @@ async fn run_ws(
     bot_user_id: &str,
     agent_session: Arc<AgentSession>,
     allowlist: &BotAllowlist,
+    delivery: &Arc<DeliveryService>,
 ) -> crate::error::Result<()> {
     // Build WSS URL: replace http(s) with ws(s)
     let ws_url = if url.starts_with("https://") {
@@ -179,8 +256,7 @@ async fn run_ws(
 
         // Process in background
         let session = agent_session.clone();
-        let api_url = url.to_string();
-        let api_token = token.to_string();
+        let delivery = delivery.clone();
         let sender_id = user_id.to_string();
         tokio::spawn(async move {
             let channel_info = ChannelInfo {
@@ -207,17 +283,19 @@ async fn run_ws(
             match session.handle_message(msg, RunContext::default()).await {
                 Ok(reply) => {
                     let chunks = formatter::format_for_channel(&reply.content, "mattermost", 16383);
-                    let client = reqwest::Client::new();
-                    for chunk in chunks {
-                        let _ = client
-                            .post(format!("{}/api/v4/posts", api_url))
-                            .bearer_auth(&api_token)
-                            .json(&serde_json::json!({
-                                "channel_id": channel_id,
-                                "message": chunk,
-                            }))
-                            .send()
-                            .await;
+                    let queued = QueuedDelivery::text_chunks(
+                        "mattermost",
+                        format!("channel:{}", channel_id),
+                        chunks,
+                    );
+                    match delivery.submit(queued).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            tracing::warn!(channel = "mattermost", "reply queued for retry");
+                        }
+                        Err(e) => {
+                            tracing::error!(channel = "mattermost", error = %e, "failed to queue reply");
+                        }
                     }
                 }
                 Err(e) => {
@@ -66,3 +66,31 @@ pub mod tlon;
 
 #[cfg(feature = "bot-zalo")]
 pub mod zalo;
+
+use std::sync::Arc;
+
+use crate::config::SynapseConfig;
+use crate::gateway::messages::{ChannelRegistry, DeliveryService};
+
+/// Resolve the outbound delivery service for an adapter.
+///
+/// Inside `synapse serve` the gateway passes its shared service so every
+/// channel's queue is visible through the `delivery.*` RPCs. Standalone
+/// `synapse bot <platform>` runs get their own service and retry worker.
+///
+/// Google Chat and WebChat answer in the HTTP response to the inbound
+/// request, so they have no outbound send to queue; Nostr and Tlon don't
+/// reply yet.
+pub(crate) fn resolve_delivery(
+    config: &SynapseConfig,
+    shared: Option<Arc<DeliveryService>>,
+) -> Arc<DeliveryService> {
+    shared.unwrap_or_else(|| {
+        let service = Arc::new(DeliveryService::new(
+            &config.delivery,
+            Arc::new(tokio::sync::RwLock::new(ChannelRegistry::new())),
+        ));
+        service.spawn_worker();
+        service
+    })
+}
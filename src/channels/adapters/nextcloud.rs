@@ -9,14 +9,76 @@ use crate::agent;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{NextcloudBotConfig, SynapseConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the Nextcloud Talk channel.
+pub struct NextcloudSender {
+    pub client: Client,
+    /// Base URL of the Nextcloud instance, without a trailing slash.
+    pub base_url: String,
+    pub username: String,
+    pub password: String,
+}
+
+#[async_trait]
+impl ChannelSender for NextcloudSender {
+    fn channel_id(&self) -> &str {
+        "nextcloud"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let room_token = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("room:"))
+            .ok_or("missing room in delivery target (expected 'room:<token>')")?;
+
+        self.client
+            .post(format!(
+                "{}/ocs/v2.php/apps/spreed/api/v1/chat/{}",
+                self.base_url, room_token
+            ))
+            .basic_auth(&self.username, Some(&self.password))
+            .header("OCS-APIRequest", "true")
+            .json(&serde_json::json!({"message": content}))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
 
 /// Run the Nextcloud Talk bot adapter using REST long-polling.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let nc_configs: Vec<crate::config::NextcloudBotConfig> = config.channel_configs("nextcloud");
     let nc_config = nc_configs
         .first()
@@ -36,6 +98,16 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
     let client = Client::new();
     let base_url = nc_config.url.trim_end_matches('/');
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(NextcloudSender {
+            client: client.clone(),
+            base_url: base_url.to_string(),
+            username: nc_config.username.clone(),
+            password: password.clone(),
+        }))
+        .await;
+
     tracing::info!(channel = "nextcloud", url = %base_url, "adapter started (polling mode)");
 
     let poll_interval = Duration::from_secs(nc_config.poll_interval_secs.unwrap_or(3));
@@ -100,13 +172,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
                                 let session_key = room_token.clone();
                                 let session = agent_session.clone();
-                                let client = client.clone();
-                                let reply_url = format!(
-                                    "{}/ocs/v2.php/apps/spreed/api/v1/chat/{}",
-                                    base_url, room_token
-                                );
-                                let username = nc_config.username.clone();
-                                let pw = password.clone();
+                                let delivery = delivery.clone();
                                 let msg_text = content.to_string();
                                 let sender_id = actor.to_string();
 
@@ -134,15 +200,23 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
                                     msg.finalize();
                                     match session.handle_message(msg, RunContext::default()).await {
                                         Ok(reply) => {
-                                            let _ = client
-                                                .post(&reply_url)
-                                                .basic_auth(&username, Some(&pw))
-                                                .header("OCS-APIRequest", "true")
-                                                .json(
-                                                    &serde_json::json!({"message": reply.content}),
-                                                )
-                                                .send()
-                                                .await;
+                                            let queued = QueuedDelivery::text_chunks(
+                                                "nextcloud",
+                                                format!("room:{}", session_key),
+                                                vec![reply.content],
+                                            );
+                                            match delivery.submit(queued).await {
+                                                Ok(true) => {}
+                                                Ok(false) => tracing::warn!(
+                                                    channel = "nextcloud",
+                                                    "reply queued for retry"
+                                                ),
+                                                Err(e) => tracing::error!(
+                                                    channel = "nextcloud",
+                                                    error = %e,
+                                                    "failed to queue reply"
+                                                ),
+                                            }
                                         }
                                         Err(e) => {
                                             tracing::error!(channel = "nextcloud", error = %e, "agent error");
@@ -9,17 +9,79 @@ use crate::agent;
 use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::SynapseConfig;
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the Signal channel (signal-cli REST bridge).
+pub struct SignalSender {
+    pub client: reqwest::Client,
+    /// Bridge URL, without a trailing slash.
+    pub api_url: String,
+    /// The bot's own phone number.
+    pub phone_number: String,
+}
+
+#[async_trait]
+impl ChannelSender for SignalSender {
+    fn channel_id(&self) -> &str {
+        "signal"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let recipient = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("number:"))
+            .ok_or("missing recipient in delivery target (expected 'number:<e164>')")?;
+
+        let body = serde_json::json!({
+            "message": content,
+            "number": self.phone_number,
+            "recipients": [recipient],
+        });
+        self.client
+            .post(format!("{}/v2/send", self.api_url))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
 
 /// Run the Signal bot adapter using the signal-cli REST API bridge.
 ///
 /// Polls `GET /v1/receive/{number}` for incoming messages and replies via
 /// `POST /v2/send`.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let signal_configs: Vec<crate::config::SignalBotConfig> = config.channel_configs("signal");
     let signal_config = signal_configs
         .first()
@@ -51,6 +113,16 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
     );
 
     let client = reqwest::Client::new();
+
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(SignalSender {
+            client: client.clone(),
+            api_url: api_url.clone(),
+            phone_number: phone_number.clone(),
+        }))
+        .await;
+
     let receive_url = format!(
         "{}/v1/receive/{}",
         api_url,
@@ -121,9 +193,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
             // Process message in background
             let session = agent_session.clone();
-            let http = client.clone();
-            let send_url = format!("{}/v2/send", api_url);
-            let our_number = phone_number.clone();
+            let delivery = delivery.clone();
             let recipient = sender.clone();
 
             tokio::spawn(async move {
@@ -151,14 +221,18 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
                 match session.handle_message(msg, RunContext::default()).await {
                     Ok(reply) => {
                         let chunks = formatter::format_for_channel(&reply.content, "signal", 4096);
-                        for chunk in chunks {
-                            let body = serde_json::json!({
-                                "message": chunk,
-                                "number": our_number,
-                                "recipients": [recipient],
-                            });
-                            if let Err(e) = http.post(&send_url).json(&body).send().await {
-                                tracing::error!(channel = "signal", error = %e, "send error");
+                        let queued = QueuedDelivery::text_chunks(
+                            "signal",
+                            format!("number:{}", recipient),
+                            chunks,
+                        );
+                        match delivery.submit(queued).await {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                tracing::warn!(channel = "signal", "reply queued for retry");
+                            }
+                            Err(e) => {
+                                tracing::error!(channel = "signal", error = %e, "failed to queue reply");
                             }
                         }
                     }
@@ -21,7 +21,9 @@ use crate::channels::reactions;
 use crate::config::bots::resolve_secret;
 use crate::config::{BotAllowlist, SynapseConfig};
 use crate::gateway::messages::sender::{ChannelSender, SendResult};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, Interaction, QueuedDelivery, SenderInfo,
+};
 use crate::gateway::presence::now_ms;
 
 // ---------------------------------------------------------------------------
@@ -29,7 +31,6 @@ use crate::gateway::presence::now_ms;
 // ---------------------------------------------------------------------------
 
 /// Outbound sender for the Slack channel.
-#[allow(dead_code)]
 pub struct SlackSender {
     /// Bot OAuth token used for `chat.postMessage`.
     pub bot_token: String,
@@ -45,7 +46,7 @@ impl ChannelSender for SlackSender {
         &self,
         target: &DeliveryContext,
         content: &str,
-        _meta: Option<&serde_json::Value>,
+        meta: Option<&serde_json::Value>,
     ) -> crate::error::Result<SendResult> {
         let channel = target
             .to
@@ -54,20 +55,44 @@ impl ChannelSender for SlackSender {
             .ok_or("missing or invalid channel in delivery target (expected 'channel:<id>')")?;
 
         let client = reqwest::Client::new();
+        // Queued text arrives split to Slack's limit; this only splits what
+        // rendering pushed over it.
         let chunks = formatter::format_for_channel(content, "slack", 4000);
+        let last = chunks.len().saturating_sub(1);
         let mut last_ts: Option<String> = None;
-        for chunk in chunks {
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let mut body = serde_json::json!({
+                "channel": channel,
+                "text": chunk,
+            });
+            if let Some(ref thread_ts) = target.thread_id {
+                body["thread_ts"] = serde_json::Value::String(thread_ts.clone());
+            }
+            // Buttons go on the last chunk.
+            if i == last {
+                if let Some(blocks) = meta
+                    .and_then(|m| m.get("interactive"))
+                    .and_then(|interactive| button_blocks(&chunk, interactive))
+                {
+                    body["blocks"] = blocks;
+                }
+            }
             let resp: serde_json::Value = client
                 .post("https://slack.com/api/chat.postMessage")
                 .bearer_auth(&self.bot_token)
-                .json(&serde_json::json!({
-                    "channel": channel,
-                    "text": chunk,
-                }))
+                .json(&body)
                 .send()
                 .await?
+                .error_for_status()?
                 .json()
                 .await?;
+            if !resp.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+                let err = resp
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown error");
+                return Err(format!("chat.postMessage failed: {}", err).into());
+            }
             if let Some(ts) = resp.get("ts").and_then(|v| v.as_str()) {
                 last_ts = Some(ts.to_string());
             }
@@ -80,8 +105,81 @@ impl ChannelSender for SlackSender {
     }
 }
 
+/// Block Kit blocks for a `{"type":"buttons"}` interactive payload: the
+/// text as a section followed by an actions block.
+fn button_blocks(text: &str, interactive: &serde_json::Value) -> Option<serde_json::Value> {
+    if interactive.get("type").and_then(|v| v.as_str()) != Some("buttons") {
+        return None;
+    }
+    let elements: Vec<serde_json::Value> = interactive
+        .get("buttons")?
+        .as_array()?
+        .iter()
+        .filter_map(|b| {
+            let value = b.get("value")?.as_str()?;
+            let mut button = serde_json::json!({
+                "type": "button",
+                "text": {"type": "plain_text", "text": b.get("text")?.as_str()?},
+                "value": value,
+                "action_id": value,
+            });
+            // Slack only knows these two styles; anything else is the default.
+            if let Some(style @ ("primary" | "danger")) = b.get("style").and_then(|v| v.as_str()) {
+                button["style"] = style.into();
+            }
+            Some(button)
+        })
+        .collect();
+    Some(serde_json::json!([
+        {"type": "section", "text": {"type": "mrkdwn", "text": text}},
+        {"type": "actions", "elements": elements},
+    ]))
+}
+
+/// Hand a button press from an `interactive` envelope to the delivery
+/// service and post its acknowledgement in the same thread.
+async fn handle_block_actions(delivery: &DeliveryService, payload: &serde_json::Value) {
+    if payload.get("type").and_then(|v| v.as_str()) != Some("block_actions") {
+        return;
+    }
+    let Some(value) = payload.pointer("/actions/0/value").and_then(|v| v.as_str()) else {
+        return;
+    };
+    let interaction = Interaction {
+        channel: "slack".into(),
+        user_id: payload
+            .pointer("/user/id")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        value: value.to_string(),
+    };
+    let Some(ack) = delivery.handle_interaction(&interaction).await else {
+        return;
+    };
+    let Some(channel) = payload.pointer("/channel/id").and_then(|v| v.as_str()) else {
+        return;
+    };
+    let thread_ts = payload
+        .pointer("/message/thread_ts")
+        .or_else(|| payload.pointer("/message/ts"))
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let queued = QueuedDelivery::text_chunks("slack", format!("channel:{}", channel), vec![ack])
+        .with_thread_id(thread_ts);
+    if let Err(e) = delivery.submit(queued).await {
+        tracing::error!(channel = "slack", error = %e, "failed to queue interaction reply");
+    }
+}
+
 /// Run the Slack bot adapter using Socket Mode.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let slack_configs: Vec<crate::config::SlackBotConfig> = config.channel_configs("slack");
     let slack_config = slack_configs
         .first()
@@ -104,6 +202,12 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
     let config_arc = Arc::new(config.clone());
     let allowlist = slack_config.allowlist.clone();
     let agent_session = Arc::new(AgentSession::new(model, config_arc, true).with_channel("slack"));
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(SlackSender {
+            bot_token: bot_token.clone(),
+        }))
+        .await;
 
     if !allowlist.is_empty() {
         tracing::info!(
@@ -121,6 +225,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
             &app_token,
             &bot_token,
             agent_session.clone(),
+            delivery.clone(),
             &allowlist,
         )
         .await
@@ -304,6 +409,7 @@ async fn run_socket_mode(
     app_token: &str,
     bot_token: &str,
     agent_session: Arc<AgentSession>,
+    delivery: Arc<DeliveryService>,
     allowlist: &BotAllowlist,
 ) -> crate::error::Result<()> {
     // Step 1: Open a WebSocket connection via apps.connections.open
@@ -349,6 +455,15 @@ async fn run_socket_mode(
         // Check event type
         let event_type = payload.get("type").and_then(|v| v.as_str()).unwrap_or("");
 
+        // Button presses (e.g. exec approvals)
+        if event_type == "interactive" {
+            if let Some(inner) = payload.get("payload").cloned() {
+                let delivery = delivery.clone();
+                tokio::spawn(async move { handle_block_actions(&delivery, &inner).await });
+            }
+            continue;
+        }
+
         if event_type != "events_api" {
             continue;
         }
@@ -410,8 +525,26 @@ async fn run_socket_mode(
 
         // Process in background
         let session = agent_session.clone();
+        let delivery = delivery.clone();
         let bot_token = bot_token.to_string();
         tokio::spawn(async move {
+            // Replies answering an interactive prompt (e.g. "YES <code>")
+            // don't go to the agent.
+            let interaction = Interaction {
+                channel: "slack".into(),
+                user_id: Some(sender_id.clone()),
+                value: text.clone(),
+            };
+            if let Some(ack) = delivery.handle_interaction(&interaction).await {
+                let queued =
+                    QueuedDelivery::text_chunks("slack", format!("channel:{}", channel), vec![ack])
+                        .with_thread_id(Some(ts.clone()));
+                if let Err(e) = delivery.submit(queued).await {
+                    tracing::error!(channel = "slack", error = %e, "failed to queue reply");
+                }
+                return;
+            }
+
             // React with eyes to indicate processing
             reactions::slack_react(&bot_token, &channel, &ts, "eyes").await;
 
@@ -444,22 +577,26 @@ async fn run_socket_mode(
 
             match session.handle_message(msg, RunContext::default()).await {
                 Ok(reply) => {
-                    // Split long replies into chunks
-                    let chunks = formatter::format_for_channel(&reply.content, "slack", 4000);
-                    let client = reqwest::Client::new();
-                    for chunk in chunks {
-                        let _ = client
-                            .post("https://slack.com/api/chat.postMessage")
-                            .bearer_auth(&bot_token)
-                            .json(&serde_json::json!({
-                                "channel": channel,
-                                "text": chunk,
-                            }))
-                            .send()
-                            .await;
+                    // The delivery queue splits long replies and retries any
+                    // chunk Slack rejects; the sender renders them as mrkdwn.
+                    let queued = QueuedDelivery::text_chunks(
+                        "slack",
+                        format!("channel:{}", channel),
+                        vec![reply.content],
+                    );
+                    match delivery.submit(queued).await {
+                        Ok(true) => {
+                            // React with checkmark on success
+                            reactions::slack_react(&bot_token, &channel, &ts, "white_check_mark")
+                                .await;
+                        }
+                        Ok(false) => {
+                            tracing::warn!(channel = "slack", "reply queued for retry");
+                        }
+                        Err(e) => {
+                            tracing::error!(channel = "slack", error = %e, "failed to queue reply");
+                        }
                     }
-                    // React with checkmark on success
-                    reactions::slack_react(&bot_token, &channel, &ts, "white_check_mark").await;
                 }
                 Err(e) => {
                     tracing::error!(channel = "slack", error = %e, "message handler error");
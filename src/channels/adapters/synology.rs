@@ -8,17 +8,62 @@ use serde::Deserialize;
 use crate::agent;
 use crate::channels::handler::AgentSession;
 use crate::config::{SynapseConfig, SynologyBotConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
 
 #[derive(Clone)]
 struct AppState {
     agent_session: Arc<AgentSession>,
     allowlist: crate::config::BotAllowlist,
-    outgoing_url: Option<String>,
+    /// Set when `outgoing_webhook_url` is configured.
+    delivery: Option<Arc<DeliveryService>>,
+}
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the Synology Chat channel.
+///
+/// Posts to the configured outgoing webhook, which is bound to a single
+/// Synology channel; the delivery target only records the conversation.
+pub struct SynologySender {
+    pub client: reqwest::Client,
+    pub outgoing_url: String,
+}
+
+#[async_trait]
+impl ChannelSender for SynologySender {
+    fn channel_id(&self) -> &str {
+        "synology"
+    }
+
+    async fn send(
+        &self,
+        _target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        self.client
+            .post(&self.outgoing_url)
+            .json(&serde_json::json!({"text": content}))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
 }
 
 #[derive(Deserialize)]
@@ -34,7 +79,14 @@ struct SynologyWebhook {
 }
 
 /// Run the Synology Chat bot adapter (incoming webhook mode).
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies posted to the outgoing webhook are routed through `delivery` (or a
+/// standalone delivery service if `None`) so failed sends are retried.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let syn_configs: Vec<crate::config::SynologyBotConfig> = config.channel_configs("synology");
     let syn_config = syn_configs
         .first()
@@ -46,10 +98,24 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
     let port = syn_config.port.unwrap_or(8091);
 
+    let delivery = match syn_config.outgoing_webhook_url.clone() {
+        Some(outgoing_url) => {
+            let delivery = super::resolve_delivery(config, delivery);
+            delivery
+                .register_sender(Arc::new(SynologySender {
+                    client: reqwest::Client::new(),
+                    outgoing_url,
+                }))
+                .await;
+            Some(delivery)
+        }
+        None => None,
+    };
+
     let state = AppState {
         agent_session,
         allowlist: syn_config.allowlist.clone(),
-        outgoing_url: syn_config.outgoing_webhook_url.clone(),
+        delivery,
     };
 
     let app = Router::new()
@@ -122,13 +188,19 @@ async fn handle_webhook(
     {
         Ok(reply) => {
             // If outgoing webhook URL is configured, send there
-            if let Some(ref url) = state.outgoing_url {
-                let client = reqwest::Client::new();
-                let _ = client
-                    .post(url)
-                    .json(&serde_json::json!({"text": reply.content}))
-                    .send()
-                    .await;
+            if let Some(ref delivery) = state.delivery {
+                let queued = QueuedDelivery::text_chunks(
+                    "synology",
+                    format!("chat:{}", session_key),
+                    vec![reply.content.clone()],
+                );
+                match delivery.submit(queued).await {
+                    Ok(true) => {}
+                    Ok(false) => tracing::warn!(channel = "synology", "reply queued for retry"),
+                    Err(e) => {
+                        tracing::error!(channel = "synology", error = %e, "failed to queue reply")
+                    }
+                }
             }
             (
                 StatusCode::OK,
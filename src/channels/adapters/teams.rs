@@ -12,21 +12,24 @@ use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
 
 use crate::agent;
 use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{BotAllowlist, SynapseConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 
 /// Shared state for the axum webhook server.
-#[allow(dead_code)]
 struct AppState {
     agent_session: Arc<AgentSession>,
+    delivery: Arc<DeliveryService>,
     allowlist: BotAllowlist,
-    app_id: String,
-    app_password: String,
 }
 
 /// Bot Framework Activity payload (subset of fields we care about).
@@ -73,14 +76,11 @@ struct ChannelAccount {
 /// Reply payload sent back to the Bot Framework REST API.
 #[derive(Debug, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
-struct ReplyActivity {
+struct ReplyActivity<'a> {
     #[serde(rename = "type")]
     activity_type: &'static str,
-    text: String,
-    conversation: Option<ConversationRef>,
-    from: Option<ChannelAccount>,
-    recipient: Option<ChannelAccount>,
-    reply_to_id: Option<String>,
+    text: &'a str,
+    reply_to_id: Option<&'a str>,
 }
 
 /// Build the Bot Framework REST API URL for sending a reply.
@@ -122,58 +122,70 @@ async fn fetch_bot_token(
     Ok(token)
 }
 
-/// Send a reply message via the Bot Framework REST API.
-async fn send_reply(
-    client: &reqwest::Client,
-    app_id: &str,
-    app_password: &str,
-    service_url: &str,
-    activity: &Activity,
-    text: &str,
-) {
-    // Obtain OAuth token for this call.
-    let token = match fetch_bot_token(client, app_id, app_password).await {
-        Ok(t) => t,
-        Err(e) => {
-            tracing::error!(channel = "teams", error = %e, "failed to obtain bot token");
-            return;
-        }
-    };
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
 
-    let conversation_id = activity
-        .conversation
-        .as_ref()
-        .and_then(|c| c.id.as_deref())
-        .unwrap_or_default();
+/// Outbound sender for the Microsoft Teams channel.
+///
+/// Delivery targets are `conversation:<serviceUrl> <conversationId>`: the
+/// Bot Framework needs both, and neither contains spaces.
+pub struct TeamsSender {
+    pub client: reqwest::Client,
+    pub app_id: String,
+    pub app_password: String,
+}
 
-    if conversation_id.is_empty() {
-        tracing::warn!(
-            channel = "teams",
-            "no conversation ID in activity, cannot reply"
-        );
-        return;
+#[async_trait]
+impl ChannelSender for TeamsSender {
+    fn channel_id(&self) -> &str {
+        "teams"
     }
 
-    let url = reply_url(service_url, conversation_id);
-
-    let reply = ReplyActivity {
-        activity_type: "message",
-        text: text.to_string(),
-        conversation: activity.conversation.clone(),
-        // Swap from/recipient for the reply direction
-        from: activity.recipient.clone(),
-        recipient: activity.from.clone(),
-        reply_to_id: activity.id.clone(),
-    };
-
-    if let Err(e) = client
-        .post(&url)
-        .bearer_auth(&token)
-        .json(&reply)
-        .send()
-        .await
-    {
-        tracing::error!(channel = "teams", error = %e, "failed to send reply");
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let (service_url, conversation_id) = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("conversation:"))
+            .and_then(|s| s.split_once(' '))
+            .ok_or(
+                "missing conversation in delivery target \
+                 (expected 'conversation:<serviceUrl> <conversationId>')",
+            )?;
+
+        // Obtain OAuth token for this call.
+        let token = fetch_bot_token(&self.client, &self.app_id, &self.app_password).await?;
+
+        let reply = ReplyActivity {
+            activity_type: "message",
+            text: content,
+            reply_to_id: meta
+                .and_then(|m| m.get("reply_to_id"))
+                .and_then(|v| v.as_str()),
+        };
+        let resp: serde_json::Value = self
+            .client
+            .post(reply_url(service_url, conversation_id))
+            .bearer_auth(&token)
+            .json(&reply)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(SendResult {
+            message_id: resp
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            delivered_at_ms: now_ms(),
+        })
     }
 }
 
@@ -237,25 +249,8 @@ async fn handle_message(
 
     // Process in the background so we respond to Bot Framework promptly (< 5 s).
     let session = state.agent_session.clone();
-    let app_id = state.app_id.clone();
-    let app_password = state.app_password.clone();
-    // We need to clone the activity fields we'll need inside the spawn.
+    let delivery = state.delivery.clone();
     let activity_id = activity.id.clone();
-    let conversation = activity.conversation.clone();
-    let from = activity.from.clone();
-    let recipient = activity.recipient.clone();
-
-    // Reconstruct a minimal activity for send_reply.
-    let activity_ref = Activity {
-        activity_type: activity.activity_type.clone(),
-        id: activity_id,
-        service_url: Some(service_url.clone()),
-        channel_id: activity.channel_id.clone(),
-        conversation,
-        from,
-        recipient,
-        text: Some(text.clone()),
-    };
 
     let is_group = activity
         .conversation
@@ -264,8 +259,6 @@ async fn handle_message(
         .unwrap_or(false);
 
     tokio::spawn(async move {
-        let client = reqwest::Client::new();
-
         let channel_info = ChannelInfo {
             platform: "teams".into(),
             native_channel_id: Some(conversation_id.clone()),
@@ -287,34 +280,31 @@ async fn handle_message(
             chat_info,
         );
         msg.finalize();
-        match session.handle_message(msg, RunContext::default()).await {
-            Ok(reply) => {
-                let chunks = formatter::format_for_channel(&reply.content, "teams", 4000);
-                for chunk in &chunks {
-                    send_reply(
-                        &client,
-                        &app_id,
-                        &app_password,
-                        &service_url,
-                        &activity_ref,
-                        chunk,
-                    )
-                    .await;
-                }
-            }
+        let chunks = match session.handle_message(msg, RunContext::default()).await {
+            Ok(reply) => formatter::format_for_channel(&reply.content, "teams", 4000),
             Err(e) => {
                 tracing::error!(channel = "teams", error = %e, "handler error");
-                // Attempt to relay the error back to the user.
-                send_reply(
-                    &client,
-                    &app_id,
-                    &app_password,
-                    &service_url,
-                    &activity_ref,
-                    &format!("Error: {}", e),
-                )
-                .await;
+                // Relay the error back to the user.
+                vec![format!("Error: {}", e)]
             }
+        };
+        if conversation_id.is_empty() {
+            tracing::warn!(
+                channel = "teams",
+                "no conversation ID in activity, cannot reply"
+            );
+            return;
+        }
+        let queued = QueuedDelivery::text_chunks(
+            "teams",
+            format!("conversation:{} {}", service_url, conversation_id),
+            chunks,
+        )
+        .with_reply_to(activity_id);
+        match delivery.submit(queued).await {
+            Ok(true) => {}
+            Ok(false) => tracing::warn!(channel = "teams", "reply queued for retry"),
+            Err(e) => tracing::error!(channel = "teams", error = %e, "failed to queue reply"),
         }
     });
 
@@ -322,7 +312,14 @@ async fn handle_message(
 }
 
 /// Run the Microsoft Teams bot adapter (Bot Framework webhook mode).
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let teams_configs: Vec<crate::config::TeamsBotConfig> = config.channel_configs("teams");
     let teams_config = teams_configs
         .first()
@@ -358,11 +355,19 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
         "adapter started"
     );
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(TeamsSender {
+            client: reqwest::Client::new(),
+            app_id: teams_config.app_id.clone(),
+            app_password,
+        }))
+        .await;
+
     let state = Arc::new(AppState {
         agent_session,
+        delivery,
         allowlist,
-        app_id: teams_config.app_id.clone(),
-        app_password,
     });
 
     let app = Router::new()
@@ -19,7 +19,10 @@ use crate::channels::reactions;
 use crate::config::bots::resolve_secret;
 use crate::config::SynapseConfig;
 use crate::gateway::messages::sender::{ChannelSender, SendResult};
-use crate::gateway::messages::{Attachment, ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::{
+    Attachment, ChannelInfo, ChatInfo, DeliveryService, InboundMessage, Interaction,
+    QueuedDelivery, SenderInfo,
+};
 use crate::gateway::presence::now_ms;
 use synaptic::logging;
 
@@ -28,7 +31,6 @@ use synaptic::logging;
 // ---------------------------------------------------------------------------
 
 /// Outbound sender for the Telegram channel.
-#[allow(dead_code)]
 pub struct TelegramSender {
     /// HTTP client for making API calls.
     pub client: reqwest::Client,
@@ -46,7 +48,7 @@ impl ChannelSender for TelegramSender {
         &self,
         target: &DeliveryContext,
         content: &str,
-        _meta: Option<&serde_json::Value>,
+        meta: Option<&serde_json::Value>,
     ) -> crate::error::Result<SendResult> {
         let chat_id = target
             .to
@@ -54,26 +56,36 @@ impl ChannelSender for TelegramSender {
             .and_then(|s| s.strip_prefix("chat:"))
             .ok_or("missing or invalid chat_id in delivery target (expected 'chat:<id>')")?;
 
+        let interactive = meta.and_then(|m| m.get("interactive"));
+        if let Some(html) = interactive.and_then(expandable_html) {
+            let body = serde_json::json!({
+                "chat_id": chat_id,
+                "text": html,
+                "parse_mode": "HTML",
+            });
+            return Ok(SendResult {
+                message_id: self.send_message(&body).await?,
+                delivered_at_ms: now_ms(),
+            });
+        }
+
+        let keyboard = interactive.and_then(inline_keyboard);
+        // Queued text arrives split to Telegram's limit; this only splits
+        // what rendering pushed over it.
         let chunks = formatter::format_for_channel(content, "telegram", 4096);
+        let last = chunks.len().saturating_sub(1);
         let mut last_message_id: Option<String> = None;
-        for chunk in chunks {
-            let resp: serde_json::Value = self
-                .client
-                .post(format!("{}/sendMessage", self.base_url))
-                .json(&serde_json::json!({
-                    "chat_id": chat_id,
-                    "text": chunk,
-                }))
-                .send()
-                .await?
-                .json()
-                .await?;
-            if let Some(msg_id) = resp
-                .get("result")
-                .and_then(|r| r.get("message_id"))
-                .and_then(|v| v.as_i64())
-            {
-                last_message_id = Some(msg_id.to_string());
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let mut body = serde_json::json!({
+                "chat_id": chat_id,
+                "text": chunk,
+            });
+            // Buttons go on the last chunk.
+            if let (true, Some(keyboard)) = (i == last, keyboard.as_ref()) {
+                body["reply_markup"] = keyboard.clone();
+            }
+            if let Some(msg_id) = self.send_message(&body).await? {
+                last_message_id = Some(msg_id);
             }
         }
 
@@ -84,8 +96,133 @@ impl ChannelSender for TelegramSender {
     }
 }
 
+impl TelegramSender {
+    /// Call `sendMessage` and return the new message's ID.
+    async fn send_message(&self, body: &serde_json::Value) -> crate::error::Result<Option<String>> {
+        let resp: serde_json::Value = self
+            .client
+            .post(format!("{}/sendMessage", self.base_url))
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if !resp.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+            let err = resp
+                .get("description")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown error");
+            return Err(format!("sendMessage failed: {}", err).into());
+        }
+        Ok(resp
+            .get("result")
+            .and_then(|r| r.get("message_id"))
+            .and_then(|v| v.as_i64())
+            .map(|id| id.to_string()))
+    }
+}
+
+/// Inline keyboard markup for a `{"type":"buttons"}` interactive payload,
+/// one row of buttons whose `callback_data` is the button value.
+fn inline_keyboard(interactive: &serde_json::Value) -> Option<serde_json::Value> {
+    if interactive.get("type").and_then(|v| v.as_str()) != Some("buttons") {
+        return None;
+    }
+    let row: Vec<serde_json::Value> = interactive
+        .get("buttons")?
+        .as_array()?
+        .iter()
+        .filter_map(|b| {
+            Some(serde_json::json!({
+                "text": b.get("text")?.as_str()?,
+                "callback_data": b.get("value")?.as_str()?,
+            }))
+        })
+        .collect();
+    Some(serde_json::json!({"inline_keyboard": [row]}))
+}
+
+/// HTML for a `{"type":"collapsible"}` interactive payload: the title, then
+/// each section as an expandable blockquote.  `None` if it wouldn't fit in
+/// one message, in which case the plain text is sent instead.
+fn expandable_html(interactive: &serde_json::Value) -> Option<String> {
+    if interactive.get("type").and_then(|v| v.as_str()) != Some("collapsible") {
+        return None;
+    }
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let collapsed = interactive
+        .get("collapsed")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let quote = if collapsed {
+        "<blockquote expandable>"
+    } else {
+        "<blockquote>"
+    };
+    let mut html = String::new();
+    if let Some(title) = interactive.get("title").and_then(|v| v.as_str()) {
+        html.push_str(&format!("<b>{}</b>\n", escape(title)));
+    }
+    for section in interactive.get("sections")?.as_array()? {
+        html.push_str(&format!(
+            "<b>{}</b>\n{}{}</blockquote>\n",
+            escape(section.get("title")?.as_str()?),
+            quote,
+            escape(section.get("text")?.as_str()?)
+        ));
+    }
+    (html.chars().count() <= 4096).then_some(html)
+}
+
+/// Hand an inline keyboard press to the delivery service and show its
+/// acknowledgement as the callback answer.
+async fn handle_callback_query(
+    client: &reqwest::Client,
+    base_url: &str,
+    delivery: &DeliveryService,
+    query: &serde_json::Value,
+) {
+    let (Some(id), Some(data)) = (
+        query.get("id").and_then(|v| v.as_str()),
+        query.get("data").and_then(|v| v.as_str()),
+    ) else {
+        return;
+    };
+    let interaction = Interaction {
+        channel: "telegram".into(),
+        user_id: query
+            .pointer("/from/id")
+            .and_then(|v| v.as_i64())
+            .map(|id| id.to_string()),
+        value: data.to_string(),
+    };
+    let ack = delivery.handle_interaction(&interaction).await;
+    // Always answer so the client stops showing a spinner.
+    let mut answer = serde_json::json!({"callback_query_id": id});
+    if let Some(ack) = ack {
+        answer["text"] = ack.into();
+    }
+    let _ = client
+        .post(format!("{}/answerCallbackQuery", base_url))
+        .json(&answer)
+        .send()
+        .await;
+}
+
 /// Run the Telegram bot adapter using Long Polling.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let tg_configs: Vec<crate::config::TelegramBotConfig> = config.channel_configs("telegram");
     let tg_config = tg_configs
         .first()
@@ -123,6 +260,14 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
     let client = reqwest::Client::new();
     let base_url = format!("https://api.telegram.org/bot{}", bot_token);
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(TelegramSender {
+            client: client.clone(),
+            base_url: base_url.clone(),
+        }))
+        .await;
+
     let mut offset: i64 = 0;
 
     loop {
@@ -168,6 +313,17 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
                 .unwrap_or(0);
             offset = update_id + 1;
 
+            // Inline keyboard presses (e.g. exec approvals)
+            if let Some(query) = update.get("callback_query").cloned() {
+                let http = client.clone();
+                let base = base_url.clone();
+                let delivery = delivery.clone();
+                tokio::spawn(async move {
+                    handle_callback_query(&http, &base, &delivery, &query).await;
+                });
+                continue;
+            }
+
             let message = match update.get("message") {
                 Some(m) => m,
                 None => continue,
@@ -257,6 +413,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
             // Process in background
             let session = agent_session.clone();
+            let delivery = delivery.clone();
             let base = base_url.clone();
             let http = client.clone();
             let sender_id = user_id.clone().unwrap_or_default();
@@ -271,6 +428,26 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
                 let _guard = span.enter();
                 tracing::info!("processing telegram message");
 
+                // Replies answering an interactive prompt (e.g. "YES <code>")
+                // don't go to the agent.
+                let interaction = Interaction {
+                    channel: "telegram".into(),
+                    user_id: Some(sender_id.clone()),
+                    value: text.clone(),
+                };
+                if let Some(ack) = delivery.handle_interaction(&interaction).await {
+                    let queued = QueuedDelivery::text_chunks(
+                        "telegram",
+                        format!("chat:{}", chat_id),
+                        vec![ack],
+                    )
+                    .with_reply_to(Some(message_id.to_string()));
+                    if let Err(e) = delivery.submit(queued).await {
+                        tracing::error!(channel = "telegram", error = %e, "failed to queue reply");
+                    }
+                    return;
+                }
+
                 // React with eyes to indicate processing
                 reactions::telegram_react(&base, chat_id, message_id, "\u{1f440}").await;
 
@@ -309,21 +486,26 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
                 match session.handle_message(msg, RunContext::default()).await {
                     Ok(reply) => {
-                        // Split long replies into chunks
-                        let chunks =
-                            formatter::format_for_channel(&reply.content, "telegram", 4096);
-                        for chunk in chunks {
-                            let _ = http
-                                .post(format!("{}/sendMessage", base))
-                                .json(&serde_json::json!({
-                                    "chat_id": chat_id,
-                                    "text": chunk,
-                                }))
-                                .send()
-                                .await;
+                        // The delivery queue splits long replies and retries
+                        // any chunk Telegram rejects.
+                        let queued = QueuedDelivery::text_chunks(
+                            "telegram",
+                            format!("chat:{}", chat_id),
+                            vec![reply.content],
+                        );
+                        match delivery.submit(queued).await {
+                            Ok(true) => {
+                                // React with checkmark on success
+                                reactions::telegram_react(&base, chat_id, message_id, "\u{2705}")
+                                    .await;
+                            }
+                            Ok(false) => {
+                                tracing::warn!(channel = "telegram", "reply queued for retry");
+                            }
+                            Err(e) => {
+                                tracing::error!(channel = "telegram", error = %e, "failed to queue reply");
+                            }
                         }
-                        // React with checkmark on success
-                        reactions::telegram_react(&base, chat_id, message_id, "\u{2705}").await;
                     }
                     Err(e) => {
                         eprintln!("Telegram handler error: {}", e);
@@ -1,24 +1,87 @@
 use std::sync::atomic::{AtomicU8, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
+use tokio::sync::mpsc::UnboundedSender;
 
 use crate::agent;
 use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{BotAllowlist, SynapseConfig, TwitchBotConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the Twitch channel.
+///
+/// Writes through the current chat connection; while the adapter is
+/// reconnecting sends fail and the delivery queue retries them.
+#[derive(Default)]
+pub struct TwitchSender {
+    /// Line sink of the current connection, if any.
+    conn: Mutex<Option<UnboundedSender<String>>>,
+}
+
+#[async_trait]
+impl ChannelSender for TwitchSender {
+    fn channel_id(&self) -> &str {
+        "twitch"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let channel = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("channel:"))
+            .ok_or("missing Twitch channel in delivery target (expected 'channel:<#name>')")?;
+        let conn = self
+            .conn
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("not connected to Twitch chat")?;
+
+        for irc_line in content.lines() {
+            conn.send(format!("PRIVMSG {} :{}", channel, irc_line))
+                .map_err(|_| "Twitch chat connection closed")?;
+        }
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
 
 /// Run the Twitch bot adapter using IRC over TCP (irc.chat.twitch.tv:6667).
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so replies made while disconnected are sent after reconnecting.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let twitch_configs: Vec<crate::config::TwitchBotConfig> = config.channel_configs("twitch");
     let twitch_config = twitch_configs
         .first()
@@ -38,10 +101,23 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
         );
     }
 
+    let sender = Arc::new(TwitchSender::default());
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery.register_sender(sender.clone()).await;
+
     tracing::info!(channel = "twitch", "adapter started (IRC mode)");
 
     loop {
-        match run_twitch_irc(twitch_config, agent_session.clone(), &allowlist).await {
+        let result = run_twitch_irc(
+            twitch_config,
+            agent_session.clone(),
+            &allowlist,
+            &sender,
+            &delivery,
+        )
+        .await;
+        *sender.conn.lock().unwrap() = None;
+        match result {
             Ok(()) => break,
             Err(e) => {
                 tracing::warn!(channel = "twitch", error = %e, "connection error, reconnecting in 10s");
@@ -57,6 +133,8 @@ async fn run_twitch_irc(
     config: &TwitchBotConfig,
     agent_session: Arc<AgentSession>,
     allowlist: &BotAllowlist,
+    sender: &TwitchSender,
+    delivery: &Arc<DeliveryService>,
 ) -> crate::error::Result<()> {
     let addr = "irc.chat.twitch.tv:6667";
     let stream = TcpStream::connect(addr).await?;
@@ -104,6 +182,8 @@ async fn run_twitch_irc(
         send(format!("JOIN {}", ch));
         tracing::info!(channel = "twitch", twitch_channel = %ch, "joining channel");
     }
+    // Replies queued while disconnected go out on their next retry.
+    *sender.conn.lock().unwrap() = Some(tx.clone());
 
     let own_nick = config.nick.to_lowercase();
     let mut lines = BufReader::new(reader_half).lines();
@@ -136,7 +216,7 @@ async fn run_twitch_irc(
 
         let session_key = parsed.target.clone();
         let session = agent_session.clone();
-        let tx_clone = tx.clone();
+        let delivery = delivery.clone();
         let reply_target = parsed.target.clone();
         let message = parsed.message.clone();
         let sender_nick = parsed.sender_nick.clone();
@@ -166,10 +246,16 @@ async fn run_twitch_irc(
             match session.handle_message(msg, RunContext::default()).await {
                 Ok(reply) => {
                     let chunks = formatter::format_for_channel(&reply.content, "twitch", 400);
-                    for chunk in chunks {
-                        for irc_line in chunk.lines() {
-                            let _ =
-                                tx_clone.send(format!("PRIVMSG {} :{}", reply_target, irc_line));
+                    let queued = QueuedDelivery::text_chunks(
+                        "twitch",
+                        format!("channel:{}", reply_target),
+                        chunks,
+                    );
+                    match delivery.submit(queued).await {
+                        Ok(true) => {}
+                        Ok(false) => tracing::warn!(channel = "twitch", "reply queued for retry"),
+                        Err(e) => {
+                            tracing::error!(channel = "twitch", error = %e, "failed to queue reply")
                         }
                     }
                 }
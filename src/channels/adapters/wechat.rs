@@ -14,11 +14,16 @@ use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{BotAllowlist, SynapseConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
 
 /// WeCom Bot Webhook URL template.
 const WECOM_WEBHOOK_BASE: &str = "https://qyapi.weixin.qq.com/cgi-bin/webhook/send";
@@ -27,11 +32,68 @@ const WECOM_WEBHOOK_BASE: &str = "https://qyapi.weixin.qq.com/cgi-bin/webhook/se
 #[allow(dead_code)]
 struct AppState {
     agent_session: Arc<AgentSession>,
+    delivery: Arc<DeliveryService>,
     allowlist: BotAllowlist,
-    webhook_key: String,
     token: Option<String>,
 }
 
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the WeCom channel.
+///
+/// Posts to the bot webhook, which is bound to a single group chat; the
+/// delivery target only records who the reply is for.
+pub struct WeChatSender {
+    pub client: reqwest::Client,
+    pub webhook_key: String,
+}
+
+#[async_trait]
+impl ChannelSender for WeChatSender {
+    fn channel_id(&self) -> &str {
+        "wechat"
+    }
+
+    async fn send(
+        &self,
+        _target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let body = serde_json::json!({
+            "msgtype": "text",
+            "text": {
+                "content": content,
+            }
+        });
+        let resp: serde_json::Value = self
+            .client
+            .post(WECOM_WEBHOOK_BASE)
+            .query(&[("key", &self.webhook_key)])
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let errcode = resp.get("errcode").and_then(|v| v.as_i64()).unwrap_or(0);
+        if errcode != 0 {
+            let err = resp
+                .get("errmsg")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown error");
+            return Err(format!("webhook send failed ({}): {}", errcode, err).into());
+        }
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
+
 /// Query parameters for GET verification request.
 #[derive(Debug, serde::Deserialize)]
 struct VerifyQuery {
@@ -196,10 +258,10 @@ async fn handle_callback(
     // Use sender ID as session key (WeCom bot webhook replies go to the group/chat,
     // not a specific user, so we maintain per-user conversation context)
     let session_key = format!("wechat:{}", sender_id);
-    let webhook_key = state.webhook_key.clone();
 
     // Process in background so we respond to WeCom quickly (5s timeout requirement)
     let session = state.agent_session.clone();
+    let delivery = state.delivery.clone();
     tokio::spawn(async move {
         let channel_info = ChannelInfo {
             platform: "wechat".into(),
@@ -221,37 +283,19 @@ async fn handle_callback(
             chat_info,
         );
         msg.finalize();
-        match session.handle_message(msg, RunContext::default()).await {
-            Ok(reply) => {
-                let client = reqwest::Client::new();
-                let webhook_url = format!("{}?key={}", WECOM_WEBHOOK_BASE, webhook_key);
-                let chunks = formatter::format_for_channel(&reply.content, "wechat", 2048);
-                for chunk in chunks {
-                    let body = serde_json::json!({
-                        "msgtype": "text",
-                        "text": {
-                            "content": chunk,
-                        }
-                    });
-
-                    if let Err(e) = client.post(&webhook_url).json(&body).send().await {
-                        tracing::error!(channel = "wechat", error = %e, "failed to send reply");
-                    }
-                }
-            }
+        let chunks = match session.handle_message(msg, RunContext::default()).await {
+            Ok(reply) => formatter::format_for_channel(&reply.content, "wechat", 2048),
             Err(e) => {
                 tracing::error!(channel = "wechat", error = %e, "handler error");
-                // Attempt to send error back via webhook
-                let client = reqwest::Client::new();
-                let webhook_url = format!("{}?key={}", WECOM_WEBHOOK_BASE, webhook_key);
-                let body = serde_json::json!({
-                    "msgtype": "text",
-                    "text": {
-                        "content": format!("Error: {}", e),
-                    }
-                });
-                let _ = client.post(&webhook_url).json(&body).send().await;
+                // Send the error back via webhook
+                vec![format!("Error: {}", e)]
             }
+        };
+        let queued = QueuedDelivery::text_chunks("wechat", format!("user:{}", sender_id), chunks);
+        match delivery.submit(queued).await {
+            Ok(true) => {}
+            Ok(false) => tracing::warn!(channel = "wechat", "reply queued for retry"),
+            Err(e) => tracing::error!(channel = "wechat", error = %e, "failed to queue reply"),
         }
     });
 
@@ -259,7 +303,14 @@ async fn handle_callback(
 }
 
 /// Run the WeCom (WeChat Work) bot adapter.
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let wc_configs: Vec<crate::config::WeChatBotConfig> = config.channel_configs("wechat");
     let wc_config = wc_configs
         .first()
@@ -288,10 +339,18 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
         );
     }
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(WeChatSender {
+            client: reqwest::Client::new(),
+            webhook_key,
+        }))
+        .await;
+
     let state = Arc::new(AppState {
         agent_session,
+        delivery,
         allowlist,
-        webhook_key,
         token: wc_config.token.clone(),
     });
 
@@ -13,11 +13,65 @@ use crate::channels::formatter;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::SynapseConfig;
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the WhatsApp channel (bridge `POST /send`).
+pub struct WhatsAppSender {
+    pub client: reqwest::Client,
+    /// Bridge URL, without a trailing slash.
+    pub bridge_url: String,
+    pub api_key: Option<String>,
+}
+
+#[async_trait]
+impl ChannelSender for WhatsAppSender {
+    fn channel_id(&self) -> &str {
+        "whatsapp"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let chat_id = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("chat:"))
+            .ok_or("missing chat in delivery target (expected 'chat:<jid>')")?;
+
+        let mut req =
+            self.client
+                .post(format!("{}/send", self.bridge_url))
+                .json(&serde_json::json!({
+                    "to": chat_id,
+                    "text": content,
+                }));
+        if let Some(ref key) = self.api_key {
+            req = req.bearer_auth(key);
+        }
+        req.send().await?.error_for_status()?;
+
+        Ok(SendResult {
+            message_id: None,
+            delivered_at_ms: now_ms(),
+        })
+    }
+}
 
 /// Run the WhatsApp bot adapter using a Baileys-compatible REST/WebSocket bridge.
 ///
@@ -37,7 +91,14 @@ use synaptic::core::{
 ///   "body": "Hello!"
 /// }
 /// ```
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let wa_configs: Vec<crate::config::WhatsAppBotConfig> = config.channel_configs("whatsapp");
     let wa_config = wa_configs
         .first()
@@ -71,6 +132,15 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
         );
     }
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(WhatsAppSender {
+            client: reqwest::Client::new(),
+            bridge_url: bridge_url.clone(),
+            api_key: api_key.clone(),
+        }))
+        .await;
+
     tracing::info!(channel = "whatsapp", "adapter started");
 
     loop {
@@ -79,6 +149,7 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
             api_key.as_deref(),
             agent_session.clone(),
             &allowlist,
+            &delivery,
         )
         .await
         {
@@ -99,6 +170,7 @@ async fn run_ws_loop(
     api_key: Option<&str>,
     agent_session: Arc<AgentSession>,
     allowlist: &crate::config::BotAllowlist,
+    delivery: &Arc<DeliveryService>,
 ) -> crate::error::Result<()> {
     // Build the WebSocket URL — replace http(s) scheme with ws(s).
     let ws_url = if bridge_url.starts_with("https://") {
@@ -201,8 +273,7 @@ async fn run_ws_loop(
 
         // Process the message in a background task so we don't block the event loop.
         let session = agent_session.clone();
-        let bridge = bridge_url.to_string();
-        let api_key_owned = api_key.map(|k| k.to_string());
+        let delivery = delivery.clone();
         tokio::spawn(async move {
             let channel_info = ChannelInfo {
                 platform: "whatsapp".into(),
@@ -232,18 +303,18 @@ async fn run_ws_loop(
             match session.handle_message(msg, RunContext::default()).await {
                 Ok(reply) => {
                     let chunks = formatter::format_for_channel(&reply.content, "whatsapp", 2000);
-                    let http = reqwest::Client::new();
-                    for chunk in chunks {
-                        let send_url = format!("{}/send", bridge);
-                        let mut req = http.post(&send_url).json(&serde_json::json!({
-                            "to": chat_id,
-                            "text": chunk,
-                        }));
-                        if let Some(ref key) = api_key_owned {
-                            req = req.bearer_auth(key);
+                    let queued = QueuedDelivery::text_chunks(
+                        "whatsapp",
+                        format!("chat:{}", chat_id),
+                        chunks,
+                    );
+                    match delivery.submit(queued).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            tracing::warn!(channel = "whatsapp", "reply queued for retry");
                         }
-                        if let Err(e) = req.send().await {
-                            tracing::error!(channel = "whatsapp", error = %e, "send error");
+                        Err(e) => {
+                            tracing::error!(channel = "whatsapp", error = %e, "failed to queue reply");
                         }
                     }
                 }
@@ -9,17 +9,83 @@ use crate::agent;
 use crate::channels::handler::AgentSession;
 use crate::config::bots::resolve_secret;
 use crate::config::{SynapseConfig, ZaloBotConfig};
-use crate::gateway::messages::{ChannelInfo, ChatInfo, InboundMessage, SenderInfo};
+use crate::gateway::messages::sender::{ChannelSender, SendResult};
+use crate::gateway::messages::{
+    ChannelInfo, ChatInfo, DeliveryService, InboundMessage, QueuedDelivery, SenderInfo,
+};
+use crate::gateway::presence::now_ms;
 use synaptic::core::{
     ChannelAdapter, ChannelCap, ChannelContext, ChannelHealth, ChannelManifest, ChannelStatus,
     HealthStatus, MessageEnvelope as CoreMessageEnvelope, Outbound, RunContext,
 };
+use synaptic::DeliveryContext;
 
 #[derive(Clone)]
 struct AppState {
     agent_session: Arc<AgentSession>,
     allowlist: crate::config::BotAllowlist,
-    access_token: String,
+    delivery: Arc<DeliveryService>,
+}
+
+// ---------------------------------------------------------------------------
+// ChannelSender implementation
+// ---------------------------------------------------------------------------
+
+/// Outbound sender for the Zalo channel (OA customer-service messages).
+pub struct ZaloOaSender {
+    pub client: reqwest::Client,
+    pub access_token: String,
+}
+
+#[async_trait]
+impl ChannelSender for ZaloOaSender {
+    fn channel_id(&self) -> &str {
+        "zalo"
+    }
+
+    async fn send(
+        &self,
+        target: &DeliveryContext,
+        content: &str,
+        _meta: Option<&serde_json::Value>,
+    ) -> crate::error::Result<SendResult> {
+        let user_id = target
+            .to
+            .as_deref()
+            .and_then(|s| s.strip_prefix("user:"))
+            .ok_or("missing user in delivery target (expected 'user:<id>')")?;
+
+        let resp: serde_json::Value = self
+            .client
+            .post("https://openapi.zalo.me/v3.0/oa/message/cs")
+            .header("access_token", &self.access_token)
+            .json(&serde_json::json!({
+                "recipient": {"user_id": user_id},
+                "message": {"text": content}
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let error = resp.get("error").and_then(|v| v.as_i64()).unwrap_or(0);
+        if error != 0 {
+            let message = resp
+                .get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown error");
+            return Err(format!("send failed ({}): {}", error, message).into());
+        }
+
+        Ok(SendResult {
+            message_id: resp
+                .get("data")
+                .and_then(|d| d.get("message_id"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            delivered_at_ms: now_ms(),
+        })
+    }
 }
 
 #[derive(Deserialize)]
@@ -47,7 +113,14 @@ struct ZaloMessage {
 }
 
 /// Run the Zalo bot adapter (webhook HTTP mode).
-pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate::error::Result<()> {
+///
+/// Replies are routed through `delivery` (or a standalone delivery service if
+/// `None`) so failed sends are retried instead of dropped.
+pub async fn run(
+    config: &SynapseConfig,
+    model_override: Option<&str>,
+    delivery: Option<Arc<DeliveryService>>,
+) -> crate::error::Result<()> {
     let zalo_configs: Vec<crate::config::ZaloBotConfig> = config.channel_configs("zalo");
     let zalo_config = zalo_configs
         .first()
@@ -66,10 +139,18 @@ pub async fn run(config: &SynapseConfig, model_override: Option<&str>) -> crate:
 
     let port = zalo_config.port.unwrap_or(8092);
 
+    let delivery = super::resolve_delivery(config, delivery);
+    delivery
+        .register_sender(Arc::new(ZaloOaSender {
+            client: reqwest::Client::new(),
+            access_token,
+        }))
+        .await;
+
     let state = AppState {
         agent_session,
         allowlist: zalo_config.allowlist.clone(),
-        access_token,
+        delivery,
     };
 
     let app = Router::new()
@@ -108,7 +189,7 @@ async fn handle_webhook(
     }
 
     let session = state.agent_session.clone();
-    let access_token = state.access_token.clone();
+    let delivery = state.delivery.clone();
 
     tokio::spawn(async move {
         let channel_info = ChannelInfo {
@@ -134,16 +215,18 @@ async fn handle_webhook(
         match session.handle_message(msg, RunContext::default()).await {
             Ok(reply) => {
                 // Send reply via Zalo OA API
-                let client = reqwest::Client::new();
-                let _ = client
-                    .post("https://openapi.zalo.me/v3.0/oa/message/cs")
-                    .header("access_token", &access_token)
-                    .json(&serde_json::json!({
-                        "recipient": {"user_id": sender_id},
-                        "message": {"text": reply.content}
-                    }))
-                    .send()
-                    .await;
+                let queued = QueuedDelivery::text_chunks(
+                    "zalo",
+                    format!("user:{}", sender_id),
+                    vec![reply.content],
+                );
+                match delivery.submit(queued).await {
+                    Ok(true) => {}
+                    Ok(false) => tracing::warn!(channel = "zalo", "reply queued for retry"),
+                    Err(e) => {
+                        tracing::error!(channel = "zalo", error = %e, "failed to queue reply")
+                    }
+                }
             }
             Err(e) => {
                 tracing::error!(channel = "zalo", error = %e, "agent error");
@@ -99,32 +99,40 @@ pub fn chunk_message(text: &str, max_len: usize) -> Vec<String> {
             break;
         }
 
-        let slice = &remaining[..max_len];
+        // Byte limit, moved back to a char boundary.
+        let mut cut = max_len;
+        while !remaining.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        if cut == 0 {
+            cut = remaining.chars().next().map_or(1, char::len_utf8);
+        }
+        let slice = &remaining[..cut];
 
         // Check if we're inside a code block — if so, try to find the closing ```
         // within the slice, or extend the chunk to include the full code block.
         let split_at = if let Some(code_fence_start) = find_open_code_fence(slice) {
             // There's an unclosed code fence in the slice.
             // Try to find the closing fence after the slice boundary.
-            let after = &remaining[max_len..];
+            let after = &remaining[cut..];
             if let Some(close_offset) = after.find("\n```") {
-                let full_block_end = max_len + close_offset + 4; // include "\n```"
-                                                                 // Skip trailing newline after the closing fence
+                // Include the "\n```", then skip the rest of the closing fence line
+                let full_block_end = cut + close_offset + 4;
                 remaining[full_block_end..]
                     .find('\n')
                     .map(|i| full_block_end + i)
                     .unwrap_or(full_block_end)
             } else {
                 // No closing fence found — split before the code fence
-                if code_fence_start > max_len / 4 {
+                if code_fence_start > cut / 4 {
                     code_fence_start
                 } else {
                     // Code fence is near the start; fall back to normal splitting
-                    find_best_split_point(slice, max_len)
+                    find_best_split_point(slice, cut)
                 }
             }
         } else {
-            find_best_split_point(slice, max_len)
+            find_best_split_point(slice, cut)
         };
 
         chunks.push(remaining[..split_at].trim_end().to_string());
@@ -172,6 +180,23 @@ fn find_open_code_fence(text: &str) -> Option<usize> {
     last_open_pos
 }
 
+/// Message size limit of `channel`, as used by the `chunk_*` helpers below;
+/// 4000 for channels not listed.
+pub fn chunk_limit(channel: &str) -> usize {
+    match channel {
+        "discord" | "whatsapp" => 2000,
+        "telegram" | "lark" | "feishu" | "signal" | "googlechat" => 4096,
+        "dingtalk" => 20000,
+        "mattermost" => 16383,
+        "matrix" => 60000,
+        "imessage" => 10000,
+        "line" => 5000,
+        "wechat" => 2048,
+        "irc" | "twitch" => 400,
+        _ => 4000,
+    }
+}
+
 /// Chunk for Discord (2000 char limit).
 #[allow(dead_code)]
 pub fn chunk_discord(text: &str) -> Vec<String> {
@@ -281,6 +306,14 @@ mod tests {
         assert!(chunks[0].len() <= 30);
     }
 
+    #[test]
+    fn splits_multibyte_text_on_char_boundaries() {
+        let text = "飞书".repeat(100);
+        let chunks = chunk_message(&text, 100);
+        assert!(chunks.iter().all(|c| c.len() <= 100));
+        assert_eq!(chunks.concat(), text);
+    }
+
     #[test]
     fn splits_at_newline() {
         let text = "Line one\nLine two\nLine three\nLine four";
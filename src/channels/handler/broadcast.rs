@@ -1,4 +1,5 @@
 use super::*;
+use crate::config::{AgentBroadcastGroup, BroadcastAggregation};
 use crate::gateway::messages::InboundMessage;
 use synaptic::core::RunContext;
 
@@ -6,152 +7,90 @@ impl AgentSession {
     /// Handle broadcast: fan out to multiple agents in parallel.
     ///
     /// Each agent processes the message independently with its own session/prompt/memory.
-    /// Replies are collected and merged into a single response.
+    /// Replies are collected and merged into a single response — under the
+    /// aggregated strategy by the group's synthesizer agent.
     pub(super) async fn handle_broadcast_message(
         &self,
         msg: &InboundMessage,
         agents: &[ResolvedAgentInfo],
-        strategy: &crate::config::BroadcastStrategy,
+        group: &AgentBroadcastGroup,
     ) -> crate::error::Result<AgentReply> {
         use crate::config::BroadcastStrategy;
 
         let request_id = msg.request_id.clone();
-        let session_key = msg.session_key.clone();
         let content_blocks = self.download_attachments(&msg.attachments).await;
 
         // Build a DeliveryContext from the inbound message for the reply
         let delivery_target = Self::delivery_context_from_inbound(msg);
 
-        match strategy {
+        match group.strategy {
             BroadcastStrategy::Parallel | BroadcastStrategy::Aggregated => {
-                // Spawn all agents concurrently
-                let mut set = tokio::task::JoinSet::new();
-                for agent_info in agents {
-                    let _sid_key = format!(
-                        "agent:{}:{}",
-                        agent_info.id,
-                        session_key.trim_start_matches("agent:default:")
-                    );
-                    let text = msg.content.clone();
-                    let blocks = content_blocks.clone();
-                    let agent_id = agent_info.id.clone();
-                    let prompt = agent_info.prompt_override.clone();
-
-                    // Create agent info for the spawned task
-                    let info = ResolvedAgentInfo {
-                        id: agent_id.clone(),
-                        model_override: agent_info.model_override.clone(),
-                        prompt_override: prompt,
-                        def: agent_info.def.clone(),
-                    };
-
-                    let memory_store = self.session_mgr.memory();
-                    let model = self.model.clone();
-                    let config = self.config.clone();
-                    let deep = self.deep_agent;
-
-                    let checkpointer = Arc::new(self.session_mgr.checkpointer());
-
-                    set.spawn(async move {
-                        // Use a unique session for each broadcast agent
-                        let memory = memory_store;
-                        let sid = uuid::Uuid::new_v4().to_string();
-
-                        // Build messages
-                        let mut messages = memory.load(&sid).await.unwrap_or_default();
-                        if messages.is_empty() {
-                            let sys_prompt = info
-                                .prompt_override
-                                .clone()
-                                .or_else(|| config.agent_config().system_prompt.clone())
-                                .unwrap_or_else(|| "You are a helpful AI assistant.".into());
-                            messages.push(Message::system(&sys_prompt));
-                        }
-                        let human_msg = if blocks.is_empty() {
-                            Message::human(&text)
-                        } else {
-                            Message::human(&text).with_content_blocks(blocks)
-                        };
-                        memory.append(&sid, human_msg.clone()).await.ok();
-                        messages.push(human_msg);
-
-                        if deep {
-                            let cwd =
-                                std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-                            let agent = agent::build_deep_agent_with_callback(
-                                model,
-                                &config,
-                                &cwd,
-                                checkpointer,
-                                vec![], // broadcast mode: no MCP tools
-                                None,
-                                Some(Arc::new(agent::BotSafetyCallback)),
-                                None,
-                                None,
-                                None,
-                                "broadcast",
-                                None,
-                                None, // no event bus in broadcast mode
-                                None, // no plugin registry in broadcast mode
-                                None, // no channel registry in broadcast mode
-                                crate::agent::SessionKind::Full,
-                                &[], // no bundle skills in broadcast mode
-                            )
-                            .await
-                            .map_err(|e| {
-                                crate::error::SynapseError::Channel(format!("agent build: {}", e))
-                            })?;
-
-                            let initial_state = MessageState::with_messages(messages);
-                            let result = agent.invoke(initial_state).await.map_err(|e| {
-                                crate::error::SynapseError::Channel(format!("agent error: {}", e))
-                            })?;
-                            let response = extract_final_response(&result.into_state().messages);
-                            Ok::<(String, String), crate::error::SynapseError>((agent_id, response))
-                        } else {
-                            let req = ChatRequest::new(messages);
-                            let resp = model.chat(req).await.map_err(|e| {
-                                crate::error::SynapseError::Channel(format!("chat error: {}", e))
-                            })?;
-                            let text = resp.message.content().to_string();
-                            Ok((agent_id, text))
-                        }
-                    });
-                }
+                // Run all agents concurrently, each in its own persistent session
+                let mut pending: futures::stream::FuturesUnordered<_> = agents
+                    .iter()
+                    .map(|agent_info| {
+                        self.run_broadcast_agent(msg, group, agent_info, &content_blocks)
+                    })
+                    .collect();
 
-                // Collect results
+                // Collect results, giving up on agents still running at the deadline
+                let deadline = tokio::time::Instant::now()
+                    + std::time::Duration::from_secs(group.timeout_secs);
                 let mut replies: Vec<(String, String)> = Vec::new();
-                while let Some(result) = set.join_next().await {
-                    match result {
-                        Ok(Ok((agent_id, response))) => {
+                loop {
+                    match tokio::time::timeout_at(deadline, pending.next()).await {
+                        Ok(Some(Ok((agent_id, response)))) => {
                             tracing::info!(agent = %agent_id, "broadcast agent completed");
                             replies.push((agent_id, response));
                         }
-                        Ok(Err(e)) => {
+                        Ok(Some(Err(e))) => {
                             tracing::warn!(error = %e, "broadcast agent failed");
                         }
-                        Err(e) => {
-                            tracing::warn!(error = %e, "broadcast task panicked");
+                        Ok(None) => break,
+                        Err(_) => {
+                            // Dropping the remaining futures cancels those runs.
+                            tracing::warn!(
+                                broadcast = %group.name,
+                                pending = pending.len(),
+                                timeout_secs = group.timeout_secs,
+                                "broadcast timed out, dropping late agents"
+                            );
+                            break;
                         }
                     }
                 }
+                drop(pending);
+                // Keep the group's agent order regardless of completion order
+                replies.sort_by_key(|(id, _)| agents.iter().position(|a| &a.id == id));
 
-                // Merge responses
-                let merged = if replies.len() == 1 {
-                    replies.into_iter().next().unwrap().1
+                let mut payloads = Vec::new();
+                let merged = if group.strategy == BroadcastStrategy::Aggregated && replies.len() > 1
+                {
+                    match self.synthesize(&msg.content, group, &replies).await {
+                        Ok(text) => text,
+                        Err(e) => {
+                            tracing::warn!(broadcast = %group.name, error = %e, "synthesizer failed, concatenating answers");
+                            concatenate_replies(&replies)
+                        }
+                    }
+                } else if replies.len() == 1 {
+                    replies[0].1.clone()
                 } else {
-                    replies
-                        .iter()
-                        .map(|(agent_id, response)| format!("**[{}]**\n\n{}", agent_id, response))
-                        .collect::<Vec<_>>()
-                        .join("\n\n---\n\n")
+                    concatenate_replies(&replies)
                 };
+                payloads.push(OutboundPayload {
+                    text: Some(merged.clone()),
+                    ..Default::default()
+                });
+                if group.strategy == BroadcastStrategy::Aggregated
+                    && group.attach_answers
+                    && replies.len() > 1
+                {
+                    payloads.push(answers_attachment(&replies));
+                }
 
                 Ok(AgentReply {
-                    payloads: vec![OutboundPayload {
-                        text: Some(merged.clone()),
-                        ..Default::default()
-                    }],
+                    payloads,
                     content: merged,
                     delivery_target,
                     turn_id: request_id,
@@ -161,19 +100,11 @@ impl AgentSession {
                 // Process agents one by one, return last response
                 let mut last_response = String::new();
                 for agent_info in agents {
-                    let sid = self.resolve_session(&session_key, msg).await?;
                     match self
-                        .handle_deep_agent(
-                            &sid,
-                            &msg.content,
-                            &content_blocks,
-                            RunContext::default(),
-                            agent_info,
-                            Some(&msg.request_id),
-                        )
+                        .run_broadcast_agent(msg, group, agent_info, &content_blocks)
                         .await
                     {
-                        Ok((response, _, _)) => {
+                        Ok((_, response)) => {
                             last_response = response;
                         }
                         Err(e) => {
@@ -193,4 +124,212 @@ impl AgentSession {
             }
         }
     }
+
+    /// Run one group member on `msg` in its own session.
+    ///
+    /// The session key is derived from the agent, the group and the chat, so
+    /// every member keeps its own conversation with each chat across turns.
+    /// Agents are built exactly as for single-agent routing (MCP tools,
+    /// plugins, schedule tools), just without streaming.
+    async fn run_broadcast_agent(
+        &self,
+        msg: &InboundMessage,
+        group: &AgentBroadcastGroup,
+        agent_info: &ResolvedAgentInfo,
+        content_blocks: &[ContentBlock],
+    ) -> crate::error::Result<(String, String)> {
+        let session_key =
+            crate::session::key::broadcast_key(&agent_info.id, &group.name, &msg.session_key);
+        let sid = self.resolve_session(&session_key, msg).await?;
+
+        let (response, input_tokens, output_tokens) = if self.deep_agent {
+            self.handle_deep_agent(
+                &sid,
+                &msg.content,
+                content_blocks,
+                RunContext::default(),
+                agent_info,
+                Some(&msg.request_id),
+                Self::schedule_origin(msg, agent_info),
+                Self::memory_scope(msg),
+                self.budget_guard(msg, agent_info),
+            )
+            .await?
+        } else {
+            let response = self
+                .handle_simple_chat(&sid, &msg.content, content_blocks)
+                .await?;
+            (response, 0, 0)
+        };
+
+        let token_delta = (input_tokens + output_tokens) as u64;
+        if token_delta > 0 {
+            if let Ok(Some(mut info)) = self.session_mgr.get_session(&sid).await {
+                info.total_tokens += token_delta;
+                let _ = self.session_mgr.update_session(&info).await;
+            }
+        }
+        Ok((agent_info.id.clone(), response))
+    }
+
+    /// Merge the agents' answers into one reply with the group's synthesizer.
+    async fn synthesize(
+        &self,
+        question: &str,
+        group: &AgentBroadcastGroup,
+        replies: &[(String, String)],
+    ) -> crate::error::Result<String> {
+        let synthesizer_id = group.synthesizer.as_deref().unwrap_or("default");
+        let def = self
+            .config
+            .agents
+            .as_ref()
+            .and_then(|a| a.list.iter().find(|d| d.id == synthesizer_id));
+        let model = match def.and_then(|d| d.model.as_deref()) {
+            Some(name) => agent::build_model_by_name(&self.config, name)?,
+            None => self.model.clone(),
+        };
+
+        let mut system = def
+            .and_then(|d| d.system_prompt.clone())
+            .map(|p| format!("{}\n\n", p))
+            .unwrap_or_default();
+        system.push_str(&format!(
+            "You are the judge for the '{}' broadcast group. Several agents answered \
+             the same message independently; their answers follow.\n\n{}",
+            group.name,
+            aggregation_instructions(group.aggregation)
+        ));
+        let mut prompt = format!("## Message\n\n{}\n\n## Answers", question);
+        for (agent_id, response) in replies {
+            prompt.push_str(&format!("\n\n### {}\n\n{}", agent_id, response));
+        }
+
+        let request = ChatRequest::new(vec![Message::system(&system), Message::human(&prompt)]);
+        let verdict = model
+            .chat(request)
+            .await
+            .map_err(|e| crate::error::SynapseError::Channel(format!("synthesizer: {}", e)))?
+            .message
+            .content()
+            .to_string();
+        tracing::info!(
+            broadcast = %group.name,
+            synthesizer = %synthesizer_id,
+            mode = ?group.aggregation,
+            answers = replies.len(),
+            "broadcast answers aggregated"
+        );
+
+        if group.aggregation == BroadcastAggregation::PickBest {
+            if let Some((agent_id, response)) = pick_named(&verdict, replies) {
+                return Ok(format!("{}\n\n_Best answer: {}_", response, agent_id));
+            }
+            tracing::warn!(broadcast = %group.name, verdict = %verdict, "synthesizer named no known agent");
+        }
+        Ok(verdict)
+    }
+}
+
+/// Judge instructions for each aggregation mode.
+fn aggregation_instructions(mode: BroadcastAggregation) -> &'static str {
+    match mode {
+        BroadcastAggregation::Synthesize => {
+            "Write one reply to the message that merges the answers: keep what they \
+             agree on, resolve contradictions, drop repetition, and keep any unique \
+             insight. Reply to the user directly; don't mention the agents."
+        }
+        BroadcastAggregation::Vote => {
+            "Treat each answer as a vote. Reply with the position most answers support, \
+             then state how many agents agreed and summarise any notable dissent."
+        }
+        BroadcastAggregation::Rank => {
+            "Rank the answers from best to worst, one line of justification each \
+             (e.g. `1. agent-id — reason`), then give the best answer in full."
+        }
+        BroadcastAggregation::PickBest => {
+            "Decide which single answer is best. Reply with only that agent's id \
+             (the heading above its answer) and nothing else."
+        }
+    }
+}
+
+/// The reply whose agent id the judge's verdict names.
+fn pick_named<'a>(verdict: &str, replies: &'a [(String, String)]) -> Option<&'a (String, String)> {
+    let named = verdict
+        .trim()
+        .trim_matches(|c: char| c == '`' || c == '*' || c == '#' || c == '"' || c.is_whitespace());
+    replies
+        .iter()
+        .find(|(id, _)| id == named)
+        .or_else(|| replies.iter().find(|(id, _)| verdict.contains(id.as_str())))
+}
+
+/// Plain concatenation: each agent's answer under its id.
+fn concatenate_replies(replies: &[(String, String)]) -> String {
+    replies
+        .iter()
+        .map(|(agent_id, response)| format!("**[{}]**\n\n{}", agent_id, response))
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n")
+}
+
+/// Collapsible attachment carrying each agent's own answer.  Lark renders it
+/// as collapsible card panels and Telegram as expandable quotes; the other
+/// channels send the text.
+fn answers_attachment(replies: &[(String, String)]) -> OutboundPayload {
+    let sections: Vec<_> = replies
+        .iter()
+        .map(|(agent_id, response)| serde_json::json!({ "title": agent_id, "text": response }))
+        .collect();
+    OutboundPayload {
+        text: Some(concatenate_replies(replies)),
+        interactive: Some(serde_json::json!({
+            "type": "collapsible",
+            "title": format!("Individual answers ({})", replies.len()),
+            "collapsed": true,
+            "sections": sections,
+        })),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replies() -> Vec<(String, String)> {
+        vec![
+            ("security".to_string(), "Looks safe.".to_string()),
+            ("style".to_string(), "Rename `x`.".to_string()),
+        ]
+    }
+
+    #[test]
+    fn pick_named_matches_agent_ids() {
+        let replies = replies();
+        assert_eq!(pick_named("style", &replies).unwrap().0, "style");
+        assert_eq!(
+            pick_named("**`security`**\n", &replies).unwrap().0,
+            "security"
+        );
+        assert_eq!(
+            pick_named("The best answer is from style.", &replies)
+                .unwrap()
+                .0,
+            "style"
+        );
+        assert!(pick_named("none of them", &replies).is_none());
+    }
+
+    #[test]
+    fn attachment_is_collapsible_with_every_answer() {
+        let payload = answers_attachment(&replies());
+        let interactive = payload.interactive.unwrap();
+        assert_eq!(interactive["type"], "collapsible");
+        assert_eq!(interactive["collapsed"], true);
+        assert_eq!(interactive["sections"].as_array().unwrap().len(), 2);
+        assert_eq!(interactive["sections"][1]["title"], "style");
+        assert!(payload.text.unwrap().contains("**[security]**"));
+    }
 }
@@ -15,6 +15,7 @@ impl AgentSession {
     ///
     /// When no `StreamingOutputHandle` is present in the RunContext, streaming
     /// callbacks are no-ops (guarded by `if let Some(ref handle) = output_handle`).
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn handle_deep_agent(
         &self,
         session_id: &str,
@@ -23,6 +24,9 @@ impl AgentSession {
         ctx: RunContext,
         agent_info: &ResolvedAgentInfo,
         request_id: Option<&str>,
+        schedule_origin: Option<crate::tools::ScheduleOrigin>,
+        memory_scope: Option<crate::memory::MemoryScope>,
+        budget: Option<crate::gateway::budgets::BudgetGuard>,
     ) -> crate::error::Result<(String, u32, u32)> {
         let memory = self.session_mgr.memory();
 
@@ -88,8 +92,11 @@ impl AgentSession {
             self.plugins.as_ref().map(|p| p.event_bus.clone()),
             self.plugins.as_ref().map(|p| p.plugin_registry.clone()),
             None, // no channel registry in bot mode
+            schedule_origin,
+            memory_scope.clone(),
             crate::agent::SessionKind::Full,
             &[], // TODO: pass bundle_skills_dirs from gateway
+            budget,
         )
         .await
         .map_err(|e| {
@@ -231,6 +238,20 @@ impl AgentSession {
             memory.append(session_id, m).await.ok();
         }
 
+        // Capture the turn for the memory provider (user profile synthesis).
+        let memory_provider = self.memory_provider().await;
+        let provider_key = memory_scope.as_ref().and_then(|s| s.session_key.clone());
+        if let (Some(provider), Some(key)) = (memory_provider.clone(), provider_key.clone()) {
+            let (text, response) = (text.to_string(), response.clone());
+            tokio::spawn(async move {
+                for (role, content) in [("user", text), ("assistant", response)] {
+                    if let Err(e) = provider.add_message(&key, role, &content).await {
+                        tracing::warn!(error = %e, "failed to capture turn for memory");
+                    }
+                }
+            });
+        }
+
         // Token-aware trimming with pre-compaction LTM flush
         let mut current = memory.load(session_id).await.unwrap_or_default();
         let token_count = HeuristicTokenCounter.count_messages(&current);
@@ -247,9 +268,15 @@ impl AgentSession {
             let keep_recent = self.config.memory.keep_recent;
             let discard_end = current.len().saturating_sub(keep_recent);
             if discard_end > 0 {
-                ltm.flush_before_compact(&current[..discard_end], self.model.as_ref())
+                let scope = memory_scope.unwrap_or_else(crate::memory::MemoryScope::unrestricted);
+                ltm.flush_before_compact(&current[..discard_end], self.model.as_ref(), &scope)
                     .await;
             }
+            if let (Some(provider), Some(key)) = (memory_provider, provider_key) {
+                if let Err(e) = provider.commit(&key).await {
+                    tracing::warn!(error = %e, "memory commit before compaction failed");
+                }
+            }
 
             let opts = crate::tools::PruningOptions::from_config(&self.config.memory);
             crate::tools::prune_tool_results_with_options(&mut current, &opts);
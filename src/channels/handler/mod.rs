@@ -54,11 +54,8 @@ struct ResolvedAgentInfo {
 enum ResolvedRoute {
     Single(ResolvedAgentInfo),
     Broadcast {
-        group_name: String,
-        strategy: crate::config::BroadcastStrategy,
+        group: crate::config::AgentBroadcastGroup,
         agents: Vec<ResolvedAgentInfo>,
-        #[allow(dead_code)]
-        timeout_secs: u64,
     },
 }
 
@@ -127,6 +124,8 @@ pub struct AgentSession {
     tracking: Option<TrackingCapability>,
     /// Plugin system capabilities (event bus, plugin registry).
     plugins: Option<PluginCapability>,
+    /// Spend budgets enforced on every turn.
+    budgets: Option<Arc<crate::gateway::budgets::Budgets>>,
 }
 
 impl AgentSession {
@@ -153,6 +152,7 @@ impl AgentSession {
             gateway: None,
             tracking: None,
             plugins: None,
+            budgets: None,
         }
     }
 
@@ -202,6 +202,7 @@ impl AgentSession {
             gateway: None,
             tracking: None,
             plugins: None,
+            budgets: None,
         }
     }
 
@@ -234,6 +235,12 @@ impl AgentSession {
         self
     }
 
+    /// Enforce spend budgets.
+    pub fn with_budgets(mut self, budgets: Arc<crate::gateway::budgets::Budgets>) -> Self {
+        self.budgets = Some(budgets);
+        self
+    }
+
     /// Set plugin system capabilities (event bus, plugin registry).
     pub fn with_plugins(
         mut self,
@@ -317,6 +324,81 @@ impl AgentSession {
         }
     }
 
+    /// Where jobs created with the schedule tools during this turn report
+    /// back to: the chat the message came from, run as the same agent.
+    ///
+    /// Adapters address chats as `channel:<id>` (Slack, Discord) or
+    /// `chat:<id>` (everyone else).  `None` for messages without a native
+    /// chat id, e.g. from the web UI.
+    fn schedule_origin(
+        msg: &InboundMessage,
+        agent_info: &ResolvedAgentInfo,
+    ) -> Option<crate::tools::ScheduleOrigin> {
+        let chat_id = msg.channel.native_channel_id.as_deref()?;
+        let to = match msg.channel.platform.as_str() {
+            "slack" | "discord" => format!("channel:{}", chat_id),
+            _ => format!("chat:{}", chat_id),
+        };
+        Some(crate::tools::ScheduleOrigin {
+            session_key: msg.session_key.clone(),
+            agent_id: agent_info.id.clone(),
+            user_id: msg.sender.id.clone(),
+            deliver: crate::cron::DeliverTarget {
+                channel: msg.channel.platform.clone(),
+                to,
+                account_id: msg.channel.account_id.clone(),
+                thread_id: msg.thread.thread_id.clone(),
+            },
+        })
+    }
+
+    /// The memory provider registered by the memory plugin, if any.
+    async fn memory_provider(&self) -> Option<Arc<dyn synaptic::memory::MemoryProvider>> {
+        let plugins = self.plugins.as_ref()?;
+        let registry = plugins.plugin_registry.read().await;
+        registry.memory_slot().cloned()
+    }
+
+    /// What memories this turn may recall and how saved ones are tagged.
+    /// Web chat is scoped to the signed-in dashboard user; without one (auth
+    /// disabled) it sees agent-wide memories only.
+    fn memory_scope(msg: &InboundMessage) -> Option<crate::memory::MemoryScope> {
+        if msg.channel.platform == "web" {
+            let scope = crate::memory::MemoryScope::session_only(&msg.session_key);
+            return Some(match msg.sender.username.clone() {
+                Some(user) => scope.with_peer("web", format!("user:{}", user)),
+                None => scope,
+            });
+        }
+        Some(
+            crate::memory::MemoryScope::from_session_key(&msg.session_key)
+                .with_sender(msg.sender.id.clone()),
+        )
+    }
+
+    /// The spend budgets this turn counts against, if any are configured.
+    pub(super) fn budget_guard(
+        &self,
+        msg: &InboundMessage,
+        agent_info: &ResolvedAgentInfo,
+    ) -> Option<crate::gateway::budgets::BudgetGuard> {
+        let budgets = self.budgets.as_ref().filter(|b| !b.is_empty())?;
+        let platform = &msg.channel.platform;
+        Some(crate::gateway::budgets::BudgetGuard {
+            budgets: budgets.clone(),
+            scope: crate::gateway::budgets::BudgetScope {
+                agent: agent_info.id.clone(),
+                sender: msg.sender.id.clone(),
+                channel: platform.clone(),
+                chat: msg
+                    .channel
+                    .native_channel_id
+                    .as_ref()
+                    .map(|id| format!("{}:{}", platform, id)),
+            },
+        })
+    }
+
     /// Build a `TurnSource` from an `InboundMessage` for cross-channel race prevention.
     fn turn_source_from_inbound(msg: &InboundMessage) -> TurnSource {
         TurnSource {
@@ -365,14 +447,12 @@ impl AgentSession {
 
         // Broadcast in streaming mode: fall back to non-streaming broadcast
         if let ResolvedRoute::Broadcast {
-            ref group_name,
-            ref strategy,
+            ref group,
             ref agents,
-            ..
         } = route
         {
-            tracing::info!(broadcast = %group_name, "dispatching broadcast (streaming fallback)");
-            return self.handle_broadcast_message(&msg, agents, strategy).await;
+            tracing::info!(broadcast = %group.name, "dispatching broadcast (streaming fallback)");
+            return self.handle_broadcast_message(&msg, agents, group).await;
         }
 
         let agent_info = match route {
@@ -415,6 +495,9 @@ impl AgentSession {
                 ctx,
                 &agent_info,
                 Some(&msg.request_id),
+                Self::schedule_origin(&msg, &agent_info),
+                Self::memory_scope(&msg),
+                self.budget_guard(&msg, &agent_info),
             )
             .await
         } else {
@@ -53,10 +53,8 @@ impl AgentSession {
                         })
                         .collect();
                     ResolvedRoute::Broadcast {
-                        group_name: group.name.clone(),
-                        strategy: group.strategy.clone(),
+                        group: group.clone(),
                         agents: infos,
-                        timeout_secs: group.timeout_secs,
                     }
                 }
             }
@@ -18,70 +18,72 @@ pub async fn run_bot(
 ) -> crate::error::Result<()> {
     match platform {
         #[cfg(feature = "bot-lark")]
-        "lark" | "feishu" => adapters::lark::run(config, model_override, None, None, None).await,
+        "lark" | "feishu" => {
+            adapters::lark::run(config, model_override, None, None, None, None).await
+        }
 
         #[cfg(feature = "bot-slack")]
-        "slack" => adapters::slack::run(config, model_override).await,
+        "slack" => adapters::slack::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-telegram")]
-        "telegram" => adapters::telegram::run(config, model_override).await,
+        "telegram" => adapters::telegram::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-discord")]
-        "discord" => adapters::discord::run(config, model_override).await,
+        "discord" => adapters::discord::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-dingtalk")]
-        "dingtalk" => adapters::dingtalk::run(config, model_override).await,
+        "dingtalk" => adapters::dingtalk::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-mattermost")]
-        "mattermost" => adapters::mattermost::run(config, model_override).await,
+        "mattermost" => adapters::mattermost::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-matrix")]
-        "matrix" => adapters::matrix::run(config, model_override).await,
+        "matrix" => adapters::matrix::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-teams")]
-        "teams" => adapters::teams::run(config, model_override).await,
+        "teams" => adapters::teams::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-whatsapp")]
-        "whatsapp" => adapters::whatsapp::run(config, model_override).await,
+        "whatsapp" => adapters::whatsapp::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-signal")]
-        "signal" => adapters::signal::run(config, model_override).await,
+        "signal" => adapters::signal::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-imessage")]
-        "imessage" => adapters::imessage::run(config, model_override).await,
+        "imessage" => adapters::imessage::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-line")]
-        "line" => adapters::line::run(config, model_override).await,
+        "line" => adapters::line::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-googlechat")]
         "googlechat" | "gchat" => adapters::googlechat::run(config, model_override).await,
 
         #[cfg(feature = "bot-wechat")]
-        "wechat" | "wecom" => adapters::wechat::run(config, model_override).await,
+        "wechat" | "wecom" => adapters::wechat::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-irc")]
-        "irc" => adapters::irc::run(config, model_override).await,
+        "irc" => adapters::irc::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-webchat")]
         "webchat" => adapters::webchat::run(config, model_override).await,
 
         #[cfg(feature = "bot-twitch")]
-        "twitch" => adapters::twitch::run(config, model_override).await,
+        "twitch" => adapters::twitch::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-nostr")]
         "nostr" => adapters::nostr::run(config, model_override).await,
 
         #[cfg(feature = "bot-nextcloud")]
-        "nextcloud" => adapters::nextcloud::run(config, model_override).await,
+        "nextcloud" => adapters::nextcloud::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-synology")]
-        "synology" => adapters::synology::run(config, model_override).await,
+        "synology" => adapters::synology::run(config, model_override, None).await,
 
         #[cfg(feature = "bot-tlon")]
         "tlon" | "urbit" => adapters::tlon::run(config, model_override).await,
 
         #[cfg(feature = "bot-zalo")]
-        "zalo" => adapters::zalo::run(config, model_override).await,
+        "zalo" => adapters::zalo::run(config, model_override, None).await,
 
         _ => {
             let available = available_platforms();
@@ -76,6 +76,40 @@ pub enum Command {
         name: Option<String>,
     },
 
+    /// Manage dashboard user accounts.
+    #[cfg(feature = "web")]
+    Users {
+        /// Action: list, add, remove, passwd, role.
+        action: String,
+        /// Account name.
+        username: Option<String>,
+        /// Role for add/role: admin, operator or viewer.
+        #[arg(long, default_value = "operator")]
+        role: String,
+        /// Password for add/passwd (read from stdin if omitted).
+        #[arg(long)]
+        password: Option<String>,
+    },
+
+    /// Manage API tokens for scripts and CI.
+    #[cfg(feature = "web")]
+    Tokens {
+        /// Action: list, create, revoke.
+        action: String,
+        /// Token name (for create) or ID (for revoke).
+        target: Option<String>,
+        /// Scope to grant (repeatable): chat, *, or <area>.<read|write|admin>,
+        /// e.g. sessions.read, cron.write, config.admin.
+        #[arg(long = "scope")]
+        scopes: Vec<String>,
+        /// Days until the token expires (never if omitted).
+        #[arg(long)]
+        expires_days: Option<u64>,
+        /// Requests per minute (unlimited if omitted).
+        #[arg(long)]
+        rate_limit: Option<u32>,
+    },
+
     /// Manage DM pairing for bot channels.
     #[cfg(feature = "web")]
     Pairing {
@@ -182,15 +216,28 @@ pub enum Command {
     #[cfg(feature = "tui")]
     Tui,
 
-    /// Manage long-term memory (list, search, status, clear).
+    /// Manage long-term memory (list, search, status, ingest, consolidate, history,
+    /// export, import, migrate, clear).
     Memory {
-        /// Action: list, search, status, clear.
+        /// Action: list, search, status, ingest, consolidate, history, export, import,
+        /// migrate, clear.
         action: String,
-        /// Query string (for search action).
+        /// Query string (for search), the file, directory or URL to ingest, or the
+        /// JSONL file to import or export to (export defaults to stdout).
         query: Option<String>,
-        /// Maximum results (for search/list).
+        /// Maximum results (for search/list/history).
         #[arg(long, short = 'n', default_value = "10")]
         limit: usize,
+        /// Agent whose memory to use.
+        #[arg(long, default_value = "default")]
+        agent: String,
+        /// Backend to migrate from: file, sqlite, postgres, redis, a SQLite path
+        /// or a postgres:// / redis:// URL (defaults to the configured backend).
+        #[arg(long)]
+        from: Option<String>,
+        /// Backend to migrate to (same forms as --from).
+        #[arg(long)]
+        to: Option<String>,
     },
 
     /// Connect to a remote Synapse Gateway via WebSocket.
@@ -226,6 +273,36 @@ pub enum Command {
         name: Option<String>,
     },
 
+    /// Inspect the audit log (verify, query, replay).
+    Audit {
+        /// Action: verify, query, replay.
+        #[arg(default_value = "verify")]
+        action: String,
+        /// Audit log to read (defaults to the configured `[audit] path`).
+        #[arg(long)]
+        file: Option<String>,
+        /// Only entries of this category (tool, rpc, http, pairing, terminal,
+        /// usage).
+        #[arg(long)]
+        category: Option<String>,
+        /// Only this action, or a prefix ending in `*` (e.g. `config.*`).
+        #[arg(long)]
+        filter: Option<String>,
+        /// Only entries by this actor.
+        #[arg(long)]
+        actor: Option<String>,
+        /// Only entries of this session key; for replay, the web terminal
+        /// session (`term-...`).
+        #[arg(long)]
+        session: Option<String>,
+        /// Maximum entries (newest) for query.
+        #[arg(long, short = 'n', default_value = "50")]
+        limit: usize,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Manage sandbox containers.
     #[cfg(feature = "sandbox")]
     Sandbox {
@@ -0,0 +1,178 @@
+use colored::Colorize;
+
+use crate::audit::{AuditLog, AuditQuery};
+use crate::config::SynapseConfig;
+
+/// Filters for `synapse audit query`.
+pub struct AuditArgs {
+    pub file: Option<String>,
+    pub category: Option<String>,
+    pub filter: Option<String>,
+    pub actor: Option<String>,
+    pub session: Option<String>,
+    pub limit: usize,
+    pub json: bool,
+}
+
+pub fn run_audit_command(
+    config: &SynapseConfig,
+    action: &str,
+    args: &AuditArgs,
+) -> crate::error::Result<()> {
+    let path = args
+        .file
+        .as_ref()
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| config.audit.path());
+
+    match action {
+        "verify" => {
+            let report = crate::audit::verify(&path);
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if report.ok {
+                println!(
+                    "{} {} entries, chain intact ({})",
+                    "ok:".green().bold(),
+                    report.entries,
+                    path.display()
+                );
+                if let Some(ref head) = report.head {
+                    println!("  {} {}", "Head:".bold(), head.dimmed());
+                }
+            } else {
+                println!(
+                    "{} chain broken at line {}: {}",
+                    "tampered:".red().bold(),
+                    report.broken_at.unwrap_or(0),
+                    report.error.as_deref().unwrap_or("unknown error")
+                );
+                println!(
+                    "  {} {} entries before it verified",
+                    "Intact:".bold(),
+                    report.entries
+                );
+            }
+            if !report.ok {
+                return Err(format!("audit log {} failed verification", path.display()).into());
+            }
+        }
+
+        "query" | "list" => {
+            let entries = AuditLog::open(path).query(&AuditQuery {
+                category: args.category.clone(),
+                action: args.filter.clone(),
+                actor: args.actor.clone(),
+                session_key: args.session.clone(),
+                limit: Some(args.limit),
+                ..Default::default()
+            });
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else if entries.is_empty() {
+                println!("{}", "No matching audit entries.".dimmed());
+            } else {
+                for e in &entries {
+                    let ts = chrono::DateTime::from_timestamp_millis(e.ts as i64)
+                        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    println!(
+                        "{:>6} {} {:8} {:30} {:20} {}",
+                        e.seq,
+                        ts.dimmed(),
+                        e.category,
+                        e.action.cyan(),
+                        e.actor.as_deref().unwrap_or("-"),
+                        e.outcome.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+        }
+
+        "replay" => {
+            let id = args
+                .session
+                .as_deref()
+                .ok_or("replay needs --session <terminal id>")?;
+            replay_terminal(&AuditLog::open(path), id)?;
+        }
+
+        _ => {
+            eprintln!(
+                "{} unknown action '{}'. Available: verify, query, replay",
+                "error:".red().bold(),
+                action
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Longest pause kept when replaying a recording (seconds).
+const MAX_REPLAY_IDLE_SECS: f64 = 2.0;
+
+/// Play back web terminal session `id`, checking its recording against the
+/// hash sealed in the audit log.
+fn replay_terminal(log: &AuditLog, id: &str) -> crate::error::Result<()> {
+    use sha2::{Digest, Sha256};
+    use std::io::Write;
+
+    let find = |action: &str| {
+        log.query(&AuditQuery {
+            category: Some("terminal".into()),
+            action: Some(action.into()),
+            session_key: Some(id.to_string()),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .pop()
+    };
+    let close = find("terminal.close");
+    let entry = close
+        .clone()
+        .or_else(|| find("terminal.open"))
+        .ok_or_else(|| format!("no terminal session '{}' in the audit log", id))?;
+    let path = entry
+        .detail
+        .get("recording")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("terminal session '{}' was not recorded", id))?;
+    let data = std::fs::read(path)?;
+
+    match close
+        .as_ref()
+        .and_then(|e| e.detail.get("recording_sha256"))
+        .and_then(|v| v.as_str())
+    {
+        Some(sealed) if format!("{:x}", Sha256::digest(&data)) != sealed => {
+            return Err(format!("recording {} does not match the audit log", path).into());
+        }
+        Some(_) => {}
+        None => eprintln!(
+            "{} session did not close cleanly; the recording is unverified",
+            "warning:".yellow().bold()
+        ),
+    }
+
+    let mut stdout = std::io::stdout();
+    let mut last = 0.0;
+    for line in String::from_utf8_lossy(&data).lines().skip(1) {
+        let Ok(serde_json::Value::Array(event)) = serde_json::from_str(line) else {
+            continue;
+        };
+        if event.get(1).and_then(|k| k.as_str()) != Some("o") {
+            continue;
+        }
+        let t = event.first().and_then(|t| t.as_f64()).unwrap_or(last);
+        let pause = (t - last).clamp(0.0, MAX_REPLAY_IDLE_SECS);
+        std::thread::sleep(std::time::Duration::from_secs_f64(pause));
+        last = t;
+        if let Some(text) = event.get(2).and_then(|d| d.as_str()) {
+            stdout.write_all(text.as_bytes())?;
+            stdout.flush()?;
+        }
+    }
+    println!();
+    Ok(())
+}
@@ -1,3 +1,4 @@
+pub mod audit_cmd;
 #[cfg(feature = "web")]
 pub mod devices;
 pub mod models_cmd;
@@ -7,7 +8,12 @@ pub mod plugin_cmd;
 #[cfg(feature = "web")]
 pub mod qr;
 pub mod skill_cmd;
+#[cfg(feature = "web")]
+pub mod tokens_cmd;
+#[cfg(feature = "web")]
+pub mod users_cmd;
 
+pub use self::audit_cmd::run_audit_command;
 pub use self::models_cmd::run_models_command;
 pub use self::plugin_cmd::run_plugin_command;
 pub use self::skill_cmd::run_skill_command;
@@ -0,0 +1,80 @@
+use crate::gateway::api_tokens::ApiTokenStore;
+
+pub struct TokensArgs {
+    /// Token name (create) or ID (revoke).
+    pub target: Option<String>,
+    pub scopes: Vec<String>,
+    pub expires_days: Option<u64>,
+    pub rate_limit: Option<u32>,
+}
+
+fn format_ms(ms: Option<u64>) -> String {
+    ms.and_then(|ms| chrono::DateTime::from_timestamp_millis(ms as i64))
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub fn run(action: &str, args: &TokensArgs) {
+    let mut store = ApiTokenStore::load(ApiTokenStore::default_path());
+
+    let result = match action {
+        "list" => {
+            if store.list().is_empty() {
+                println!("No API tokens.");
+                return;
+            }
+            println!(
+                "{:<10}  {:<20}  {:<32}  {:<16}  {:<16}  {:<8}",
+                "ID", "NAME", "SCOPES", "EXPIRES", "LAST USED", "STATUS"
+            );
+            let now = crate::gateway::presence::now_ms();
+            for token in store.list() {
+                let status = if token.revoked_at.is_some() {
+                    "revoked"
+                } else if token.expires_at.is_some_and(|e| e <= now) {
+                    "expired"
+                } else {
+                    "active"
+                };
+                println!(
+                    "{:<10}  {:<20}  {:<32}  {:<16}  {:<16}  {:<8}",
+                    token.id,
+                    token.name,
+                    token.scopes.join(","),
+                    format_ms(token.expires_at),
+                    format_ms(token.last_used_at),
+                    status
+                );
+            }
+            Ok(())
+        }
+        "create" => match args.target.as_deref() {
+            Some(name) => store
+                .create(
+                    name,
+                    args.scopes.clone(),
+                    args.expires_days.map(|d| d * 86_400),
+                    args.rate_limit,
+                )
+                .map(|(token, secret)| {
+                    println!("Created API token {} ({})", token.id, token.name);
+                    println!("Token: {secret}");
+                    println!("Store it now; it cannot be shown again.");
+                }),
+            None => Err("a token name is required for create".into()),
+        },
+        "revoke" => match args.target.as_deref() {
+            Some(id) => store
+                .revoke(id)
+                .map(|token| println!("Revoked API token {} ({})", token.id, token.name)),
+            None => Err("a token ID is required for revoke".into()),
+        },
+        _ => Err(format!(
+            "Unknown action: {action}. Use: list, create, revoke"
+        )),
+    };
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::BufRead;
+
+use crate::gateway::users::{UserRole, UserStore};
+
+pub struct UsersArgs {
+    pub username: Option<String>,
+    pub role: String,
+    pub password: Option<String>,
+}
+
+/// The password from `--password`, or the first line of stdin.
+fn read_password(args: &UsersArgs) -> Result<String, String> {
+    if let Some(ref password) = args.password {
+        return Ok(password.clone());
+    }
+    eprint!("Password: ");
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+    let password = line.trim_end_matches(['\r', '\n']).to_string();
+    if password.is_empty() {
+        return Err("empty password".into());
+    }
+    Ok(password)
+}
+
+pub fn run(action: &str, args: &UsersArgs) {
+    let mut store = UserStore::load(UserStore::default_path());
+    let username = || {
+        args.username
+            .as_deref()
+            .ok_or_else(|| format!("a username is required for {action}"))
+    };
+
+    let result = match action {
+        "list" => {
+            if store.list().is_empty() {
+                println!("No users.");
+                return;
+            }
+            println!("{:<24}  {:<10}  {:<20}", "USERNAME", "ROLE", "CREATED");
+            for user in store.list() {
+                let created = chrono::DateTime::from_timestamp(user.created_at as i64, 0)
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                println!(
+                    "{:<24}  {:<10}  {:<20}",
+                    user.username,
+                    user.role.as_str(),
+                    created
+                );
+            }
+            Ok(())
+        }
+        "add" => username().and_then(|name| {
+            let role: UserRole = args.role.parse()?;
+            let password = read_password(args)?;
+            store.add(name, &password, role)?;
+            println!("Added {name} ({})", role.as_str());
+            Ok(())
+        }),
+        "remove" => username().and_then(|name| {
+            store.remove(name)?;
+            println!("Removed {name}; their sessions are revoked");
+            Ok(())
+        }),
+        "passwd" => username().and_then(|name| {
+            if store.get(name).is_none() {
+                return Err(format!("no user '{name}'"));
+            }
+            let password = read_password(args)?;
+            store.set_password(name, &password)?;
+            println!("Password changed for {name}; existing sessions are revoked");
+            Ok(())
+        }),
+        "role" => username().and_then(|name| {
+            let role: UserRole = args.role.parse()?;
+            store.set_role(name, role)?;
+            println!("{name} is now {}", role.as_str());
+            Ok(())
+        }),
+        _ => Err(format!(
+            "Unknown action: {action}. Use: list, add, remove, passwd, role"
+        )),
+    };
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
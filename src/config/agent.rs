@@ -136,9 +136,19 @@ pub struct AgentBroadcastGroup {
     /// Execution strategy.
     #[serde(default)]
     pub strategy: BroadcastStrategy,
-    /// Timeout in seconds for aggregated strategy (default: 60).
+    /// Seconds to wait for agents under the parallel and aggregated
+    /// strategies; late answers are dropped (default: 60).
     #[serde(default = "default_broadcast_timeout")]
     pub timeout_secs: u64,
+    /// Agent that merges the answers under the aggregated strategy
+    /// (default: the default agent).
+    pub synthesizer: Option<String>,
+    /// How the synthesizer combines the answers.
+    #[serde(default)]
+    pub aggregation: BroadcastAggregation,
+    /// Attach each agent's own answer to the merged reply.
+    #[serde(default = "default_true")]
+    pub attach_answers: bool,
 }
 
 /// Broadcast execution strategy.
@@ -150,10 +160,26 @@ pub enum BroadcastStrategy {
     Parallel,
     /// Agents process one after another.
     Sequential,
-    /// All agents process in parallel, replies are merged into one message.
+    /// All agents process in parallel, then a synthesizer agent merges the
+    /// replies into one message.
     Aggregated,
 }
 
+/// How the synthesizer combines answers under [`BroadcastStrategy::Aggregated`].
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BroadcastAggregation {
+    /// Merge the answers into one reply.
+    #[default]
+    Synthesize,
+    /// Treat each answer as a vote and reply with the majority position.
+    Vote,
+    /// Rank the answers, then give the best one in full.
+    Rank,
+    /// Reply with the single best answer, verbatim.
+    PickBest,
+}
+
 fn default_broadcast_timeout() -> u64 {
     60
 }
@@ -59,6 +59,24 @@ pub struct MemoryConfig {
     #[serde(default = "default_ltm_candidate_multiplier")]
     pub ltm_candidate_multiplier: usize,
 
+    /// Whether to consolidate memories on save: merge near-duplicates and,
+    /// when a model is available, supersede contradicted memories.
+    /// Opt-in: superseded memories leave recall (they stay in the history).
+    /// Default: false.
+    #[serde(default)]
+    pub ltm_consolidate: bool,
+
+    /// Embedding similarity (0.0–1.0) above which two memories are treated as
+    /// duplicates and merged. Default: 0.9.
+    #[serde(default = "default_ltm_duplicate_threshold")]
+    pub ltm_duplicate_threshold: f32,
+
+    /// Hours between background consolidation passes in the gateway when
+    /// `ltm_consolidate` is on (0 = disabled).
+    /// Default: 24.
+    #[serde(default = "default_ltm_consolidate_interval_hours")]
+    pub ltm_consolidate_interval_hours: u64,
+
     /// Whether to flush important memories before compaction.
     #[serde(default = "default_true")]
     pub pre_compact_flush: bool,
@@ -174,6 +192,12 @@ fn default_ltm_min_score() -> f32 {
 fn default_ltm_candidate_multiplier() -> usize {
     4
 }
+fn default_ltm_duplicate_threshold() -> f32 {
+    0.9
+}
+fn default_ltm_consolidate_interval_hours() -> u64 {
+    24
+}
 fn default_ltm_backend() -> String {
     "file".to_string()
 }
@@ -209,6 +233,9 @@ impl Default for MemoryConfig {
             ltm_vector_weight: default_ltm_vector_weight(),
             ltm_min_score: default_ltm_min_score(),
             ltm_candidate_multiplier: default_ltm_candidate_multiplier(),
+            ltm_consolidate: false,
+            ltm_duplicate_threshold: default_ltm_duplicate_threshold(),
+            ltm_consolidate_interval_hours: default_ltm_consolidate_interval_hours(),
             pre_compact_flush: true,
             session_prune_days: default_session_prune_days(),
             max_tool_result_chars: default_max_tool_result_chars(),
@@ -44,4 +44,93 @@ pub struct ScheduleEntry {
     #[serde(default = "default_true")]
     pub enabled: bool,
     pub description: Option<String>,
+    /// IANA timezone `cron` is evaluated in, e.g. "Asia/Shanghai" (default: UTC).
+    pub timezone: Option<String>,
+    /// Random delay of up to this many seconds added to each run (default: 0).
+    #[serde(default)]
+    pub jitter_secs: u64,
+    /// Catch-up policy for runs missed while the gateway was down:
+    /// "skip", "run_once" (default) or "run_all".
+    #[serde(default)]
+    pub misfire: crate::cron::MisfirePolicy,
+    /// Agent from `agents.list` to run the prompt with (default: the default agent).
+    pub agent: Option<String>,
+    /// Channel peer to post the result to, e.g.
+    /// `{ channel = "lark", to = "chat:oc_xxx" }`.
+    pub deliver: Option<crate::cron::DeliverTarget>,
+}
+
+/// Limits for jobs the agent creates from chat with the `schedule_*` tools
+/// (`[schedule_tool]`).
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct ScheduleToolConfig {
+    /// Active jobs a single user may have at once (default: 10).
+    #[serde(default = "default_schedule_tool_max_jobs")]
+    pub max_jobs_per_user: usize,
+    /// Shortest allowed gap between runs of a recurring job, in seconds
+    /// (default: 300).
+    #[serde(default = "default_schedule_tool_min_interval")]
+    pub min_interval_secs: u64,
+}
+
+fn default_schedule_tool_max_jobs() -> usize {
+    10
+}
+fn default_schedule_tool_min_interval() -> u64 {
+    300
+}
+
+impl Default for ScheduleToolConfig {
+    fn default() -> Self {
+        Self {
+            max_jobs_per_user: default_schedule_tool_max_jobs(),
+            min_interval_secs: default_schedule_tool_min_interval(),
+        }
+    }
+}
+
+/// Outbound delivery queue configuration (`[delivery]`).
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct DeliveryConfig {
+    /// Failed attempts after which a delivery is dead-lettered (default: 8).
+    #[serde(default = "default_delivery_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry in seconds; doubles on each failure (default: 2).
+    #[serde(default = "default_delivery_base_delay_secs")]
+    pub base_delay_secs: u64,
+    /// Upper bound for the retry delay in seconds (default: 600).
+    #[serde(default = "default_delivery_max_delay_secs")]
+    pub max_delay_secs: u64,
+    /// How often the retry worker scans the queue, in seconds (default: 5).
+    #[serde(default = "default_delivery_poll_secs")]
+    pub poll_interval_secs: u64,
+    /// Queue directory (default: `~/.synapse/delivery`).
+    pub queue_dir: Option<String>,
+}
+
+fn default_delivery_max_attempts() -> u32 {
+    8
+}
+fn default_delivery_base_delay_secs() -> u64 {
+    2
+}
+fn default_delivery_max_delay_secs() -> u64 {
+    600
+}
+fn default_delivery_poll_secs() -> u64 {
+    5
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_delivery_max_attempts(),
+            base_delay_secs: default_delivery_base_delay_secs(),
+            max_delay_secs: default_delivery_max_delay_secs(),
+            poll_interval_secs: default_delivery_poll_secs(),
+            queue_dir: None,
+        }
+    }
 }
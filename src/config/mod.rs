@@ -60,10 +60,19 @@ pub struct SynapseConfig {
     pub sandbox: Option<crate::sandbox::SandboxConfig>,
     /// Authentication configuration (for web server).
     pub auth: Option<AuthConfig>,
+    /// Web terminal.
+    #[serde(default)]
+    pub terminal: TerminalConfig,
 
     /// Scheduled jobs.
     #[serde(rename = "schedule")]
     pub schedules: Option<Vec<ScheduleEntry>>,
+    /// Limits for jobs created by the agent's schedule tools.
+    #[serde(default)]
+    pub schedule_tool: ScheduleToolConfig,
+    /// Webhook triggers.
+    #[serde(default, rename = "webhook")]
+    pub webhooks: Vec<WebhookConfig>,
     /// Voice configuration.
     pub voice: Option<VoiceConfig>,
 
@@ -82,10 +91,16 @@ pub struct SynapseConfig {
 
     /// Rate limiting for model calls.
     pub rate_limit: Option<RateLimitConfig>,
+    /// Spend budgets per agent, sender, channel, chat or model.
+    #[serde(default)]
+    pub budgets: Vec<BudgetConfig>,
     /// Secret masking configuration.
     pub secrets: Option<SecretsConfig>,
     /// Security middleware configuration.
     pub security: Option<SecurityConfig>,
+    /// Hash-chained audit log.
+    #[serde(default)]
+    pub audit: AuditConfig,
 
     /// Custom slash commands.
     #[serde(rename = "command")]
@@ -122,6 +137,10 @@ pub struct SynapseConfig {
     #[serde(default)]
     pub plugins: crate::plugins::config::PluginsConfig,
 
+    /// Outbound delivery queue (retry/backoff/dead-letter) configuration.
+    #[serde(default)]
+    pub delivery: DeliveryConfig,
+
     /// Heartbeat configuration for periodic proactive agent runs.
     #[serde(default)]
     pub heartbeat: HeartbeatConfig,
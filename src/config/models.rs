@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// A model catalog entry defined via `[[models]]` in config.
 #[derive(Debug, Clone, Deserialize)]
@@ -17,6 +17,10 @@ pub struct ModelEntry {
     pub max_tokens: Option<u32>,
     /// Default thinking level: off, low, medium, high.
     pub thinking: Option<String>,
+    /// USD per million input tokens, for spend budgets.
+    pub input_cost_per_mtok: Option<f64>,
+    /// USD per million output tokens, for spend budgets.
+    pub output_cost_per_mtok: Option<f64>,
 }
 
 /// A custom provider defined via `[[providers]]` in config.
@@ -42,3 +46,90 @@ pub struct ChannelModelBinding {
     /// Model name or alias to use for this channel.
     pub model: String,
 }
+
+/// A spend budget defined via `[[budgets]]` in config.
+///
+/// Caps the tokens and/or USD spent in a calendar day or month (UTC) by one
+/// agent, sender, channel, chat or model.  Without `key` every agent (sender,
+/// ...) gets a cap of its own.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BudgetConfig {
+    /// Name shown in status and alerts (default: derived from scope and key).
+    pub name: Option<String>,
+    /// What the budget is counted per.
+    pub scope: BudgetScopeKind,
+    /// Only this agent ID, sender ID, channel (e.g. "telegram"), chat
+    /// (`<channel>:<chat id>`) or model.
+    pub key: Option<String>,
+    /// Period after which spend starts again from zero.
+    #[serde(default)]
+    pub period: BudgetPeriod,
+    /// Token cap (input + output).
+    pub max_tokens: Option<u64>,
+    /// USD cap; needs `input_cost_per_mtok` / `output_cost_per_mtok` on the
+    /// `[[models]]` entries.
+    pub max_usd: Option<f64>,
+    /// What happens once the cap is reached.
+    #[serde(default)]
+    pub action: BudgetAction,
+    /// Model name or alias to switch to under `action = "downgrade"`.
+    pub downgrade_to: Option<String>,
+    /// Reply sent instead of calling the model under `action = "refuse"`.
+    pub message: Option<String>,
+}
+
+/// Dimension a budget is counted per.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetScopeKind {
+    Agent,
+    Sender,
+    /// Channel platform, e.g. "telegram".
+    Channel,
+    /// A single chat or group: `<channel>:<chat id>`.
+    Chat,
+    Model,
+}
+
+impl BudgetScopeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Agent => "agent",
+            Self::Sender => "sender",
+            Self::Channel => "channel",
+            Self::Chat => "chat",
+            Self::Model => "model",
+        }
+    }
+}
+
+/// Budget period (UTC calendar).
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Daily,
+    #[default]
+    Monthly,
+}
+
+impl BudgetPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Monthly => "monthly",
+        }
+    }
+}
+
+/// What to do once a budget is used up.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// Alert operators but keep going.
+    Warn,
+    /// Use `downgrade_to` instead of the configured model.
+    Downgrade,
+    /// Reply with `message` without calling the model.
+    #[default]
+    Refuse,
+}
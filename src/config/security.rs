@@ -90,6 +90,40 @@ impl Default for SecurityConfig {
     }
 }
 
+/// Tamper-evident audit log of tool calls and privileged actions.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditConfig {
+    /// Whether the audit log is written. Default: true.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Log file. Default: `~/.synapse/audit/audit.jsonl`.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+impl AuditConfig {
+    /// The log file, with a leading `~/` expanded.
+    pub fn path(&self) -> std::path::PathBuf {
+        let home = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+        match self.path.as_deref() {
+            Some(p) => match p.strip_prefix("~/") {
+                Some(rest) => home.join(rest),
+                None => std::path::PathBuf::from(p),
+            },
+            None => home.join(".synapse").join("audit").join("audit.jsonl"),
+        }
+    }
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+        }
+    }
+}
+
 /// Tool policy configuration — controls tool access via allow/deny lists,
 /// owner-only restrictions, and named tool groups.
 #[derive(Debug, Clone, Default, Deserialize)]
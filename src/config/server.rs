@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use super::memory::default_true;
+
 #[derive(Debug, Clone, Deserialize)]
 #[allow(dead_code)]
 pub struct ServeConfig {
@@ -7,6 +9,37 @@ pub struct ServeConfig {
     pub port: Option<u16>,
 }
 
+/// Web terminal (`/ws/terminal`).  Only admins may open it.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct TerminalConfig {
+    /// Whether the terminal is available (default: false).  It also needs
+    /// `[auth] enabled`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Run the shell in the session's sandbox (see `[sandbox]`) instead of
+    /// on the host (default: false).
+    #[serde(default)]
+    pub sandbox: bool,
+    /// Shell to run (default: `$SHELL`, then `/bin/bash`).
+    pub shell: Option<String>,
+    /// Record output next to the audit log for replay with
+    /// `synapse audit replay` (default: true).
+    #[serde(default = "default_true")]
+    pub record: bool,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sandbox: false,
+            shell: None,
+            record: true,
+        }
+    }
+}
+
 /// Authentication configuration for the web server.
 #[derive(Debug, Clone, Deserialize)]
 #[allow(dead_code)]
@@ -14,13 +47,17 @@ pub struct AuthConfig {
     /// Whether authentication is enabled (default: false).
     #[serde(default)]
     pub enabled: bool,
-    /// Password hash. If empty, first login sets the password.
+    /// Legacy single-password hash; logs in as `admin`. Named accounts are
+    /// managed with `synapse users`.
     pub password_hash: Option<String>,
-    /// JWT secret for session tokens. Auto-generated if not set.
+    /// JWT secret for session tokens. Generated and stored in
+    /// `~/.synapse/auth/jwt-secret` if not set.
     pub jwt_secret: Option<String>,
     /// Session duration in seconds (default: 86400 = 24h).
     #[serde(default = "default_session_duration")]
     pub session_duration: u64,
+    /// Single sign-on through an OpenID Connect provider.
+    pub oidc: Option<OidcConfig>,
 }
 
 fn default_session_duration() -> u64 {
@@ -34,20 +71,128 @@ impl Default for AuthConfig {
             password_hash: None,
             jwt_secret: None,
             session_duration: default_session_duration(),
+            oidc: None,
         }
     }
 }
 
+/// OpenID Connect login (authorization code flow with PKCE).
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct OidcConfig {
+    /// Issuer URL; the discovery document is read from
+    /// `<issuer>/.well-known/openid-configuration`.
+    pub issuer: String,
+    pub client_id: String,
+    /// Client secret, for confidential clients.  Public clients rely on
+    /// PKCE alone.
+    pub client_secret: Option<String>,
+    /// Environment variable holding the client secret.
+    pub client_secret_env: Option<String>,
+    /// Callback URL registered with the provider, ending in
+    /// `/api/auth/oidc/callback`.
+    pub redirect_url: String,
+    /// Scopes to request (default: openid, email, profile).
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+    /// Claim listing the user's groups (default: "groups").
+    #[serde(default = "default_groups_claim")]
+    pub groups_claim: String,
+    /// Claim → role rules; a user gets the highest role any rule grants.
+    #[serde(default)]
+    pub role_mappings: Vec<OidcRoleMapping>,
+    /// Role for users no rule matches; unset, they are refused.
+    pub default_role: Option<String>,
+    /// Email domains allowed to log in; empty allows any.  Requires a
+    /// verified `email` claim when set.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+}
+
+/// Grants `role` to users whose `claim` (default: the groups claim) equals
+/// or contains `value`.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct OidcRoleMapping {
+    pub claim: Option<String>,
+    pub value: String,
+    /// "admin", "operator" or "viewer".
+    pub role: String,
+}
+
+fn default_oidc_scopes() -> Vec<String> {
+    vec!["openid".into(), "email".into(), "profile".into()]
+}
+
+fn default_groups_claim() -> String {
+    "groups".to_string()
+}
+
 /// Multi-gateway deployment configuration.
 #[derive(Debug, Clone, Deserialize)]
 #[allow(dead_code)]
 pub struct GatewayConfig {
-    /// Unique identifier for this instance (defaults to hostname if unset).
+    /// Unique identifier for this instance (defaults to `<hostname>:<pid>` if unset).
     pub instance_id: Option<String>,
     /// URL for the shared store (e.g. "redis://...", "postgres://...").
     pub shared_store_url: Option<String>,
     /// Whether to enable leader election for singleton tasks like the scheduler.
     pub leader_election: Option<bool>,
+    /// Path of the leader lease file (default: `~/.synapse/leader.lease`).
+    pub leader_lease_url: Option<String>,
+    /// Seconds a leader's lease lasts without renewal (default: 30).
+    pub leader_lease_ttl_secs: Option<u64>,
+}
+
+/// How a webhook sender signs its requests.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[allow(dead_code)]
+pub enum WebhookSignature {
+    /// `X-Hub-Signature-256: sha256=<hex HMAC of the body>`.
+    #[default]
+    Github,
+    /// `Stripe-Signature: t=<unix ts>,v1=<hex HMAC of "<ts>.<body>">`.
+    Stripe,
+}
+
+/// A webhook trigger (`[[webhook]]`), served at `POST /api/webhooks/{name}`.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct WebhookConfig {
+    pub name: String,
+    /// Agent id from `agents.list` that handles the request.
+    #[serde(default = "default_webhook_agent")]
+    pub agent: String,
+    /// Prompt template.  `{{a.b.0}}` placeholders are filled from the JSON
+    /// body, `{{headers.<name>}}` from request headers, and `{{body}}` with
+    /// the whole payload.
+    #[serde(default = "default_webhook_prompt")]
+    pub prompt: String,
+    /// HMAC secret the sender signs requests with.  Without a secret (or
+    /// `secret_env`) requests must carry gateway auth instead.
+    pub secret: Option<String>,
+    /// Environment variable holding the secret.
+    pub secret_env: Option<String>,
+    /// Signature header format.
+    #[serde(default)]
+    pub signature: WebhookSignature,
+    /// Session key template.  Requests rendering to the same key continue
+    /// one conversation; unset, every request gets a fresh session.
+    pub session_key: Option<String>,
+    /// Respond at once with a run id instead of waiting for the agent.
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
+    /// Post the agent's answer to a channel.
+    pub deliver: Option<crate::cron::DeliverTarget>,
+}
+
+fn default_webhook_agent() -> String {
+    "default".to_string()
+}
+
+fn default_webhook_prompt() -> String {
+    "{{body}}".to_string()
 }
 
 /// A broadcast group — sends messages to multiple channels.
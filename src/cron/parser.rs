@@ -1,17 +1,32 @@
-//! Cron expression parser for standard 5-field cron syntax.
+//! Cron expression parser for standard 5-field cron syntax, with the common
+//! Quartz-style extensions.
 //!
 //! Supports:
-//! - `*` wildcard
+//! - `*` wildcard (`?` is accepted as an alias in the day fields)
 //! - Specific values (`5`, `12`)
 //! - Ranges (`1-5`, `MON-FRI`)
 //! - Lists (`1,3,5`, `MON,WED,FRI`)
 //! - Step values (`*/2`, `0-12/3`)
 //! - Day-of-week names (`MON`, `TUE`, `WED`, `THU`, `FRI`, `SAT`, `SUN`)
 //! - Month names (`JAN`–`DEC`)
+//! - Day-of-month modifiers: `L` (last day), `L-3` (third-to-last day),
+//!   `LW` (last weekday), `15W` (weekday nearest the 15th)
+//! - Day-of-week modifiers: `5L` (last Friday), `1#2` (second Monday),
+//!   `L` (Saturday)
+//! - Macros: `@yearly`/`@annually`, `@monthly`, `@weekly`, `@daily`/`@midnight`,
+//!   `@hourly` and `@reboot`
 //!
-//! Field order: `minute hour day-of-month month day-of-week`
+//! Field order: `[second] minute hour day-of-month month day-of-week` — the
+//! seconds field is optional; 5-field expressions fire at second 0.
+//!
+//! Expressions can be evaluated in any [`TimeZone`]: fields are matched
+//! against local wall-clock time, so `0 9 * * *` in `Europe/Berlin` fires at
+//! 09:00 Berlin time on both sides of a DST change.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
 
-use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+/// The `@reboot` macro: fire once when the scheduler starts.
+const REBOOT: &str = "@reboot";
 
 /// Parsed representation of a single cron field.
 #[derive(Debug, Clone)]
@@ -31,14 +46,99 @@ impl Field {
     }
 }
 
-/// Parsed 5-field cron schedule.
+/// Quartz-style day modifier whose matching day depends on the month.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DayModifier {
+    /// `L` / `L-n` (day-of-month): `n` days before the last day of the month.
+    LastDay(u32),
+    /// `LW` (day-of-month): last Monday–Friday of the month.
+    LastWeekday,
+    /// `15W` (day-of-month): Monday–Friday nearest the given day, without
+    /// leaving the month.
+    NearestWeekday(u32),
+    /// `5L` (day-of-week): last occurrence of the weekday in the month.
+    LastOfWeekday(u32),
+    /// `1#2` (day-of-week): `n`th occurrence of the weekday in the month.
+    NthWeekday(u32, u32),
+}
+
+impl DayModifier {
+    fn matches(&self, date: NaiveDate) -> bool {
+        let last = last_day_of_month(date.year(), date.month());
+        let dow = date.weekday().num_days_from_sunday();
+        let day = date.day();
+        match *self {
+            DayModifier::LastDay(offset) => last.checked_sub(offset) == Some(day),
+            DayModifier::LastWeekday => {
+                let last_dow = (dow + last - day) % 7;
+                let target = match last_dow {
+                    6 => last - 1, // Saturday → Friday
+                    0 => last - 2, // Sunday → Friday
+                    _ => last,
+                };
+                day == target
+            }
+            DayModifier::NearestWeekday(nominal) => {
+                if nominal > last {
+                    return false;
+                }
+                let nominal_dow = (dow + 7 * 5 + nominal - day) % 7;
+                let target = match nominal_dow {
+                    6 if nominal == 1 => 3,              // Sat 1st → Mon 3rd
+                    6 => nominal - 1,                    // Sat → Fri
+                    0 if nominal == last => nominal - 2, // Sun last → Fri
+                    0 => nominal + 1,                    // Sun → Mon
+                    _ => nominal,
+                };
+                day == target
+            }
+            DayModifier::LastOfWeekday(weekday) => dow == weekday && day + 7 > last,
+            DayModifier::NthWeekday(weekday, n) => dow == weekday && (day - 1) / 7 + 1 == n,
+        }
+    }
+}
+
+/// A day-of-month or day-of-week field: plain values plus any modifiers.
+#[derive(Debug, Clone)]
+struct DayField {
+    values: Field,
+    modifiers: Vec<DayModifier>,
+}
+
+impl DayField {
+    /// Whether the field restricts anything (i.e. isn't `*` / `?`).
+    fn is_restricted(&self) -> bool {
+        !matches!(self.values, Field::Any) || !self.modifiers.is_empty()
+    }
+
+    fn matches(&self, value: u32, date: NaiveDate) -> bool {
+        self.values.matches(value) || self.modifiers.iter().any(|m| m.matches(date))
+    }
+}
+
+/// Number of days in the given month.
+fn last_day_of_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(31)
+}
+
+/// Parsed cron schedule.
 #[derive(Debug, Clone)]
 struct CronSchedule {
+    /// `None` for 5-field expressions (fire at second 0, ignore seconds when matching).
+    seconds: Option<Field>,
     minutes: Field,
     hours: Field,
-    days_of_month: Field,
+    days_of_month: DayField,
     months: Field,
-    days_of_week: Field,
+    days_of_week: DayField,
 }
 
 /// Parse a named day-of-week abbreviation to 0-based Sunday index (0=Sun..6=Sat).
@@ -159,20 +259,112 @@ fn parse_field(expr: &str, min: u32, max: u32, is_dow: bool, is_month: bool) ->
     Some(Field::Values(values))
 }
 
-/// Parse a 5-field cron expression string.
+/// Parse a day-of-month (`is_dow == false`) or day-of-week field, including
+/// the `L`, `W` and `#` modifiers.
+fn parse_day_field(expr: &str, is_dow: bool) -> Option<DayField> {
+    if expr == "*" || expr == "?" {
+        return Some(DayField {
+            values: Field::Any,
+            modifiers: Vec::new(),
+        });
+    }
+
+    let (min, max) = if is_dow { (0, 6) } else { (1, 31) };
+    let mut values: Vec<u32> = Vec::new();
+    let mut modifiers = Vec::new();
+
+    for part in expr.split(',') {
+        let upper = part.to_uppercase();
+        if let Some(modifier) = parse_day_modifier(&upper, is_dow) {
+            modifiers.push(modifier);
+        } else if is_dow && upper == "L" {
+            // Quartz: `L` alone in day-of-week means the last day of the week.
+            values.push(6);
+        } else {
+            match parse_field(part, min, max, is_dow, false)? {
+                Field::Any => values.extend(min..=max),
+                Field::Values(v) => values.extend(v),
+            }
+        }
+    }
+
+    values.sort_unstable();
+    values.dedup();
+    Some(DayField {
+        values: Field::Values(values),
+        modifiers,
+    })
+}
+
+/// Parse a single `L` / `W` / `#` token.  Returns `None` for anything else
+/// (including malformed modifiers, which then fail as plain values).
+fn parse_day_modifier(token: &str, is_dow: bool) -> Option<DayModifier> {
+    if is_dow {
+        if let Some((day, nth)) = token.split_once('#') {
+            let day = parse_value(day, true, false).filter(|d| *d <= 6)?;
+            let nth: u32 = nth.parse().ok().filter(|n| (1..=5).contains(n))?;
+            return Some(DayModifier::NthWeekday(day, nth));
+        }
+        let day = token.strip_suffix('L').filter(|d| !d.is_empty())?;
+        let day = parse_value(day, true, false).filter(|d| *d <= 6)?;
+        return Some(DayModifier::LastOfWeekday(day));
+    }
+
+    match token {
+        "L" => Some(DayModifier::LastDay(0)),
+        "LW" => Some(DayModifier::LastWeekday),
+        _ => {
+            if let Some(offset) = token.strip_prefix("L-") {
+                let offset: u32 = offset.parse().ok().filter(|o| *o <= 30)?;
+                return Some(DayModifier::LastDay(offset));
+            }
+            let day: u32 = token.strip_suffix('W')?.parse().ok()?;
+            (1..=31)
+                .contains(&day)
+                .then_some(DayModifier::NearestWeekday(day))
+        }
+    }
+}
+
+/// Expand an `@macro` to its field form.  `@reboot` has no field form.
+fn expand_macro(expr: &str) -> Option<&'static str> {
+    match expr.to_lowercase().as_str() {
+        "@yearly" | "@annually" => Some("0 0 1 1 *"),
+        "@monthly" => Some("0 0 1 * *"),
+        "@weekly" => Some("0 0 * * 0"),
+        "@daily" | "@midnight" => Some("0 0 * * *"),
+        "@hourly" => Some("0 * * * *"),
+        _ => None,
+    }
+}
+
+/// Parse a 5- or 6-field cron expression string (or an `@macro`).
 fn parse(expr: &str) -> Option<CronSchedule> {
+    let expr = expr.trim();
+    let expr = if expr.starts_with('@') {
+        expand_macro(expr)?
+    } else {
+        expr
+    };
+
     let fields: Vec<&str> = expr.split_whitespace().collect();
-    if fields.len() != 5 {
-        return None;
-    }
+    let (seconds, fields) = match fields.len() {
+        5 => (None, &fields[..]),
+        6 => (
+            Some(parse_field(fields[0], 0, 59, false, false)?),
+            &fields[1..],
+        ),
+        _ => return None,
+    };
 
     let minutes = parse_field(fields[0], 0, 59, false, false)?;
     let hours = parse_field(fields[1], 0, 23, false, false)?;
-    let days_of_month = parse_field(fields[2], 1, 31, false, false)?;
+    let days_of_month = parse_day_field(fields[2], false)?;
     let months = parse_field(fields[3], 1, 12, false, true)?;
-    let days_of_week = parse_field(fields[4], 0, 6, true, false)?;
+    let days_of_week = parse_day_field(fields[4], true)?;
 
     Some(CronSchedule {
+        seconds,
         minutes,
         hours,
         days_of_month,
@@ -182,63 +374,101 @@ fn parse(expr: &str) -> Option<CronSchedule> {
 }
 
 impl CronSchedule {
-    /// Check whether a given UTC datetime matches this schedule.
+    /// Check whether a given local wall-clock time matches this schedule.
+    ///
+    /// The seconds are only compared for 6-field expressions.
+    fn matches(&self, dt: &NaiveDateTime) -> bool {
+        match &self.seconds {
+            Some(seconds) => seconds.matches(dt.second()) && self.matches_minute(dt),
+            None => self.matches_minute(dt),
+        }
+    }
+
+    /// Check the minute-and-coarser fields of a local wall-clock time.
     ///
     /// Follows the standard cron convention: if both `day-of-month` and
     /// `day-of-week` are restricted (non-`*`), the datetime matches when
     /// *either* condition is true (OR semantics).  When only one is
     /// restricted, only that condition is tested.
-    fn matches(&self, dt: &DateTime<Utc>) -> bool {
-        let minute = dt.minute();
-        let hour = dt.hour();
+    fn matches_minute(&self, dt: &NaiveDateTime) -> bool {
+        let date = dt.date();
         let dom = dt.day();
-        let month = dt.month();
         // chrono weekday: Mon=0 .. Sun=6; cron convention: Sun=0 .. Sat=6
         let dow = dt.weekday().num_days_from_sunday();
 
-        if !self.minutes.matches(minute) {
+        if !self.minutes.matches(dt.minute()) {
             return false;
         }
-        if !self.hours.matches(hour) {
+        if !self.hours.matches(dt.hour()) {
             return false;
         }
-        if !self.months.matches(month) {
+        if !self.months.matches(dt.month()) {
             return false;
         }
 
         // Day matching: OR semantics when both are restricted
-        let dom_restricted = !matches!(self.days_of_month, Field::Any);
-        let dow_restricted = !matches!(self.days_of_week, Field::Any);
-
-        match (dom_restricted, dow_restricted) {
+        match (
+            self.days_of_month.is_restricted(),
+            self.days_of_week.is_restricted(),
+        ) {
             (true, true) => {
                 // Either day-of-month OR day-of-week must match
-                self.days_of_month.matches(dom) || self.days_of_week.matches(dow)
+                self.days_of_month.matches(dom, date) || self.days_of_week.matches(dow, date)
             }
-            (true, false) => self.days_of_month.matches(dom),
-            (false, true) => self.days_of_week.matches(dow),
+            (true, false) => self.days_of_month.matches(dom, date),
+            (false, true) => self.days_of_week.matches(dow, date),
             (false, false) => true,
         }
     }
 
-    /// Compute the next firing time strictly after `after`.
+    /// Seconds within a matching minute at which the schedule fires, ascending.
+    fn fire_seconds(&self) -> Vec<u32> {
+        match &self.seconds {
+            Some(field) => (0..60).filter(|s| field.matches(*s)).collect(),
+            None => vec![0],
+        }
+    }
+
+    /// Compute the next firing time strictly after `after`, evaluating the
+    /// fields in `tz`.
     ///
-    /// Iterates minute-by-minute up to a maximum of 366 days to handle edge
-    /// cases like Feb 29.  Returns `None` if no match is found within that
-    /// window (should not happen for well-formed schedules).
-    fn next_after(&self, after: &DateTime<Utc>) -> Option<DateTime<Utc>> {
-        // Start from the next whole minute after `after`
-        let mut candidate = *after + Duration::minutes(1);
-        // Zero out seconds/nanoseconds
-        candidate = candidate
-            .with_second(0)
-            .and_then(|d| d.with_nanosecond(0))?;
+    /// Walks minute-by-minute up to a maximum of 366 days to handle edge
+    /// cases like Feb 29, checking the seconds field only within matching
+    /// minutes.  Returns `None` if no match is found within that window
+    /// (e.g. `0 0 30 2 *`).
+    ///
+    /// DST transitions are handled by tracking the latest local time seen:
+    /// wall-clock minutes skipped by a spring-forward fire at the first
+    /// instant after the gap, and minutes repeated by a fall-back only fire
+    /// on their first occurrence.
+    fn next_after<Z: TimeZone>(&self, after: &DateTime<Utc>, tz: &Z) -> Option<DateTime<Utc>> {
+        let seconds = self.fire_seconds();
+        // Start from the minute containing `after`; earlier seconds are skipped below.
+        let mut candidate = after.with_second(0).and_then(|d| d.with_nanosecond(0))?;
 
         let limit = *after + Duration::days(366);
+        let mut high_water = (candidate - Duration::minutes(1))
+            .with_timezone(tz)
+            .naive_local();
 
         while candidate <= limit {
-            if self.matches(&candidate) {
-                return Some(candidate);
+            let local = candidate.with_timezone(tz).naive_local();
+            if local > high_water {
+                // Normally a single minute; longer across a spring-forward gap.
+                let mut wall = high_water + Duration::minutes(1);
+                while wall <= local {
+                    if self.matches_minute(&wall) {
+                        let next = seconds
+                            .iter()
+                            .map(|s| candidate + Duration::seconds(i64::from(*s)))
+                            .find(|t| t > after);
+                        if next.is_some() {
+                            return next;
+                        }
+                    }
+                    wall += Duration::minutes(1);
+                }
+                high_water = local;
             }
             candidate += Duration::minutes(1);
         }
@@ -252,24 +482,48 @@ impl CronSchedule {
 pub struct CronParser;
 
 impl CronParser {
-    /// Return `true` if `expr` is a syntactically valid 5-field cron expression.
+    /// Return `true` if `expr` is a syntactically valid cron expression
+    /// (5 or 6 fields, or an `@macro`).
     pub fn is_valid(expr: &str) -> bool {
-        parse(expr).is_some()
+        Self::is_reboot(expr) || parse(expr).is_some()
+    }
+
+    /// Return `true` if `expr` is `@reboot`, which fires once at startup and
+    /// never has a next run.
+    pub fn is_reboot(expr: &str) -> bool {
+        expr.trim().eq_ignore_ascii_case(REBOOT)
     }
 
     /// Compute the next firing time strictly after `after`.
     ///
-    /// Returns `None` if the expression is invalid or no candidate is found
-    /// within a 366-day window.
+    /// Returns `None` if the expression is invalid, is `@reboot`, or no
+    /// candidate is found within a 366-day window.
     pub fn next_after(expr: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
-        parse(expr)?.next_after(&after)
+        Self::next_after_in(expr, after, &Utc)
+    }
+
+    /// Like [`CronParser::next_after`], but matches the fields against local
+    /// time in `tz`.
+    pub fn next_after_in<Z: TimeZone>(
+        expr: &str,
+        after: DateTime<Utc>,
+        tz: &Z,
+    ) -> Option<DateTime<Utc>> {
+        parse(expr)?.next_after(&after, tz)
     }
 
     /// Return `true` if the given datetime matches the cron expression.
     ///
-    /// Only minute-resolution is considered (seconds are ignored).
+    /// Seconds are only considered for 6-field expressions.
     pub fn matches(expr: &str, dt: &DateTime<Utc>) -> bool {
-        parse(expr).map(|s| s.matches(dt)).unwrap_or(false)
+        Self::matches_in(expr, dt, &Utc)
+    }
+
+    /// Like [`CronParser::matches`], but matches against local time in `tz`.
+    pub fn matches_in<Z: TimeZone>(expr: &str, dt: &DateTime<Utc>, tz: &Z) -> bool {
+        parse(expr)
+            .map(|s| s.matches(&dt.with_timezone(tz).naive_local()))
+            .unwrap_or(false)
     }
 }
 
@@ -285,6 +539,11 @@ mod tests {
             .unwrap()
     }
 
+    fn utc_s(year: i32, month: u32, day: u32, hour: u32, min: u32, sec: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, min, sec)
+            .unwrap()
+    }
+
     // ── is_valid ─────────────────────────────────────────────────────────────
 
     #[test]
@@ -323,7 +582,7 @@ mod tests {
 
     #[test]
     fn test_invalid_too_many_fields() {
-        assert!(!CronParser::is_valid("* * * * * *"));
+        assert!(!CronParser::is_valid("* * * * * * *"));
     }
 
     #[test]
@@ -424,4 +683,252 @@ mod tests {
         let after = utc(2024, 1, 1, 0, 0);
         assert!(CronParser::next_after("not a cron", after).is_none());
     }
+
+    // ── time zones ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_next_after_in_timezone() {
+        // 09:00 in Shanghai (UTC+8, no DST) is 01:00 UTC
+        let tz = chrono_tz::Asia::Shanghai;
+        let next = CronParser::next_after_in("0 9 * * *", utc(2024, 6, 15, 2, 0), &tz).unwrap();
+        assert_eq!(next, utc(2024, 6, 16, 1, 0));
+    }
+
+    #[test]
+    fn test_next_after_in_timezone_follows_dst() {
+        // Berlin is UTC+1 in winter and UTC+2 in summer
+        let tz = chrono_tz::Europe::Berlin;
+        let winter = CronParser::next_after_in("0 9 * * *", utc(2024, 1, 10, 12, 0), &tz);
+        assert_eq!(winter, Some(utc(2024, 1, 11, 8, 0)));
+        let summer = CronParser::next_after_in("0 9 * * *", utc(2024, 7, 10, 12, 0), &tz);
+        assert_eq!(summer, Some(utc(2024, 7, 11, 7, 0)));
+    }
+
+    #[test]
+    fn test_next_after_in_spring_forward_gap_fires_once() {
+        // 2024-03-31 02:30 does not exist in Berlin (clocks jump 02:00 -> 03:00,
+        // i.e. 01:00 UTC). The job fires at the first instant after the gap.
+        let tz = chrono_tz::Europe::Berlin;
+        let next = CronParser::next_after_in("30 2 * * *", utc(2024, 3, 30, 12, 0), &tz).unwrap();
+        assert_eq!(next, utc(2024, 3, 31, 1, 0));
+        let after = CronParser::next_after_in("30 2 * * *", next, &tz).unwrap();
+        assert_eq!(after, utc(2024, 4, 1, 0, 30));
+    }
+
+    #[test]
+    fn test_next_after_in_fall_back_fires_once() {
+        // 2024-10-27 02:30 happens twice in Berlin (00:30 and 01:30 UTC);
+        // only the first occurrence fires.
+        let tz = chrono_tz::Europe::Berlin;
+        let next = CronParser::next_after_in("30 2 * * *", utc(2024, 10, 26, 12, 0), &tz).unwrap();
+        assert_eq!(next, utc(2024, 10, 27, 0, 30));
+        let after = CronParser::next_after_in("30 2 * * *", next, &tz).unwrap();
+        assert_eq!(after, utc(2024, 10, 28, 1, 30));
+    }
+
+    #[test]
+    fn test_matches_in_timezone() {
+        let tz = chrono_tz::Asia::Shanghai;
+        assert!(CronParser::matches_in(
+            "0 9 * * *",
+            &utc(2024, 6, 15, 1, 0),
+            &tz
+        ));
+        assert!(!CronParser::matches_in(
+            "0 9 * * *",
+            &utc(2024, 6, 15, 9, 0),
+            &tz
+        ));
+    }
+
+    // ── seconds field ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_valid_six_fields() {
+        assert!(CronParser::is_valid("* * * * * *"));
+        assert!(CronParser::is_valid("*/10 * * * * *"));
+        assert!(!CronParser::is_valid("60 * * * * *"));
+    }
+
+    #[test]
+    fn test_next_after_seconds_step() {
+        let after = utc_s(2024, 6, 15, 12, 30, 5);
+        let next = CronParser::next_after("*/10 * * * * *", after).unwrap();
+        assert_eq!(next, utc_s(2024, 6, 15, 12, 30, 10));
+        let next =
+            CronParser::next_after("*/10 * * * * *", utc_s(2024, 6, 15, 12, 30, 50)).unwrap();
+        assert_eq!(next, utc_s(2024, 6, 15, 12, 31, 0));
+    }
+
+    #[test]
+    fn test_next_after_seconds_in_later_minute() {
+        // "30 0 9 * * *" — 09:00:30 daily
+        let after = utc_s(2024, 6, 15, 9, 0, 30);
+        let next = CronParser::next_after("30 0 9 * * *", after).unwrap();
+        assert_eq!(next, utc_s(2024, 6, 16, 9, 0, 30));
+    }
+
+    #[test]
+    fn test_matches_seconds() {
+        assert!(CronParser::matches(
+            "15 * * * * *",
+            &utc_s(2024, 1, 1, 0, 0, 15)
+        ));
+        assert!(!CronParser::matches(
+            "15 * * * * *",
+            &utc_s(2024, 1, 1, 0, 0, 16)
+        ));
+        // 5-field expressions ignore seconds
+        assert!(CronParser::matches(
+            "* * * * *",
+            &utc_s(2024, 1, 1, 0, 0, 16)
+        ));
+    }
+
+    // ── macros ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_macros() {
+        let after = utc(2024, 6, 15, 12, 30);
+        assert_eq!(
+            CronParser::next_after("@hourly", after),
+            Some(utc(2024, 6, 15, 13, 0))
+        );
+        assert_eq!(
+            CronParser::next_after("@daily", after),
+            Some(utc(2024, 6, 16, 0, 0))
+        );
+        assert_eq!(
+            CronParser::next_after("@midnight", after),
+            Some(utc(2024, 6, 16, 0, 0))
+        );
+        // 2024-06-16 is a Sunday
+        assert_eq!(
+            CronParser::next_after("@weekly", after),
+            Some(utc(2024, 6, 16, 0, 0))
+        );
+        assert_eq!(
+            CronParser::next_after("@monthly", after),
+            Some(utc(2024, 7, 1, 0, 0))
+        );
+        assert_eq!(
+            CronParser::next_after("@yearly", after),
+            Some(utc(2025, 1, 1, 0, 0))
+        );
+        assert_eq!(
+            CronParser::next_after("@annually", after),
+            Some(utc(2025, 1, 1, 0, 0))
+        );
+        assert!(!CronParser::is_valid("@fortnightly"));
+    }
+
+    #[test]
+    fn test_reboot_macro() {
+        assert!(CronParser::is_valid("@reboot"));
+        assert!(CronParser::is_reboot("@REBOOT"));
+        assert!(!CronParser::is_reboot("@daily"));
+        assert!(CronParser::next_after("@reboot", utc(2024, 1, 1, 0, 0)).is_none());
+        assert!(!CronParser::matches("@reboot", &utc(2024, 1, 1, 0, 0)));
+    }
+
+    // ── L / W / # modifiers ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_valid_modifiers() {
+        for expr in [
+            "0 0 L * *",
+            "0 0 L-3 * *",
+            "0 0 LW * *",
+            "0 0 15W * *",
+            "0 0 ? * 5L",
+            "0 0 * * FRIL",
+            "0 0 * * 1#2",
+            "0 0 * * MON#1,FRI#3",
+            "0 0 * * L",
+        ] {
+            assert!(CronParser::is_valid(expr), "{expr} should be valid");
+        }
+        for expr in [
+            "0 0 * * 1#6",
+            "0 0 * * 7L",
+            "0 0 32W * *",
+            "0 0 W * *",
+            "0 0 L-31 * *",
+        ] {
+            assert!(!CronParser::is_valid(expr), "{expr} should be invalid");
+        }
+    }
+
+    #[test]
+    fn test_next_after_last_day_of_month() {
+        // Leap year February
+        let next = CronParser::next_after("0 0 L * *", utc(2024, 2, 10, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 2, 29, 0, 0));
+        let next = CronParser::next_after("0 0 L * *", next).unwrap();
+        assert_eq!(next, utc(2024, 3, 31, 0, 0));
+        let next = CronParser::next_after("0 0 L-2 * *", utc(2024, 4, 1, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 4, 28, 0, 0));
+    }
+
+    #[test]
+    fn test_next_after_last_weekday_of_month() {
+        // 2024-08-31 is a Saturday → last weekday is Friday 30th
+        let next = CronParser::next_after("0 0 LW * *", utc(2024, 8, 1, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 8, 30, 0, 0));
+        // 2024-09-30 is a Monday
+        let next = CronParser::next_after("0 0 LW * *", next).unwrap();
+        assert_eq!(next, utc(2024, 9, 30, 0, 0));
+    }
+
+    #[test]
+    fn test_next_after_nearest_weekday() {
+        // 2024-06-15 is a Saturday → Friday 14th
+        let next = CronParser::next_after("0 9 15W * *", utc(2024, 6, 1, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 6, 14, 9, 0));
+        // 2024-09-15 is a Sunday → Monday 16th
+        let next = CronParser::next_after("0 9 15W * *", utc(2024, 9, 1, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 9, 16, 9, 0));
+        // 2024-06-01 is a Saturday; 1W must not leave the month → Monday 3rd
+        let next = CronParser::next_after("0 9 1W * *", utc(2024, 5, 31, 12, 0)).unwrap();
+        assert_eq!(next, utc(2024, 6, 3, 9, 0));
+        // 2024-03-31 is a Sunday; 31W → Friday 29th
+        let next = CronParser::next_after("0 9 31W * *", utc(2024, 3, 1, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 3, 29, 9, 0));
+    }
+
+    #[test]
+    fn test_next_after_last_friday() {
+        // Last Friday of June 2024 is the 28th
+        let next = CronParser::next_after("0 17 * * 5L", utc(2024, 6, 1, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 6, 28, 17, 0));
+        let next = CronParser::next_after("0 17 * * FRIL", next).unwrap();
+        assert_eq!(next, utc(2024, 7, 26, 17, 0));
+    }
+
+    #[test]
+    fn test_next_after_nth_weekday() {
+        // Second Monday of June 2024 is the 10th
+        let next = CronParser::next_after("0 9 * * 1#2", utc(2024, 6, 1, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 6, 10, 9, 0));
+        let next = CronParser::next_after("0 9 * * MON#2", next).unwrap();
+        assert_eq!(next, utc(2024, 7, 8, 9, 0));
+        // Fifth Friday only exists in some months: Aug 2024 has one (30th)
+        let next = CronParser::next_after("0 9 * * 5#5", utc(2024, 6, 1, 0, 0)).unwrap();
+        assert_eq!(next, utc(2024, 8, 30, 9, 0));
+    }
+
+    #[test]
+    fn test_dow_l_alone_is_saturday() {
+        // 2024-06-15 is a Saturday
+        assert!(CronParser::matches("0 9 * * L", &utc(2024, 6, 15, 9, 0)));
+        assert!(!CronParser::matches("0 9 * * L", &utc(2024, 6, 14, 9, 0)));
+    }
+
+    #[test]
+    fn test_modifiers_or_with_other_day_field() {
+        // "1st of the month OR last Friday" — both restricted → OR semantics
+        assert!(CronParser::matches("0 0 1 * 5L", &utc(2024, 6, 1, 0, 0)));
+        assert!(CronParser::matches("0 0 1 * 5L", &utc(2024, 6, 28, 0, 0)));
+        assert!(!CronParser::matches("0 0 1 * 5L", &utc(2024, 6, 21, 0, 0)));
+    }
 }
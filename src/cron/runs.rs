@@ -0,0 +1,331 @@
+//! Cron run history — one append-only JSONL file per job.
+//!
+//! Every finished run (scheduled or manually triggered) is appended to
+//! `<dir>/<job>.jsonl`, the job name escaped so distinct names never share a
+//! file.  Appends hold `<dir>/<job>.lock`, so the scheduler, webhooks and
+//! other instances can record the same job concurrently.  Only the newest
+//! [`MAX_RUNS_PER_JOB`] runs are listed; the file is trimmed to them now and
+//! then, rather than rewritten on every append.
+//! A run may also be recorded while still [`RunStatus::Running`]; recording
+//! it again under the same id supersedes the earlier entry.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Runs kept per job; older entries are not listed and are dropped when the
+/// log is trimmed.
+pub const MAX_RUNS_PER_JOB: usize = 500;
+/// A job's log is checked for trimming whenever an append crosses a multiple
+/// of this size.
+const TRIM_CHECK_BYTES: u64 = 256 * 1024;
+
+/// Outcome of a single run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Success,
+    Error,
+}
+
+/// Token usage reported by the model for a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+}
+
+/// A single recorded run of a scheduled job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronRun {
+    /// Unique run identifier.
+    pub id: String,
+    /// Name / id of the job this run belongs to.
+    pub schedule_name: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Wall-clock duration in milliseconds.
+    pub duration_ms: Option<u64>,
+    pub status: RunStatus,
+    /// The agent's response text.
+    pub result: Option<String>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub usage: Option<RunUsage>,
+    /// Session the run's conversation was recorded in.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Outbound delivery carrying the result, if the job posts to a channel.
+    #[serde(default)]
+    pub delivery_id: Option<String>,
+}
+
+impl CronRun {
+    /// Start a new run record for `schedule_name`, timestamped now.
+    pub fn start(schedule_name: impl Into<String>) -> Self {
+        let schedule_name = schedule_name.into();
+        let started_at = Utc::now();
+        Self {
+            id: format!(
+                "run-{}-{}-{}",
+                schedule_name,
+                started_at.timestamp_millis(),
+                &uuid::Uuid::new_v4().simple().to_string()[..6]
+            ),
+            schedule_name,
+            started_at,
+            finished_at: None,
+            duration_ms: None,
+            status: RunStatus::Running,
+            result: None,
+            error: None,
+            usage: None,
+            session_id: None,
+            delivery_id: None,
+        }
+    }
+
+    /// Mark the run as succeeded with the agent's response.
+    pub fn succeed(mut self, result: impl Into<String>, usage: Option<RunUsage>) -> Self {
+        self.finish(RunStatus::Success);
+        self.result = Some(result.into());
+        self.usage = usage;
+        self
+    }
+
+    /// Mark the run as failed.
+    pub fn fail(mut self, error: impl Into<String>) -> Self {
+        self.finish(RunStatus::Error);
+        self.error = Some(error.into());
+        self
+    }
+
+    fn finish(&mut self, status: RunStatus) {
+        let now = Utc::now();
+        self.finished_at = Some(now);
+        self.duration_ms = Some((now - self.started_at).num_milliseconds().max(0) as u64);
+        self.status = status;
+    }
+}
+
+/// One page of run history, newest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunPage {
+    /// Total runs recorded for the job.
+    pub total: usize,
+    pub runs: Vec<CronRun>,
+}
+
+/// File-backed run history for all jobs.
+pub struct CronRunLog {
+    dir: PathBuf,
+}
+
+impl CronRunLog {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Default directory: `~/.synapse/cron/runs`.
+    pub fn default_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".synapse")
+            .join("cron")
+            .join("runs")
+    }
+
+    /// Log file of a job: its name with every byte outside `[A-Za-z0-9_-]`
+    /// written as `%XX`.
+    fn path_for(&self, schedule_name: &str) -> PathBuf {
+        let mut file = String::with_capacity(schedule_name.len());
+        for b in schedule_name.bytes() {
+            if b.is_ascii_alphanumeric() || b == b'-' || b == b'_' {
+                file.push(b as char);
+            } else {
+                file.push_str(&format!("%{:02X}", b));
+            }
+        }
+        self.dir.join(format!("{}.jsonl", file))
+    }
+
+    /// Append a run to its job's log, under the job's lock file.
+    pub fn record(&self, run: &CronRun) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(&run.schedule_name);
+        let mut line = serde_json::to_string(run)?;
+        line.push('\n');
+
+        let lock = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path.with_extension("lock"))?;
+        // Held until `lock` is dropped.
+        lock.lock()?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let before = file.metadata()?.len();
+        file.write_all(line.as_bytes())?;
+        let after = before + line.len() as u64;
+        if before / TRIM_CHECK_BYTES != after / TRIM_CHECK_BYTES {
+            self.trim(&run.schedule_name)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite a job's log with only its newest [`MAX_RUNS_PER_JOB`] runs.
+    /// The caller holds the job's lock.
+    fn trim(&self, schedule_name: &str) -> std::io::Result<()> {
+        let runs = self.load(schedule_name);
+        let mut content = String::new();
+        for r in &runs {
+            content.push_str(&serde_json::to_string(r)?);
+            content.push('\n');
+        }
+        let path = self.path_for(schedule_name);
+        let tmp = path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &path)
+    }
+
+    /// Page through a job's runs, newest first.
+    pub fn list(&self, schedule_name: &str, offset: usize, limit: usize) -> RunPage {
+        let runs = self.load(schedule_name);
+        RunPage {
+            total: runs.len(),
+            runs: runs.into_iter().rev().skip(offset).take(limit).collect(),
+        }
+    }
+
+    /// Latest record of run `id` of a job.
+    pub fn get(&self, schedule_name: &str, id: &str) -> Option<CronRun> {
+        self.load(schedule_name).into_iter().find(|r| r.id == id)
+    }
+
+    /// The newest [`MAX_RUNS_PER_JOB`] runs of a job, oldest first, with later
+    /// records of a run replacing earlier ones.  Malformed lines are skipped.
+    fn load(&self, schedule_name: &str) -> Vec<CronRun> {
+        let Ok(content) = std::fs::read_to_string(self.path_for(schedule_name)) else {
+            return Vec::new();
+        };
+        let mut runs: Vec<CronRun> = Vec::new();
+        for run in content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str::<CronRun>(l).ok())
+        {
+            match runs.iter_mut().find(|r| r.id == run.id) {
+                Some(existing) => *existing = run,
+                None => runs.push(run),
+            }
+        }
+        if runs.len() > MAX_RUNS_PER_JOB {
+            runs.drain(..runs.len() - MAX_RUNS_PER_JOB);
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_page_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = CronRunLog::new(dir.path().to_path_buf());
+        for i in 0..5 {
+            let run = CronRun::start("brief").succeed(format!("result {i}"), None);
+            log.record(&run).unwrap();
+        }
+        log.record(&CronRun::start("other").fail("boom")).unwrap();
+
+        let page = log.list("brief", 0, 2);
+        assert_eq!(page.total, 5);
+        assert_eq!(page.runs.len(), 2);
+        assert_eq!(page.runs[0].result.as_deref(), Some("result 4"));
+        assert_eq!(page.runs[1].result.as_deref(), Some("result 3"));
+
+        let page = log.list("brief", 4, 10);
+        assert_eq!(page.runs.len(), 1);
+        assert_eq!(page.runs[0].result.as_deref(), Some("result 0"));
+
+        let other = log.list("other", 0, 10);
+        assert_eq!(other.total, 1);
+        assert_eq!(other.runs[0].status, RunStatus::Error);
+        assert_eq!(other.runs[0].error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn finished_run_has_duration_and_usage() {
+        let usage = RunUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+        };
+        let run = CronRun::start("brief").succeed("ok", Some(usage));
+        assert_eq!(run.status, RunStatus::Success);
+        assert!(run.finished_at.is_some());
+        assert!(run.duration_ms.is_some());
+        assert_eq!(run.usage, Some(usage));
+    }
+
+    #[test]
+    fn names_are_escaped_into_distinct_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = CronRunLog::new(dir.path().to_path_buf());
+        log.record(&CronRun::start("../evil name").fail("x"))
+            .unwrap();
+        assert!(dir.path().join("%2E%2E%2Fevil%20name.jsonl").exists());
+        assert_eq!(log.list("../evil name", 0, 10).total, 1);
+
+        log.record(&CronRun::start("a.b").fail("x")).unwrap();
+        log.record(&CronRun::start("a_b").fail("x")).unwrap();
+        log.record(&CronRun::start("a_b").fail("x")).unwrap();
+        assert_eq!(log.list("a.b", 0, 10).total, 1);
+        assert_eq!(log.list("a_b", 0, 10).total, 2);
+    }
+
+    #[test]
+    fn later_record_supersedes_running_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = CronRunLog::new(dir.path().to_path_buf());
+        let run = CronRun::start("hook");
+        log.record(&run).unwrap();
+        assert_eq!(log.get("hook", &run.id).unwrap().status, RunStatus::Running);
+
+        let id = run.id.clone();
+        log.record(&run.succeed("done", None)).unwrap();
+        let page = log.list("hook", 0, 10);
+        assert_eq!(page.total, 1);
+        assert_eq!(page.runs[0].status, RunStatus::Success);
+        assert_eq!(
+            log.get("hook", &id).unwrap().result.as_deref(),
+            Some("done")
+        );
+        assert!(log.get("hook", "nope").is_none());
+    }
+
+    #[test]
+    fn log_is_trimmed() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = CronRunLog::new(dir.path().to_path_buf());
+        let path = log.path_for("busy");
+        let mut trimmed = false;
+        for _ in 0..MAX_RUNS_PER_JOB * 3 {
+            let before = std::fs::metadata(&path).map_or(0, |m| m.len());
+            log.record(&CronRun::start("busy").fail("x".repeat(200)))
+                .unwrap();
+            trimmed |= std::fs::metadata(&path).unwrap().len() < before;
+        }
+        assert!(trimmed);
+        assert_eq!(log.list("busy", 0, 1).total, MAX_RUNS_PER_JOB);
+    }
+}
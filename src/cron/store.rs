@@ -1,16 +1,81 @@
 //! Cron job persistence — in-memory store with optional JSONL file backing.
+//!
+//! The gateway keeps its jobs in `~/.synapse/cron/jobs.jsonl` (see
+//! [`CronStore::default_path`]).
 
-use std::sync::RwLock;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
+use super::parser::CronParser;
+
+/// Expression recorded for one-shot jobs, which have no recurring schedule.
+pub const ONCE: &str = "@once";
+
+/// Upper bound on catch-up runs fired for a single job under
+/// [`MisfirePolicy::RunAll`].
+const MAX_CATCH_UP_RUNS: usize = 100;
+
+/// How late a run may start before it counts as a misfire.
+const MISFIRE_GRACE_SECS: i64 = 60;
+
+/// What to do with runs whose scheduled time passed while the service was
+/// not running (or was otherwise unable to fire them on time).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Drop missed runs and wait for the next scheduled time.
+    Skip,
+    /// Fire once to catch up, however many runs were missed.
+    #[default]
+    RunOnce,
+    /// Fire every missed run, oldest first.
+    RunAll,
+}
+
+impl MisfirePolicy {
+    /// Parse a policy name (`skip`, `run_once`, `run_all`; dashes accepted).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "skip" => Some(Self::Skip),
+            "run_once" => Some(Self::RunOnce),
+            "run_all" => Some(Self::RunAll),
+            _ => None,
+        }
+    }
+}
+
+/// Channel peer a job's result is posted to once the run finishes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliverTarget {
+    /// Channel adapter name, e.g. "lark" or "slack".
+    pub channel: String,
+    /// Platform-specific recipient, e.g. "chat:oc_xxx" for a Lark group.
+    pub to: String,
+    /// Bot account to send from, for channels with several accounts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// Thread to post into, where the channel supports threads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+}
+
+/// Parse an IANA timezone name such as `Asia/Shanghai`.
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.parse().ok()
+}
+
 /// A single cron-scheduled agent job.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CronJob {
     /// Unique job identifier (UUID v4).
     pub id: String,
-    /// Standard 5-field cron expression (`minute hour dom month dow`).
+    /// Cron expression (`[second] minute hour dom month dow`, or an `@macro`).
     pub expression: String,
     /// Agent identifier to invoke when the job fires.
     pub agent_id: String,
@@ -28,6 +93,36 @@ pub struct CronJob {
     pub failure_count: u32,
     /// Maximum retries before the job is disabled.
     pub max_retries: u32,
+    /// IANA timezone the expression is evaluated in; `None` means UTC.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Upper bound, in seconds, of the random delay added to each run.
+    #[serde(default)]
+    pub jitter_secs: u64,
+    /// Catch-up behaviour for runs missed while the service was down.
+    #[serde(default)]
+    pub misfire: MisfirePolicy,
+    /// A manual run was requested and hasn't been picked up yet.
+    #[serde(default)]
+    pub run_now: bool,
+    /// Mirrored from a `[[schedule]]` config entry (and dropped when the
+    /// entry is removed), as opposed to added at runtime.
+    #[serde(default)]
+    pub from_config: bool,
+    /// Where to post the agent's response; `None` only records the run.
+    #[serde(default)]
+    pub deliver: Option<DeliverTarget>,
+    /// Fire once at `next_run`, then delete the job once a run succeeds (a
+    /// reminder rather than a recurring schedule).  Failed runs are retried
+    /// until `max_retries`, after which the job is kept, disabled.
+    #[serde(default)]
+    pub once: bool,
+    /// Session key of the chat the job was created from, if any.
+    #[serde(default)]
+    pub session_key: Option<String>,
+    /// User who created the job from chat (for per-user limits).
+    #[serde(default)]
+    pub owner: Option<String>,
 }
 
 impl CronJob {
@@ -49,16 +144,166 @@ impl CronJob {
             next_run: None,
             failure_count: 0,
             max_retries: 3,
+            timezone: None,
+            jitter_secs: 0,
+            misfire: MisfirePolicy::default(),
+            run_now: false,
+            from_config: false,
+            deliver: None,
+            once: false,
+            session_key: None,
+            owner: None,
+        }
+    }
+
+    /// Create a job that fires once at `at` and is removed once it succeeds.
+    pub fn once_at(
+        id: impl Into<String>,
+        at: DateTime<Utc>,
+        agent_id: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        let mut job = Self::new(id, ONCE, agent_id, message);
+        job.once = true;
+        job.next_run = Some(at);
+        job
+    }
+
+    /// Set the timezone the expression is evaluated in.
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Set the maximum random delay added to each run.
+    pub fn with_jitter_secs(mut self, jitter_secs: u64) -> Self {
+        self.jitter_secs = jitter_secs;
+        self
+    }
+
+    /// Set the misfire policy.
+    pub fn with_misfire(mut self, misfire: MisfirePolicy) -> Self {
+        self.misfire = misfire;
+        self
+    }
+
+    /// Post each run's result to `target`.
+    pub fn with_deliver(mut self, target: DeliverTarget) -> Self {
+        self.deliver = Some(target);
+        self
+    }
+
+    /// The job's timezone, falling back to UTC when unset or unknown.
+    pub fn tz(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(parse_timezone)
+            .unwrap_or(Tz::UTC)
+    }
+
+    /// First scheduled time for a job that is just being started: `now` for
+    /// `@reboot` jobs, the fixed time of one-shot jobs, otherwise the next
+    /// time after `now`.
+    pub fn first_run(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.once {
+            self.next_run
+        } else if CronParser::is_reboot(&self.expression) {
+            Some(now)
+        } else {
+            self.next_after(now)
+        }
+    }
+
+    /// Next scheduled (un-jittered) time strictly after `after`; always
+    /// `None` for one-shot jobs.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.once {
+            return None;
+        }
+        CronParser::next_after_in(&self.expression, after, &self.tz())
+    }
+
+    /// Record the outcome of a run: a success resets `failure_count`, a
+    /// failure counts against it and disables the job once it exceeds
+    /// `max_retries`.  Returns `true` if this disabled the job.
+    pub fn record_outcome(&mut self, succeeded: bool) -> bool {
+        if succeeded {
+            self.failure_count = 0;
+            return false;
+        }
+        self.failure_count += 1;
+        if self.enabled && self.failure_count > self.max_retries {
+            self.enabled = false;
+            return true;
+        }
+        false
+    }
+
+    /// Jitter applied to the run scheduled at `scheduled`, in `0..=jitter_secs`.
+    ///
+    /// Derived from the job id and scheduled time rather than drawn fresh, so
+    /// a restart doesn't reroll the delay of a run that is already waiting.
+    pub fn jitter_for(&self, scheduled: DateTime<Utc>) -> Duration {
+        if self.jitter_secs == 0 {
+            return Duration::zero();
+        }
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.id.hash(&mut hasher);
+        scheduled.timestamp().hash(&mut hasher);
+        Duration::seconds((hasher.finish() % (self.jitter_secs + 1)) as i64)
+    }
+
+    /// Scheduled times that should fire at `now`, oldest first.
+    ///
+    /// Empty if `next_run` (plus its jitter) hasn't arrived yet.  If more
+    /// than one run has come due, or the single due run is late by more than
+    /// the grace period, the job's [`MisfirePolicy`] decides what fires.
+    pub fn due_runs(&self, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let Some(first) = self.next_run else {
+            return Vec::new();
+        };
+        let fire_at = first + self.jitter_for(first);
+        if fire_at > now {
+            return Vec::new();
+        }
+
+        let mut missed = vec![first];
+        while missed.len() < MAX_CATCH_UP_RUNS {
+            match self.next_after(*missed.last().unwrap_or(&first)) {
+                Some(next) if next <= now => missed.push(next),
+                _ => break,
+            }
+        }
+
+        let late = now - fire_at > Duration::seconds(MISFIRE_GRACE_SECS);
+        if missed.len() == 1 && !late {
+            return missed;
+        }
+        match self.misfire {
+            MisfirePolicy::Skip => Vec::new(),
+            MisfirePolicy::RunOnce => missed.split_off(missed.len() - 1),
+            MisfirePolicy::RunAll => missed,
         }
     }
 }
 
-/// In-memory cron job store.
+/// Cron job store.
 ///
 /// All operations acquire the inner `RwLock`; the store is `Send + Sync` and
 /// can be wrapped in `Arc` and shared across tasks.
+///
+/// A store created with [`CronStore::open`] is backed by a JSONL file (one
+/// job per line).  Every mutation rewrites the file atomically, and picks up
+/// changes made by other `CronStore` instances on the same file first.
 pub struct CronStore {
     jobs: RwLock<Vec<CronJob>>,
+    backing: Option<Backing>,
+}
+
+struct Backing {
+    path: PathBuf,
+    /// Modification time of `path` as of the last load or write.
+    mtime: Mutex<Option<SystemTime>>,
 }
 
 impl Default for CronStore {
@@ -68,37 +313,107 @@ impl Default for CronStore {
 }
 
 impl CronStore {
-    /// Create an empty store.
+    /// Create an empty in-memory store.
     pub fn new() -> Self {
         Self {
             jobs: RwLock::new(Vec::new()),
+            backing: None,
         }
     }
 
-    /// Create a store pre-populated with the given jobs.
+    /// Create an in-memory store pre-populated with the given jobs.
     pub fn with_jobs(jobs: Vec<CronJob>) -> Self {
         Self {
             jobs: RwLock::new(jobs),
+            backing: None,
+        }
+    }
+
+    /// Open a file-backed store, loading any jobs already persisted at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let (jobs, mtime) = load_jobs(&path);
+        Self {
+            jobs: RwLock::new(jobs),
+            backing: Some(Backing {
+                path,
+                mtime: Mutex::new(mtime),
+            }),
+        }
+    }
+
+    /// Default job file: `~/.synapse/cron/jobs.jsonl`.
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".synapse")
+            .join("cron")
+            .join("jobs.jsonl")
+    }
+
+    /// Re-read the backing file if it changed since it was last loaded or
+    /// written.  Returns `true` if the in-memory jobs were replaced.
+    pub fn reload_if_changed(&self) -> bool {
+        let mut guard = self.jobs.write().expect("cron store write lock poisoned");
+        self.sync_from_disk(&mut guard)
+    }
+
+    fn sync_from_disk(&self, jobs: &mut Vec<CronJob>) -> bool {
+        let Some(backing) = &self.backing else {
+            return false;
+        };
+        let current = file_mtime(&backing.path);
+        let mut mtime = backing
+            .mtime
+            .lock()
+            .expect("cron store mtime lock poisoned");
+        if current.is_none() || current == *mtime {
+            return false;
         }
+        let (loaded, loaded_mtime) = load_jobs(&backing.path);
+        *jobs = loaded;
+        *mtime = loaded_mtime;
+        true
+    }
+
+    fn persist(&self, jobs: &[CronJob]) {
+        let Some(backing) = &self.backing else {
+            return;
+        };
+        if let Err(e) = write_jobs(&backing.path, jobs) {
+            tracing::warn!(path = %backing.path.display(), error = %e, "failed to persist cron jobs");
+            return;
+        }
+        *backing
+            .mtime
+            .lock()
+            .expect("cron store mtime lock poisoned") = file_mtime(&backing.path);
     }
 
     /// Add or replace a job.  If a job with the same `id` already exists it is
     /// replaced; otherwise the job is appended.
     pub fn upsert(&self, job: CronJob) {
         let mut guard = self.jobs.write().expect("cron store write lock poisoned");
+        self.sync_from_disk(&mut guard);
         if let Some(existing) = guard.iter_mut().find(|j| j.id == job.id) {
             *existing = job;
         } else {
             guard.push(job);
         }
+        self.persist(&guard);
     }
 
     /// Remove the job with the given `id`.  Returns `true` if a job was removed.
     pub fn remove(&self, id: &str) -> bool {
         let mut guard = self.jobs.write().expect("cron store write lock poisoned");
+        self.sync_from_disk(&mut guard);
         let before = guard.len();
         guard.retain(|j| j.id != id);
-        guard.len() < before
+        let removed = guard.len() < before;
+        if removed {
+            self.persist(&guard);
+        }
+        removed
     }
 
     /// Return a snapshot of all jobs.
@@ -137,13 +452,66 @@ impl CronStore {
         F: FnOnce(&mut CronJob),
     {
         let mut guard = self.jobs.write().expect("cron store write lock poisoned");
+        self.sync_from_disk(&mut guard);
         if let Some(job) = guard.iter_mut().find(|j| j.id == id) {
             f(job);
+            self.persist(&guard);
             true
         } else {
             false
         }
     }
+
+    /// Request an immediate out-of-schedule run; the next tick picks it up.
+    /// Returns `true` when the job was found.
+    pub fn trigger(&self, id: &str) -> bool {
+        self.update(id, |j| j.run_now = true)
+    }
+}
+
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Load jobs from a JSONL file.  A missing file is an empty store; malformed
+/// lines are logged and skipped.
+fn load_jobs(path: &Path) -> (Vec<CronJob>, Option<SystemTime>) {
+    let mtime = file_mtime(path);
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (Vec::new(), mtime),
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "failed to read cron jobs");
+            return (Vec::new(), mtime);
+        }
+    };
+    let jobs = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(job) => Some(job),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "skipping malformed cron job");
+                None
+            }
+        })
+        .collect();
+    (jobs, mtime)
+}
+
+/// Atomically replace the JSONL file with `jobs`.
+fn write_jobs(path: &Path, jobs: &[CronJob]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut content = String::new();
+    for job in jobs {
+        content.push_str(&serde_json::to_string(job)?);
+        content.push('\n');
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)
 }
 
 #[cfg(test)]
@@ -195,6 +563,83 @@ mod tests {
         let _ = job1; // suppress unused warning
     }
 
+    #[test]
+    fn file_backed_store_persists_and_reloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cron").join("jobs.jsonl");
+
+        let store = CronStore::open(&path);
+        store.upsert(
+            CronJob::new("job-1", "0 9 * * *", "a", "brief").with_timezone("Europe/Berlin"),
+        );
+        store.upsert(CronJob::new("job-2", "* * * * *", "b", "ping"));
+        assert!(store.remove("job-2"));
+        store.update("job-1", |j| j.failure_count = 2);
+
+        let reopened = CronStore::open(&path);
+        let jobs = reopened.list();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(jobs[0].failure_count, 2);
+        assert!(!path.with_extension("jsonl.tmp").exists());
+    }
+
+    #[test]
+    fn file_backed_store_sees_other_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.jsonl");
+        let a = CronStore::open(&path);
+        a.upsert(CronJob::new("job-1", "* * * * *", "a", "m"));
+
+        // A second instance (e.g. an RPC handler) adds a job ...
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let b = CronStore::open(&path);
+        b.upsert(CronJob::new("job-2", "* * * * *", "a", "m"));
+
+        // ... which the first one picks up rather than overwriting.
+        a.trigger("job-1");
+        let ids: Vec<String> = CronStore::open(&path)
+            .list()
+            .into_iter()
+            .map(|j| j.id)
+            .collect();
+        assert_eq!(ids, vec!["job-1", "job-2"]);
+        assert!(a.get("job-1").unwrap().run_now);
+    }
+
+    #[test]
+    fn deliver_target_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.jsonl");
+        let target = DeliverTarget {
+            channel: "lark".into(),
+            to: "chat:oc_123".into(),
+            account_id: None,
+            thread_id: Some("t-1".into()),
+        };
+        CronStore::open(&path).upsert(
+            CronJob::new("job-1", "0 9 * * *", "ops", "brief").with_deliver(target.clone()),
+        );
+
+        let job = CronStore::open(&path).get("job-1").unwrap();
+        assert_eq!(job.agent_id, "ops");
+        assert_eq!(job.deliver, Some(target));
+
+        // Jobs written before delivery targets existed still load.
+        let legacy = r#"{"id":"old","expression":"* * * * *","agent_id":"default","channel":null,"message":"m","enabled":true,"last_run":null,"next_run":null,"failure_count":0,"max_retries":3}"#;
+        std::fs::write(&path, format!("{legacy}\n")).unwrap();
+        assert_eq!(CronStore::open(&path).get("old").unwrap().deliver, None);
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.jsonl");
+        let job = serde_json::to_string(&CronJob::new("job-1", "* * * * *", "a", "m")).unwrap();
+        std::fs::write(&path, format!("{job}\nnot json\n")).unwrap();
+        assert_eq!(CronStore::open(&path).list().len(), 1);
+    }
+
     #[test]
     fn update_job() {
         let store = CronStore::new();
@@ -203,4 +648,99 @@ mod tests {
         assert!(found);
         assert_eq!(store.get("job-1").unwrap().message, "new");
     }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(year, month, day, hour, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn next_after_uses_job_timezone() {
+        let job = CronJob::new("j", "0 9 * * *", "a", "m").with_timezone("Asia/Shanghai");
+        assert_eq!(
+            job.next_after(utc(2024, 6, 15, 2, 0)),
+            Some(utc(2024, 6, 16, 1, 0))
+        );
+    }
+
+    #[test]
+    fn jitter_is_bounded_and_stable() {
+        let job = CronJob::new("j", "* * * * *", "a", "m").with_jitter_secs(30);
+        let at = utc(2024, 6, 15, 9, 0);
+        let jitter = job.jitter_for(at);
+        assert!(jitter >= Duration::zero() && jitter <= Duration::seconds(30));
+        assert_eq!(jitter, job.jitter_for(at));
+        assert_eq!(
+            CronJob::new("j", "* * * * *", "a", "m").jitter_for(at),
+            Duration::zero()
+        );
+    }
+
+    #[test]
+    fn due_runs_on_time_fires_once() {
+        let mut job = CronJob::new("j", "0 * * * *", "a", "m").with_misfire(MisfirePolicy::Skip);
+        job.next_run = Some(utc(2024, 6, 15, 9, 0));
+        assert!(job.due_runs(utc(2024, 6, 15, 8, 59)).is_empty());
+        assert_eq!(
+            job.due_runs(utc(2024, 6, 15, 9, 0)),
+            vec![utc(2024, 6, 15, 9, 0)]
+        );
+    }
+
+    #[test]
+    fn due_runs_applies_misfire_policy() {
+        let mut job = CronJob::new("j", "0 * * * *", "a", "m");
+        job.next_run = Some(utc(2024, 6, 15, 9, 0));
+        let now = utc(2024, 6, 15, 11, 30);
+
+        job.misfire = MisfirePolicy::Skip;
+        assert!(job.due_runs(now).is_empty());
+
+        job.misfire = MisfirePolicy::RunOnce;
+        assert_eq!(job.due_runs(now), vec![utc(2024, 6, 15, 11, 0)]);
+
+        job.misfire = MisfirePolicy::RunAll;
+        assert_eq!(
+            job.due_runs(now),
+            vec![
+                utc(2024, 6, 15, 9, 0),
+                utc(2024, 6, 15, 10, 0),
+                utc(2024, 6, 15, 11, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn reboot_job_runs_once() {
+        let now = utc(2024, 6, 15, 9, 0);
+        let job = CronJob::new("j", "@reboot", "a", "m");
+        assert_eq!(job.first_run(now), Some(now));
+        assert_eq!(job.next_after(now), None);
+    }
+
+    #[test]
+    fn repeated_failures_disable_the_job() {
+        let mut job = CronJob::new("j", "0 9 * * *", "a", "m");
+        job.max_retries = 2;
+        assert!(!job.record_outcome(false));
+        assert!(!job.record_outcome(true));
+        assert_eq!(job.failure_count, 0);
+        assert!(!job.record_outcome(false));
+        assert!(!job.record_outcome(false));
+        assert!(job.record_outcome(false));
+        assert!(!job.enabled);
+        assert_eq!(job.failure_count, 3);
+    }
+
+    #[test]
+    fn misfire_policy_parse() {
+        assert_eq!(MisfirePolicy::parse("skip"), Some(MisfirePolicy::Skip));
+        assert_eq!(
+            MisfirePolicy::parse("run-once"),
+            Some(MisfirePolicy::RunOnce)
+        );
+        assert_eq!(MisfirePolicy::parse("RUN_ALL"), Some(MisfirePolicy::RunAll));
+        assert_eq!(MisfirePolicy::parse("later"), None);
+    }
 }
@@ -820,6 +820,20 @@ fn build_config_schema() -> Vec<ConfigSectionSchema> {
                     f.default_value = Some("false".into());
                     f
                 },
+                {
+                    let mut f = field("leader_lease_url", "Leader Lease File", "string");
+                    f.description = Some(
+                        "Lease file shared by the instances (default ~/.synapse/leader.lease)"
+                            .into(),
+                    );
+                    f
+                },
+                {
+                    let mut f = field("leader_lease_ttl_secs", "Leader Lease TTL", "number");
+                    f.description = Some("Seconds before a silent leader is replaced".into());
+                    f.default_value = Some("30".into());
+                    f
+                },
             ],
         },
         ConfigSectionSchema {
@@ -184,6 +184,7 @@ async fn debug_invoke(
                     "operator.approvals".to_string(),
                 ]),
                 broadcaster: state.network.broadcaster.clone(),
+                api_token_id: None,
             });
             let frame = state
                 .network
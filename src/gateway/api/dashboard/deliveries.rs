@@ -0,0 +1,71 @@
+use axum::extract::{self, State};
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::routing::{delete, get, post};
+use axum::Router;
+use serde::Serialize;
+
+use super::OkResponse;
+use crate::gateway::messages::QueuedDelivery;
+use crate::gateway::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/dashboard/deliveries", get(get_deliveries))
+        .route("/dashboard/deliveries/{id}/retry", post(retry_delivery))
+        .route("/dashboard/deliveries/{id}", delete(discard_delivery))
+}
+
+// ---------------------------------------------------------------------------
+// GET /api/dashboard/deliveries
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+struct DeliveriesResponse {
+    pending: Vec<QueuedDelivery>,
+    dead_letters: Vec<QueuedDelivery>,
+}
+
+async fn get_deliveries(State(state): State<AppState>) -> Json<DeliveriesResponse> {
+    let delivery = &state.channel.delivery;
+    Json(DeliveriesResponse {
+        pending: delivery.pending().await,
+        dead_letters: delivery.dead_letters().await,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// POST /api/dashboard/deliveries/{id}/retry
+// ---------------------------------------------------------------------------
+
+async fn retry_delivery(
+    State(state): State<AppState>,
+    extract::Path(id): extract::Path<String>,
+) -> Result<Json<OkResponse>, (StatusCode, String)> {
+    match state.channel.delivery.retry(&id).await {
+        Ok(true) => Ok(Json(OkResponse { ok: true })),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            format!("dead-lettered delivery '{}' not found", id),
+        )),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DELETE /api/dashboard/deliveries/{id}
+// ---------------------------------------------------------------------------
+
+async fn discard_delivery(
+    State(state): State<AppState>,
+    extract::Path(id): extract::Path<String>,
+) -> Result<Json<OkResponse>, (StatusCode, String)> {
+    if state.channel.delivery.discard(&id).await {
+        Ok(Json(OkResponse { ok: true }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            format!("delivery '{}' not found", id),
+        ))
+    }
+}
@@ -2,6 +2,7 @@ mod agents;
 mod channels;
 mod config;
 mod debug;
+mod deliveries;
 mod monitoring;
 mod nodes;
 mod plugins;
@@ -37,6 +38,7 @@ pub fn routes() -> Router<AppState> {
         .merge(monitoring::routes())
         .merge(plugins::routes())
         .merge(debug::routes())
+        .merge(deliveries::routes())
 }
 
 // ---------------------------------------------------------------------------
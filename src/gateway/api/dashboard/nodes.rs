@@ -316,5 +316,7 @@ async fn get_exec_approvals(State(state): State<AppState>) -> Json<serde_json::V
         "security_mode": config.mode,
         "ask_policy": config.ask,
         "allowlist": config.allowlist,
+        "denylist": config.denylist,
+        "approvers": config.approvers,
     }))
 }
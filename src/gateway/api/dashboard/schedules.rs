@@ -1,5 +1,3 @@
-use std::path::Path;
-
 use axum::extract::{self, State};
 use axum::http::StatusCode;
 use axum::response::Json;
@@ -8,6 +6,8 @@ use axum::Router;
 use serde::{Deserialize, Serialize};
 
 use super::{read_config_file, OkResponse, ToggleResponse};
+use crate::config::SynapseConfig;
+use crate::cron::{parse_timezone, CronRun, CronRunLog, CronStore, DeliverTarget, MisfirePolicy};
 use crate::gateway::state::AppState;
 
 pub fn routes() -> Router<AppState> {
@@ -36,6 +36,29 @@ struct ScheduleResponseItem {
     interval_secs: Option<u64>,
     enabled: bool,
     description: Option<String>,
+    timezone: Option<String>,
+    jitter_secs: u64,
+    misfire: MisfirePolicy,
+    agent: Option<String>,
+    deliver: Option<DeliverTarget>,
+}
+
+impl From<CreateScheduleRequest> for ScheduleResponseItem {
+    fn from(body: CreateScheduleRequest) -> Self {
+        Self {
+            name: body.name,
+            prompt: body.prompt,
+            cron: body.cron,
+            interval_secs: body.interval_secs,
+            enabled: body.enabled,
+            description: body.description,
+            timezone: body.timezone,
+            jitter_secs: body.jitter_secs,
+            misfire: body.misfire,
+            agent: body.agent,
+            deliver: body.deliver,
+        }
+    }
 }
 
 async fn get_schedules(State(state): State<AppState>) -> Json<Vec<ScheduleResponseItem>> {
@@ -54,6 +77,11 @@ async fn get_schedules(State(state): State<AppState>) -> Json<Vec<ScheduleRespon
                     interval_secs: e.interval_secs,
                     enabled: e.enabled,
                     description: e.description.clone(),
+                    timezone: e.timezone.clone(),
+                    jitter_secs: e.jitter_secs,
+                    misfire: e.misfire,
+                    agent: e.agent.clone(),
+                    deliver: e.deliver.clone(),
                 })
                 .collect()
         })
@@ -75,6 +103,13 @@ struct CreateScheduleRequest {
     #[serde(default = "default_true_fn")]
     enabled: bool,
     description: Option<String>,
+    timezone: Option<String>,
+    #[serde(default)]
+    jitter_secs: u64,
+    #[serde(default)]
+    misfire: MisfirePolicy,
+    agent: Option<String>,
+    deliver: Option<DeliverTarget>,
 }
 
 fn default_true_fn() -> bool {
@@ -82,9 +117,10 @@ fn default_true_fn() -> bool {
 }
 
 async fn create_schedule(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(body): Json<CreateScheduleRequest>,
 ) -> Result<Json<ScheduleResponseItem>, (StatusCode, String)> {
+    validate_schedule(&body, &state.core.config)?;
     let (path, content) = read_config_file().await?;
     let mut doc: toml::Value = toml::from_str(&content).map_err(|e| {
         (
@@ -93,14 +129,7 @@ async fn create_schedule(
         )
     })?;
 
-    let new_entry = build_schedule_toml(
-        &body.name,
-        &body.prompt,
-        &body.cron,
-        &body.interval_secs,
-        body.enabled,
-        &body.description,
-    );
+    let new_entry = build_schedule_toml(&body);
 
     let schedules = doc
         .as_table_mut()
@@ -123,14 +152,7 @@ async fn create_schedule(
 
     tracing::info!("schedule created");
 
-    Ok(Json(ScheduleResponseItem {
-        name: body.name,
-        prompt: body.prompt,
-        cron: body.cron,
-        interval_secs: body.interval_secs,
-        enabled: body.enabled,
-        description: body.description,
-    }))
+    Ok(Json(body.into()))
 }
 
 // ---------------------------------------------------------------------------
@@ -138,10 +160,11 @@ async fn create_schedule(
 // ---------------------------------------------------------------------------
 
 async fn update_schedule(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     extract::Path(name): extract::Path<String>,
     Json(body): Json<CreateScheduleRequest>,
 ) -> Result<Json<ScheduleResponseItem>, (StatusCode, String)> {
+    validate_schedule(&body, &state.core.config)?;
     let (path, content) = read_config_file().await?;
     let mut doc: toml::Value = toml::from_str(&content).map_err(|e| {
         (
@@ -155,14 +178,7 @@ async fn update_schedule(
             .iter()
             .position(|s| s.get("name").and_then(|n| n.as_str()) == Some(&name))
         {
-            arr[pos] = build_schedule_toml(
-                &body.name,
-                &body.prompt,
-                &body.cron,
-                &body.interval_secs,
-                body.enabled,
-                &body.description,
-            );
+            arr[pos] = build_schedule_toml(&body);
         } else {
             return Err((
                 StatusCode::NOT_FOUND,
@@ -183,14 +199,7 @@ async fn update_schedule(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("write: {}", e)))?;
 
-    Ok(Json(ScheduleResponseItem {
-        name: body.name,
-        prompt: body.prompt,
-        cron: body.cron,
-        interval_secs: body.interval_secs,
-        enabled: body.enabled,
-        description: body.description,
-    }))
+    Ok(Json(body.into()))
 }
 
 // ---------------------------------------------------------------------------
@@ -235,44 +244,6 @@ async fn delete_schedule(
     Ok(Json(OkResponse { ok: true }))
 }
 
-// ---------------------------------------------------------------------------
-// Schedule Run History
-// ---------------------------------------------------------------------------
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ScheduleRunEntry {
-    id: String,
-    schedule_name: String,
-    started_at: String,
-    finished_at: Option<String>,
-    status: String,
-    result: Option<String>,
-    error: Option<String>,
-}
-
-const SCHEDULE_RUNS_FILE: &str = "log/schedule_runs.json";
-
-async fn read_schedule_runs() -> Vec<ScheduleRunEntry> {
-    match tokio::fs::read_to_string(SCHEDULE_RUNS_FILE).await {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-        Err(_) => Vec::new(),
-    }
-}
-
-async fn append_schedule_run(entry: ScheduleRunEntry) {
-    let mut runs = read_schedule_runs().await;
-    runs.push(entry);
-
-    if let Some(parent) = Path::new(SCHEDULE_RUNS_FILE).parent() {
-        let _ = tokio::fs::create_dir_all(parent).await;
-    }
-    let _ = tokio::fs::write(
-        SCHEDULE_RUNS_FILE,
-        serde_json::to_string_pretty(&runs).unwrap_or_default(),
-    )
-    .await;
-}
-
 // ---------------------------------------------------------------------------
 // POST /api/dashboard/schedules/{name}/trigger
 // ---------------------------------------------------------------------------
@@ -280,41 +251,46 @@ async fn append_schedule_run(entry: ScheduleRunEntry) {
 async fn trigger_schedule(
     State(_state): State<AppState>,
     extract::Path(name): extract::Path<String>,
-) -> Json<OkResponse> {
-    let started_at = chrono::Utc::now().to_rfc3339();
-    let id = format!("run-{}-{}", name, chrono::Utc::now().timestamp_millis());
-
-    let finished_at = chrono::Utc::now().to_rfc3339();
-
-    append_schedule_run(ScheduleRunEntry {
-        id,
-        schedule_name: name,
-        started_at,
-        finished_at: Some(finished_at),
-        status: "success".to_string(),
-        result: Some("Triggered via dashboard".to_string()),
-        error: None,
-    })
-    .await;
-
-    Json(OkResponse { ok: true })
+) -> Result<Json<OkResponse>, (StatusCode, String)> {
+    let store = CronStore::open(CronStore::default_path());
+    match store.get(&name) {
+        Some(job) if !job.enabled => Err((
+            StatusCode::CONFLICT,
+            format!("schedule '{}' is disabled", name),
+        )),
+        Some(_) => {
+            store.trigger(&name);
+            Ok(Json(OkResponse { ok: true }))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            format!("cron schedule '{}' not found", name),
+        )),
+    }
 }
 
 // ---------------------------------------------------------------------------
-// GET /api/dashboard/schedules/{name}/runs
+// GET /api/dashboard/schedules/{name}/runs?offset=&limit=
 // ---------------------------------------------------------------------------
 
+#[derive(Deserialize)]
+struct RunsQuery {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "default_runs_limit")]
+    limit: usize,
+}
+
+fn default_runs_limit() -> usize {
+    50
+}
+
 async fn get_schedule_runs(
     extract::Path(name): extract::Path<String>,
-) -> Json<Vec<ScheduleRunEntry>> {
-    let all_runs = read_schedule_runs().await;
-    let runs: Vec<ScheduleRunEntry> = all_runs
-        .into_iter()
-        .filter(|r| r.schedule_name == name)
-        .rev()
-        .take(50)
-        .collect();
-    Json(runs)
+    extract::Query(query): extract::Query<RunsQuery>,
+) -> Json<Vec<CronRun>> {
+    let page = CronRunLog::new(CronRunLog::default_dir()).list(&name, query.offset, query.limit);
+    Json(page.runs)
 }
 
 // ---------------------------------------------------------------------------
@@ -374,29 +350,68 @@ async fn toggle_schedule(
 // Helpers
 // ---------------------------------------------------------------------------
 
-fn build_schedule_toml(
-    name: &str,
-    prompt: &str,
-    cron: &Option<String>,
-    interval_secs: &Option<u64>,
-    enabled: bool,
-    description: &Option<String>,
-) -> toml::Value {
+fn validate_schedule(
+    body: &CreateScheduleRequest,
+    config: &SynapseConfig,
+) -> Result<(), (StatusCode, String)> {
+    if let Some(tz) = body
+        .timezone
+        .as_deref()
+        .filter(|tz| parse_timezone(tz).is_none())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("unknown timezone '{}'", tz),
+        ));
+    }
+    if let Some(id) = body.agent.as_deref() {
+        if !crate::agent::headless::agent_exists(config, id) {
+            return Err((StatusCode::BAD_REQUEST, format!("unknown agent '{}'", id)));
+        }
+    }
+    Ok(())
+}
+
+fn build_schedule_toml(body: &CreateScheduleRequest) -> toml::Value {
     let mut tbl = toml::map::Map::new();
-    tbl.insert("name".to_string(), toml::Value::String(name.to_string()));
+    tbl.insert("name".to_string(), toml::Value::String(body.name.clone()));
     tbl.insert(
         "prompt".to_string(),
-        toml::Value::String(prompt.to_string()),
+        toml::Value::String(body.prompt.clone()),
     );
-    if let Some(c) = cron {
+    if let Some(c) = &body.cron {
         tbl.insert("cron".to_string(), toml::Value::String(c.clone()));
     }
-    if let Some(i) = interval_secs {
-        tbl.insert("interval_secs".to_string(), toml::Value::Integer(*i as i64));
+    if let Some(i) = body.interval_secs {
+        tbl.insert("interval_secs".to_string(), toml::Value::Integer(i as i64));
     }
-    tbl.insert("enabled".to_string(), toml::Value::Boolean(enabled));
-    if let Some(d) = description {
+    tbl.insert("enabled".to_string(), toml::Value::Boolean(body.enabled));
+    if let Some(d) = &body.description {
         tbl.insert("description".to_string(), toml::Value::String(d.clone()));
     }
+    if let Some(tz) = &body.timezone {
+        tbl.insert("timezone".to_string(), toml::Value::String(tz.clone()));
+    }
+    if body.jitter_secs > 0 {
+        tbl.insert(
+            "jitter_secs".to_string(),
+            toml::Value::Integer(body.jitter_secs as i64),
+        );
+    }
+    if body.misfire != MisfirePolicy::default() {
+        if let Ok(v) = toml::Value::try_from(body.misfire) {
+            tbl.insert("misfire".to_string(), v);
+        }
+    }
+    if let Some(a) = &body.agent {
+        tbl.insert("agent".to_string(), toml::Value::String(a.clone()));
+    }
+    if let Some(v) = body
+        .deliver
+        .as_ref()
+        .and_then(|d| toml::Value::try_from(d).ok())
+    {
+        tbl.insert("deliver".to_string(), v);
+    }
     toml::Value::Table(tbl)
 }
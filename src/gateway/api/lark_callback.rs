@@ -1,6 +1,7 @@
 use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
 use serde_json::{json, Value};
 
+use crate::gateway::messages::Interaction;
 use crate::gateway::state::AppState;
 
 async fn handle_card_callback(
@@ -23,6 +24,20 @@ async fn handle_card_callback(
         .split_once(':')
         .ok_or_else(|| (StatusCode::BAD_REQUEST, "invalid action format".to_string()))?;
 
+    let operator = body
+        .pointer("/operator/open_id")
+        .or_else(|| body.get("open_id"))
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let audit_pairing = |action: &str, outcome: &str| {
+        state.infra.audit.record(
+            crate::audit::AuditRecord::new("pairing", action)
+                .with_actor(format!("lark:{}", operator.as_deref().unwrap_or("unknown")))
+                .with_outcome(outcome)
+                .with_detail(json!({ "request_id": request_id })),
+        );
+    };
+
     match operation {
         "pair_approve" => {
             let result = state
@@ -31,6 +46,14 @@ async fn handle_card_callback(
                 .write()
                 .await
                 .approve(request_id);
+            audit_pairing(
+                "device.approve",
+                if result.is_some() {
+                    "ok"
+                } else {
+                    "error: not found"
+                },
+            );
             if result.is_some() {
                 Ok(Json(json!({
                     "toast": { "type": "success", "content": "Device approved" }
@@ -43,6 +66,10 @@ async fn handle_card_callback(
         }
         "pair_reject" => {
             let rejected = state.network.pairing_store.write().await.reject(request_id);
+            audit_pairing(
+                "device.reject",
+                if rejected { "ok" } else { "error: not found" },
+            );
             if rejected {
                 Ok(Json(json!({
                     "toast": { "type": "info", "content": "Device rejected" }
@@ -53,6 +80,27 @@ async fn handle_card_callback(
                 })))
             }
         }
+        // Buttons of interactive deliveries: "exec:{decision}:{request_id}"
+        "exec" => {
+            let interaction = Interaction {
+                channel: "lark".into(),
+                user_id: operator.clone(),
+                value: action_value.to_string(),
+            };
+            match state
+                .channel
+                .delivery
+                .handle_interaction(&interaction)
+                .await
+            {
+                Some(ack) => Ok(Json(json!({
+                    "toast": { "type": "info", "content": ack }
+                }))),
+                None => Ok(Json(json!({
+                    "toast": { "type": "info", "content": "Request not found or already resolved" }
+                }))),
+            }
+        }
         _ => Err((
             StatusCode::BAD_REQUEST,
             format!("unknown operation: {operation}"),
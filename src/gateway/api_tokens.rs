@@ -0,0 +1,389 @@
+//! Long-lived API tokens for scripts and CI.
+//!
+//! A token is `syn_` followed by 32 random bytes (base64url).  Only its
+//! SHA-256 hash is stored, in `~/.synapse/auth/api-tokens.json`, together
+//! with its scopes (see `rpc::scopes::check_token_scope`), optional expiry
+//! and per-minute rate limit, and when it was last used.  The gateway
+//! re-reads the file when it changes, so tokens created or revoked with
+//! `synapse tokens` apply immediately.  Uses are counted in memory; the
+//! gateway writes `last_used_at` back at most once a minute, off the request
+//! path (see [`ApiTokenStore::flush_usage`]).
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::rpc::scopes::{self, TokenLevel};
+
+/// Prefix that tells API tokens from session tokens.
+pub const TOKEN_PREFIX: &str = "syn_";
+
+/// `last_used_at` is written to disk at most this often (ms).
+const LAST_USED_SAVE_INTERVAL_MS: u64 = 60_000;
+
+/// A stored API token (without the secret).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    /// Hex SHA-256 of the token.
+    pub token_hash: String,
+    pub scopes: Vec<String>,
+    /// Ms since epoch.
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<u64>,
+    /// Requests per minute; unlimited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<u64>,
+}
+
+impl ApiToken {
+    /// Whether the token's scopes grant `level` access to `area`.
+    pub fn allows(&self, area: &str, level: TokenLevel) -> bool {
+        scopes::token_allows(&self.scopes, area, level)
+    }
+
+    /// The token as shown to operators: everything but the hash.
+    pub fn summary(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "scopes": self.scopes,
+            "created_at": self.created_at,
+            "expires_at": self.expires_at,
+            "last_used_at": self.last_used_at,
+            "rate_limit": self.rate_limit,
+            "revoked_at": self.revoked_at,
+        })
+    }
+}
+
+/// Why a token was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiTokenError {
+    Invalid,
+    Expired,
+    Revoked,
+    RateLimited,
+}
+
+impl std::fmt::Display for ApiTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Invalid => "invalid API token",
+            Self::Expired => "API token expired",
+            Self::Revoked => "API token revoked",
+            Self::RateLimited => "API token rate limit exceeded",
+        })
+    }
+}
+
+/// The API tokens file plus in-memory rate-limit windows.
+pub struct ApiTokenStore {
+    path: PathBuf,
+    tokens: Vec<ApiToken>,
+    modified: Option<(SystemTime, u64)>,
+    /// Token ID → (minute window start, requests in it).
+    windows: HashMap<String, (u64, u32)>,
+    /// Whether some `last_used_at` is newer than the file.
+    usage_dirty: bool,
+    /// When usage was last written (ms since epoch).
+    usage_saved_at: u64,
+}
+
+impl ApiTokenStore {
+    pub fn load(path: PathBuf) -> Self {
+        let mut store = Self {
+            path,
+            tokens: Vec::new(),
+            modified: None,
+            windows: HashMap::new(),
+            usage_dirty: false,
+            usage_saved_at: 0,
+        };
+        store.reload();
+        store
+    }
+
+    /// Default location: `~/.synapse/auth/api-tokens.json`.
+    pub fn default_path() -> PathBuf {
+        super::auth::default_auth_dir().join("api-tokens.json")
+    }
+
+    fn reload(&mut self) {
+        self.modified = super::users::file_stamp(&self.path);
+        let used: HashMap<String, u64> = self
+            .tokens
+            .iter()
+            .filter_map(|t| Some((t.id.clone(), t.last_used_at?)))
+            .collect();
+        self.tokens = std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        // Keep uses not yet written.
+        for token in &mut self.tokens {
+            if let Some(&at) = used.get(&token.id) {
+                token.last_used_at = token.last_used_at.max(Some(at));
+            }
+        }
+    }
+
+    /// Re-read the file if it changed since it was last read.
+    pub fn reload_if_changed(&mut self) {
+        if super::users::file_stamp(&self.path) != self.modified {
+            self.reload();
+        }
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(&self.tokens).map_err(|e| e.to_string())?;
+        super::users::write_private(&self.path, &json).map_err(|e| e.to_string())?;
+        self.modified = super::users::file_stamp(&self.path);
+        self.usage_dirty = false;
+        Ok(())
+    }
+
+    /// Whether uses counted since the last write are due to be written.
+    pub fn usage_due(&self, now: u64) -> bool {
+        self.usage_dirty && now.saturating_sub(self.usage_saved_at) >= LAST_USED_SAVE_INTERVAL_MS
+    }
+
+    /// Write `last_used_at` times if they are due.  Does file I/O, so the
+    /// gateway runs it on a blocking thread rather than in a request.
+    pub fn flush_usage(&mut self, now: u64) -> Result<(), String> {
+        if !self.usage_due(now) {
+            return Ok(());
+        }
+        // Not retried before the next interval even if the write fails.
+        self.usage_saved_at = now;
+        self.save()
+    }
+
+    pub fn list(&self) -> &[ApiToken] {
+        &self.tokens
+    }
+
+    /// Create a token.  Returns it and the secret, which is not stored and
+    /// cannot be shown again.
+    pub fn create(
+        &mut self,
+        name: &str,
+        scopes: Vec<String>,
+        ttl_secs: Option<u64>,
+        rate_limit: Option<u32>,
+    ) -> Result<(ApiToken, String), String> {
+        if name.trim().is_empty() {
+            return Err("token name is required".into());
+        }
+        if scopes.is_empty() {
+            return Err("at least one scope is required".into());
+        }
+        if let Some(bad) = scopes.iter().find(|s| !scopes::is_valid_token_scope(s)) {
+            return Err(format!(
+                "invalid scope '{}' (expected chat, *, or <area>.<read|write|admin>)",
+                bad
+            ));
+        }
+        let secret = format!(
+            "{}{}",
+            TOKEN_PREFIX,
+            super::nodes::bootstrap::generate_pairing_token()
+        );
+        let now = super::presence::now_ms();
+        let token = ApiToken {
+            id: uuid::Uuid::new_v4().to_string()[..8].to_string(),
+            name: name.trim().to_string(),
+            token_hash: hash_token(&secret),
+            scopes,
+            created_at: now,
+            expires_at: ttl_secs.map(|s| now + s * 1000),
+            last_used_at: None,
+            rate_limit,
+            revoked_at: None,
+        };
+        self.tokens.push(token.clone());
+        self.save()?;
+        Ok((token, secret))
+    }
+
+    /// Revoke a token by ID.  Revoked tokens stay listed.
+    pub fn revoke(&mut self, id: &str) -> Result<ApiToken, String> {
+        let token = self
+            .tokens
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| format!("no API token '{}'", id))?;
+        if token.revoked_at.is_none() {
+            token.revoked_at = Some(super::presence::now_ms());
+        }
+        let token = token.clone();
+        self.save()?;
+        Ok(token)
+    }
+
+    /// Look up `secret` and count a use of it against its rate limit.
+    pub fn authenticate(&mut self, secret: &str, now: u64) -> Result<ApiToken, ApiTokenError> {
+        let hash = hash_token(secret);
+        let id = self
+            .tokens
+            .iter()
+            .find(|t| t.token_hash == hash)
+            .map(|t| t.id.clone())
+            .ok_or(ApiTokenError::Invalid)?;
+        self.touch(&id, now)
+    }
+
+    /// Check that token `id` is still usable and count a use of it.  The use
+    /// is only recorded in memory; see [`Self::flush_usage`].
+    pub fn touch(&mut self, id: &str, now: u64) -> Result<ApiToken, ApiTokenError> {
+        let token = self
+            .tokens
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or(ApiTokenError::Invalid)?;
+        if token.revoked_at.is_some() {
+            return Err(ApiTokenError::Revoked);
+        }
+        if token.expires_at.is_some_and(|exp| exp <= now) {
+            return Err(ApiTokenError::Expired);
+        }
+        if let Some(limit) = token.rate_limit {
+            let window = now / 60_000;
+            let entry = self.windows.entry(token.id.clone()).or_insert((window, 0));
+            if entry.0 != window {
+                *entry = (window, 0);
+            }
+            if entry.1 >= limit {
+                return Err(ApiTokenError::RateLimited);
+            }
+            entry.1 += 1;
+        }
+        token.last_used_at = Some(now);
+        self.usage_dirty = true;
+        Ok(token.clone())
+    }
+}
+
+fn hash_token(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// Area and access level an API token needs for a REST request: the first
+/// path segment after `/api` (or `/api/dashboard`), read for GET/HEAD and
+/// write otherwise.
+pub fn http_requirement(method: &axum::http::Method, path: &str) -> (String, TokenLevel) {
+    let mut segments = path.trim_start_matches('/').split('/');
+    let mut area = segments.next().unwrap_or("");
+    if area == "api" {
+        area = segments.next().unwrap_or("");
+    }
+    if area == "dashboard" {
+        area = segments.next().unwrap_or("dashboard");
+    }
+    let level = match *method {
+        axum::http::Method::GET | axum::http::Method::HEAD => TokenLevel::Read,
+        _ => TokenLevel::Write,
+    };
+    (area.to_string(), level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_hashed_scoped_limited_and_revocable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api-tokens.json");
+        let mut store = ApiTokenStore::load(path.clone());
+        assert!(store
+            .create("ci", vec!["sessions.delete".into()], None, None)
+            .is_err());
+        let (token, secret) = store
+            .create(
+                "ci",
+                vec!["chat".into(), "sessions.read".into()],
+                Some(3600),
+                Some(2),
+            )
+            .unwrap();
+        assert!(secret.starts_with(TOKEN_PREFIX));
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains(&secret));
+        assert!(on_disk.contains(&token.token_hash));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let now = token.created_at;
+        let mut store = ApiTokenStore::load(path.clone());
+        assert!(store
+            .authenticate(&secret, now)
+            .unwrap()
+            .allows("sessions", TokenLevel::Read));
+        assert_eq!(
+            store.authenticate(&secret, now).unwrap().last_used_at,
+            Some(now)
+        );
+        // Uses reach the file only when flushed.
+        let on_disk = || ApiTokenStore::load(path.clone()).list()[0].last_used_at;
+        assert_eq!(on_disk(), None);
+        assert!(store.usage_due(now));
+        store.flush_usage(now).unwrap();
+        assert_eq!(on_disk(), Some(now));
+        assert!(!store.usage_due(now));
+        assert_eq!(
+            store.authenticate(&secret, now).unwrap_err(),
+            ApiTokenError::RateLimited
+        );
+        assert!(store.authenticate(&secret, now + 60_000).is_ok());
+        assert_eq!(
+            store.authenticate(&secret, now + 3_600_000).unwrap_err(),
+            ApiTokenError::Expired
+        );
+        assert_eq!(
+            store.authenticate("syn_nope", now).unwrap_err(),
+            ApiTokenError::Invalid
+        );
+
+        store.revoke(&token.id).unwrap();
+        assert_eq!(
+            ApiTokenStore::load(path.clone())
+                .authenticate(&secret, now + 120_000)
+                .unwrap_err(),
+            ApiTokenError::Revoked
+        );
+    }
+
+    #[test]
+    fn maps_rest_requests_to_areas() {
+        use axum::http::Method;
+        assert_eq!(
+            http_requirement(&Method::PUT, "/api/dashboard/config"),
+            ("config".to_string(), TokenLevel::Write)
+        );
+        assert_eq!(
+            http_requirement(&Method::GET, "/api/dashboard/sessions/abc"),
+            ("sessions".to_string(), TokenLevel::Read)
+        );
+        assert_eq!(
+            http_requirement(&Method::POST, "/api/webhooks/deploy"),
+            ("webhooks".to_string(), TokenLevel::Write)
+        );
+    }
+}
@@ -1,73 +1,162 @@
-//! Web UI authentication — password login with JWT sessions.
+//! Web UI authentication — user accounts with signed, expiring session tokens.
+//!
+//! Users log in with a name and password (see [`super::users`]); the legacy
+//! single `password_hash` still works and logs in as `admin`.  Tokens are
+//! HS256 JWTs signed with `jwt_secret` (generated and kept in
+//! `~/.synapse/auth/jwt-secret` when unset), so they survive restarts.
+//! Logout revokes a token; changing a password or removing a user revokes
+//! all of theirs.  Scripts authenticate with API tokens instead (see
+//! [`super::api_tokens`]), and users of an identity provider can sign in
+//! through OpenID Connect (see [`super::oidc`]).
 
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 use axum::extract::State;
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, Method, StatusCode};
 use axum::response::IntoResponse;
 use axum::{middleware, Json, Router};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
+use sha2::Sha256;
 
+use super::api_tokens::{self, ApiToken, ApiTokenError, ApiTokenStore};
+use super::oidc::OidcProvider;
 use super::state::AppState;
+use super::users::{now_secs, UserRole, UserStore};
 use crate::config::AuthConfig;
 
-/// Simple JWT-like token (HMAC-SHA256 would be ideal but we keep deps minimal).
-///
-/// For production, replace with a proper JWT library.
+type HmacSha256 = Hmac<Sha256>;
+
+/// User the legacy single password logs in as.
+const LEGACY_USER: &str = "admin";
+
+/// Claims of a session token.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
-struct SessionToken {
-    issued_at: u64,
-    expires_at: u64,
-    nonce: String,
+struct Claims {
+    sub: String,
+    role: UserRole,
+    iat: u64,
+    exp: u64,
+    jti: String,
+    /// Set for single sign-on users, who have no local account; their role
+    /// is the one mapped at login.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    idp: Option<String>,
+}
+
+/// An authenticated dashboard user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub username: String,
+    pub role: UserRole,
+}
+
+impl Identity {
+    /// RPC scopes this user may hold.
+    pub fn scopes(&self) -> HashSet<String> {
+        self.role.scopes().iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Whether this user may hold `scope`.
+    pub fn allows_scope(&self, scope: &str) -> bool {
+        self.role == UserRole::Admin || self.role.scopes().contains(&scope)
+    }
+}
+
+/// Default directory for auth state: `~/.synapse/auth`.
+pub fn default_auth_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".synapse")
+        .join("auth")
 }
 
 /// Auth state shared across handlers.
 pub struct AuthState {
     pub config: AuthConfig,
-    /// Active session tokens (in-memory; cleared on restart).
-    pub sessions: Arc<RwLock<Vec<String>>>,
+    /// HMAC key for session tokens.
+    secret: Vec<u8>,
+    users: Mutex<UserStore>,
+    /// Revoked token IDs and their expiry, persisted until they expire.
+    revoked: Mutex<HashMap<String, u64>>,
+    revoked_path: PathBuf,
+    api_tokens: Arc<Mutex<ApiTokenStore>>,
+    oidc: Option<OidcProvider>,
 }
 
 impl AuthState {
     pub fn new(config: AuthConfig) -> Self {
+        Self::open(config, default_auth_dir())
+    }
+
+    /// Auth state keeping its files (users, secret, revocations, API
+    /// tokens) in `dir`.
+    pub fn open(config: AuthConfig, dir: PathBuf) -> Self {
+        let secret = match config.jwt_secret.as_deref().filter(|s| !s.is_empty()) {
+            Some(s) => s.as_bytes().to_vec(),
+            None => load_or_create_secret(&dir.join("jwt-secret")),
+        };
+        let revoked_path = dir.join("revoked-tokens.json");
+        let revoked = std::fs::read_to_string(&revoked_path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        let oidc = config.oidc.clone().and_then(|c| {
+            OidcProvider::new(c)
+                .map_err(|e| tracing::error!(error = %e, "OIDC single sign-on disabled"))
+                .ok()
+        });
         Self {
             config,
-            sessions: Arc::new(RwLock::new(Vec::new())),
+            secret,
+            users: Mutex::new(UserStore::load(dir.join("users.json"))),
+            revoked: Mutex::new(revoked),
+            revoked_path,
+            api_tokens: Arc::new(Mutex::new(ApiTokenStore::load(dir.join("api-tokens.json")))),
+            oidc,
         }
     }
 
-    /// Verify a password against the stored hash.
-    ///
-    /// Supports argon2 hashes (prefix `$argon2`) and falls back to legacy
-    /// simple hash for backward compatibility.
+    /// The single sign-on provider, if one is configured.
+    pub fn oidc(&self) -> Option<&OidcProvider> {
+        self.oidc.as_ref()
+    }
+
+    /// Verify a password against the legacy single `password_hash`.
     pub fn verify_password(&self, password: &str) -> bool {
         match &self.config.password_hash {
-            Some(hash) => {
-                if hash.starts_with("$argon2") {
-                    // Argon2 verification
-                    use argon2::{Argon2, PasswordVerifier};
-                    use password_hash::PasswordHash;
-                    match PasswordHash::new(hash) {
-                        Ok(parsed) => Argon2::default()
-                            .verify_password(password.as_bytes(), &parsed)
-                            .is_ok(),
-                        Err(_) => false,
-                    }
-                } else {
-                    // Legacy fallback: simple hash comparison
-                    let input_hash = simple_hash(password);
-                    constant_time_eq(hash.as_bytes(), input_hash.as_bytes())
-                }
-            }
+            Some(hash) => verify_password_hash(hash, password),
             None => false, // No password set
         }
     }
 
+    /// Check credentials.  Without a username only the legacy password is
+    /// tried.
+    pub fn authenticate(&self, username: Option<&str>, password: &str) -> Option<Identity> {
+        let mut users = self.users.lock().unwrap_or_else(|e| e.into_inner());
+        users.reload_if_changed();
+        if let Some(name) = username {
+            if let Some(user) = users.authenticate(name, password) {
+                return Some(Identity {
+                    username: user.username.clone(),
+                    role: user.role,
+                });
+            }
+            if name != LEGACY_USER || users.get(name).is_some() {
+                return None;
+            }
+        }
+        self.verify_password(password).then(|| Identity {
+            username: LEGACY_USER.to_string(),
+            role: UserRole::Admin,
+        })
+    }
+
     /// Hash a password using argon2 for storage.
-    #[allow(dead_code)]
     pub fn hash_password(password: &str) -> Result<String, String> {
         use argon2::{Argon2, PasswordHasher};
         use password_hash::rand_core::OsRng;
@@ -80,20 +169,197 @@ impl AuthState {
             .map_err(|e| format!("password hashing failed: {}", e))
     }
 
-    /// Create a new session token.
-    pub async fn create_session(&self) -> String {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let token = format!("synapse_{}_{}", now, uuid::Uuid::new_v4());
-        self.sessions.write().await.push(token.clone());
-        token
+    /// Issue a session token for `identity`, valid for `session_duration`.
+    pub fn issue_token(&self, identity: &Identity) -> String {
+        self.issue(identity, None)
+    }
+
+    /// Issue a session token for a single sign-on user.
+    pub fn issue_sso_token(&self, identity: &Identity) -> String {
+        self.issue(identity, Some("oidc"))
+    }
+
+    fn issue(&self, identity: &Identity, idp: Option<&str>) -> String {
+        let now = now_secs();
+        let claims = Claims {
+            sub: identity.username.clone(),
+            role: identity.role,
+            iat: now,
+            exp: now + self.config.session_duration,
+            jti: uuid::Uuid::new_v4().to_string(),
+            idp: idp.map(str::to_string),
+        };
+        let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).unwrap_or_default());
+        let signing_input = format!("{}.{}", header, payload);
+        format!("{}.{}", signing_input, self.sign(&signing_input))
+    }
+
+    fn sign(&self, input: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts any key");
+        mac.update(input.as_bytes());
+        URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+    }
+
+    /// Claims of a correctly signed, unexpired token.
+    fn decode(&self, token: &str) -> Option<Claims> {
+        let (signing_input, signature) = token.rsplit_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        let mut mac = HmacSha256::new_from_slice(&self.secret).ok()?;
+        mac.update(signing_input.as_bytes());
+        mac.verify_slice(&signature).ok()?;
+        let (_, payload) = signing_input.split_once('.')?;
+        let claims: Claims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+        (claims.exp > now_secs()).then_some(claims)
     }
 
-    /// Check if a session token is valid.
-    pub async fn is_valid_session(&self, token: &str) -> bool {
-        self.sessions.read().await.contains(&token.to_string())
+    /// The user a session token belongs to, if it is valid, unexpired,
+    /// unrevoked and the user still exists.  The role is the user's current
+    /// one, not the one at login (except for single sign-on users, who have
+    /// no local account).
+    pub fn validate_token(&self, token: &str) -> Option<Identity> {
+        let claims = self.decode(token)?;
+        if self
+            .revoked
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(&claims.jti)
+        {
+            return None;
+        }
+        if claims.idp.is_some() {
+            return Some(Identity {
+                username: claims.sub,
+                role: claims.role,
+            });
+        }
+        let mut users = self.users.lock().unwrap_or_else(|e| e.into_inner());
+        users.reload_if_changed();
+        match users.get(&claims.sub) {
+            Some(user) if claims.iat >= user.tokens_valid_after => Some(Identity {
+                username: user.username.clone(),
+                role: user.role,
+            }),
+            Some(_) => None,
+            None if claims.sub == LEGACY_USER && self.config.password_hash.is_some() => {
+                Some(Identity {
+                    username: LEGACY_USER.to_string(),
+                    role: UserRole::Admin,
+                })
+            }
+            None => None,
+        }
+    }
+
+    /// Revoke a token.  Returns false for tokens that were already invalid.
+    pub fn revoke_token(&self, token: &str) -> bool {
+        let Some(claims) = self.decode(token) else {
+            return false;
+        };
+        let mut revoked = self.revoked.lock().unwrap_or_else(|e| e.into_inner());
+        let now = now_secs();
+        revoked.retain(|_, exp| *exp > now);
+        revoked.insert(claims.jti, claims.exp);
+        if let Some(parent) = self.revoked_path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(e) = std::fs::write(
+            &self.revoked_path,
+            serde_json::to_string(&*revoked).unwrap_or_default(),
+        ) {
+            tracing::warn!(path = %self.revoked_path.display(), error = %e, "failed to save revoked tokens");
+        }
+        true
+    }
+
+    /// The API token store, re-read if it changed on disk.
+    pub fn api_tokens(&self) -> std::sync::MutexGuard<'_, ApiTokenStore> {
+        let mut store = self.api_tokens.lock().unwrap_or_else(|e| e.into_inner());
+        store.reload_if_changed();
+        store
+    }
+
+    /// Look up an API token and count a use against its rate limit.
+    pub fn authenticate_api_token(&self, secret: &str) -> Result<ApiToken, ApiTokenError> {
+        self.count_api_token_use(|store, now| store.authenticate(secret, now))
+    }
+
+    /// Re-check API token `id` (revocation, expiry, rate limit) for another
+    /// request on an established connection.
+    pub fn touch_api_token(&self, id: &str) -> Result<ApiToken, ApiTokenError> {
+        self.count_api_token_use(|store, now| store.touch(id, now))
+    }
+
+    fn count_api_token_use(
+        &self,
+        count: impl FnOnce(&mut ApiTokenStore, u64) -> Result<ApiToken, ApiTokenError>,
+    ) -> Result<ApiToken, ApiTokenError> {
+        let now = super::presence::now_ms();
+        let mut store = self.api_tokens();
+        let result = count(&mut store, now);
+        let due = store.usage_due(now);
+        drop(store);
+        if due {
+            self.flush_api_token_usage(now);
+        }
+        result
+    }
+
+    /// Write API token uses to disk on a blocking thread, so the request
+    /// that made them never waits on the file.
+    fn flush_api_token_usage(&self, now: u64) {
+        let store = self.api_tokens.clone();
+        let flush = move || {
+            let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = store.flush_usage(now) {
+                tracing::warn!(error = %e, "failed to save API token usage");
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn_blocking(flush);
+            }
+            Err(_) => flush(),
+        }
+    }
+}
+
+/// Read the token signing key from `path`, generating it on first use.
+fn load_or_create_secret(path: &std::path::Path) -> Vec<u8> {
+    if let Ok(secret) = std::fs::read_to_string(path) {
+        let secret = secret.trim();
+        if !secret.is_empty() {
+            return secret.as_bytes().to_vec();
+        }
+    }
+    let secret = super::nodes::bootstrap::generate_pairing_token();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(e) = super::users::write_private(path, &secret) {
+        tracing::warn!(path = %path.display(), error = %e, "failed to save jwt secret; sessions will not survive a restart")
+    }
+    secret.into_bytes()
+}
+
+/// Verify `password` against a stored hash.
+///
+/// Supports argon2 hashes (prefix `$argon2`) and falls back to legacy
+/// simple hash for backward compatibility.
+pub fn verify_password_hash(hash: &str, password: &str) -> bool {
+    if hash.starts_with("$argon2") {
+        use argon2::{Argon2, PasswordVerifier};
+        use password_hash::PasswordHash;
+        match PasswordHash::new(hash) {
+            Ok(parsed) => Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok(),
+            Err(_) => false,
+        }
+    } else {
+        // Legacy fallback: simple hash comparison
+        let input_hash = simple_hash(password);
+        constant_time_eq(hash.as_bytes(), input_hash.as_bytes())
     }
 }
 
@@ -107,7 +373,7 @@ fn simple_hash(input: &str) -> String {
 }
 
 /// Constant-time string comparison.
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+pub(super) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     if a.len() != b.len() {
         return false;
     }
@@ -124,6 +390,9 @@ fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
 
 #[derive(Deserialize)]
 pub struct LoginRequest {
+    /// Omit to log in with the legacy single password.
+    #[serde(default)]
+    username: Option<String>,
     password: String,
 }
 
@@ -131,6 +400,8 @@ pub struct LoginRequest {
 pub struct LoginResponse {
     token: String,
     expires_in: u64,
+    username: String,
+    role: UserRole,
 }
 
 #[derive(Serialize)]
@@ -138,6 +409,14 @@ struct ErrorResponse {
     error: String,
 }
 
+pub(crate) fn bearer_token(headers: &HeaderMap) -> &str {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("")
+}
+
 /// POST /api/auth/login — authenticate and receive a session token.
 pub async fn login_handler(
     State(state): State<AppState>,
@@ -156,33 +435,57 @@ pub async fn login_handler(
         }
     };
 
-    if !auth.verify_password(&req.password) {
+    let Some(identity) = auth.authenticate(req.username.as_deref(), &req.password) else {
         return (
             StatusCode::UNAUTHORIZED,
             Json(ErrorResponse {
-                error: "Invalid password".to_string(),
+                error: "Invalid username or password".to_string(),
             }),
         )
             .into_response();
-    }
+    };
 
-    let token = auth.create_session().await;
+    let token = auth.issue_token(&identity);
     (
         StatusCode::OK,
         Json(LoginResponse {
             token,
             expires_in: auth.config.session_duration,
+            username: identity.username,
+            role: identity.role,
         }),
     )
         .into_response()
 }
 
+/// POST /api/auth/logout — revoke the request's session token.
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let revoked = state
+        .core
+        .auth
+        .as_ref()
+        .is_some_and(|auth| auth.revoke_token(bearer_token(&headers)));
+    Json(serde_json::json!({ "ok": revoked }))
+}
+
 /// GET /api/auth/status — check if auth is enabled and if the request is authenticated.
-pub async fn status_handler(State(state): State<AppState>) -> impl IntoResponse {
+pub async fn status_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
     #[derive(Serialize)]
     struct AuthStatus {
         auth_enabled: bool,
+        /// Whether `/api/auth/oidc/login` is available.
+        sso_enabled: bool,
         authenticated: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        username: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        role: Option<UserRole>,
     }
 
     let auth_enabled = state
@@ -191,10 +494,16 @@ pub async fn status_handler(State(state): State<AppState>) -> impl IntoResponse
         .as_ref()
         .map(|a| a.config.enabled)
         .unwrap_or(false);
+    let sso_enabled = state.core.auth.as_ref().is_some_and(|a| a.oidc().is_some());
+    let identity = identify(&state, &headers);
 
     Json(AuthStatus {
         auth_enabled,
-        authenticated: !auth_enabled, // If auth disabled, everyone is "authenticated"
+        sso_enabled,
+        // If auth disabled, everyone is "authenticated"
+        authenticated: !auth_enabled || identity.is_some(),
+        username: identity.as_ref().map(|i| i.username.clone()),
+        role: identity.map(|i| i.role),
     })
 }
 
@@ -202,37 +511,160 @@ pub async fn status_handler(State(state): State<AppState>) -> impl IntoResponse
 pub fn auth_router() -> Router<AppState> {
     Router::new()
         .route("/api/auth/login", axum::routing::post(login_handler))
+        .route("/api/auth/logout", axum::routing::post(logout_handler))
         .route("/api/auth/status", axum::routing::get(status_handler))
+        .route(
+            "/api/auth/oidc/login",
+            axum::routing::get(super::oidc::login_handler),
+        )
+        .route(
+            "/api/auth/oidc/callback",
+            axum::routing::get(super::oidc::callback_handler),
+        )
 }
 
-/// Middleware function that checks for a valid auth token.
+/// The user behind a request's `Authorization: Bearer` token, if it is valid.
+pub fn identify(state: &AppState, headers: &HeaderMap) -> Option<Identity> {
+    state
+        .core
+        .auth
+        .as_ref()?
+        .validate_token(bearer_token(headers))
+}
+
+/// Who a request passed gateway auth as.
+pub enum Caller {
+    /// Auth is disabled.
+    Anonymous,
+    User(Identity),
+    Token(ApiToken),
+}
+
+/// Apply gateway auth to a request.  Viewers may only read and API tokens
+/// need a scope for the request's area (see [`api_tokens::http_requirement`]).
+pub fn authorize(
+    state: &AppState,
+    method: &Method,
+    path: &str,
+    headers: &HeaderMap,
+) -> Result<Caller, (StatusCode, String)> {
+    let auth = match &state.core.auth {
+        Some(auth) if auth.config.enabled => auth,
+        _ => return Ok(Caller::Anonymous),
+    };
+    let bearer = bearer_token(headers);
+    if bearer.starts_with(api_tokens::TOKEN_PREFIX) {
+        let token = auth.authenticate_api_token(bearer).map_err(|e| {
+            let status = if e == ApiTokenError::RateLimited {
+                StatusCode::TOO_MANY_REQUESTS
+            } else {
+                StatusCode::UNAUTHORIZED
+            };
+            (status, e.to_string())
+        })?;
+        let (area, level) = api_tokens::http_requirement(method, path);
+        if !token.allows(&area, level) {
+            let level = format!("{:?}", level).to_lowercase();
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("API token lacks scope {area}.{level}"),
+            ));
+        }
+        return Ok(Caller::Token(token));
+    }
+    let Some(identity) = identify(state, headers) else {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Authentication required".to_string(),
+        ));
+    };
+    let read_only = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+    if identity.role == UserRole::Viewer && !read_only {
+        return Err((StatusCode::FORBIDDEN, "Read-only account".to_string()));
+    }
+    Ok(Caller::User(identity))
+}
+
+/// Middleware that applies [`authorize`]; the caller's [`Identity`] or
+/// [`ApiToken`] is added to the request extensions.
 pub async fn require_auth(
     State(state): State<AppState>,
-    request: axum::http::Request<axum::body::Body>,
+    mut request: axum::http::Request<axum::body::Body>,
     next: middleware::Next,
 ) -> impl IntoResponse {
-    let auth = match &state.core.auth {
-        Some(auth) if auth.config.enabled => auth,
-        _ => return next.run(request).await.into_response(), // Auth disabled, pass through
-    };
+    let caller = authorize(
+        &state,
+        request.method(),
+        request.uri().path(),
+        request.headers(),
+    );
+    match caller {
+        Ok(Caller::Anonymous) => {}
+        Ok(Caller::User(identity)) => {
+            request.extensions_mut().insert(identity);
+        }
+        Ok(Caller::Token(token)) => {
+            request.extensions_mut().insert(token);
+        }
+        Err((status, error)) => return (status, Json(ErrorResponse { error })).into_response(),
+    }
+    next.run(request).await.into_response()
+}
 
-    // Check Authorization header
-    let token = request
-        .headers()
-        .get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|v| v.strip_prefix("Bearer "))
-        .unwrap_or("");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if auth.is_valid_session(token).await {
-        next.run(request).await.into_response()
-    } else {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Authentication required".to_string(),
-            }),
-        )
-            .into_response()
+    #[test]
+    fn tokens_expire_revoke_and_follow_the_account() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let config = AuthConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let auth = AuthState::open(config.clone(), dir.clone());
+        let mut users = UserStore::load(dir.join("users.json"));
+        users.add("alice", "s3cret", UserRole::Operator).unwrap();
+
+        assert!(auth.authenticate(None, "s3cret").is_none());
+        let alice = auth.authenticate(Some("alice"), "s3cret").unwrap();
+        let token = auth.issue_token(&alice);
+        // Survives a restart: the generated secret was persisted.
+        let auth = AuthState::open(config.clone(), dir.clone());
+        assert_eq!(auth.validate_token(&token), Some(alice.clone()));
+        assert!(auth.validate_token(&format!("{}x", token)).is_none());
+
+        // Role changes apply to existing tokens.
+        users.set_role("alice", UserRole::Viewer).unwrap();
+        assert_eq!(auth.validate_token(&token).unwrap().role, UserRole::Viewer);
+
+        let second = auth.issue_token(&alice);
+        assert!(auth.revoke_token(&token));
+        assert!(auth.validate_token(&token).is_none());
+        assert!(auth.validate_token(&second).is_some());
+        users.remove("alice").unwrap();
+        assert!(auth.validate_token(&second).is_none());
+
+        // Single sign-on users have no account; the token carries the role.
+        let carol = Identity {
+            username: "carol@example.com".into(),
+            role: UserRole::Operator,
+        };
+        assert_eq!(
+            auth.validate_token(&auth.issue_sso_token(&carol)),
+            Some(carol)
+        );
+
+        let expired = AuthState::open(
+            AuthConfig {
+                session_duration: 0,
+                ..config
+            },
+            dir.clone(),
+        );
+        assert!(expired
+            .validate_token(&expired.issue_token(&alice))
+            .is_none());
     }
 }
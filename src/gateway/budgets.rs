@@ -0,0 +1,662 @@
+//! Spend budgets (`[[budgets]]`).
+//!
+//! [`Budgets`] keeps a ledger of what each model call spent — tokens, and USD
+//! at the `[[models]]` prices — tagged with its agent, sender, channel, chat
+//! and model, and decides before the next call whether a budget has run out
+//! (enforced by `agent::budget::BudgetMiddleware`).  On startup the current
+//! periods are seeded from the usage tracker's persisted records; those carry
+//! no sender or chat, so sender and chat budgets count from the start.
+//!
+//! The first time a budget reaches 80% and 100% in a period, a
+//! [`BudgetAlert`] goes out to [`Budgets::subscribe`]rs.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::usage::UsageTracker;
+use crate::config::{BudgetAction, BudgetConfig, BudgetPeriod, BudgetScopeKind, SynapseConfig};
+
+const DAY_MS: u64 = 86_400_000;
+
+/// Alert thresholds, in percent of a budget.
+const ALERT_THRESHOLDS: [u8; 2] = [80, 100];
+
+/// Who a model call is made for.
+#[derive(Debug, Clone, Default)]
+pub struct BudgetScope {
+    pub agent: String,
+    pub sender: Option<String>,
+    /// Channel platform, e.g. "telegram".
+    pub channel: String,
+    /// `<channel>:<chat id>`.
+    pub chat: Option<String>,
+}
+
+impl BudgetScope {
+    fn value<'a>(&'a self, kind: BudgetScopeKind, model: &'a str) -> Option<&'a str> {
+        match kind {
+            BudgetScopeKind::Agent => Some(&self.agent),
+            BudgetScopeKind::Sender => self.sender.as_deref(),
+            BudgetScopeKind::Channel => Some(&self.channel),
+            BudgetScopeKind::Chat => self.chat.as_deref(),
+            BudgetScopeKind::Model => Some(model),
+        }
+    }
+}
+
+/// The budgets, and the scope of the calls an agent makes.
+#[derive(Clone)]
+pub struct BudgetGuard {
+    pub budgets: Arc<Budgets>,
+    pub scope: BudgetScope,
+}
+
+/// What to do about the next model call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetDecision {
+    Allow,
+    /// A `warn` budget is used up; go ahead.
+    Warn {
+        budget: String,
+    },
+    /// Call `model` instead.
+    Downgrade {
+        budget: String,
+        model: String,
+    },
+    /// Reply with `message` instead of calling the model.
+    Refuse {
+        budget: String,
+        message: String,
+    },
+}
+
+impl BudgetDecision {
+    fn severity(&self) -> u8 {
+        match self {
+            Self::Allow => 0,
+            Self::Warn { .. } => 1,
+            Self::Downgrade { .. } => 2,
+            Self::Refuse { .. } => 3,
+        }
+    }
+}
+
+/// A budget reached one of [`ALERT_THRESHOLDS`].
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetAlert {
+    pub budget: String,
+    pub scope: BudgetScopeKind,
+    pub key: String,
+    pub period: BudgetPeriod,
+    /// Percent of the budget reached: 80 or 100.
+    pub threshold: u8,
+    pub tokens: u64,
+    pub usd: f64,
+    pub max_tokens: Option<u64>,
+    pub max_usd: Option<f64>,
+    pub action: BudgetAction,
+}
+
+/// A budget's spend for one key in the current period.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub budget: String,
+    pub scope: BudgetScopeKind,
+    pub key: String,
+    pub period: BudgetPeriod,
+    pub period_start_ms: u64,
+    pub resets_at_ms: u64,
+    pub tokens: u64,
+    pub usd: f64,
+    pub max_tokens: Option<u64>,
+    pub max_usd: Option<f64>,
+    /// Share of the budget used, in percent (the higher of tokens and USD).
+    pub percent: f64,
+    pub action: BudgetAction,
+    pub exceeded: bool,
+}
+
+/// What one model call spent.
+#[derive(Debug, Clone)]
+struct Charge {
+    timestamp_ms: u64,
+    scope: BudgetScope,
+    model: String,
+    tokens: u64,
+    usd: f64,
+}
+
+/// A `[[models]]` entry as far as budgets care.
+struct CatalogModel {
+    name: String,
+    /// USD per million input and output tokens.
+    prices: Option<(f64, f64)>,
+}
+
+/// The configured budgets and the ledger they are checked against.
+pub struct Budgets {
+    budgets: Vec<BudgetConfig>,
+    /// Model name or alias → catalog entry.
+    catalog: HashMap<String, CatalogModel>,
+    charges: Mutex<Vec<Charge>>,
+    /// (budget index, key, period start, threshold) already alerted.
+    alerted: Mutex<HashSet<(usize, String, u64, u8)>>,
+    alerts: broadcast::Sender<BudgetAlert>,
+}
+
+impl Budgets {
+    pub fn from_config(config: &SynapseConfig) -> Self {
+        let mut catalog = HashMap::new();
+        for entry in config.model_catalog.iter().flatten() {
+            let prices = match (entry.input_cost_per_mtok, entry.output_cost_per_mtok) {
+                (None, None) => None,
+                (input, output) => Some((input.unwrap_or(0.0), output.unwrap_or(0.0))),
+            };
+            for name in std::iter::once(&entry.name).chain(&entry.aliases) {
+                catalog.insert(
+                    name.clone(),
+                    CatalogModel {
+                        name: entry.name.clone(),
+                        prices,
+                    },
+                );
+            }
+        }
+        for budget in &config.budgets {
+            if budget.max_tokens.is_none() && budget.max_usd.is_none() {
+                tracing::warn!(budget = %budget_name(budget), "budget has neither max_tokens nor max_usd; it never runs out");
+            }
+            if budget.action == BudgetAction::Downgrade && budget.downgrade_to.is_none() {
+                tracing::warn!(budget = %budget_name(budget), "downgrade budget without downgrade_to refuses instead");
+            }
+        }
+        let budgets = config
+            .budgets
+            .iter()
+            .cloned()
+            .map(|mut budget| {
+                if budget.scope == BudgetScopeKind::Model {
+                    budget.key = budget
+                        .key
+                        .map(|k| catalog.get(&k).map_or(k.clone(), |m| m.name.clone()));
+                }
+                budget
+            })
+            .collect();
+        Self {
+            budgets,
+            catalog,
+            charges: Mutex::new(Vec::new()),
+            alerted: Mutex::new(HashSet::new()),
+            alerts: broadcast::channel(64).0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.budgets.is_empty()
+    }
+
+    /// Alerts from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<BudgetAlert> {
+        self.alerts.subscribe()
+    }
+
+    /// The configured budgets, as shown to operators.
+    pub fn summaries(&self) -> Vec<serde_json::Value> {
+        self.budgets
+            .iter()
+            .map(|b| {
+                serde_json::json!({
+                    "name": budget_name(b),
+                    "scope": b.scope,
+                    "key": b.key,
+                    "period": b.period,
+                    "max_tokens": b.max_tokens,
+                    "max_usd": b.max_usd,
+                    "action": b.action,
+                    "downgrade_to": b.downgrade_to,
+                })
+            })
+            .collect()
+    }
+
+    /// Canonical catalog name of a model name or alias.
+    pub fn canonical<'a>(&'a self, model: &'a str) -> &'a str {
+        self.catalog.get(model).map_or(model, |m| m.name.as_str())
+    }
+
+    /// USD for a call at the catalog prices; 0 for unpriced models.
+    pub fn cost_usd(&self, model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+        match self.catalog.get(model).and_then(|m| m.prices) {
+            Some((input, output)) => {
+                (input_tokens as f64 * input + output_tokens as f64 * output) / 1_000_000.0
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Load this period's spend from the usage tracker.
+    pub async fn seed(&self, tracker: &UsageTracker) {
+        if self.is_empty() {
+            return;
+        }
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let records = tracker
+            .records_since(self.oldest_period_start(now_ms))
+            .await;
+        let mut charges = self.charges.lock().unwrap();
+        for r in records {
+            let usd = if r.cost_usd > 0.0 {
+                r.cost_usd
+            } else {
+                self.cost_usd(&r.model, r.input_tokens, r.output_tokens)
+            };
+            charges.push(Charge {
+                timestamp_ms: r.timestamp_ms,
+                scope: BudgetScope {
+                    agent: r.agent_id,
+                    sender: None,
+                    channel: r.channel,
+                    chat: None,
+                },
+                model: self.canonical(&r.model).to_string(),
+                tokens: r.total_tokens.max(r.input_tokens + r.output_tokens),
+                usd,
+            });
+        }
+    }
+
+    /// Record what a call made for `scope` spent.
+    pub fn charge(
+        &self,
+        scope: &BudgetScope,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+        now_ms: u64,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+        let model = self.canonical(model);
+        let oldest = self.oldest_period_start(now_ms);
+        let mut charges = self.charges.lock().unwrap();
+        charges.retain(|c| c.timestamp_ms >= oldest);
+        charges.push(Charge {
+            timestamp_ms: now_ms,
+            scope: scope.clone(),
+            model: model.to_string(),
+            tokens: input_tokens + output_tokens,
+            usd: self.cost_usd(model, input_tokens, output_tokens),
+        });
+        self.alerted
+            .lock()
+            .unwrap()
+            .retain(|(_, _, start, _)| *start >= oldest);
+        // Alert as soon as a threshold is crossed, not on the next call.
+        self.used(&charges, scope, model, now_ms);
+    }
+
+    /// Decide about a call to `model` for `scope`.
+    pub fn check(&self, scope: &BudgetScope, model: &str, now_ms: u64) -> BudgetDecision {
+        let model = self.canonical(model);
+        let charges = self.charges.lock().unwrap();
+        let mut decision = BudgetDecision::Allow;
+        for (index, used) in self.used(&charges, scope, model, now_ms) {
+            if used < 1.0 {
+                continue;
+            }
+            let budget = &self.budgets[index];
+            let name = budget_name(budget);
+            let next = match (budget.action, &budget.downgrade_to) {
+                (BudgetAction::Warn, _) => BudgetDecision::Warn { budget: name },
+                (BudgetAction::Downgrade, Some(to)) if self.canonical(to) == model => {
+                    // Already on the cheaper model.
+                    BudgetDecision::Warn { budget: name }
+                }
+                (BudgetAction::Downgrade, Some(to)) => BudgetDecision::Downgrade {
+                    budget: name,
+                    model: self.canonical(to).to_string(),
+                },
+                (BudgetAction::Downgrade, None) | (BudgetAction::Refuse, _) => {
+                    BudgetDecision::Refuse {
+                        budget: name,
+                        message: refusal(budget),
+                    }
+                }
+            };
+            if next.severity() > decision.severity() {
+                decision = next;
+            }
+        }
+        decision
+    }
+
+    /// Spend against every budget, per key seen in its current period.
+    pub fn status(&self, now_ms: u64) -> Vec<BudgetStatus> {
+        let charges = self.charges.lock().unwrap();
+        let mut statuses = Vec::new();
+        for budget in &self.budgets {
+            let (start, end) = period_bounds(budget.period, now_ms);
+            let keys: BTreeSet<&str> = match &budget.key {
+                Some(key) => std::iter::once(key.as_str()).collect(),
+                None => charges
+                    .iter()
+                    .filter(|c| c.timestamp_ms >= start)
+                    .filter_map(|c| c.scope.value(budget.scope, &c.model))
+                    .collect(),
+            };
+            for key in keys {
+                let (tokens, usd) = spend(&charges, budget.scope, key, start);
+                let used = used_share(budget, tokens, usd);
+                statuses.push(BudgetStatus {
+                    budget: budget_name(budget),
+                    scope: budget.scope,
+                    key: key.to_string(),
+                    period: budget.period,
+                    period_start_ms: start,
+                    resets_at_ms: end,
+                    tokens,
+                    usd,
+                    max_tokens: budget.max_tokens,
+                    max_usd: budget.max_usd,
+                    percent: used * 100.0,
+                    action: budget.action,
+                    exceeded: used >= 1.0,
+                });
+            }
+        }
+        statuses
+    }
+
+    /// Share used of each budget that applies to `scope`, alerting on
+    /// thresholds reached for the first time.
+    fn used(
+        &self,
+        charges: &[Charge],
+        scope: &BudgetScope,
+        model: &str,
+        now_ms: u64,
+    ) -> Vec<(usize, f64)> {
+        let mut shares = Vec::new();
+        for (index, budget) in self.budgets.iter().enumerate() {
+            let Some(key) = scope.value(budget.scope, model) else {
+                continue;
+            };
+            if budget.key.as_deref().is_some_and(|k| k != key) {
+                continue;
+            }
+            let (start, _) = period_bounds(budget.period, now_ms);
+            let (tokens, usd) = spend(charges, budget.scope, key, start);
+            let used = used_share(budget, tokens, usd);
+            self.alert(index, key, start, used, tokens, usd);
+            shares.push((index, used));
+        }
+        shares
+    }
+
+    fn alert(&self, index: usize, key: &str, period_start: u64, used: f64, tokens: u64, usd: f64) {
+        let mut alerted = self.alerted.lock().unwrap();
+        let mut reached = None;
+        for threshold in ALERT_THRESHOLDS {
+            if used * 100.0 >= threshold as f64
+                && alerted.insert((index, key.to_string(), period_start, threshold))
+            {
+                reached = Some(threshold);
+            }
+        }
+        // Crossing both thresholds at once sends one alert.
+        let Some(threshold) = reached else {
+            return;
+        };
+        let budget = &self.budgets[index];
+        let alert = BudgetAlert {
+            budget: budget_name(budget),
+            scope: budget.scope,
+            key: key.to_string(),
+            period: budget.period,
+            threshold,
+            tokens,
+            usd,
+            max_tokens: budget.max_tokens,
+            max_usd: budget.max_usd,
+            action: budget.action,
+        };
+        tracing::warn!(budget = %alert.budget, key = %key, threshold, tokens, usd, "spend budget threshold reached");
+        let _ = self.alerts.send(alert);
+    }
+
+    /// Start of the longest current period; older spend no longer counts.
+    fn oldest_period_start(&self, now_ms: u64) -> u64 {
+        self.budgets
+            .iter()
+            .map(|b| period_bounds(b.period, now_ms).0)
+            .min()
+            .unwrap_or(now_ms)
+    }
+}
+
+/// `name`, or `<scope>:<key or *>:<period>`.
+fn budget_name(budget: &BudgetConfig) -> String {
+    budget.name.clone().unwrap_or_else(|| {
+        format!(
+            "{}:{}:{}",
+            budget.scope.as_str(),
+            budget.key.as_deref().unwrap_or("*"),
+            budget.period.as_str()
+        )
+    })
+}
+
+fn refusal(budget: &BudgetConfig) -> String {
+    budget.message.clone().unwrap_or_else(|| {
+        match budget.period {
+            BudgetPeriod::Daily => {
+                "Sorry, the usage budget for today has been reached. Please try again tomorrow."
+            }
+            BudgetPeriod::Monthly => {
+                "Sorry, the usage budget for this month has been reached. Please try again next month."
+            }
+        }
+        .to_string()
+    })
+}
+
+/// Tokens and USD spent by `key` since `since_ms`.
+fn spend(charges: &[Charge], kind: BudgetScopeKind, key: &str, since_ms: u64) -> (u64, f64) {
+    charges
+        .iter()
+        .filter(|c| c.timestamp_ms >= since_ms && c.scope.value(kind, &c.model) == Some(key))
+        .fold((0, 0.0), |(tokens, usd), c| {
+            (tokens + c.tokens, usd + c.usd)
+        })
+}
+
+/// Share of the budget used: the higher of tokens and USD.
+fn used_share(budget: &BudgetConfig, tokens: u64, usd: f64) -> f64 {
+    let by_tokens = budget
+        .max_tokens
+        .map_or(0.0, |max| tokens as f64 / max.max(1) as f64);
+    let by_usd = budget
+        .max_usd
+        .map_or(0.0, |max| if max > 0.0 { usd / max } else { f64::INFINITY });
+    by_tokens.max(by_usd)
+}
+
+/// Start and end (ms) of the UTC day or month containing `now_ms`.
+fn period_bounds(period: BudgetPeriod, now_ms: u64) -> (u64, u64) {
+    let today = now_ms / DAY_MS;
+    match period {
+        BudgetPeriod::Daily => (today * DAY_MS, (today + 1) * DAY_MS),
+        BudgetPeriod::Monthly => {
+            let (year, month, day) = UsageTracker::days_to_ymd(today);
+            let first = today - (day - 1);
+            let days = match month {
+                2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+                2 => 28,
+                4 | 6 | 9 | 11 => 30,
+                _ => 31,
+            };
+            (first * DAY_MS, (first + days) * DAY_MS)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budgets(budgets: &str) -> Budgets {
+        #[derive(serde::Deserialize)]
+        struct Sections {
+            models: Vec<crate::config::ModelEntry>,
+            budgets: Vec<BudgetConfig>,
+        }
+        let sections: Sections = toml::from_str(&format!(
+            r#"
+[[models]]
+name = "gpt-4o"
+aliases = ["big"]
+input_cost_per_mtok = 2.5
+output_cost_per_mtok = 10.0
+
+[[models]]
+name = "gpt-4o-mini"
+aliases = ["mini"]
+input_cost_per_mtok = 0.15
+output_cost_per_mtok = 0.6
+{}"#,
+            budgets
+        ))
+        .unwrap();
+        let mut config = SynapseConfig::default();
+        config.model_catalog = Some(sections.models);
+        config.budgets = sections.budgets;
+        Budgets::from_config(&config)
+    }
+
+    fn scope(sender: &str, chat: &str) -> BudgetScope {
+        BudgetScope {
+            agent: "default".into(),
+            sender: Some(sender.into()),
+            channel: "telegram".into(),
+            chat: Some(format!("telegram:{}", chat)),
+        }
+    }
+
+    // 2024-02-10 12:00 UTC
+    const NOW: u64 = 19763 * DAY_MS + DAY_MS / 2;
+
+    #[test]
+    fn refuses_each_sender_over_its_cap_and_alerts_once() {
+        let b = budgets(
+            r#"
+[[budgets]]
+scope = "sender"
+period = "daily"
+max_tokens = 1000
+"#,
+        );
+        let mut alerts = b.subscribe();
+        let alice = scope("alice", "g1");
+
+        b.charge(&alice, "gpt-4o", 600, 250, NOW);
+        let alert = alerts.try_recv().unwrap();
+        assert_eq!((alert.key.as_str(), alert.threshold), ("alice", 80));
+        assert_eq!(b.check(&alice, "gpt-4o", NOW), BudgetDecision::Allow);
+
+        b.charge(&alice, "gpt-4o", 100, 100, NOW);
+        assert_eq!(alerts.try_recv().unwrap().threshold, 100);
+        assert!(matches!(
+            b.check(&alice, "gpt-4o", NOW),
+            BudgetDecision::Refuse { ref message, .. } if message.contains("today")
+        ));
+        assert!(alerts.try_recv().is_err());
+
+        assert_eq!(
+            b.check(&scope("bob", "g1"), "gpt-4o", NOW),
+            BudgetDecision::Allow
+        );
+        assert_eq!(
+            b.check(&alice, "gpt-4o", NOW + DAY_MS),
+            BudgetDecision::Allow
+        );
+    }
+
+    #[test]
+    fn downgrades_by_usd_and_refusal_wins() {
+        let b = budgets(
+            r#"
+[[budgets]]
+name = "noisy-group"
+scope = "chat"
+key = "telegram:g1"
+max_usd = 1.0
+action = "downgrade"
+downgrade_to = "mini"
+
+[[budgets]]
+scope = "model"
+key = "mini"
+period = "daily"
+max_tokens = 10000000
+message = "Out of budget."
+"#,
+        );
+        let group = scope("alice", "g1");
+        // 100k in + 100k out at gpt-4o prices is $1.25.
+        b.charge(&group, "big", 100_000, 100_000, NOW);
+        assert_eq!(
+            b.check(&group, "gpt-4o", NOW),
+            BudgetDecision::Downgrade {
+                budget: "noisy-group".into(),
+                model: "gpt-4o-mini".into()
+            }
+        );
+        assert_eq!(
+            b.check(&group, "gpt-4o-mini", NOW),
+            BudgetDecision::Warn {
+                budget: "noisy-group".into()
+            }
+        );
+        assert_eq!(
+            b.check(&scope("alice", "g2"), "gpt-4o", NOW),
+            BudgetDecision::Allow
+        );
+
+        b.charge(&scope("bob", "g2"), "mini", 10_000_000, 0, NOW);
+        assert_eq!(
+            b.check(&group, "gpt-4o-mini", NOW),
+            BudgetDecision::Refuse {
+                budget: "model:gpt-4o-mini:daily".into(),
+                message: "Out of budget.".into()
+            }
+        );
+
+        let status = b.status(NOW);
+        assert_eq!(status.len(), 2);
+        assert_eq!(status[0].key, "telegram:g1");
+        assert!((status[0].usd - 1.25).abs() < 1e-9);
+        assert!(status[0].exceeded);
+        assert_eq!(status[1].resets_at_ms, 19764 * DAY_MS);
+    }
+
+    #[test]
+    fn monthly_periods_follow_the_calendar() {
+        assert_eq!(
+            period_bounds(BudgetPeriod::Monthly, NOW),
+            (19754 * DAY_MS, 19783 * DAY_MS)
+        );
+        assert_eq!(
+            period_bounds(BudgetPeriod::Monthly, 20818 * DAY_MS + 1),
+            (20788 * DAY_MS, 20819 * DAY_MS)
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! "Allow for session" decisions, scoped and persisted.
+//!
+//! An `AllowSession` decision allows the command again only in the scope the
+//! request came from: the same session key, agent and node.  Allows expire
+//! after a TTL, survive restarts (`~/.synapse/exec-session-allows.json`) and
+//! can be listed and revoked over RPC.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Default lifetime of an allow (24 hours).
+pub const DEFAULT_ALLOW_TTL_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Where an exec request was made.  Fields the caller didn't know are `None`
+/// and only match requests that don't know them either.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowScope {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+}
+
+/// A command allowed for the rest of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionAllow {
+    pub id: String,
+    pub command: String,
+    #[serde(flatten)]
+    pub scope: AllowScope,
+    /// Who made the decision, e.g. `slack:U123` or `rpc:dashboard`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decided_by: Option<String>,
+    /// The request the decision answered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// Persisted set of session allows.
+pub struct SessionAllowStore {
+    /// `None` keeps allows in memory only.
+    path: Option<PathBuf>,
+    allows: Vec<SessionAllow>,
+}
+
+impl SessionAllowStore {
+    /// An empty store that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            allows: Vec::new(),
+        }
+    }
+
+    /// Load allows from `path`, starting empty if it is missing or unreadable.
+    pub fn load(path: PathBuf) -> Self {
+        let allows = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            allows,
+        }
+    }
+
+    /// Default location: `~/.synapse/exec-session-allows.json`.
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".synapse")
+            .join("exec-session-allows.json")
+    }
+
+    fn save(&self) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string_pretty(&self.allows) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    tracing::warn!(path = %path.display(), error = %e, "failed to save exec session allows");
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to serialize exec session allows"),
+        }
+    }
+
+    /// Add an allow.  An existing allow for the same command and scope is
+    /// replaced, so re-approving extends it.
+    pub fn add(&mut self, allow: SessionAllow) {
+        self.allows
+            .retain(|a| !(a.command == allow.command && a.scope == allow.scope));
+        self.allows.push(allow);
+        self.save();
+    }
+
+    /// Whether `command` is allowed in `scope` at `now`.
+    pub fn is_allowed(&self, command: &str, scope: &AllowScope, now: u64) -> bool {
+        self.allows
+            .iter()
+            .any(|a| a.expires_at > now && a.command == command && a.scope == *scope)
+    }
+
+    /// Unexpired allows, optionally only those of `session_key`.
+    pub fn list(&self, session_key: Option<&str>, now: u64) -> Vec<SessionAllow> {
+        self.allows
+            .iter()
+            .filter(|a| a.expires_at > now)
+            .filter(|a| session_key.is_none() || a.scope.session_key.as_deref() == session_key)
+            .cloned()
+            .collect()
+    }
+
+    /// Remove an allow by ID.
+    pub fn revoke(&mut self, id: &str) -> Option<SessionAllow> {
+        let pos = self.allows.iter().position(|a| a.id == id)?;
+        let allow = self.allows.remove(pos);
+        self.save();
+        Some(allow)
+    }
+
+    /// Remove every allow of `session_key`.
+    pub fn revoke_session(&mut self, session_key: &str) -> Vec<SessionAllow> {
+        let (revoked, kept) = std::mem::take(&mut self.allows)
+            .into_iter()
+            .partition(|a| a.scope.session_key.as_deref() == Some(session_key));
+        self.allows = kept;
+        if !revoked.is_empty() {
+            self.save();
+        }
+        revoked
+    }
+
+    /// Drop expired allows.
+    pub fn prune(&mut self, now: u64) {
+        let before = self.allows.len();
+        self.allows.retain(|a| a.expires_at > now);
+        if self.allows.len() != before {
+            self.save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow(id: &str, command: &str, session: &str, expires_at: u64) -> SessionAllow {
+        SessionAllow {
+            id: id.into(),
+            command: command.into(),
+            scope: AllowScope {
+                session_key: Some(session.into()),
+                ..Default::default()
+            },
+            decided_by: Some("slack:U1".into()),
+            request_id: None,
+            created_at: 0,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn allows_are_scoped_expire_and_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("allows.json");
+        let mut store = SessionAllowStore::load(path.clone());
+        store.add(allow("a1", "rm", "agent:default:slack:dm:U1", 1_000));
+        store.add(allow("a2", "make", "agent:default:slack:dm:U2", 1_000));
+
+        let u1 = AllowScope {
+            session_key: Some("agent:default:slack:dm:U1".into()),
+            ..Default::default()
+        };
+        assert!(store.is_allowed("rm", &u1, 500));
+        assert!(!store.is_allowed("make", &u1, 500));
+        assert!(!store.is_allowed("rm", &AllowScope::default(), 500));
+        assert!(!store.is_allowed("rm", &u1, 1_000));
+
+        // Survives a reload.
+        let mut store = SessionAllowStore::load(path.clone());
+        assert_eq!(store.list(None, 500).len(), 2);
+        assert_eq!(store.list(Some("agent:default:slack:dm:U2"), 500).len(), 1);
+        assert_eq!(store.revoke("a1").map(|a| a.command), Some("rm".into()));
+        assert!(!store.is_allowed("rm", &u1, 500));
+        assert_eq!(store.revoke_session("agent:default:slack:dm:U2").len(), 1);
+        assert!(SessionAllowStore::load(path.clone())
+            .list(None, 0)
+            .is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! Append-only record of exec approval requests and decisions.
+//!
+//! One JSON object per line in `~/.synapse/exec-approvals-audit.jsonl`:
+//! every request (including those the policy or a session allow decided on
+//! the spot), every decision with who made it, expiries and revocations.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::allows::AllowScope;
+use super::manager::ApprovalDecision;
+
+/// What happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// A request was made and is waiting for a decision.
+    Requested,
+    /// A request was decided, by a person or automatically.
+    Decided,
+    /// A request expired without a decision.
+    Expired,
+    /// A session allow was revoked.
+    Revoked,
+}
+
+/// One audit record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalAuditEntry {
+    /// When it happened (ms since epoch).
+    pub ts: u64,
+    pub event: AuditEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub command: String,
+    #[serde(flatten)]
+    pub scope: AllowScope,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<ApprovalDecision>,
+    /// Who decided or revoked: `policy`, `session_allow`, `slack:U123`,
+    /// `rpc:<client>`, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Filters for [`ApprovalAuditLog::read`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub session_key: Option<String>,
+    /// Only entries at or after this time (ms since epoch).
+    #[serde(default)]
+    pub since: Option<u64>,
+    /// Newest entries returned (default 100).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// The audit log file.
+pub struct ApprovalAuditLog {
+    /// `None` discards entries (tests, in-memory managers).
+    path: Option<PathBuf>,
+}
+
+impl ApprovalAuditLog {
+    pub fn disabled() -> Self {
+        Self { path: None }
+    }
+
+    pub fn new(path: PathBuf) -> Self {
+        Self { path: Some(path) }
+    }
+
+    /// Default location: `~/.synapse/exec-approvals-audit.jsonl`.
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".synapse")
+            .join("exec-approvals-audit.jsonl")
+    }
+
+    /// Append an entry.  Failures are logged, never returned: auditing must
+    /// not block approvals.
+    pub fn append(&self, entry: &ApprovalAuditEntry) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            let line = serde_json::to_string(entry)?;
+            writeln!(file, "{}", line)
+        })();
+        if let Err(e) = result {
+            tracing::warn!(path = %path.display(), error = %e, "failed to write exec approval audit entry");
+        }
+    }
+
+    /// Entries matching `query`, oldest first.
+    pub fn read(&self, query: &AuditQuery) -> Vec<ApprovalAuditEntry> {
+        let Some(text) = self
+            .path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+        else {
+            return Vec::new();
+        };
+        let mut entries: Vec<ApprovalAuditEntry> = text
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .filter(|e: &ApprovalAuditEntry| {
+                query
+                    .request_id
+                    .as_ref()
+                    .is_none_or(|id| e.request_id.as_ref() == Some(id))
+                    && query
+                        .session_key
+                        .as_ref()
+                        .is_none_or(|key| e.scope.session_key.as_ref() == Some(key))
+                    && query.since.is_none_or(|since| e.ts >= since)
+            })
+            .collect();
+        let limit = query.limit.unwrap_or(100);
+        if entries.len() > limit {
+            entries.drain(..entries.len() - limit);
+        }
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_and_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = ApprovalAuditLog::new(path.clone());
+        for (i, id) in ["r1", "r2", "r1"].iter().enumerate() {
+            log.append(&ApprovalAuditEntry {
+                ts: i as u64,
+                event: if i == 2 {
+                    AuditEvent::Decided
+                } else {
+                    AuditEvent::Requested
+                },
+                request_id: Some(id.to_string()),
+                command: "rm -rf build".into(),
+                scope: AllowScope::default(),
+                decision: (i == 2).then_some(ApprovalDecision::Deny),
+                actor: (i == 2).then(|| "slack:U1".to_string()),
+                reason: None,
+            });
+        }
+
+        let r1 = log.read(&AuditQuery {
+            request_id: Some("r1".into()),
+            ..Default::default()
+        });
+        assert_eq!(r1.len(), 2);
+        assert_eq!(r1[1].actor.as_deref(), Some("slack:U1"));
+        let newest = log.read(&AuditQuery {
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(newest[0].ts, 2);
+    }
+}
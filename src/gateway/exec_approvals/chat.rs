@@ -0,0 +1,298 @@
+//! Approving exec requests from the chat they came from.
+//!
+//! A request with an origin is delivered to that conversation as a prompt
+//! with buttons (Slack Block Kit, Telegram inline keyboard, Lark card); the
+//! button values are `exec:<decision>:<request_id>`.  Channels without
+//! buttons get the text, which asks for a reply of `YES <code>`,
+//! `ALWAYS <code>` or `NO <code>`.  Either way the answer reaches
+//! [`ChatApprovals`] through the delivery service's interaction handlers.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use super::manager::{ApprovalDecision, ApprovalRequestPayload};
+use super::{ExecApprovalManager, ExecApprovalsConfig};
+use crate::gateway::messages::{Interaction, InteractionHandler, OutboundPayload, QueuedDelivery};
+
+/// Prefix of exec approval button values.
+const ACTION_PREFIX: &str = "exec:";
+
+/// The prompt for `payload`, addressed to its origin.
+pub fn prompt(payload: &ApprovalRequestPayload, reason: &str) -> Option<QueuedDelivery> {
+    let origin = payload.origin.as_ref()?;
+    let command = payload.command_line();
+    let text = format!(
+        "Approval needed to run:\n`{}`\n{}\n\nReply YES {code} to allow once, ALWAYS {code} \
+         to allow for this session, or NO {code} to deny.",
+        command,
+        reason,
+        code = payload.code
+    );
+    let button = |label: &str, decision: &str, style: &str| {
+        json!({
+            "text": label,
+            "value": format!("{}{}:{}", ACTION_PREFIX, decision, payload.request_id),
+            "style": style,
+        })
+    };
+    let interactive = json!({
+        "type": "buttons",
+        "buttons": [
+            button("Allow once", "allow_once", "primary"),
+            button("Allow for session", "allow_session", "default"),
+            button("Deny", "deny", "danger"),
+        ],
+    });
+    let delivery = QueuedDelivery::new(
+        origin.channel.clone(),
+        origin.to.clone(),
+        vec![OutboundPayload {
+            text: Some(text),
+            interactive: Some(interactive),
+            ..Default::default()
+        }],
+    )
+    .with_account_id(origin.account_id.clone())
+    .with_thread_id(origin.thread_id.clone());
+    Some(delivery)
+}
+
+/// Decision and request ID from a button value.
+pub fn parse_action(value: &str) -> Option<(ApprovalDecision, String)> {
+    let (decision, request_id) = value.strip_prefix(ACTION_PREFIX)?.split_once(':')?;
+    Some((parse_decision(decision)?, request_id.to_string()))
+}
+
+/// Decision and approval code from a text reply such as `yes K7M2PQ9X`.
+pub fn parse_reply(text: &str) -> Option<(ApprovalDecision, String)> {
+    let mut words = text.split_whitespace();
+    let decision = match words.next()?.to_ascii_lowercase().as_str() {
+        "yes" | "y" | "approve" => ApprovalDecision::AllowOnce,
+        "always" => ApprovalDecision::AllowSession,
+        "no" | "n" | "deny" => ApprovalDecision::Deny,
+        _ => return None,
+    };
+    let code = words.next()?;
+    if words.next().is_some() {
+        return None;
+    }
+    Some((decision, code.to_string()))
+}
+
+fn parse_decision(s: &str) -> Option<ApprovalDecision> {
+    match s {
+        "allow" => Some(ApprovalDecision::Allow),
+        "allow_once" => Some(ApprovalDecision::AllowOnce),
+        "allow_session" => Some(ApprovalDecision::AllowSession),
+        "deny" => Some(ApprovalDecision::Deny),
+        _ => None,
+    }
+}
+
+/// Whether `user_id` on `channel` may decide `payload`: anyone matching an
+/// `approvers` entry, or with no approvers configured, the user who
+/// triggered the request.
+pub fn may_approve(
+    config: &ExecApprovalsConfig,
+    payload: &ApprovalRequestPayload,
+    channel: &str,
+    user_id: Option<&str>,
+) -> bool {
+    let Some(user_id) = user_id else {
+        return false;
+    };
+    if config.approvers.is_empty() {
+        return payload
+            .origin
+            .as_ref()
+            .is_some_and(|o| o.channel == channel && o.user_id.as_deref() == Some(user_id));
+    }
+    config
+        .approvers
+        .iter()
+        .any(|entry| match entry.split_once(':') {
+            Some((c, u)) => c == channel && (u == "*" || u == user_id),
+            None => false,
+        })
+}
+
+/// Resolves pending exec requests from chat buttons and replies.
+pub struct ChatApprovals {
+    manager: Arc<RwLock<ExecApprovalManager>>,
+    config: Arc<RwLock<ExecApprovalsConfig>>,
+}
+
+impl ChatApprovals {
+    pub fn new(
+        manager: Arc<RwLock<ExecApprovalManager>>,
+        config: Arc<RwLock<ExecApprovalsConfig>>,
+    ) -> Self {
+        Self { manager, config }
+    }
+}
+
+#[async_trait]
+impl InteractionHandler for ChatApprovals {
+    async fn handle(&self, interaction: &Interaction) -> Option<String> {
+        let (decision, payload) =
+            if let Some((decision, request_id)) = parse_action(&interaction.value) {
+                let payload = self.manager.write().await.get(&request_id);
+                match payload {
+                    Some(p) => (decision, p),
+                    None => return Some("This request has expired or was already decided.".into()),
+                }
+            } else {
+                // Replies that don't name a pending request are ordinary messages.
+                let (decision, code) = parse_reply(&interaction.value)?;
+                let payload = self.manager.write().await.find_by_code(&code)?;
+                (decision, payload)
+            };
+
+        let allowed = may_approve(
+            &*self.config.read().await,
+            &payload,
+            &interaction.channel,
+            interaction.user_id.as_deref(),
+        );
+        let actor = format!(
+            "{}:{}",
+            interaction.channel,
+            interaction.user_id.as_deref().unwrap_or("unknown")
+        );
+        if !allowed {
+            tracing::warn!(
+                request_id = %payload.request_id,
+                channel = %interaction.channel,
+                user = ?interaction.user_id,
+                "exec approval from unauthorized user ignored"
+            );
+            return Some("You are not allowed to approve this request.".into());
+        }
+
+        if !self
+            .manager
+            .write()
+            .await
+            .resolve(&payload.request_id, decision.clone(), &actor)
+        {
+            return Some("This request has expired or was already decided.".into());
+        }
+        tracing::info!(
+            request_id = %payload.request_id,
+            channel = %interaction.channel,
+            user = ?interaction.user_id,
+            decision = ?decision,
+            "exec approval resolved from chat"
+        );
+        let verdict = match decision {
+            ApprovalDecision::Deny => "Denied",
+            ApprovalDecision::AllowSession => "Allowed for this session",
+            _ => "Allowed",
+        };
+        Some(format!("{}: `{}`", verdict, payload.command_line()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::exec_approvals::manager::ApprovalOrigin;
+
+    fn request(user: &str) -> ApprovalRequestPayload {
+        ApprovalRequestPayload {
+            request_id: "req-1".into(),
+            command: "rm".into(),
+            args: vec!["-rf".into(), "build".into()],
+            cwd: None,
+            node_id: None,
+            session_key: Some("agent:default:slack:grp:C1".into()),
+            agent_id: None,
+            created_at: crate::gateway::presence::now_ms(),
+            code: "K7M2PQ9X".into(),
+            origin: Some(ApprovalOrigin {
+                channel: "slack".into(),
+                to: "channel:C1".into(),
+                account_id: None,
+                thread_id: Some("1700000000.0001".into()),
+                user_id: Some(user.into()),
+            }),
+        }
+    }
+
+    #[test]
+    fn parses_buttons_and_replies() {
+        let delivery = prompt(&request("U1"), "'rm' is not allowlisted").unwrap();
+        assert_eq!(
+            (delivery.channel.as_str(), delivery.to.as_str()),
+            ("slack", "channel:C1")
+        );
+        let payload = &delivery.payloads[0];
+        assert!(payload.text.as_deref().unwrap().contains("YES K7M2PQ9X"));
+        let value = payload.interactive.as_ref().unwrap()["buttons"][2]["value"]
+            .as_str()
+            .unwrap();
+        assert_eq!(
+            parse_action(value),
+            Some((ApprovalDecision::Deny, "req-1".to_string()))
+        );
+
+        assert_eq!(
+            parse_reply("yes k7m2pq9x"),
+            Some((ApprovalDecision::AllowOnce, "k7m2pq9x".to_string()))
+        );
+        assert_eq!(
+            parse_reply("ALWAYS K7M2PQ9X").unwrap().0,
+            ApprovalDecision::AllowSession
+        );
+        assert_eq!(parse_reply("yes please do it"), None);
+        assert_eq!(parse_reply("yes"), None);
+    }
+
+    #[test]
+    fn only_approvers_may_decide() {
+        let mut config = ExecApprovalsConfig::default();
+        let req = request("U1");
+        assert!(may_approve(&config, &req, "slack", Some("U1")));
+        assert!(!may_approve(&config, &req, "slack", Some("U2")));
+        assert!(!may_approve(&config, &req, "telegram", Some("U1")));
+        assert!(!may_approve(&config, &req, "slack", None));
+
+        config.approvers = vec!["slack:U2".into(), "telegram:*".into()];
+        assert!(!may_approve(&config, &req, "slack", Some("U1")));
+        assert!(may_approve(&config, &req, "slack", Some("U2")));
+        assert!(may_approve(&config, &req, "telegram", Some("42")));
+    }
+
+    #[tokio::test]
+    async fn resolves_from_a_reply() {
+        let manager = Arc::new(RwLock::new(ExecApprovalManager::new()));
+        let rx = manager.write().await.create(request("U1"));
+        let approvals = ChatApprovals::new(
+            manager.clone(),
+            Arc::new(RwLock::new(ExecApprovalsConfig::default())),
+        );
+        let reply = |user: &str, value: &str| Interaction {
+            channel: "slack".into(),
+            user_id: Some(user.into()),
+            value: value.into(),
+        };
+
+        assert_eq!(approvals.handle(&reply("U1", "hello")).await, None);
+        assert_eq!(
+            approvals
+                .handle(&reply("U2", "yes K7M2PQ9X"))
+                .await
+                .as_deref(),
+            Some("You are not allowed to approve this request.")
+        );
+        assert!(approvals
+            .handle(&reply("U1", "no k7m2pq9x"))
+            .await
+            .is_some());
+        assert_eq!(rx.await.unwrap(), ApprovalDecision::Deny);
+        assert!(manager.write().await.get("req-1").is_none());
+    }
+}
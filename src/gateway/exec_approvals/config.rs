@@ -12,7 +12,7 @@ pub enum SecurityMode {
     Deny,
     /// Only allow commands matching the allowlist.
     Allowlist,
-    /// Allow all commands.
+    /// Allow all commands not on the denylist.
     Full,
 }
 
@@ -43,7 +43,19 @@ impl Default for AskPolicy {
 pub struct ExecApprovalsConfig {
     pub mode: SecurityMode,
     pub ask: AskPolicy,
+    /// Rules for commands allowed in allowlist mode (see `policy`).
     pub allowlist: Vec<String>,
+    /// Rules for commands that are always denied.
+    #[serde(default)]
+    pub denylist: Vec<String>,
+    /// Who may approve requests from chat, as `channel:user_id` (e.g.
+    /// `slack:U123`, or `slack:*` for anyone in that channel).  When empty,
+    /// only the user who triggered a request may approve it.
+    #[serde(default)]
+    pub approvers: Vec<String>,
+    /// How long "allow for session" decisions last (default 24 hours).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_allow_ttl_secs: Option<u64>,
     /// SHA256 hash of the serialized config for CAS updates.
     #[serde(skip)]
     pub config_hash: String,
@@ -57,6 +69,8 @@ pub struct NodeExecConfig {
     pub mode: Option<SecurityMode>,
     pub ask: Option<AskPolicy>,
     pub allowlist: Option<Vec<String>>,
+    #[serde(default)]
+    pub denylist: Option<Vec<String>>,
 }
 
 impl Default for ExecApprovalsConfig {
@@ -72,7 +86,11 @@ impl Default for ExecApprovalsConfig {
                 "whoami".to_string(),
                 "date".to_string(),
                 "uname".to_string(),
+                "> /dev/null".to_string(),
             ],
+            denylist: Vec::new(),
+            approvers: Vec::new(),
+            session_allow_ttl_secs: None,
             config_hash: String::new(),
             node_overrides: std::collections::HashMap::new(),
         }
@@ -108,6 +126,8 @@ impl ExecApprovalsConfig {
         new_mode: Option<SecurityMode>,
         new_ask: Option<AskPolicy>,
         new_allowlist: Option<Vec<String>>,
+        new_denylist: Option<Vec<String>>,
+        new_approvers: Option<Vec<String>>,
     ) -> Result<(), String> {
         if self.config_hash != expected_hash {
             return Err("Config hash mismatch (concurrent modification)".to_string());
@@ -121,6 +141,12 @@ impl ExecApprovalsConfig {
         if let Some(allowlist) = new_allowlist {
             self.allowlist = allowlist;
         }
+        if let Some(denylist) = new_denylist {
+            self.denylist = denylist;
+        }
+        if let Some(approvers) = new_approvers {
+            self.approvers = approvers;
+        }
         self.save();
         Ok(())
     }
@@ -130,6 +156,8 @@ impl ExecApprovalsConfig {
             "mode": self.mode,
             "ask": self.ask,
             "allowlist": self.allowlist,
+            "denylist": self.denylist,
+            "approvers": self.approvers,
         });
         let mut hasher = Sha256::new();
         hasher.update(data.to_string().as_bytes());
@@ -1,10 +1,15 @@
-//! In-memory exec approval request manager.
+//! Exec approval request manager.
+//!
+//! Pending requests live in memory; "allow for session" decisions and the
+//! audit trail are persisted (see [`super::allows`] and [`super::audit`]).
 
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 
+use super::allows::{AllowScope, SessionAllow, SessionAllowStore, DEFAULT_ALLOW_TTL_MS};
+use super::audit::{ApprovalAuditEntry, ApprovalAuditLog, AuditEvent};
 use crate::gateway::presence::now_ms;
 
 /// How long an approval request lives before expiring (5 minutes).
@@ -26,7 +31,67 @@ pub struct ApprovalRequestPayload {
     pub args: Vec<String>,
     pub cwd: Option<String>,
     pub node_id: Option<String>,
+    /// Session the command runs in; "allow for session" is scoped to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
     pub created_at: u64,
+    /// Short code approvers type in chats without buttons ("YES <code>").
+    #[serde(default)]
+    pub code: String,
+    /// Chat the request came from; the prompt is delivered there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<ApprovalOrigin>,
+}
+
+impl ApprovalRequestPayload {
+    /// The command with its arguments shell-quoted, as policy rules and
+    /// session allows see it.
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.command.clone())
+            .chain(self.args.iter().map(|a| super::shell::quote(a)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Scope a session allow for this request applies to.
+    pub fn scope(&self) -> AllowScope {
+        AllowScope {
+            session_key: self.session_key.clone(),
+            agent_id: self.agent_id.clone(),
+            node_id: self.node_id.clone(),
+        }
+    }
+
+    fn audit_entry(&self, event: AuditEvent) -> ApprovalAuditEntry {
+        ApprovalAuditEntry {
+            ts: now_ms(),
+            event,
+            request_id: Some(self.request_id.clone()),
+            command: self.command_line(),
+            scope: self.scope(),
+            decision: None,
+            actor: None,
+            reason: None,
+        }
+    }
+}
+
+/// Conversation an exec request originated in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApprovalOrigin {
+    /// Channel identifier (e.g. "slack").
+    pub channel: String,
+    /// Delivery target in that channel (e.g. "channel:C123").
+    pub to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    /// Platform user whose message triggered the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
 }
 
 pub struct ApprovalRecord {
@@ -36,23 +101,48 @@ pub struct ApprovalRecord {
 
 pub struct ExecApprovalManager {
     pending: HashMap<String, ApprovalRecord>,
-    /// Commands allowed for the current session (from AllowSession decisions).
-    session_allows: Vec<String>,
+    /// Commands allowed for a session (from AllowSession decisions).
+    allows: SessionAllowStore,
+    audit: ApprovalAuditLog,
+    /// How long an AllowSession decision lasts.
+    allow_ttl_ms: u64,
 }
 
 impl ExecApprovalManager {
+    /// A manager that keeps allows in memory and writes no audit log.
     pub fn new() -> Self {
         Self {
             pending: HashMap::new(),
-            session_allows: Vec::new(),
+            allows: SessionAllowStore::in_memory(),
+            audit: ApprovalAuditLog::disabled(),
+            allow_ttl_ms: DEFAULT_ALLOW_TTL_MS,
+        }
+    }
+
+    /// A manager backed by the allow store and audit log in `~/.synapse`.
+    pub fn load() -> Self {
+        let mut allows = SessionAllowStore::load(SessionAllowStore::default_path());
+        allows.prune(now_ms());
+        Self {
+            pending: HashMap::new(),
+            allows,
+            audit: ApprovalAuditLog::new(ApprovalAuditLog::default_path()),
+            allow_ttl_ms: DEFAULT_ALLOW_TTL_MS,
         }
     }
 
+    pub fn with_allow_ttl_ms(mut self, ttl_ms: u64) -> Self {
+        self.allow_ttl_ms = ttl_ms;
+        self
+    }
+
     /// Create a new approval request. Returns a receiver for the decision.
     pub fn create(
         &mut self,
         payload: ApprovalRequestPayload,
     ) -> oneshot::Receiver<ApprovalDecision> {
+        self.audit
+            .append(&payload.audit_entry(AuditEvent::Requested));
         let (tx, rx) = oneshot::channel();
         let id = payload.request_id.clone();
         self.pending.insert(
@@ -65,31 +155,127 @@ impl ExecApprovalManager {
         rx
     }
 
-    /// Resolve a pending approval request with a decision.
-    pub fn resolve(&mut self, request_id: &str, decision: ApprovalDecision) -> bool {
-        if let Some(mut record) = self.pending.remove(request_id) {
-            if decision == ApprovalDecision::AllowSession {
-                self.session_allows.push(record.payload.command.clone());
-            }
-            if let Some(tx) = record.tx.take() {
-                let _ = tx.send(decision);
-            }
-            true
-        } else {
-            false
+    /// Resolve a pending approval request with a decision made by `actor`.
+    pub fn resolve(&mut self, request_id: &str, decision: ApprovalDecision, actor: &str) -> bool {
+        let Some(mut record) = self.pending.remove(request_id) else {
+            return false;
+        };
+        let payload = &record.payload;
+        if decision == ApprovalDecision::AllowSession {
+            let now = now_ms();
+            self.allows.add(SessionAllow {
+                id: uuid::Uuid::new_v4().to_string(),
+                command: payload.command_line(),
+                scope: payload.scope(),
+                decided_by: Some(actor.to_string()),
+                request_id: Some(payload.request_id.clone()),
+                created_at: now,
+                expires_at: now + self.allow_ttl_ms,
+            });
         }
+        self.audit.append(&ApprovalAuditEntry {
+            decision: Some(decision.clone()),
+            actor: Some(actor.to_string()),
+            ..payload.audit_entry(AuditEvent::Decided)
+        });
+        if let Some(tx) = record.tx.take() {
+            let _ = tx.send(decision);
+        }
+        true
+    }
+
+    /// Record a request decided without asking anyone (by the policy or a
+    /// session allow).
+    pub fn record_decision(
+        &self,
+        payload: &ApprovalRequestPayload,
+        decision: ApprovalDecision,
+        actor: &str,
+        reason: &str,
+    ) {
+        self.audit.append(&ApprovalAuditEntry {
+            decision: Some(decision),
+            actor: Some(actor.to_string()),
+            reason: Some(reason.to_string()),
+            ..payload.audit_entry(AuditEvent::Decided)
+        });
+    }
+
+    /// A pending request by ID.
+    pub fn get(&mut self, request_id: &str) -> Option<ApprovalRequestPayload> {
+        self.expire();
+        self.pending.get(request_id).map(|r| r.payload.clone())
+    }
+
+    /// The pending request with approval code `code` (case-insensitive).
+    pub fn find_by_code(&mut self, code: &str) -> Option<ApprovalRequestPayload> {
+        self.expire();
+        self.pending
+            .values()
+            .find(|r| !r.payload.code.is_empty() && r.payload.code.eq_ignore_ascii_case(code))
+            .map(|r| r.payload.clone())
     }
 
     /// Expire old pending requests.
     pub fn expire(&mut self) {
         let now = now_ms();
-        self.pending
-            .retain(|_, r| now.saturating_sub(r.payload.created_at) < APPROVAL_TTL_MS);
+        let expired: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, r)| now.saturating_sub(r.payload.created_at) >= APPROVAL_TTL_MS)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in expired {
+            if let Some(record) = self.pending.remove(&id) {
+                self.audit
+                    .append(&record.payload.audit_entry(AuditEvent::Expired));
+            }
+        }
+    }
+
+    /// Check if a command is allowed for the session (and agent and node)
+    /// in `scope`.
+    pub fn is_session_allowed(&self, command: &str, scope: &AllowScope) -> bool {
+        self.allows.is_allowed(command, scope, now_ms())
+    }
+
+    /// Unexpired session allows, optionally only those of `session_key`.
+    pub fn session_allows(&self, session_key: Option<&str>) -> Vec<SessionAllow> {
+        self.allows.list(session_key, now_ms())
     }
 
-    /// Check if a command is allowed for the current session.
-    pub fn is_session_allowed(&self, command: &str) -> bool {
-        self.session_allows.iter().any(|c| c == command)
+    /// Revoke a session allow by ID on behalf of `actor`.
+    pub fn revoke_allow(&mut self, id: &str, actor: &str) -> Option<SessionAllow> {
+        let allow = self.allows.revoke(id)?;
+        self.audit_revoked(&allow, actor);
+        Some(allow)
+    }
+
+    /// Revoke every allow of `session_key` on behalf of `actor`.
+    pub fn revoke_session(&mut self, session_key: &str, actor: &str) -> Vec<SessionAllow> {
+        let revoked = self.allows.revoke_session(session_key);
+        for allow in &revoked {
+            self.audit_revoked(allow, actor);
+        }
+        revoked
+    }
+
+    fn audit_revoked(&self, allow: &SessionAllow, actor: &str) {
+        self.audit.append(&ApprovalAuditEntry {
+            ts: now_ms(),
+            event: AuditEvent::Revoked,
+            request_id: allow.request_id.clone(),
+            command: allow.command.clone(),
+            scope: allow.scope.clone(),
+            decision: None,
+            actor: Some(actor.to_string()),
+            reason: None,
+        });
+    }
+
+    /// The audit log of requests and decisions.
+    pub fn audit_log(&self) -> &ApprovalAuditLog {
+        &self.audit
     }
 
     /// Get a snapshot of pending requests (for dashboard display).
@@ -104,3 +290,53 @@ impl Default for ExecApprovalManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: &str, session_key: &str) -> ApprovalRequestPayload {
+        ApprovalRequestPayload {
+            request_id: id.into(),
+            command: "make deploy".into(),
+            args: Vec::new(),
+            cwd: None,
+            node_id: None,
+            session_key: Some(session_key.into()),
+            agent_id: Some("default".into()),
+            created_at: now_ms(),
+            code: String::new(),
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn session_allow_only_covers_its_session() {
+        let mut mgr = ExecApprovalManager::new();
+        let req = request("r1", "agent:default:slack:dm:U1");
+        let scope = req.scope();
+        let _rx = mgr.create(req);
+        assert!(mgr.resolve("r1", ApprovalDecision::AllowSession, "slack:U1"));
+        assert!(!mgr.resolve("r1", ApprovalDecision::Deny, "slack:U1"));
+
+        assert!(mgr.is_session_allowed("make deploy", &scope));
+        assert!(!mgr.is_session_allowed(
+            "make deploy",
+            &request("r2", "agent:default:slack:dm:U2").scope()
+        ));
+        // The allow covers the exact argument list only.
+        let mut other = request("r3", "agent:default:slack:dm:U1");
+        other.command = "make".into();
+        other.args = vec!["deploy".into()];
+        assert!(mgr.is_session_allowed(&other.command_line(), &scope));
+        other.args.push("PROD=1; rm -rf ~".into());
+        assert_eq!(other.command_line(), "make deploy 'PROD=1; rm -rf ~'");
+        assert!(!mgr.is_session_allowed(&other.command_line(), &scope));
+
+        let allows = mgr.session_allows(None);
+        assert_eq!(allows[0].decided_by.as_deref(), Some("slack:U1"));
+
+        assert!(mgr.revoke_allow(&allows[0].id, "rpc:cli").is_some());
+        assert!(!mgr.is_session_allowed("make deploy", &scope));
+    }
+}
@@ -1,8 +1,12 @@
 //! Exec approvals subsystem for command execution gating.
 
+pub mod allows;
+pub mod audit;
+pub mod chat;
 pub mod config;
 pub mod manager;
 pub mod policy;
+pub mod shell;
 
 pub use config::ExecApprovalsConfig;
 pub use manager::ExecApprovalManager;
@@ -1,67 +1,231 @@
 //! Policy evaluation for exec approval commands.
+//!
+//! The command line is split into the simple commands it runs (see
+//! [`super::shell`]) and every one of them is checked: a line is allowed only
+//! if each command is, and denied if any is.
+//!
+//! Allowlist and denylist entries are a program pattern optionally followed
+//! by argument patterns, e.g. `ls`, `git status|diff|log` or `git push*`.
+//! Patterns support `*` wildcards and `|` alternatives.  A program-only
+//! entry covers any arguments; otherwise an allow entry must match the
+//! leading arguments in order, while a deny entry matches its arguments
+//! anywhere in order (so `git push*` also catches `git -C repo push`) and
+//! matches the program with or without its directory.
+//!
+//! Commands whose program can't be known from the line (see
+//! [`SimpleCommand::opaque`]) are never allowed, not even in full mode.
+//!
+//! Files written by output redirections are checked as `> <path>` (e.g.
+//! `> /dev/null` or `> /tmp/*`), so `echo x >> ~/.bashrc` needs a rule for
+//! the write as well as for `echo`.
+
+use serde::Serialize;
 
 use super::config::{AskPolicy, ExecApprovalsConfig, SecurityMode};
+use super::shell::{self, SimpleCommand};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PolicyResult {
     Allow,
     Deny,
     Ask,
 }
 
+/// How one command of the line fared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Allowed,
+    Denied,
+    /// No rule covers it.
+    Unmatched,
+}
+
+/// The verdict on one command of the line and the rule behind it.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandMatch {
+    pub command: String,
+    pub verdict: Verdict,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+/// Why a command line got its result.
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub result: PolicyResult,
+    pub mode: SecurityMode,
+    pub commands: Vec<CommandMatch>,
+    /// Set when the line could not be parsed; it then matches no rule.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_error: Option<String>,
+    pub reason: String,
+}
+
 /// Evaluate a command against the exec approvals config.
 pub fn evaluate(
     config: &ExecApprovalsConfig,
     command: &str,
     node_id: Option<&str>,
 ) -> PolicyResult {
+    explain(config, command, node_id).result
+}
+
+/// Evaluate a command and report which rule decided each part of it.
+pub fn explain(config: &ExecApprovalsConfig, command: &str, node_id: Option<&str>) -> Explanation {
     // Check for node-specific overrides
-    let (mode, ask, allowlist) = if let Some(nid) = node_id {
-        if let Some(ovr) = config.node_overrides.get(nid) {
-            (
-                ovr.mode.as_ref().unwrap_or(&config.mode),
-                ovr.ask.as_ref().unwrap_or(&config.ask),
-                ovr.allowlist.as_ref().unwrap_or(&config.allowlist),
-            )
-        } else {
-            (&config.mode, &config.ask, &config.allowlist)
+    let ovr = node_id.and_then(|nid| config.node_overrides.get(nid));
+    let mode = ovr.and_then(|o| o.mode.as_ref()).unwrap_or(&config.mode);
+    let ask = ovr.and_then(|o| o.ask.as_ref()).unwrap_or(&config.ask);
+    let allowlist = ovr
+        .and_then(|o| o.allowlist.as_ref())
+        .unwrap_or(&config.allowlist);
+    let denylist = ovr
+        .and_then(|o| o.denylist.as_ref())
+        .unwrap_or(&config.denylist);
+
+    let mut explanation = Explanation {
+        result: PolicyResult::Deny,
+        mode: mode.clone(),
+        commands: Vec::new(),
+        parse_error: None,
+        reason: String::new(),
+    };
+    if *mode == SecurityMode::Deny {
+        explanation.reason = "exec is disabled (mode: deny)".into();
+        return explanation;
+    }
+
+    let parsed = match shell::parse(command) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            explanation.parse_error = Some(e);
+            Vec::new()
         }
-    } else {
-        (&config.mode, &config.ask, &config.allowlist)
     };
+    explanation.commands = parsed
+        .iter()
+        .map(|cmd| {
+            let denied = denylist.iter().find(|rule| denies(rule, cmd));
+            // A program only known when the line runs is never allowed.
+            let allowed = || match mode {
+                _ if cmd.opaque => None,
+                SecurityMode::Full => Some("mode: full".to_string()),
+                _ => allowlist.iter().find(|rule| allows(rule, cmd)).cloned(),
+            };
+            let (verdict, rule) = match denied {
+                Some(rule) => (Verdict::Denied, Some(rule.clone())),
+                None => match allowed() {
+                    Some(rule) => (Verdict::Allowed, Some(rule)),
+                    None => (Verdict::Unmatched, None),
+                },
+            };
+            CommandMatch {
+                command: cmd.display(),
+                verdict,
+                rule,
+            }
+        })
+        .collect();
 
-    match mode {
-        SecurityMode::Deny => PolicyResult::Deny,
-        SecurityMode::Full => {
-            if *ask == AskPolicy::Always {
-                PolicyResult::Ask
+    let all_allowed = !explanation.commands.is_empty()
+        && explanation
+            .commands
+            .iter()
+            .all(|c| c.verdict == Verdict::Allowed);
+    // Full mode can't check deny rules against a line it can't parse.
+    let unchecked_full =
+        *mode == SecurityMode::Full && explanation.parse_error.is_some() && denylist.is_empty();
+
+    let (result, reason) = if let Some(denied) = explanation
+        .commands
+        .iter()
+        .find(|c| c.verdict == Verdict::Denied)
+    {
+        (
+            PolicyResult::Deny,
+            format!(
+                "'{}' is denied by rule '{}'",
+                denied.command,
+                denied.rule.as_deref().unwrap_or_default()
+            ),
+        )
+    } else if all_allowed || unchecked_full {
+        if *ask == AskPolicy::Always {
+            (PolicyResult::Ask, "ask policy is always".to_string())
+        } else {
+            let reason = if *mode == SecurityMode::Full {
+                "mode: full"
             } else {
-                PolicyResult::Allow
-            }
+                "every command is allowlisted"
+            };
+            (PolicyResult::Allow, reason.to_string())
         }
-        SecurityMode::Allowlist => {
-            let base_cmd = command.split_whitespace().next().unwrap_or(command);
-            let matched = allowlist.iter().any(|pattern| {
-                if pattern.contains('*') {
-                    glob_match(pattern, base_cmd)
-                } else {
-                    pattern == base_cmd
-                }
-            });
-
-            if matched {
-                if *ask == AskPolicy::Always {
-                    PolicyResult::Ask
-                } else {
-                    PolicyResult::Allow
-                }
-            } else if *ask == AskPolicy::Off {
-                PolicyResult::Deny
-            } else {
-                PolicyResult::Ask
-            }
+    } else {
+        let miss = match (
+            &explanation.parse_error,
+            explanation
+                .commands
+                .iter()
+                .find(|c| c.verdict == Verdict::Unmatched),
+        ) {
+            (Some(e), _) => format!("command could not be parsed ({})", e),
+            (None, Some(c)) => format!("'{}' is not allowlisted", c.command),
+            (None, None) => "empty command".to_string(),
+        };
+        if *ask == AskPolicy::Off {
+            (PolicyResult::Deny, miss)
+        } else {
+            (PolicyResult::Ask, miss)
         }
+    };
+    explanation.result = result;
+    explanation.reason = reason;
+    explanation
+}
+
+/// Whether allow `rule` covers `cmd`: the program as written, then the
+/// leading arguments in order.
+fn allows(rule: &str, cmd: &SimpleCommand) -> bool {
+    let mut patterns = rule.split_whitespace();
+    let Some(program) = patterns.next() else {
+        return false;
+    };
+    if !token_match(program, &cmd.program) {
+        return false;
+    }
+    let patterns: Vec<&str> = patterns.collect();
+    patterns.len() <= cmd.args.len()
+        && patterns
+            .iter()
+            .zip(&cmd.args)
+            .all(|(p, arg)| token_match(p, arg))
+}
+
+/// Whether deny `rule` covers `cmd`: the program with or without its path,
+/// then the argument patterns anywhere in order.
+fn denies(rule: &str, cmd: &SimpleCommand) -> bool {
+    let mut patterns = rule.split_whitespace();
+    let Some(program) = patterns.next() else {
+        return false;
+    };
+    if !token_match(program, &cmd.program) && !token_match(program, cmd.basename()) {
+        return false;
     }
+    let mut args = cmd.args.iter();
+    patterns.all(|p| args.any(|arg| token_match(p, arg)))
+}
+
+/// Match one word against a pattern with `|` alternatives and `*` wildcards.
+fn token_match(pattern: &str, text: &str) -> bool {
+    pattern.split('|').any(|alt| {
+        if alt.contains('*') {
+            glob_match(alt, text)
+        } else {
+            alt == text
+        }
+    })
 }
 
 /// Simple glob matching (supports * wildcard).
@@ -133,4 +297,117 @@ mod tests {
         assert_eq!(evaluate(&config, "ls", None), PolicyResult::Allow);
         assert_eq!(evaluate(&config, "rm", None), PolicyResult::Ask);
     }
+
+    #[test]
+    fn test_policy_checks_every_command() {
+        let config = ExecApprovalsConfig::default();
+        assert_eq!(evaluate(&config, "ls -la | cat", None), PolicyResult::Allow);
+        assert_eq!(evaluate(&config, "ls; rm -rf ~", None), PolicyResult::Ask);
+        assert_eq!(
+            evaluate(&config, "echo $(rm -rf ~)", None),
+            PolicyResult::Ask
+        );
+        assert_eq!(
+            evaluate(&config, "ls 2>/dev/null", None),
+            PolicyResult::Allow
+        );
+        assert_eq!(
+            evaluate(&config, "echo x >> ~/.bashrc", None),
+            PolicyResult::Ask
+        );
+        assert_eq!(
+            evaluate(&config, "echo 'unterminated", None),
+            PolicyResult::Ask
+        );
+        assert_eq!(evaluate(&config, "", None), PolicyResult::Ask);
+    }
+
+    #[test]
+    fn test_policy_argument_rules() {
+        let config = ExecApprovalsConfig {
+            allowlist: vec!["git status|diff|log".into(), "cargo *".into()],
+            denylist: vec!["git push* --force|-f".into(), "rm -rf".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            evaluate(&config, "git status --short", None),
+            PolicyResult::Allow
+        );
+        assert_eq!(
+            evaluate(&config, "git log && cargo test", None),
+            PolicyResult::Allow
+        );
+        assert_eq!(
+            evaluate(&config, "git commit -m x", None),
+            PolicyResult::Ask
+        );
+        assert_eq!(evaluate(&config, "git", None), PolicyResult::Ask);
+        assert_eq!(
+            evaluate(&config, "git push origin main", None),
+            PolicyResult::Ask
+        );
+        assert_eq!(
+            evaluate(&config, "git push --force", None),
+            PolicyResult::Deny
+        );
+        assert_eq!(
+            evaluate(&config, "git -C repo push -f", None),
+            PolicyResult::Deny
+        );
+        assert_eq!(
+            evaluate(&config, "sudo /bin/rm -rf /", None),
+            PolicyResult::Deny
+        );
+
+        // Deny rules also hold in full mode.
+        let full = ExecApprovalsConfig {
+            mode: SecurityMode::Full,
+            ask: AskPolicy::Off,
+            ..config
+        };
+        assert_eq!(
+            evaluate(&full, "git commit -m x", None),
+            PolicyResult::Allow
+        );
+        assert_eq!(evaluate(&full, "ls; rm -rf /", None), PolicyResult::Deny);
+        assert_eq!(
+            evaluate(&full, "nice -n 5 rm -rf /", None),
+            PolicyResult::Deny
+        );
+        assert_eq!(
+            evaluate(&full, "timeout -s KILL 5 rm -rf /", None),
+            PolicyResult::Deny
+        );
+        assert_eq!(
+            evaluate(&full, "xargs -n1 rm -rf", None),
+            PolicyResult::Deny
+        );
+
+        // Programs it can't know are never allowed, not even in full mode.
+        assert_eq!(evaluate(&full, "$CMD -rf /", None), PolicyResult::Deny);
+        assert_eq!(
+            evaluate(&full, "sudo --frobnicate x rm -rf /", None),
+            PolicyResult::Deny
+        );
+    }
+
+    #[test]
+    fn test_explain_names_the_rule() {
+        let config = ExecApprovalsConfig {
+            allowlist: vec!["git status|diff".into(), "ls".into()],
+            denylist: vec!["git push*".into()],
+            ..Default::default()
+        };
+        let e = explain(&config, "ls && git push", None);
+        assert_eq!(e.result, PolicyResult::Deny);
+        assert_eq!(e.commands[0].rule.as_deref(), Some("ls"));
+        assert_eq!(e.commands[1].verdict, Verdict::Denied);
+        assert_eq!(e.commands[1].rule.as_deref(), Some("git push*"));
+        assert_eq!(e.reason, "'git push' is denied by rule 'git push*'");
+
+        let e = explain(&config, "git diff | less", None);
+        assert_eq!(e.result, PolicyResult::Ask);
+        assert_eq!(e.commands[1].verdict, Verdict::Unmatched);
+        assert_eq!(e.reason, "'less' is not allowlisted");
+    }
 }
@@ -0,0 +1,787 @@
+//! Splitting a shell command line into the simple commands it would run.
+//!
+//! Policy rules apply to programs and their arguments, so a command line is
+//! broken up the way a POSIX shell would: at pipes, `&&`/`||`, `;`, `&`,
+//! newlines and subshell or group boundaries.  Quotes and escapes are
+//! resolved, leading `NAME=value` assignments are dropped, every file an
+//! output redirection writes is reported as a command of its own (`>` and
+//! the target, see [`WRITE`]), and command substitutions (`$(..)`, backticks, `<(..)`) as well as
+//! scripts passed to `sh -c` or `eval` are parsed as commands of their own.
+//! Commands run through wrappers such as `sudo` or `env` are reported both
+//! as the wrapper and as the wrapped command, skipping the wrapper's options
+//! and their values.
+//!
+//! Anything the parser can't follow (unterminated quotes, here-documents) is
+//! an error, and the policy treats the whole line as unmatched.  A command
+//! whose program can't be known until the line runs — an expansion or glob,
+//! or a wrapper option the parser doesn't know — is marked
+//! [`opaque`](SimpleCommand::opaque) instead.
+
+/// Program name of the pseudo-command reporting a redirection's write.
+pub const WRITE: &str = ">";
+
+/// A program and its arguments, with quoting resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// The program actually run can't be told from the line: the program
+    /// word holds an expansion or glob, or this is a wrapper whose command
+    /// couldn't be found.  No allow rule covers an opaque command.
+    pub opaque: bool,
+}
+
+impl SimpleCommand {
+    /// The command as a single space-separated line.
+    pub fn display(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Program name without its directory.
+    pub fn basename(&self) -> &str {
+        self.program.rsplit('/').next().unwrap_or(&self.program)
+    }
+}
+
+/// Reserved words skipped when they start a command.
+const KEYWORDS: &[&str] = &[
+    "!", "{", "}", "if", "then", "else", "elif", "fi", "do", "done", "while", "until",
+];
+
+/// A program that runs the command given in its arguments, and how its
+/// options are spelled so the command can be found after them.
+struct Wrapper {
+    name: &'static str,
+    /// Short options without a value.
+    flags: &'static str,
+    /// Short options taking a value, attached (`-uroot`) or as the next word.
+    valued: &'static str,
+    /// Short options whose optional value can only be attached (`-i{}`).
+    optional: &'static str,
+    /// Long options without a value, or with an optional `=value`.
+    long_flags: &'static [&'static str],
+    /// Long options taking a value, as `--name=value` or the next word.
+    long_valued: &'static [&'static str],
+    /// Words between the options and the command (`timeout`'s duration).
+    operands: usize,
+}
+
+impl Wrapper {
+    const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            flags: "",
+            valued: "",
+            optional: "",
+            long_flags: &[],
+            long_valued: &[],
+            operands: 0,
+        }
+    }
+}
+
+const WRAPPERS: &[Wrapper] = &[
+    Wrapper {
+        flags: "AbEHhiKklnPSsVv",
+        valued: "CDgpRrTtUu",
+        long_flags: &[
+            "askpass",
+            "background",
+            "help",
+            "list",
+            "login",
+            "non-interactive",
+            "preserve-env",
+            "preserve-groups",
+            "remove-timestamp",
+            "reset-timestamp",
+            "set-home",
+            "shell",
+            "stdin",
+            "validate",
+            "version",
+        ],
+        long_valued: &[
+            "chdir",
+            "chroot",
+            "close-from",
+            "command-timeout",
+            "group",
+            "host",
+            "other-user",
+            "prompt",
+            "role",
+            "type",
+            "user",
+        ],
+        ..Wrapper::new("sudo")
+    },
+    Wrapper {
+        flags: "Lns",
+        valued: "Cu",
+        ..Wrapper::new("doas")
+    },
+    // `-S` splits a string into a command line of its own: unknown here.
+    Wrapper {
+        flags: "0iv",
+        valued: "CPu",
+        long_flags: &["debug", "ignore-environment", "null"],
+        long_valued: &["chdir", "unset"],
+        ..Wrapper::new("env")
+    },
+    Wrapper {
+        valued: "n",
+        long_valued: &["adjustment"],
+        ..Wrapper::new("nice")
+    },
+    Wrapper::new("nohup"),
+    Wrapper {
+        flags: "apqv",
+        valued: "fo",
+        long_flags: &["append", "portability", "quiet", "verbose"],
+        long_valued: &["format", "output"],
+        ..Wrapper::new("time")
+    },
+    Wrapper {
+        flags: "v",
+        valued: "ks",
+        long_flags: &["foreground", "preserve-status", "verbose"],
+        long_valued: &["kill-after", "signal"],
+        operands: 1,
+        ..Wrapper::new("timeout")
+    },
+    Wrapper {
+        flags: "cl",
+        valued: "a",
+        ..Wrapper::new("exec")
+    },
+    Wrapper {
+        flags: "pVv",
+        ..Wrapper::new("command")
+    },
+    Wrapper {
+        flags: "0oprtx",
+        valued: "adEILnPs",
+        optional: "eil",
+        long_flags: &[
+            "eof",
+            "exit",
+            "interactive",
+            "no-run-if-empty",
+            "null",
+            "open-tty",
+            "replace",
+            "show-limits",
+            "verbose",
+        ],
+        long_valued: &[
+            "arg-file",
+            "delimiter",
+            "max-args",
+            "max-chars",
+            "max-lines",
+            "max-procs",
+            "process-slot-var",
+        ],
+        ..Wrapper::new("xargs")
+    },
+    Wrapper {
+        valued: "eio",
+        long_valued: &["error", "input", "output"],
+        ..Wrapper::new("stdbuf")
+    },
+];
+
+/// Where the command a wrapper runs starts in its arguments.
+enum Wrapped {
+    At(usize),
+    /// The wrapper runs nothing (`sudo -v`, `timeout 5`).
+    Nothing,
+    /// An option the wrapper table doesn't know: its arity is unknown.
+    Unknown,
+}
+
+impl Wrapper {
+    fn find(name: &str) -> Option<&'static Wrapper> {
+        WRAPPERS.iter().find(|w| w.name == name)
+    }
+
+    /// Skip the options in `args` (getopt style: clustered short options,
+    /// attached or separate values, `--name[=value]`, `--`).
+    fn command(&self, args: &[String]) -> Wrapped {
+        let mut i = 0;
+        while let Some(arg) = args.get(i) {
+            if arg == "--" {
+                i += 1;
+                break;
+            }
+            if self.name == "env" && (arg == "-" || is_assignment(arg)) {
+                i += 1;
+                continue;
+            }
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, inline) = match long.split_once('=') {
+                    Some((name, _)) => (name, true),
+                    None => (long, false),
+                };
+                if self.long_valued.contains(&name) {
+                    i += if inline { 1 } else { 2 };
+                } else if self.long_flags.contains(&name) {
+                    i += 1;
+                } else {
+                    return Wrapped::Unknown;
+                }
+                continue;
+            }
+            let Some(cluster) = arg.strip_prefix('-') else {
+                break;
+            };
+            if cluster.is_empty() {
+                return Wrapped::Unknown;
+            }
+            // `nice -5` is the old spelling of `nice -n 5`.
+            if self.name == "nice" && cluster.chars().all(|c| c.is_ascii_digit()) {
+                i += 1;
+                continue;
+            }
+            let mut words = 1;
+            for (at, c) in cluster.char_indices() {
+                if self.valued.contains(c) {
+                    if at + c.len_utf8() == cluster.len() {
+                        words = 2;
+                    }
+                    break;
+                } else if self.optional.contains(c) {
+                    break;
+                } else if !self.flags.contains(c) {
+                    return Wrapped::Unknown;
+                }
+            }
+            i += words;
+        }
+        let at = i + self.operands;
+        if at < args.len() {
+            Wrapped::At(at)
+        } else {
+            Wrapped::Nothing
+        }
+    }
+}
+
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh"];
+
+/// Nesting limit for substitutions and `sh -c` scripts.
+const MAX_DEPTH: usize = 8;
+
+/// `word` quoted so a POSIX shell reads it back unchanged.
+pub fn quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%^".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// Every simple command `line` runs, in order of appearance.
+pub fn parse(line: &str) -> Result<Vec<SimpleCommand>, String> {
+    parse_at(line, 0)
+}
+
+fn parse_at(line: &str, depth: usize) -> Result<Vec<SimpleCommand>, String> {
+    if depth > MAX_DEPTH {
+        return Err("command nesting is too deep".into());
+    }
+    let mut lexer = Lexer {
+        chars: line.chars().collect(),
+        pos: 0,
+        depth,
+        nested: Vec::new(),
+    };
+    let mut commands = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut writes: Vec<String> = Vec::new();
+    let mut redirect: Option<Redirect> = None;
+
+    loop {
+        match lexer.next_token()? {
+            Token::Word(word) => match redirect.take() {
+                Some(Redirect::Write) => writes.push(word),
+                // `>&2` and `2>&-` duplicate or close a descriptor.
+                Some(Redirect::Duplicate)
+                    if word == "-" || word.chars().all(|c| c.is_ascii_digit()) => {}
+                Some(Redirect::Duplicate) => writes.push(word),
+                Some(Redirect::Read) => {}
+                None => {
+                    if !(words.is_empty() && is_assignment(&word)) {
+                        words.push(word);
+                    }
+                }
+            },
+            Token::Redirect(kind) => redirect = Some(kind),
+            token @ (Token::Separator | Token::End) => {
+                if redirect.is_some() {
+                    return Err("redirection without a target".into());
+                }
+                push_command(std::mem::take(&mut words), depth, &mut commands)?;
+                commands.extend(writes.drain(..).map(|target| SimpleCommand {
+                    program: WRITE.to_string(),
+                    args: vec![target],
+                    opaque: false,
+                }));
+                if matches!(token, Token::End) {
+                    break;
+                }
+            }
+        }
+    }
+    commands.append(&mut lexer.nested);
+    Ok(commands)
+}
+
+/// Add the command made of `words`, plus whatever it runs in turn.
+fn push_command(
+    mut words: Vec<String>,
+    depth: usize,
+    commands: &mut Vec<SimpleCommand>,
+) -> Result<(), String> {
+    while words
+        .first()
+        .is_some_and(|w| KEYWORDS.contains(&w.as_str()))
+    {
+        words.remove(0);
+    }
+    if words.is_empty() {
+        return Ok(());
+    }
+    let command = SimpleCommand {
+        program: words[0].clone(),
+        args: words[1..].to_vec(),
+        opaque: words[0].contains(['$', '`', '*', '?', '[']),
+    };
+    let name = command.basename().to_string();
+    let args = command.args.clone();
+    let index = commands.len();
+    commands.push(command);
+
+    if SHELLS.contains(&name.as_str()) {
+        if let Some(i) = args.iter().position(|a| a == "-c") {
+            if let Some(script) = args.get(i + 1) {
+                commands.extend(parse_at(script, depth + 1)?);
+            }
+        }
+    } else if name == "eval" {
+        commands.extend(parse_at(&args.join(" "), depth + 1)?);
+    } else if let Some(wrapper) = Wrapper::find(&name) {
+        match wrapper.command(&args) {
+            Wrapped::At(at) => push_command(args[at..].to_vec(), depth, commands)?,
+            Wrapped::Nothing => {}
+            Wrapped::Unknown => commands[index].opaque = true,
+        }
+    }
+    Ok(())
+}
+
+/// `NAME=value` prefixes set the environment rather than naming a program.
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// What a redirection does with its target.
+#[derive(Clone, Copy)]
+enum Redirect {
+    Read,
+    Write,
+    /// `>&`/`<&`: a descriptor number, or a file to write with `>&file`.
+    Duplicate,
+}
+
+enum Token {
+    Word(String),
+    /// A redirection operator; the next word is its target.
+    Redirect(Redirect),
+    /// Anything that ends a simple command.
+    Separator,
+    End,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+    /// Commands found in substitutions.
+    nested: Vec<SimpleCommand>,
+}
+
+impl Lexer {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn next_token(&mut self) -> Result<Token, String> {
+        while matches!(self.peek(0), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+        if self.peek(0) == Some('#') {
+            while !matches!(self.peek(0), None | Some('\n')) {
+                self.pos += 1;
+            }
+        }
+        let Some(c) = self.peek(0) else {
+            return Ok(Token::End);
+        };
+        match c {
+            '\n' | ';' | '|' | '(' | ')' => {
+                self.pos += 1;
+                // `||`, `;;`, `|&` are single operators.
+                if matches!((c, self.peek(0)), ('|', Some('|' | '&')) | (';', Some(';'))) {
+                    self.pos += 1;
+                }
+                Ok(Token::Separator)
+            }
+            '&' => {
+                self.pos += 1;
+                match self.peek(0) {
+                    Some('&') => {
+                        self.pos += 1;
+                        Ok(Token::Separator)
+                    }
+                    // `&>` and `&>>` redirect both output streams.
+                    Some('>') => {
+                        self.pos += 1;
+                        if self.peek(0) == Some('>') {
+                            self.pos += 1;
+                        }
+                        Ok(Token::Redirect(Redirect::Write))
+                    }
+                    _ => Ok(Token::Separator),
+                }
+            }
+            '<' | '>' => self.redirect(),
+            _ => self.word(),
+        }
+    }
+
+    /// A redirection operator at the current position.
+    fn redirect(&mut self) -> Result<Token, String> {
+        let op = self.chars[self.pos];
+        self.pos += 1;
+        match (op, self.peek(0)) {
+            ('<', Some('<')) => {
+                if self.peek(1) == Some('<') {
+                    // Here-string: the next word is its text.
+                    self.pos += 2;
+                    return Ok(Token::Redirect(Redirect::Read));
+                }
+                Err("here-documents are not supported".into())
+            }
+            (_, Some('(')) => {
+                // Process substitution: an argument produced by a command list.
+                self.pos += 1;
+                let inner = self.balanced(')')?;
+                self.nested.extend(parse_at(&inner, self.depth + 1)?);
+                Ok(Token::Word(format!("{}({})", op, inner)))
+            }
+            (_, Some('&')) => {
+                self.pos += 1;
+                Ok(Token::Redirect(Redirect::Duplicate))
+            }
+            // `>>`, `>|` and `<>` all open the target for writing.
+            ('>', Some('>' | '|')) | ('<', Some('>')) => {
+                self.pos += 1;
+                Ok(Token::Redirect(Redirect::Write))
+            }
+            ('>', _) => Ok(Token::Redirect(Redirect::Write)),
+            _ => Ok(Token::Redirect(Redirect::Read)),
+        }
+    }
+
+    fn word(&mut self) -> Result<Token, String> {
+        let mut word = String::new();
+        while let Some(c) = self.peek(0) {
+            match c {
+                ' ' | '\t' | '\n' | ';' | '&' | '|' | '(' | ')' => break,
+                '<' | '>' => {
+                    // `2>file`: a file descriptor number belongs to the operator.
+                    if word.chars().all(|d| d.is_ascii_digit()) && !word.is_empty() {
+                        return self.redirect();
+                    }
+                    break;
+                }
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek(0) {
+                        Some('\n') => {}
+                        Some(escaped) => word.push(escaped),
+                        None => {}
+                    }
+                    self.pos += 1;
+                }
+                '\'' => {
+                    self.pos += 1;
+                    loop {
+                        match self.peek(0) {
+                            Some('\'') => break,
+                            Some(q) => word.push(q),
+                            None => return Err("unterminated single quote".into()),
+                        }
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                }
+                '"' => {
+                    self.pos += 1;
+                    self.double_quoted(&mut word)?;
+                }
+                '$' if self.peek(1) == Some('(') => {
+                    self.pos += 2;
+                    let inner = self.balanced(')')?;
+                    self.nested.extend(parse_at(&inner, self.depth + 1)?);
+                    word.push_str(&format!("$({})", inner));
+                }
+                '`' => {
+                    self.pos += 1;
+                    let inner = self.backticks()?;
+                    self.nested.extend(parse_at(&inner, self.depth + 1)?);
+                    word.push_str(&format!("`{}`", inner));
+                }
+                _ => {
+                    word.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        Ok(Token::Word(word))
+    }
+
+    /// Contents of a double-quoted string, after the opening quote.
+    fn double_quoted(&mut self, word: &mut String) -> Result<(), String> {
+        loop {
+            match self.peek(0) {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some('\\') if matches!(self.peek(1), Some('"' | '\\' | '$' | '`' | '\n')) => {
+                    if self.peek(1) != Some('\n') {
+                        word.push(self.chars[self.pos + 1]);
+                    }
+                    self.pos += 2;
+                }
+                Some('$') if self.peek(1) == Some('(') => {
+                    self.pos += 2;
+                    let inner = self.balanced(')')?;
+                    self.nested.extend(parse_at(&inner, self.depth + 1)?);
+                    word.push_str(&format!("$({})", inner));
+                }
+                Some('`') => {
+                    self.pos += 1;
+                    let inner = self.backticks()?;
+                    self.nested.extend(parse_at(&inner, self.depth + 1)?);
+                    word.push_str(&format!("`{}`", inner));
+                }
+                Some(c) => {
+                    word.push(c);
+                    self.pos += 1;
+                }
+                None => return Err("unterminated double quote".into()),
+            }
+        }
+    }
+
+    /// Text up to the `close` matching an already consumed opener, skipping
+    /// over quoted and nested parentheses.
+    fn balanced(&mut self, close: char) -> Result<String, String> {
+        let start = self.pos;
+        let mut level = 0usize;
+        let mut quote: Option<char> = None;
+        while let Some(c) = self.peek(0) {
+            match (quote, c) {
+                (Some(q), _) if c == q => quote = None,
+                (Some('"'), '\\') | (None, '\\') => self.pos += 1,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '(') => level += 1,
+                (None, _) if c == close => {
+                    if level == 0 {
+                        let inner: String = self.chars[start..self.pos].iter().collect();
+                        self.pos += 1;
+                        return Ok(inner);
+                    }
+                    level -= 1;
+                }
+                (None, _) => {}
+            }
+            self.pos += 1;
+        }
+        Err("unterminated command substitution".into())
+    }
+
+    /// Text up to the closing backtick.
+    fn backticks(&mut self) -> Result<String, String> {
+        let mut inner = String::new();
+        loop {
+            match self.peek(0) {
+                Some('`') => {
+                    self.pos += 1;
+                    return Ok(inner);
+                }
+                Some('\\') if matches!(self.peek(1), Some('`' | '\\' | '$')) => {
+                    inner.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                }
+                Some(c) => {
+                    inner.push(c);
+                    self.pos += 1;
+                }
+                None => return Err("unterminated backtick substitution".into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(line: &str) -> Vec<String> {
+        parse(line).unwrap().iter().map(|c| c.display()).collect()
+    }
+
+    #[test]
+    fn splits_lists_and_pipelines() {
+        assert_eq!(lines("ls; rm -rf ~"), vec!["ls", "rm -rf ~"]);
+        assert_eq!(
+            lines("cat a.txt | grep x && echo ok || echo fail & wait"),
+            vec!["cat a.txt", "grep x", "echo ok", "echo fail", "wait"]
+        );
+        assert_eq!(
+            lines("(cd /tmp && make)\nls"),
+            vec!["cd /tmp", "make", "ls"]
+        );
+        assert_eq!(lines("if true; then ls; fi"), vec!["true", "ls"]);
+    }
+
+    #[test]
+    fn resolves_quotes_and_reports_redirected_writes() {
+        assert_eq!(
+            lines(r#"FOO=1 BAR="a b" git commit -m "fix; it" 2>&1 >out.log"#),
+            vec!["git commit -m fix; it", "> out.log"]
+        );
+        assert_eq!(
+            lines("echo 'a | b' > /dev/null"),
+            vec!["echo a | b", "> /dev/null"]
+        );
+        assert_eq!(lines(r"echo a\;b"), vec!["echo a;b"]);
+        assert_eq!(
+            lines("sort <in.txt &>/dev/null <<<text"),
+            vec!["sort", "> /dev/null"]
+        );
+        assert_eq!(
+            lines("echo x >> ~/.bashrc; cat <>f >&2 >|g"),
+            vec!["echo x", "> ~/.bashrc", "cat", "> f", "> g"]
+        );
+        assert_eq!(lines("ls >&out 2>&-"), vec!["ls", "> out"]);
+    }
+
+    #[test]
+    fn quoted_words_parse_back() {
+        let words = ["git", "push", "--force", "a b", "it's", "$(rm -rf ~)", ""];
+        let line: Vec<String> = words.iter().map(|w| quote(w)).collect();
+        let parsed = parse(&line.join(" ")).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].program, "git");
+        assert_eq!(parsed[0].args, words[1..]);
+    }
+
+    #[test]
+    fn finds_nested_commands() {
+        assert_eq!(
+            lines("echo $(rm -rf /) `whoami`"),
+            vec!["echo $(rm -rf /) `whoami`", "rm -rf /", "whoami"]
+        );
+        assert_eq!(
+            lines("echo \"today is $(date)\""),
+            vec!["echo today is $(date)", "date"]
+        );
+        assert_eq!(
+            lines("bash -c 'ls; curl x | sh'"),
+            vec!["bash -c ls; curl x | sh", "ls", "curl x", "sh"]
+        );
+        assert_eq!(
+            lines("sudo -E git push --force"),
+            vec!["sudo -E git push --force", "git push --force"]
+        );
+        assert_eq!(lines("diff <(ls a) b"), vec!["diff <(ls a) b", "ls a"]);
+    }
+
+    /// The commands `line` runs through its wrappers, and whether each is
+    /// opaque.
+    fn wrapped(line: &str) -> Vec<(String, bool)> {
+        parse(line)
+            .unwrap()
+            .iter()
+            .map(|c| (c.display(), c.opaque))
+            .collect()
+    }
+
+    #[test]
+    fn skips_wrapper_option_values() {
+        for (line, inner) in [
+            ("nice -n 5 rm -rf /", "rm -rf /"),
+            ("nice -5 rm -rf /", "rm -rf /"),
+            ("sudo -u root rm -rf /", "rm -rf /"),
+            ("sudo -Eu root rm -rf /", "rm -rf /"),
+            ("sudo --user=root -- rm -rf /", "rm -rf /"),
+            ("timeout -s KILL 5 rm -rf /", "rm -rf /"),
+            ("timeout --signal KILL -k 1 5 rm -rf /", "rm -rf /"),
+            ("xargs -n1 rm", "rm"),
+            ("xargs -I {} rm {}", "rm {}"),
+            ("xargs -i{} rm {}", "rm {}"),
+            ("env -C /tmp FOO=1 rm -rf /", "rm -rf /"),
+            ("stdbuf -o L rm -rf /", "rm -rf /"),
+        ] {
+            let commands = wrapped(line);
+            assert_eq!(commands.len(), 2, "{line}");
+            assert!(!commands[0].1, "{line}");
+            assert_eq!(commands[1], (inner.to_string(), false), "{line}");
+        }
+        assert_eq!(wrapped("timeout 5"), vec![("timeout 5".to_string(), false)]);
+    }
+
+    #[test]
+    fn marks_programs_it_cannot_know() {
+        // Unknown wrapper options: the wrapper itself is opaque.
+        assert_eq!(
+            wrapped("sudo --frobnicate x rm -rf /"),
+            vec![("sudo --frobnicate x rm -rf /".to_string(), true)]
+        );
+        assert!(wrapped("env -S 'rm -rf /'")[0].1);
+        // Expansions and globs in the program word.
+        assert!(wrapped("$CMD -rf /")[0].1);
+        assert!(wrapped("/bin/r? -rf /")[0].1);
+        assert!(wrapped("sudo `which rm` -rf /")[1].1);
+        assert!(!wrapped("rm -rf '$HOME'")[0].1);
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_follow() {
+        assert!(parse("echo 'oops").is_err());
+        assert!(parse("cat <<EOF\nhi\nEOF").is_err());
+        assert!(parse("echo $(ls").is_err());
+        assert!(parse("ls >").is_err());
+    }
+}
@@ -1,10 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use synaptic::DeliveryContext;
 
 use super::outbound::OutboundPayload;
 
 /// Mirror delivery to additional targets.
-#[allow(dead_code)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeliveryMirror {
     pub channel: String,
@@ -13,7 +13,6 @@ pub struct DeliveryMirror {
 }
 
 /// A persistent delivery task with retry semantics.
-#[allow(dead_code)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct QueuedDelivery {
     pub id: String,
@@ -32,30 +31,234 @@ pub struct QueuedDelivery {
     pub retry_count: u32,
     pub last_attempt_at: Option<u64>,
     pub last_error: Option<String>,
+    /// Number of leading payloads already delivered (so retries never resend them).
+    #[serde(default)]
+    pub delivered_payloads: usize,
+    /// Number of leading payloads the mirror target has received.
+    #[serde(default)]
+    pub mirror_delivered_payloads: usize,
+    /// When the delivery was moved to the dead-letter queue (ms since epoch).
+    #[serde(default)]
+    pub dead_lettered_at: Option<u64>,
+}
+
+impl QueuedDelivery {
+    /// Create a delivery to `to` on `channel` with the given payloads.
+    pub fn new(
+        channel: impl Into<String>,
+        to: impl Into<String>,
+        payloads: Vec<OutboundPayload>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            channel: channel.into(),
+            to: to.into(),
+            account_id: None,
+            thread_id: None,
+            reply_to_id: None,
+            payloads,
+            best_effort: false,
+            silent: false,
+            gif_playback: false,
+            force_document: false,
+            mirror: None,
+            enqueued_at: now_ms(),
+            retry_count: 0,
+            last_attempt_at: None,
+            last_error: None,
+            delivered_payloads: 0,
+            mirror_delivered_payloads: 0,
+            dead_lettered_at: None,
+        }
+    }
+
+    /// Create a delivery with one text payload per chunk.
+    pub fn text_chunks(
+        channel: impl Into<String>,
+        to: impl Into<String>,
+        chunks: Vec<String>,
+    ) -> Self {
+        let payloads = chunks
+            .into_iter()
+            .map(|text| OutboundPayload {
+                text: Some(text),
+                ..Default::default()
+            })
+            .collect();
+        Self::new(channel, to, payloads)
+    }
+
+    /// Split text payloads over the channel's message limit into one
+    /// payload per chunk, so every send is a single message and progress is
+    /// recorded per chunk.  Interactive payloads are left whole for the
+    /// sender to render.
+    pub fn split_for_channel(mut self) -> Self {
+        let limit = crate::channels::formatter::chunk_limit(&self.channel);
+        let needs_split = |p: &OutboundPayload| {
+            p.interactive.is_none() && p.text.as_ref().is_some_and(|t| t.len() > limit)
+        };
+        if !self.payloads.iter().any(needs_split) {
+            return self;
+        }
+        self.payloads = std::mem::take(&mut self.payloads)
+            .into_iter()
+            .flat_map(|payload| match payload.text {
+                Some(ref text) if needs_split(&payload) => {
+                    crate::channels::formatter::chunk_message(text, limit)
+                        .into_iter()
+                        .map(|chunk| OutboundPayload {
+                            text: Some(chunk),
+                            ..payload.clone()
+                        })
+                        .collect()
+                }
+                _ => vec![payload],
+            })
+            .collect();
+        self
+    }
+
+    pub fn with_account_id(mut self, account_id: Option<String>) -> Self {
+        self.account_id = account_id;
+        self
+    }
+
+    pub fn with_thread_id(mut self, thread_id: Option<String>) -> Self {
+        self.thread_id = thread_id;
+        self
+    }
+
+    pub fn with_reply_to(mut self, reply_to_id: Option<String>) -> Self {
+        self.reply_to_id = reply_to_id;
+        self
+    }
+
+    pub fn with_mirror(mut self, mirror: Option<DeliveryMirror>) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Primary delivery target.
+    pub fn target(&self) -> DeliveryContext {
+        DeliveryContext {
+            channel: self.channel.clone(),
+            to: Some(self.to.clone()),
+            account_id: self.account_id.clone(),
+            thread_id: self.thread_id.clone(),
+            meta: None,
+        }
+    }
+
+    /// Mirror delivery target, if configured.
+    pub fn mirror_target(&self) -> Option<DeliveryContext> {
+        self.mirror.as_ref().map(|m| DeliveryContext {
+            channel: m.channel.clone(),
+            to: Some(m.to.clone()),
+            account_id: m.account_id.clone(),
+            thread_id: None,
+            meta: None,
+        })
+    }
+
+    /// Per-send metadata passed through to `ChannelSender::send`.
+    pub fn send_meta(&self) -> serde_json::Value {
+        serde_json::json!({
+            "delivery_id": self.id,
+            "reply_to_id": self.reply_to_id,
+            "silent": self.silent,
+        })
+    }
+
+    /// Whether the delivery is due for another attempt at `now` under `policy`.
+    pub fn is_due(&self, now: u64, policy: &RetryPolicy) -> bool {
+        match self.last_attempt_at {
+            None => true,
+            Some(last) => now >= last + policy.backoff_ms(self.retry_count),
+        }
+    }
+}
+
+/// Exponential backoff and dead-letter policy for queued deliveries.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Failed attempts after which a delivery is dead-lettered.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay_ms: u64,
+    /// Upper bound for the backoff delay.
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            base_delay_ms: 2_000,
+            max_delay_ms: 600_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff delay after `retry_count` failed attempts (`base * 2^(n-1)`, capped).
+    pub fn backoff_ms(&self, retry_count: u32) -> u64 {
+        if retry_count == 0 {
+            return 0;
+        }
+        let exp = (retry_count - 1).min(30);
+        self.base_delay_ms
+            .saturating_mul(1u64 << exp)
+            .min(self.max_delay_ms)
+    }
+}
+
+/// Outcome of recording a failed attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureOutcome {
+    /// The delivery stays queued and will be retried after backoff.
+    Retry,
+    /// The delivery exhausted its attempts and was moved to the dead-letter queue.
+    DeadLettered,
+    /// The delivery was best-effort and has been dropped.
+    Dropped,
 }
 
 /// Persistent delivery queue with disk-backed storage and retry support.
-#[allow(dead_code)]
+///
+/// Pending deliveries live in `queue_dir/{id}.json`; dead-lettered ones are
+/// moved to `queue_dir/dead/{id}.json` so they survive restarts until an
+/// operator retries or discards them.
 pub struct DeliveryQueue {
     queue_dir: PathBuf,
     pending: Vec<QueuedDelivery>,
+    dead: Vec<QueuedDelivery>,
 }
 
-#[allow(dead_code)]
 impl DeliveryQueue {
     pub fn new(queue_dir: PathBuf) -> Self {
         Self {
             queue_dir,
             pending: Vec::new(),
+            dead: Vec::new(),
         }
     }
 
+    fn dead_dir(&self) -> PathBuf {
+        self.queue_dir.join("dead")
+    }
+
+    fn persist(dir: &std::path::Path, delivery: &QueuedDelivery) -> Result<(), std::io::Error> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.json", delivery.id));
+        let tmp = dir.join(format!("{}.json.tmp", delivery.id));
+        let json = serde_json::to_string_pretty(delivery).map_err(std::io::Error::other)?;
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &path)
+    }
+
     /// Enqueue a delivery — persists to disk before returning.
     pub fn enqueue(&mut self, delivery: QueuedDelivery) -> Result<(), std::io::Error> {
-        let path = self.queue_dir.join(format!("{}.json", delivery.id));
-        std::fs::create_dir_all(&self.queue_dir)?;
-        let json = serde_json::to_string_pretty(&delivery).map_err(std::io::Error::other)?;
-        std::fs::write(&path, json)?;
+        Self::persist(&self.queue_dir, &delivery)?;
         self.pending.push(delivery);
         Ok(())
     }
@@ -67,45 +270,231 @@ impl DeliveryQueue {
         self.pending.retain(|d| d.id != id);
     }
 
-    /// Mark a delivery as failed — updates retry info, persists.
-    pub fn mark_failed(&mut self, id: &str, error: &str) {
+    /// Record delivery progress (payloads delivered to the primary and
+    /// mirror targets) and persist.
+    pub fn mark_progress(&mut self, id: &str, delivered_payloads: usize, mirror_payloads: usize) {
         if let Some(d) = self.pending.iter_mut().find(|d| d.id == id) {
-            d.retry_count += 1;
-            d.last_error = Some(error.to_string());
-            d.last_attempt_at = Some(
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_millis() as u64,
-            );
-            // Re-persist
-            let path = self.queue_dir.join(format!("{}.json", d.id));
-            if let Ok(json) = serde_json::to_string_pretty(d) {
-                let _ = std::fs::write(path, json);
-            }
+            d.delivered_payloads = delivered_payloads;
+            d.mirror_delivered_payloads = mirror_payloads;
+            let _ = Self::persist(&self.queue_dir, d);
         }
     }
 
-    /// Load pending deliveries from disk (call on startup).
+    /// Mark a delivery as failed — updates retry info and persists, moving it
+    /// to the dead-letter queue once `policy.max_attempts` is reached.
+    pub fn mark_failed(&mut self, id: &str, error: &str, policy: &RetryPolicy) -> FailureOutcome {
+        let Some(idx) = self.pending.iter().position(|d| d.id == id) else {
+            return FailureOutcome::Dropped;
+        };
+        let now = now_ms();
+        let d = &mut self.pending[idx];
+        d.retry_count += 1;
+        d.last_error = Some(error.to_string());
+        d.last_attempt_at = Some(now);
+
+        if d.best_effort {
+            self.mark_complete(id);
+            return FailureOutcome::Dropped;
+        }
+
+        if d.retry_count >= policy.max_attempts {
+            let mut dead = self.pending.remove(idx);
+            dead.dead_lettered_at = Some(now);
+            let _ = std::fs::remove_file(self.queue_dir.join(format!("{}.json", id)));
+            let _ = Self::persist(&self.dead_dir(), &dead);
+            self.dead.push(dead);
+            return FailureOutcome::DeadLettered;
+        }
+
+        let _ = Self::persist(&self.queue_dir, d);
+        FailureOutcome::Retry
+    }
+
+    /// Move a dead-lettered delivery back to the pending queue with a fresh retry budget.
+    pub fn requeue_dead(&mut self, id: &str) -> Result<bool, std::io::Error> {
+        let Some(idx) = self.dead.iter().position(|d| d.id == id) else {
+            return Ok(false);
+        };
+        let mut delivery = self.dead.remove(idx);
+        let _ = std::fs::remove_file(self.dead_dir().join(format!("{}.json", id)));
+        delivery.retry_count = 0;
+        delivery.last_attempt_at = None;
+        delivery.dead_lettered_at = None;
+        self.enqueue(delivery)?;
+        Ok(true)
+    }
+
+    /// Permanently drop a pending or dead-lettered delivery.
+    pub fn discard(&mut self, id: &str) -> bool {
+        let before = self.pending.len() + self.dead.len();
+        self.mark_complete(id);
+        let _ = std::fs::remove_file(self.dead_dir().join(format!("{}.json", id)));
+        self.dead.retain(|d| d.id != id);
+        before != self.pending.len() + self.dead.len()
+    }
+
+    /// Load pending and dead-lettered deliveries from disk (call on startup).
     pub fn load_pending(&mut self) -> Result<(), std::io::Error> {
-        if !self.queue_dir.exists() {
-            return Ok(());
+        self.pending = Self::load_dir(&self.queue_dir)?;
+        self.dead = Self::load_dir(&self.dead_dir())?;
+        self.pending.sort_by_key(|d| d.enqueued_at);
+        self.dead.sort_by_key(|d| d.enqueued_at);
+        Ok(())
+    }
+
+    fn load_dir(dir: &std::path::Path) -> Result<Vec<QueuedDelivery>, std::io::Error> {
+        let mut out = Vec::new();
+        if !dir.exists() {
+            return Ok(out);
         }
-        for entry in std::fs::read_dir(&self.queue_dir)? {
+        for entry in std::fs::read_dir(dir)? {
             let entry = entry?;
             if entry.path().extension().is_some_and(|e| e == "json") {
                 if let Ok(content) = std::fs::read_to_string(entry.path()) {
                     if let Ok(delivery) = serde_json::from_str::<QueuedDelivery>(&content) {
-                        self.pending.push(delivery);
+                        out.push(delivery);
                     }
                 }
             }
         }
-        Ok(())
+        Ok(out)
     }
 
     /// Get all pending deliveries.
     pub fn pending_deliveries(&self) -> &[QueuedDelivery] {
         &self.pending
     }
+
+    /// Get all dead-lettered deliveries.
+    pub fn dead_letters(&self) -> &[QueuedDelivery] {
+        &self.dead
+    }
+
+    /// Pending deliveries whose backoff has elapsed at `now`.
+    pub fn due(&self, now: u64, policy: &RetryPolicy) -> Vec<QueuedDelivery> {
+        self.pending
+            .iter()
+            .filter(|d| d.is_due(now, policy))
+            .cloned()
+            .collect()
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(dir: &std::path::Path) -> (DeliveryQueue, String) {
+        let mut queue = DeliveryQueue::new(dir.to_path_buf());
+        let d = QueuedDelivery::text_chunks("slack", "channel:C1", vec!["hi".into()]);
+        let id = d.id.clone();
+        queue.enqueue(d).unwrap();
+        (queue, id)
+    }
+
+    #[test]
+    fn backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay_ms: 1000,
+            max_delay_ms: 5000,
+        };
+        assert_eq!(policy.backoff_ms(0), 0);
+        assert_eq!(policy.backoff_ms(1), 1000);
+        assert_eq!(policy.backoff_ms(2), 2000);
+        assert_eq!(policy.backoff_ms(3), 4000);
+        assert_eq!(policy.backoff_ms(4), 5000);
+        assert_eq!(policy.backoff_ms(40), 5000);
+    }
+
+    #[test]
+    fn failed_delivery_waits_for_backoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut queue, id) = sample(dir.path());
+        let policy = RetryPolicy::default();
+        assert_eq!(queue.due(now_ms(), &policy).len(), 1);
+
+        assert_eq!(
+            queue.mark_failed(&id, "HTTP 502", &policy),
+            FailureOutcome::Retry
+        );
+        assert!(queue.due(now_ms(), &policy).is_empty());
+        assert_eq!(queue.due(now_ms() + 60_000, &policy).len(), 1);
+    }
+
+    #[test]
+    fn exhausted_delivery_is_dead_lettered_and_survives_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut queue, id) = sample(dir.path());
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            ..Default::default()
+        };
+        assert_eq!(queue.mark_failed(&id, "e1", &policy), FailureOutcome::Retry);
+        assert_eq!(
+            queue.mark_failed(&id, "e2", &policy),
+            FailureOutcome::DeadLettered
+        );
+        assert!(queue.pending_deliveries().is_empty());
+
+        let mut reloaded = DeliveryQueue::new(dir.path().to_path_buf());
+        reloaded.load_pending().unwrap();
+        assert!(reloaded.pending_deliveries().is_empty());
+        assert_eq!(reloaded.dead_letters().len(), 1);
+        assert_eq!(reloaded.dead_letters()[0].last_error.as_deref(), Some("e2"));
+
+        assert!(reloaded.requeue_dead(&id).unwrap());
+        assert_eq!(reloaded.pending_deliveries().len(), 1);
+        assert_eq!(reloaded.pending_deliveries()[0].retry_count, 0);
+    }
+
+    #[test]
+    fn best_effort_delivery_is_dropped_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = DeliveryQueue::new(dir.path().to_path_buf());
+        let mut d = QueuedDelivery::text_chunks("telegram", "chat:1", vec!["x".into()]);
+        d.best_effort = true;
+        let id = d.id.clone();
+        queue.enqueue(d).unwrap();
+        assert_eq!(
+            queue.mark_failed(&id, "boom", &RetryPolicy::default()),
+            FailureOutcome::Dropped
+        );
+        assert!(queue.pending_deliveries().is_empty());
+        assert!(queue.dead_letters().is_empty());
+    }
+
+    #[test]
+    fn progress_is_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut queue, id) = sample(dir.path());
+        queue.mark_progress(&id, 1, 0);
+        let mut reloaded = DeliveryQueue::new(dir.path().to_path_buf());
+        reloaded.load_pending().unwrap();
+        assert_eq!(reloaded.pending_deliveries()[0].delivered_payloads, 1);
+    }
+
+    #[test]
+    fn long_text_is_split_into_one_payload_per_message() {
+        let text = "para\n\n".repeat(300);
+        let d = QueuedDelivery::text_chunks("irc", "target:#c", vec![text.clone(), "ok".into()])
+            .split_for_channel();
+        assert!(d.payloads.len() > 2);
+        assert!(d
+            .payloads
+            .iter()
+            .all(|p| p.text.as_ref().unwrap().len() <= 400));
+        assert_eq!(d.payloads.last().unwrap().text.as_deref(), Some("ok"));
+
+        let mut prompt = QueuedDelivery::text_chunks("irc", "target:#c", vec![text]);
+        prompt.payloads[0].interactive = Some(serde_json::json!({"type": "buttons"}));
+        assert_eq!(prompt.split_for_channel().payloads.len(), 1);
+    }
 }
@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Mutex, Notify, RwLock};
+
+use super::delivery::{DeliveryQueue, FailureOutcome, QueuedDelivery, RetryPolicy};
+use super::registry::ChannelRegistry;
+use super::sender::{ChannelSender, Interaction, InteractionHandler};
+use crate::config::DeliveryConfig;
+
+/// Durable outbound delivery: persists every reply before sending and retries
+/// failed sends in the background with exponential backoff.
+///
+/// Channel adapters call [`DeliveryService::submit`] instead of posting
+/// directly, so a 5xx from the platform or a restart mid-send never loses an
+/// agent reply. Deliveries that exhaust their retry budget are dead-lettered
+/// and can be inspected, retried or discarded via the `delivery.*` RPCs.
+pub struct DeliveryService {
+    queue: Mutex<DeliveryQueue>,
+    registry: Arc<RwLock<ChannelRegistry>>,
+    policy: RetryPolicy,
+    poll_interval: Duration,
+    /// Delivery IDs currently being sent (guards against worker/inline races).
+    in_flight: Mutex<HashSet<String>>,
+    wake: Notify,
+    /// Handlers for answers to interactive deliveries.
+    interactions: std::sync::RwLock<Vec<Arc<dyn InteractionHandler>>>,
+}
+
+/// Default queue directory: `~/.synapse/delivery`.
+pub fn default_queue_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".synapse")
+        .join("delivery")
+}
+
+impl DeliveryService {
+    /// Create a service from `[delivery]` config, loading any deliveries left
+    /// on disk by a previous run.
+    pub fn new(config: &DeliveryConfig, registry: Arc<RwLock<ChannelRegistry>>) -> Self {
+        let queue_dir = config
+            .queue_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(default_queue_dir);
+        let mut queue = DeliveryQueue::new(queue_dir);
+        if let Err(e) = queue.load_pending() {
+            tracing::warn!(error = %e, "failed to load delivery queue from disk");
+        }
+        let pending = queue.pending_deliveries().len();
+        let dead = queue.dead_letters().len();
+        if pending > 0 || dead > 0 {
+            tracing::info!(pending, dead, "restored outbound delivery queue");
+        }
+
+        Self {
+            queue: Mutex::new(queue),
+            registry,
+            policy: RetryPolicy {
+                max_attempts: config.max_attempts.max(1),
+                base_delay_ms: config.base_delay_secs * 1000,
+                max_delay_ms: config.max_delay_secs * 1000,
+            },
+            poll_interval: Duration::from_secs(config.poll_interval_secs.max(1)),
+            in_flight: Mutex::new(HashSet::new()),
+            wake: Notify::new(),
+            interactions: std::sync::RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Register an outbound sender so queued deliveries for its channel can be sent.
+    pub async fn register_sender(&self, sender: Arc<dyn ChannelSender>) {
+        self.registry.write().await.register(sender);
+        // Deliveries restored from disk may have been waiting for this channel.
+        self.wake.notify_one();
+    }
+
+    /// Register a handler for button presses and text replies answering
+    /// interactive deliveries.
+    pub fn register_interaction_handler(&self, handler: Arc<dyn InteractionHandler>) {
+        self.interactions.write().unwrap().push(handler);
+    }
+
+    /// Offer an interaction to the registered handlers.  Returns the
+    /// acknowledgement of the handler that consumed it, if any.
+    pub async fn handle_interaction(&self, interaction: &Interaction) -> Option<String> {
+        let handlers = self.interactions.read().unwrap().clone();
+        for handler in handlers {
+            if let Some(ack) = handler.handle(interaction).await {
+                return Some(ack);
+            }
+        }
+        None
+    }
+
+    /// Persist a delivery and attempt it immediately.
+    ///
+    /// Long texts are split to the channel's message limit first, one
+    /// payload per message, so a retry resumes at the message that failed.
+    ///
+    /// Returns `Ok(true)` if it was delivered right away, `Ok(false)` if the
+    /// attempt failed and the delivery was left queued for the retry worker.
+    /// Only fails if the delivery could not be persisted.
+    pub async fn submit(&self, delivery: QueuedDelivery) -> crate::error::Result<bool> {
+        let delivery = delivery.split_for_channel();
+        let id = delivery.id.clone();
+        self.queue.lock().await.enqueue(delivery)?;
+        Ok(self.attempt(&id).await)
+    }
+
+    /// Snapshot of pending deliveries.
+    pub async fn pending(&self) -> Vec<QueuedDelivery> {
+        self.queue.lock().await.pending_deliveries().to_vec()
+    }
+
+    /// Snapshot of dead-lettered deliveries.
+    pub async fn dead_letters(&self) -> Vec<QueuedDelivery> {
+        self.queue.lock().await.dead_letters().to_vec()
+    }
+
+    /// Move a dead-lettered delivery back to the pending queue and wake the worker.
+    pub async fn retry(&self, id: &str) -> crate::error::Result<bool> {
+        let requeued = self.queue.lock().await.requeue_dead(id)?;
+        if requeued {
+            self.wake.notify_one();
+        }
+        Ok(requeued)
+    }
+
+    /// Permanently drop a pending or dead-lettered delivery.
+    pub async fn discard(&self, id: &str) -> bool {
+        self.queue.lock().await.discard(id)
+    }
+
+    /// Spawn the background retry worker.
+    pub fn spawn_worker(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(service.poll_interval) => {}
+                    _ = service.wake.notified() => {}
+                }
+                service.run_due().await;
+            }
+        })
+    }
+
+    /// Attempt every delivery whose backoff has elapsed.
+    async fn run_due(&self) {
+        let due = {
+            let queue = self.queue.lock().await;
+            queue.due(now_ms(), &self.policy)
+        };
+        for delivery in due {
+            self.attempt(&delivery.id).await;
+        }
+    }
+
+    /// Send one delivery (resuming after any already-delivered payloads) and
+    /// record the outcome. Returns `true` if the delivery completed.
+    ///
+    /// The delivery is re-read and claimed under the queue lock, so a stale
+    /// snapshot (e.g. from [`run_due`](Self::run_due)) can't send it again
+    /// after another attempt completed or claimed it.
+    async fn attempt(&self, id: &str) -> bool {
+        let delivery = {
+            let queue = self.queue.lock().await;
+            let Some(delivery) = queue.pending_deliveries().iter().find(|d| d.id == id) else {
+                return false;
+            };
+            if !self.in_flight.lock().await.insert(delivery.id.clone()) {
+                return false;
+            }
+            delivery.clone()
+        };
+        let result = self.send(&delivery).await;
+
+        // Drop the claim under the queue lock so it ends with the outcome.
+        let mut queue = self.queue.lock().await;
+        self.in_flight.lock().await.remove(&delivery.id);
+        match result {
+            Ok(()) => {
+                queue.mark_complete(&delivery.id);
+                true
+            }
+            Err(SendFailure::NoSender) => {
+                // The adapter for this channel isn't running (yet); leave the
+                // delivery untouched so it doesn't burn its retry budget.
+                tracing::debug!(
+                    delivery_id = %delivery.id,
+                    channel = %delivery.channel,
+                    "no sender registered, delivery deferred"
+                );
+                false
+            }
+            Err(SendFailure::Failed {
+                delivered,
+                mirror_delivered,
+                error,
+            }) => {
+                queue.mark_progress(&delivery.id, delivered, mirror_delivered);
+                match queue.mark_failed(&delivery.id, &error, &self.policy) {
+                    FailureOutcome::Retry => tracing::warn!(
+                        delivery_id = %delivery.id,
+                        channel = %delivery.channel,
+                        attempt = delivery.retry_count + 1,
+                        error = %error,
+                        "outbound delivery failed, will retry"
+                    ),
+                    FailureOutcome::DeadLettered => tracing::error!(
+                        delivery_id = %delivery.id,
+                        channel = %delivery.channel,
+                        attempts = delivery.retry_count + 1,
+                        error = %error,
+                        "outbound delivery dead-lettered"
+                    ),
+                    FailureOutcome::Dropped => tracing::warn!(
+                        delivery_id = %delivery.id,
+                        channel = %delivery.channel,
+                        error = %error,
+                        "best-effort delivery dropped"
+                    ),
+                }
+                false
+            }
+        }
+    }
+
+    async fn send(&self, delivery: &QueuedDelivery) -> Result<(), SendFailure> {
+        let (primary, mirror) = {
+            let registry = self.registry.read().await;
+            let primary = registry.get(&delivery.channel).cloned();
+            let mirror = delivery
+                .mirror
+                .as_ref()
+                .and_then(|m| registry.get(&m.channel).cloned());
+            (primary, mirror)
+        };
+        let Some(primary) = primary else {
+            return Err(SendFailure::NoSender);
+        };
+
+        let target = delivery.target();
+        let mut delivered = delivery.delivered_payloads;
+        for payload in delivery.payloads.iter().skip(delivered) {
+            if let Some(text) = payload.text.as_deref().filter(|t| !t.is_empty()) {
+                // Senders that can render `interactive` (buttons, cards) do;
+                // the rest send the text.
+                let mut meta = delivery.send_meta();
+                if let Some(ref interactive) = payload.interactive {
+                    meta["interactive"] = interactive.clone();
+                }
+                if let Err(e) = primary.send(&target, text, Some(&meta)).await {
+                    return Err(SendFailure::Failed {
+                        delivered,
+                        mirror_delivered: delivery.mirror_delivered_payloads,
+                        error: e.to_string(),
+                    });
+                }
+            }
+            delivered += 1;
+        }
+
+        // Mirror fan-out: only after the primary target has everything.
+        let mut mirror_delivered = delivery.mirror_delivered_payloads;
+        if let Some(mirror_target) = delivery
+            .mirror_target()
+            .filter(|_| mirror_delivered < delivery.payloads.len())
+        {
+            let Some(mirror) = mirror else {
+                return Err(SendFailure::Failed {
+                    delivered,
+                    mirror_delivered,
+                    error: format!(
+                        "no sender registered for mirror channel '{}'",
+                        mirror_target.channel
+                    ),
+                });
+            };
+            for payload in delivery.payloads.iter().skip(mirror_delivered) {
+                if let Some(text) = payload.text.as_deref().filter(|t| !t.is_empty()) {
+                    if let Err(e) = mirror.send(&mirror_target, text, None).await {
+                        return Err(SendFailure::Failed {
+                            delivered,
+                            mirror_delivered,
+                            error: format!("mirror: {}", e),
+                        });
+                    }
+                }
+                mirror_delivered += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum SendFailure {
+    NoSender,
+    Failed {
+        /// Payloads the primary target has received.
+        delivered: usize,
+        /// Payloads the mirror target has received.
+        mirror_delivered: usize,
+        error: String,
+    },
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
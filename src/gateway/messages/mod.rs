@@ -1,6 +1,7 @@
 pub mod adapter;
 pub mod dedup;
 pub mod delivery;
+pub mod delivery_worker;
 pub mod events;
 pub mod inbound;
 pub mod normalize;
@@ -15,7 +16,8 @@ pub use adapter::{ChannelOutboundAdapter, OutboundContext};
 #[allow(unused_imports)]
 pub use dedup::InboundDeduplicator;
 #[allow(unused_imports)]
-pub use delivery::{DeliveryMirror, DeliveryQueue, QueuedDelivery};
+pub use delivery::{DeliveryMirror, DeliveryQueue, QueuedDelivery, RetryPolicy};
+pub use delivery_worker::DeliveryService;
 pub use events::{MessageReceivedEvent, MessageSentEvent};
 #[allow(unused_imports)]
 pub use inbound::{
@@ -33,4 +35,4 @@ pub use routing::{
     resolve_delivery_target, update_last_route, RouteError, SessionDeliveryState, TurnSource,
 };
 #[allow(unused_imports)]
-pub use sender::{ChannelSender, SendResult};
+pub use sender::{ChannelSender, Interaction, InteractionHandler, SendResult};
@@ -24,3 +24,23 @@ pub trait ChannelSender: Send + Sync {
         meta: Option<&serde_json::Value>,
     ) -> crate::error::Result<SendResult>;
 }
+
+/// A user's answer to an interactive delivery: a button press (`value` is
+/// the button's value) or a text message that may answer a prompt.
+#[derive(Debug, Clone)]
+pub struct Interaction {
+    /// Channel identifier (e.g. "slack").
+    pub channel: String,
+    /// Platform user ID of whoever pressed or replied.
+    pub user_id: Option<String>,
+    pub value: String,
+}
+
+/// Consumes interactions meant for it, e.g. exec approval buttons.
+#[async_trait]
+pub trait InteractionHandler: Send + Sync {
+    /// Handle `interaction` if it is addressed to this handler, returning the
+    /// acknowledgement to show the user.  `None` leaves it to others (text
+    /// messages then go to the agent as usual).
+    async fn handle(&self, interaction: &Interaction) -> Option<String>;
+}
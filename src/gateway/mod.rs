@@ -1,4 +1,5 @@
 // Modules available regardless of feature flags
+pub mod budgets;
 pub mod tunnel;
 pub mod usage;
 
@@ -6,6 +7,8 @@ pub mod usage;
 #[cfg(feature = "web")]
 mod api;
 #[cfg(feature = "web")]
+pub mod api_tokens;
+#[cfg(feature = "web")]
 pub mod auth;
 #[cfg(feature = "web")]
 pub mod canvas;
@@ -26,6 +29,8 @@ mod metrics;
 #[cfg(feature = "web")]
 pub mod nodes;
 #[cfg(feature = "web")]
+pub mod oidc;
+#[cfg(feature = "web")]
 pub mod presence;
 #[cfg(feature = "web")]
 mod request_id;
@@ -40,6 +45,8 @@ mod terminal;
 #[cfg(feature = "web")]
 pub mod trace_aggregator;
 #[cfg(feature = "web")]
+pub mod users;
+#[cfg(feature = "web")]
 pub mod webhooks;
 #[cfg(feature = "web")]
 mod ws;
@@ -72,7 +79,14 @@ use crate::config::SynapseConfig;
 /// Start the web server.
 #[allow(dead_code)]
 pub async fn run_server(config: &SynapseConfig, host: &str, port: u16) -> crate::error::Result<()> {
-    run_server_with_log_buffer(config, host, port, None).await
+    run_server_with_log_buffer(
+        config,
+        host,
+        port,
+        None,
+        crate::leader::LeaderElection::start(config),
+    )
+    .await
 }
 
 #[cfg(feature = "web")]
@@ -82,6 +96,7 @@ pub async fn run_server_with_log_buffer(
     host: &str,
     port: u16,
     log_buffer: Option<synaptic::logging::LogBuffer>,
+    leader: Arc<crate::leader::LeaderElection>,
 ) -> crate::error::Result<()> {
     let app_state = if let Some(buf) = log_buffer {
         state::AppState::with_log_buffer(config, buf).await?
@@ -89,17 +104,50 @@ pub async fn run_server_with_log_buffer(
         state::AppState::new(config).await?
     };
 
+    // Scheduled jobs post their results through the gateway's adapters. The
+    // scheduler always runs here, since jobs can be added at runtime via the
+    // `cron.*` RPCs and the agent's schedule tools.
+    let _scheduler = match crate::scheduler::start_scheduler(
+        config,
+        app_state.agent.model.clone(),
+        Some(app_state.channel.delivery.clone()),
+        Some(app_state.agent.budgets.clone()),
+        leader.clone(),
+    )
+    .await
+    {
+        Ok(s) => Some(s),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to start scheduler");
+            None
+        }
+    };
+
+    for hook in &config.webhooks {
+        if !crate::agent::headless::agent_exists(config, &hook.agent) {
+            tracing::warn!(webhook = %hook.name, agent = %hook.agent, "webhook names an unknown agent");
+        }
+    }
+
     // Build the main API router with auth middleware on protected routes
     let protected_api = api::create_router(app_state.clone())
         .merge(webhooks::routes().with_state(app_state.clone()))
-        .merge(terminal::routes().with_state(app_state.clone()))
+        .layer(middleware::from_fn_with_state(
+            app_state.infra.audit.clone(),
+            audit_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             app_state.clone(),
             auth::require_auth,
         ));
 
-    // Auth routes (not protected) + health endpoint
-    let public_routes = auth::auth_router().with_state(app_state.clone());
+    // Auth routes (not protected) + health endpoint. Webhook triggers check
+    // their HMAC signature (or gateway auth) themselves, and the terminal
+    // WebSocket authenticates itself (header or subprotocol) and requires an admin.
+    let public_routes = auth::auth_router()
+        .merge(webhooks::public_routes())
+        .merge(terminal::routes())
+        .with_state(app_state.clone());
 
     let health_state = app_state.clone();
     let health_route = axum::Router::new().route(
@@ -212,6 +260,29 @@ pub async fn run_server_with_log_buffer(
     // polling) and auto-reconnects on failure with exponential backoff.
     spawn_channel_adapters(config, channel_manager.clone(), &app_state);
 
+    if config.memory.session_prune_days > 0 {
+        let mut maintenance =
+            crate::session::maintenance::SessionMaintenance::default().with_leader(leader.clone());
+        maintenance.prune_after_days = config.memory.session_prune_days as u32;
+        maintenance.spawn(
+            app_state.session.sessions.clone(),
+            std::time::Duration::from_secs(3600),
+        );
+    }
+
+    if config.memory.ltm_enabled
+        && config.memory.ltm_consolidate
+        && config.memory.ltm_consolidate_interval_hours > 0
+    {
+        crate::memory::consolidate::spawn(
+            config.clone(),
+            app_state.agent.model.clone(),
+            app_state.agent.ltm.clone(),
+            leader.clone(),
+            std::time::Duration::from_secs(config.memory.ltm_consolidate_interval_hours * 3600),
+        );
+    }
+
     let health_monitor = channel_health::ChannelHealthMonitor::new(
         channel_manager,
         channel_health::HealthMonitorConfig::default(),
@@ -254,6 +325,7 @@ pub async fn run_server_with_log_buffer(
     let broadcaster = shutdown_broadcaster;
     let event_bus_shutdown = app_state.infra.event_bus.clone();
     let plugin_registry_shutdown = app_state.infra.plugin_registry.clone();
+    let audit_shutdown = app_state.infra.audit.clone();
     axum::serve(listener, app)
         .with_graceful_shutdown(async move {
             let _ = tokio::signal::ctrl_c().await;
@@ -272,6 +344,8 @@ pub async fn run_server_with_log_buffer(
                     service.stop().await;
                 }
             }
+            // Hand the leader lease to a standby instance right away
+            leader.release().await;
             // Broadcast shutdown event to all connected clients
             broadcaster
                 .broadcast("shutdown", serde_json::json!({"reason": "server_shutdown"}))
@@ -280,6 +354,8 @@ pub async fn run_server_with_log_buffer(
             tokio::time::sleep(std::time::Duration::from_secs(2)).await;
         })
         .await?;
+    // Entries still queued for the audit writer
+    let _ = tokio::task::spawn_blocking(move || audit_shutdown.flush()).await;
 
     Ok(())
 }
@@ -379,6 +455,49 @@ async fn request_metrics_middleware(
     response
 }
 
+// ---------------------------------------------------------------------------
+// Audit of mutating API requests
+// ---------------------------------------------------------------------------
+
+/// Record authenticated requests that can change state (anything but GET,
+/// HEAD and OPTIONS) in the audit log, with their response status.
+#[cfg(feature = "web")]
+async fn audit_middleware(
+    axum::extract::State(audit): axum::extract::State<Arc<crate::audit::AuditLog>>,
+    request: axum::http::Request<axum::body::Body>,
+    next: middleware::Next,
+) -> impl IntoResponse {
+    use axum::http::Method;
+
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return next.run(request).await;
+    }
+    let action = format!("{} {}", request.method(), request.uri().path());
+    let actor = if let Some(identity) = request.extensions().get::<auth::Identity>() {
+        format!("user:{}", identity.username)
+    } else if let Some(token) = request.extensions().get::<api_tokens::ApiToken>() {
+        format!("token:{}", token.id)
+    } else {
+        "http".to_string()
+    };
+    let response = next.run(request).await;
+    let status = response.status();
+    let outcome = if status.is_success() {
+        "ok".to_string()
+    } else {
+        format!("error: {}", status)
+    };
+    audit.record(
+        crate::audit::AuditRecord::new("http", action)
+            .with_actor(actor)
+            .with_outcome(outcome),
+    );
+    response
+}
+
 // ---------------------------------------------------------------------------
 // Health endpoint
 // ---------------------------------------------------------------------------
@@ -476,6 +595,7 @@ fn spawn_channel_adapters(
             let mgr = manager.clone();
             let event_bus = app_state.infra.event_bus.clone();
             let plugin_registry = app_state.infra.plugin_registry.clone();
+            let delivery = app_state.channel.delivery.clone();
             let handle = Arc::new(channel_manager::ChannelStatusHandleImpl::new(
                 "lark",
                 &account_id,
@@ -490,6 +610,7 @@ fn spawn_channel_adapters(
                         Some(status_handle.clone()),
                         Some(event_bus.clone()),
                         Some(plugin_registry.clone()),
+                        Some(delivery.clone()),
                     )
                     .await
                     {
@@ -512,28 +633,31 @@ fn spawn_channel_adapters(
     }
 
     #[cfg(feature = "bot-telegram")]
-    spawn_simple_adapter::<crate::config::TelegramBotConfig, _, _>(
-        config,
-        "telegram",
-        &manager,
-        |cfg| async move { crate::channels::adapters::telegram::run(&cfg, None).await },
-    );
+    spawn_simple_adapter::<crate::config::TelegramBotConfig, _, _>(config, "telegram", &manager, {
+        let delivery = app_state.channel.delivery.clone();
+        move |cfg| {
+            let delivery = delivery.clone();
+            async move { crate::channels::adapters::telegram::run(&cfg, None, Some(delivery)).await }
+        }
+    });
 
     #[cfg(feature = "bot-discord")]
-    spawn_simple_adapter::<crate::config::DiscordBotConfig, _, _>(
-        config,
-        "discord",
-        &manager,
-        |cfg| async move { crate::channels::adapters::discord::run(&cfg, None).await },
-    );
+    spawn_simple_adapter::<crate::config::DiscordBotConfig, _, _>(config, "discord", &manager, {
+        let delivery = app_state.channel.delivery.clone();
+        move |cfg| {
+            let delivery = delivery.clone();
+            async move { crate::channels::adapters::discord::run(&cfg, None, Some(delivery)).await }
+        }
+    });
 
     #[cfg(feature = "bot-slack")]
-    spawn_simple_adapter::<crate::config::SlackBotConfig, _, _>(
-        config,
-        "slack",
-        &manager,
-        |cfg| async move { crate::channels::adapters::slack::run(&cfg, None).await },
-    );
+    spawn_simple_adapter::<crate::config::SlackBotConfig, _, _>(config, "slack", &manager, {
+        let delivery = app_state.channel.delivery.clone();
+        move |cfg| {
+            let delivery = delivery.clone();
+            async move { crate::channels::adapters::slack::run(&cfg, None, Some(delivery)).await }
+        }
+    });
 }
 
 #[cfg(feature = "web")]
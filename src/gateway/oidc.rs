@@ -0,0 +1,746 @@
+//! OpenID Connect single sign-on for the dashboard.
+//!
+//! `/api/auth/oidc/login` sends the browser to the provider with a PKCE
+//! challenge, state and nonce.  `/api/auth/oidc/callback` exchanges the code,
+//! checks the ID token against the provider's JWKS (RS256), the issuer,
+//! audience, expiry and nonce, and then issues a normal session token.  The
+//! user's role comes from `role_mappings` over the ID token's claims, and
+//! `allowed_domains` restricts who may log in at all (to verified emails).
+//!
+//! The state is also set in a short-lived cookie, and the callback only
+//! accepts it from the browser that started the login.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::Json;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::auth::Identity;
+use super::state::AppState;
+use super::users::{now_secs, UserRole};
+use crate::config::OidcConfig;
+
+/// How long a started login may take to come back (ms).
+const PENDING_TTL_MS: u64 = 10 * 60 * 1000;
+
+/// Logins waiting for the provider at most; the oldest is dropped beyond.
+const MAX_PENDING_LOGINS: usize = 1000;
+
+/// Cookie binding a login's state to the browser that started it.
+const STATE_COOKIE: &str = "synapse_oidc_state";
+
+/// Clock skew tolerated on `exp` and `iat` (seconds).
+const CLOCK_SKEW_SECS: u64 = 60;
+
+/// DER prefix of a PKCS#1 v1.5 SHA-256 `DigestInfo`.
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// The parts of the discovery document the flow needs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    n: Option<String>,
+    #[serde(default)]
+    e: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+/// A login that was sent to the provider and has not come back yet.
+struct PendingLogin {
+    verifier: String,
+    nonce: String,
+    created_at: u64,
+}
+
+/// A configured provider plus its cached discovery document and keys.
+pub struct OidcProvider {
+    config: OidcConfig,
+    client_secret: Option<String>,
+    /// `(claim, value, role)`, parsed from `role_mappings`.
+    mappings: Vec<(String, String, UserRole)>,
+    default_role: Option<UserRole>,
+    http: reqwest::Client,
+    metadata: Mutex<Option<ProviderMetadata>>,
+    keys: Mutex<Vec<Jwk>>,
+    /// State → pending login.
+    pending: Mutex<HashMap<String, PendingLogin>>,
+}
+
+impl OidcProvider {
+    pub fn new(config: OidcConfig) -> Result<Self, String> {
+        let client_secret = if config.client_secret.is_some() || config.client_secret_env.is_some()
+        {
+            Some(crate::config::resolve_secret(
+                config.client_secret.as_deref(),
+                config.client_secret_env.as_deref(),
+                "OIDC client secret",
+            )?)
+        } else {
+            None
+        };
+        let mappings = config
+            .role_mappings
+            .iter()
+            .map(|m| {
+                let claim = m
+                    .claim
+                    .clone()
+                    .unwrap_or_else(|| config.groups_claim.clone());
+                Ok((claim, m.value.clone(), m.role.parse::<UserRole>()?))
+            })
+            .collect::<Result<_, String>>()?;
+        let default_role = config
+            .default_role
+            .as_deref()
+            .map(str::parse::<UserRole>)
+            .transpose()?;
+        Ok(Self {
+            config,
+            client_secret,
+            mappings,
+            default_role,
+            http: reqwest::Client::new(),
+            metadata: Mutex::new(None),
+            keys: Mutex::new(Vec::new()),
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// The discovery document, fetched once.
+    async fn metadata(&self) -> Result<ProviderMetadata, String> {
+        if let Some(meta) = self
+            .metadata
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+        {
+            return Ok(meta);
+        }
+        let issuer = self.config.issuer.trim_end_matches('/');
+        let url = format!("{}/.well-known/openid-configuration", issuer);
+        let meta: ProviderMetadata = self.get_json(&url).await?;
+        if meta.issuer.trim_end_matches('/') != issuer {
+            return Err(format!(
+                "discovery document is for issuer '{}', expected '{}'",
+                meta.issuer, self.config.issuer
+            ));
+        }
+        *self.metadata.lock().unwrap_or_else(|e| e.into_inner()) = Some(meta.clone());
+        Ok(meta)
+    }
+
+    /// The provider's signing keys; `refresh` re-fetches them, e.g. after a
+    /// key rotation.
+    async fn keys(&self, meta: &ProviderMetadata, refresh: bool) -> Result<Vec<Jwk>, String> {
+        if !refresh {
+            let keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+            if !keys.is_empty() {
+                return Ok(keys.clone());
+            }
+        }
+        let set: JwkSet = self.get_json(&meta.jwks_uri).await?;
+        *self.keys.lock().unwrap_or_else(|e| e.into_inner()) = set.keys.clone();
+        Ok(set.keys)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, String> {
+        let resp = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("GET {}: {}", url, e))?;
+        if !resp.status().is_success() {
+            return Err(format!("GET {}: HTTP {}", url, resp.status()));
+        }
+        resp.json()
+            .await
+            .map_err(|e| format!("GET {}: invalid response: {}", url, e))
+    }
+
+    /// Start a login: remember a PKCE verifier and nonce under a fresh state
+    /// and return the provider URL to send the browser to, and the state.
+    pub async fn begin(&self) -> Result<(String, String), String> {
+        let meta = self.metadata().await?;
+        let state = super::nodes::bootstrap::generate_pairing_token();
+        let verifier = super::nodes::bootstrap::generate_pairing_token();
+        let nonce = super::nodes::bootstrap::generate_pairing_token();
+        let mut url = reqwest::Url::parse(&meta.authorization_endpoint)
+            .map_err(|e| format!("invalid authorization endpoint: {}", e))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &pkce_challenge(&verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        let now = super::presence::now_ms();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, p| now.saturating_sub(p.created_at) < PENDING_TTL_MS);
+        while pending.len() >= MAX_PENDING_LOGINS {
+            let Some(oldest) = pending
+                .iter()
+                .min_by_key(|(_, p)| p.created_at)
+                .map(|(state, _)| state.clone())
+            else {
+                break;
+            };
+            pending.remove(&oldest);
+        }
+        pending.insert(
+            state.clone(),
+            PendingLogin {
+                verifier,
+                nonce,
+                created_at: now,
+            },
+        );
+        Ok((url.into(), state))
+    }
+
+    /// Finish a login: redeem `code` for an ID token, validate it and map
+    /// its claims to a dashboard identity.
+    pub async fn complete(&self, code: &str, state: &str) -> Result<Identity, String> {
+        let login = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(state)
+            .filter(|p| super::presence::now_ms().saturating_sub(p.created_at) < PENDING_TTL_MS)
+            .ok_or("unknown or expired login state")?;
+        let meta = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", login.verifier.as_str()),
+        ];
+        if let Some(secret) = &self.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        let resp = self
+            .http
+            .post(&meta.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| format!("token request failed: {}", e))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("token request failed: HTTP {}: {}", status, body));
+        }
+        let body: Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("invalid token response: {}", e))?;
+        let id_token = body
+            .get("id_token")
+            .and_then(Value::as_str)
+            .ok_or("token response has no id_token")?;
+
+        let claims = self.verify_id_token(&meta, id_token, &login.nonce).await?;
+        self.identity_for(&claims)
+    }
+
+    /// Check an ID token's signature and standard claims; returns its claims.
+    async fn verify_id_token(
+        &self,
+        meta: &ProviderMetadata,
+        token: &str,
+        nonce: &str,
+    ) -> Result<Value, String> {
+        let (signing_input, signature) = token.rsplit_once('.').ok_or("malformed ID token")?;
+        let (header, payload) = signing_input.split_once('.').ok_or("malformed ID token")?;
+        let header = decode_segment(header)?;
+        if header.get("alg").and_then(Value::as_str) != Some("RS256") {
+            return Err("ID token must be signed with RS256".into());
+        }
+        let kid = header.get("kid").and_then(Value::as_str);
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| "malformed ID token signature")?;
+
+        let mut key = find_key(&self.keys(meta, false).await?, kid);
+        if key.is_none() {
+            key = find_key(&self.keys(meta, true).await?, kid);
+        }
+        let key = key.ok_or("ID token signed with an unknown key")?;
+        verify_rs256(&key, signing_input.as_bytes(), &signature)?;
+
+        let claims = decode_segment(payload)?;
+        let now = now_secs();
+        if claims.get("iss").and_then(Value::as_str) != Some(meta.issuer.as_str()) {
+            return Err("ID token issuer mismatch".into());
+        }
+        let audience_ok = match claims.get("aud") {
+            Some(Value::String(aud)) => *aud == self.config.client_id,
+            Some(Value::Array(auds)) => auds
+                .iter()
+                .any(|a| a.as_str() == Some(self.config.client_id.as_str())),
+            _ => false,
+        };
+        if !audience_ok {
+            return Err("ID token audience mismatch".into());
+        }
+        match claims.get("exp").and_then(Value::as_u64) {
+            Some(exp) if exp + CLOCK_SKEW_SECS > now => {}
+            _ => return Err("ID token expired".into()),
+        }
+        if claims
+            .get("iat")
+            .and_then(Value::as_u64)
+            .is_some_and(|iat| iat > now + CLOCK_SKEW_SECS)
+        {
+            return Err("ID token issued in the future".into());
+        }
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            return Err("ID token nonce mismatch".into());
+        }
+        Ok(claims)
+    }
+
+    /// Apply `allowed_domains` and the role mappings to ID token claims.
+    pub fn identity_for(&self, claims: &Value) -> Result<Identity, String> {
+        let email = claims.get("email").and_then(Value::as_str);
+        if !self.config.allowed_domains.is_empty() {
+            let email = email.ok_or("ID token has no email claim")?;
+            if claims.get("email_verified").and_then(Value::as_bool) != Some(true) {
+                return Err(format!("email {} is not verified", email));
+            }
+            let domain = email.rsplit_once('@').map(|(_, d)| d).unwrap_or("");
+            if !self
+                .config
+                .allowed_domains
+                .iter()
+                .any(|d| d.trim_start_matches('@').eq_ignore_ascii_case(domain))
+            {
+                return Err(format!("email domain '{}' is not allowed", domain));
+            }
+        }
+
+        let role = self
+            .mappings
+            .iter()
+            .filter(|(claim, value, _)| claim_matches(claim_at(claims, claim), value))
+            .map(|(_, _, role)| *role)
+            .min_by_key(|role| role_rank(*role))
+            .or(self.default_role)
+            .ok_or("no role mapping matches this user")?;
+
+        let username = email
+            .or_else(|| claims.get("preferred_username").and_then(Value::as_str))
+            .or_else(|| claims.get("sub").and_then(Value::as_str))
+            .ok_or("ID token has no subject")?;
+        Ok(Identity {
+            username: username.to_string(),
+            role,
+        })
+    }
+}
+
+/// `BASE64URL(SHA256(verifier))`, the S256 code challenge.
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn decode_segment(segment: &str) -> Result<Value, String> {
+    URL_SAFE_NO_PAD
+        .decode(segment)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| "malformed ID token".to_string())
+}
+
+/// The RSA key with `kid`, or the only RSA key when the token names none.
+fn find_key(keys: &[Jwk], kid: Option<&str>) -> Option<Jwk> {
+    let mut rsa = keys.iter().filter(|k| k.kty == "RSA");
+    match kid {
+        Some(kid) => rsa.find(|k| k.kid.as_deref() == Some(kid)).cloned(),
+        None => {
+            let first = rsa.next()?;
+            rsa.next().is_none().then(|| first.clone())
+        }
+    }
+}
+
+fn verify_rs256(key: &Jwk, signing_input: &[u8], signature: &[u8]) -> Result<(), String> {
+    let component = |v: &Option<String>| {
+        v.as_deref()
+            .and_then(|v| URL_SAFE_NO_PAD.decode(v).ok())
+            .map(|bytes| BigUint::from_bytes_be(&bytes))
+            .ok_or_else(|| "malformed JWKS key".to_string())
+    };
+    let public = RsaPublicKey::new(component(&key.n)?, component(&key.e)?)
+        .map_err(|e| format!("unusable JWKS key: {}", e))?;
+    let scheme = Pkcs1v15Sign {
+        hash_len: Some(32),
+        prefix: SHA256_DIGEST_INFO.into(),
+    };
+    public
+        .verify(scheme, &Sha256::digest(signing_input), signature)
+        .map_err(|_| "invalid ID token signature".to_string())
+}
+
+/// The claim at a dotted path, e.g. `realm_access.roles`.
+fn claim_at<'a>(claims: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(claims, |v, key| v.get(key))
+}
+
+/// Whether a claim equals `value` or is a list containing it.
+fn claim_matches(claim: Option<&Value>, value: &str) -> bool {
+    match claim {
+        Some(Value::String(s)) => s == value,
+        Some(Value::Array(items)) => items.iter().any(|i| i.as_str() == Some(value)),
+        Some(Value::Bool(b)) => b.to_string() == value,
+        _ => false,
+    }
+}
+
+/// Lower is more privileged.
+fn role_rank(role: UserRole) -> u8 {
+    match role {
+        UserRole::Admin => 0,
+        UserRole::Operator => 1,
+        UserRole::Viewer => 2,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// API handlers
+// ---------------------------------------------------------------------------
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({ "error": message.into() }))).into_response()
+}
+
+/// `Set-Cookie` value for the state cookie; `None` clears it.
+fn state_cookie(oidc: &OidcProvider, login_state: Option<&str>) -> String {
+    let max_age = match login_state {
+        Some(_) => PENDING_TTL_MS / 1000,
+        None => 0,
+    };
+    // `Lax` so the cookie comes along on the provider's redirect back.
+    let secure = if oidc.config.redirect_url.starts_with("https://") {
+        "; Secure"
+    } else {
+        ""
+    };
+    format!(
+        "{}={}; Path=/api/auth/oidc; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        STATE_COOKIE,
+        login_state.unwrap_or(""),
+        max_age,
+        secure
+    )
+}
+
+/// The state cookie sent with a request.
+fn cookie_state(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == STATE_COOKIE)
+        .map(|(_, value)| value)
+}
+
+/// GET /api/auth/oidc/login — redirect to the identity provider.
+pub async fn login_handler(State(state): State<AppState>) -> Response {
+    let Some(oidc) = state.core.auth.as_ref().and_then(|a| a.oidc()) else {
+        return error(StatusCode::NOT_FOUND, "Single sign-on not configured");
+    };
+    match oidc.begin().await {
+        Ok((url, login_state)) => (
+            [(header::SET_COOKIE, state_cookie(oidc, Some(&login_state)))],
+            Redirect::to(&url),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::warn!(error = %e, "OIDC login failed to start");
+            error(StatusCode::BAD_GATEWAY, e)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// GET /api/auth/oidc/callback — finish the login and hand the session token
+/// to the dashboard in the URL fragment.
+pub async fn callback_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    let Some(auth) = state.core.auth.as_ref() else {
+        return error(StatusCode::NOT_FOUND, "Single sign-on not configured");
+    };
+    let Some(oidc) = auth.oidc() else {
+        return error(StatusCode::NOT_FOUND, "Single sign-on not configured");
+    };
+    if let Some(err) = query.error {
+        let detail = query.error_description.unwrap_or_default();
+        return error(
+            StatusCode::UNAUTHORIZED,
+            format!("identity provider returned {}: {}", err, detail),
+        );
+    }
+    let (Some(code), Some(login_state)) = (query.code, query.state) else {
+        return error(StatusCode::BAD_REQUEST, "missing code or state");
+    };
+    let same_browser = cookie_state(&headers).is_some_and(|cookie| {
+        super::auth::constant_time_eq(cookie.as_bytes(), login_state.as_bytes())
+    });
+    if !same_browser {
+        tracing::warn!("OIDC callback without the login's state cookie");
+        return error(
+            StatusCode::UNAUTHORIZED,
+            "login was not started in this browser",
+        );
+    }
+    match oidc.complete(&code, &login_state).await {
+        Ok(identity) => {
+            tracing::info!(user = %identity.username, role = identity.role.as_str(), "OIDC login");
+            (
+                [(header::SET_COOKIE, state_cookie(oidc, None))],
+                Redirect::to(&format!("/#token={}", auth.issue_sso_token(&identity))),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "OIDC login rejected");
+            error(StatusCode::UNAUTHORIZED, e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OidcRoleMapping;
+    use axum::extract::Form;
+    use rsa::traits::PublicKeyParts;
+    use rsa::RsaPrivateKey;
+    use std::sync::Arc;
+
+    /// What the mock provider expects and puts in its next ID token.
+    #[derive(Default)]
+    struct MockIdp {
+        issuer: String,
+        challenge: String,
+        claims: Value,
+    }
+
+    fn sign(key: &RsaPrivateKey, claims: &Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"RS256","typ":"JWT","kid":"k1"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let input = format!("{}.{}", header, payload);
+        let scheme = Pkcs1v15Sign {
+            hash_len: Some(32),
+            prefix: SHA256_DIGEST_INFO.into(),
+        };
+        let sig = key.sign(scheme, &Sha256::digest(input.as_bytes())).unwrap();
+        format!("{}.{}", input, URL_SAFE_NO_PAD.encode(sig))
+    }
+
+    /// Serve discovery, JWKS and a token endpoint that checks PKCE.
+    async fn mock_idp(key: RsaPrivateKey) -> (String, Arc<Mutex<MockIdp>>) {
+        use axum::routing::{get, post};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let idp = Arc::new(Mutex::new(MockIdp {
+            issuer: issuer.clone(),
+            ..Default::default()
+        }));
+        let public = key.to_public_key();
+        let jwks = serde_json::json!({ "keys": [{
+            "kty": "RSA", "kid": "k1", "alg": "RS256",
+            "n": URL_SAFE_NO_PAD.encode(public.n().to_bytes_be()),
+            "e": URL_SAFE_NO_PAD.encode(public.e().to_bytes_be()),
+        }]});
+        let discovery = serde_json::json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{}/authorize", issuer),
+            "token_endpoint": format!("{}/token", issuer),
+            "jwks_uri": format!("{}/jwks", issuer),
+        });
+        let token_idp = idp.clone();
+        let app = axum::Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                get(move || async move { Json(discovery) }),
+            )
+            .route("/jwks", get(move || async move { Json(jwks) }))
+            .route(
+                "/token",
+                post(
+                    move |Form(form): Form<HashMap<String, String>>| async move {
+                        let idp = token_idp.lock().unwrap();
+                        let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+                        if form.get("code").map(String::as_str) != Some("good-code")
+                            || pkce_challenge(&verifier) != idp.challenge
+                        {
+                            return (StatusCode::BAD_REQUEST, "invalid_grant").into_response();
+                        }
+                        Json(serde_json::json!({ "id_token": sign(&key, &idp.claims) }))
+                            .into_response()
+                    },
+                ),
+            );
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (issuer, idp)
+    }
+
+    fn mapping(value: &str, role: &str) -> OidcRoleMapping {
+        OidcRoleMapping {
+            claim: None,
+            value: value.into(),
+            role: role.into(),
+        }
+    }
+
+    /// Start a login and prime the mock with its challenge and an ID token
+    /// carrying `claims` plus the login's nonce.  Returns the state.
+    async fn start(provider: &OidcProvider, idp: &Mutex<MockIdp>, mut claims: Value) -> String {
+        let url = reqwest::Url::parse(&provider.begin().await.unwrap().0).unwrap();
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.into_owned())
+                .unwrap()
+        };
+        assert_eq!(param("code_challenge_method"), "S256");
+        assert_eq!(param("client_id"), "synapse");
+        let mut idp = idp.lock().unwrap();
+        let now = now_secs();
+        claims["iss"] = idp.issuer.clone().into();
+        claims["aud"] = "synapse".into();
+        claims["iat"] = now.into();
+        claims["exp"] = (now + 300).into();
+        claims["nonce"] = param("nonce").into();
+        idp.challenge = param("code_challenge");
+        idp.claims = claims;
+        param("state")
+    }
+
+    #[tokio::test]
+    async fn logs_in_against_a_mock_provider() {
+        use password_hash::rand_core::OsRng;
+        let key = RsaPrivateKey::new(&mut OsRng, 1024).unwrap();
+        let (issuer, idp) = mock_idp(key).await;
+        let provider = OidcProvider::new(OidcConfig {
+            issuer,
+            client_id: "synapse".into(),
+            client_secret: None,
+            client_secret_env: None,
+            redirect_url: "http://gateway/api/auth/oidc/callback".into(),
+            scopes: vec!["openid".into(), "email".into()],
+            groups_claim: "groups".into(),
+            role_mappings: vec![mapping("staff", "viewer"), mapping("admins", "admin")],
+            default_role: None,
+            allowed_domains: vec!["example.com".into()],
+        })
+        .unwrap();
+
+        let alice = serde_json::json!({
+            "sub": "1", "email": "alice@example.com", "email_verified": true,
+            "groups": ["staff", "admins"],
+        });
+        let state = start(&provider, &idp, alice.clone()).await;
+        assert!(provider.complete("bad-code", &state).await.is_err());
+        // The failed attempt consumed the state.
+        assert!(provider.complete("good-code", &state).await.is_err());
+        let state = start(&provider, &idp, alice).await;
+        assert_eq!(
+            provider.complete("good-code", &state).await.unwrap(),
+            Identity {
+                username: "alice@example.com".into(),
+                role: UserRole::Admin,
+            }
+        );
+
+        let mallory = serde_json::json!({
+            "sub": "2", "email": "mallory@evil.com", "email_verified": true, "groups": ["admins"],
+        });
+        let state = start(&provider, &idp, mallory).await;
+        let err = provider.complete("good-code", &state).await.unwrap_err();
+        assert!(err.contains("not allowed"), "{}", err);
+
+        let nobody = serde_json::json!({
+            "sub": "3", "email": "bob@example.com", "email_verified": true,
+        });
+        let state = start(&provider, &idp, nobody).await;
+        assert!(provider.complete("good-code", &state).await.is_err());
+
+        // Without `email_verified`, the email can't vouch for the domain.
+        let unverified = serde_json::json!({
+            "sub": "4", "email": "carol@example.com", "groups": ["admins"],
+        });
+        let state = start(&provider, &idp, unverified).await;
+        let err = provider.complete("good-code", &state).await.unwrap_err();
+        assert!(err.contains("not verified"), "{}", err);
+
+        let replayed = serde_json::json!({ "sub": "1", "email": "alice@example.com" });
+        let state = start(&provider, &idp, replayed).await;
+        idp.lock().unwrap().claims["nonce"] = "other".into();
+        let err = provider.complete("good-code", &state).await.unwrap_err();
+        assert!(err.contains("nonce"), "{}", err);
+
+        // Abandoned logins don't pile up.
+        for _ in 0..MAX_PENDING_LOGINS + 5 {
+            provider.begin().await.unwrap();
+        }
+        assert_eq!(provider.pending.lock().unwrap().len(), MAX_PENDING_LOGINS);
+    }
+
+    #[test]
+    fn reads_the_state_cookie() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(cookie_state(&headers), None);
+        headers.insert(
+            header::COOKIE,
+            "theme=dark; synapse_oidc_state=abc123".parse().unwrap(),
+        );
+        assert_eq!(cookie_state(&headers), Some("abc123"));
+    }
+}
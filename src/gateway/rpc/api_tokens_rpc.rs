@@ -0,0 +1,59 @@
+//! API token management RPC methods (admin only).
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use super::router::RpcContext;
+use super::types::RpcError;
+use crate::gateway::api_tokens::ApiTokenStore;
+
+/// Run `f` on the gateway's token store, or on the file when auth is off.
+fn with_store<T>(ctx: &RpcContext, f: impl FnOnce(&mut ApiTokenStore) -> T) -> T {
+    match ctx.state.core.auth {
+        Some(ref auth) => f(&mut auth.api_tokens()),
+        None => f(&mut ApiTokenStore::load(ApiTokenStore::default_path())),
+    }
+}
+
+pub async fn handle_list(ctx: Arc<RpcContext>, _params: Value) -> Result<Value, RpcError> {
+    let tokens: Vec<Value> = with_store(&ctx, |store| {
+        store.list().iter().map(|t| t.summary()).collect()
+    });
+    Ok(json!({ "tokens": tokens }))
+}
+
+/// Create a token.  Params: `name`, `scopes`, optional `expires_in_secs`
+/// and `rate_limit` (requests per minute).  The secret is only returned here.
+pub async fn handle_create(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let name = params
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("missing name"))?;
+    let scopes: Vec<String> = params
+        .get("scopes")
+        .and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|s| s.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+    let ttl = params.get("expires_in_secs").and_then(|v| v.as_u64());
+    let rate_limit = params
+        .get("rate_limit")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as u32);
+    let (token, secret) = with_store(&ctx, |store| store.create(name, scopes, ttl, rate_limit))
+        .map_err(RpcError::invalid_request)?;
+    Ok(json!({ "token": token.summary(), "secret": secret }))
+}
+
+pub async fn handle_revoke(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let id = params
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("missing id"))?;
+    let token = with_store(&ctx, |store| store.revoke(id)).map_err(RpcError::not_found)?;
+    Ok(json!({ "ok": true, "token": token.summary() }))
+}
@@ -0,0 +1,28 @@
+//! Audit log RPC methods (admin only).
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use super::router::RpcContext;
+use super::types::RpcError;
+
+/// Query the audit log; params are an [`crate::audit::AuditQuery`].
+pub async fn handle_query(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let query: crate::audit::AuditQuery = serde_json::from_value(params)
+        .map_err(|e| RpcError::invalid_request(format!("invalid query: {e}")))?;
+    let entries = ctx.state.infra.audit.query(&query);
+    Ok(json!({ "entries": entries }))
+}
+
+/// Check the hash chain of the audit log.
+pub async fn handle_verify(ctx: Arc<RpcContext>, _params: Value) -> Result<Value, RpcError> {
+    let audit = ctx.state.infra.audit.clone();
+    let report = tokio::task::spawn_blocking(move || audit.verify())
+        .await
+        .map_err(|e| RpcError::internal(e.to_string()))?;
+    Ok(json!({
+        "path": ctx.state.infra.audit.path().map(|p| p.display().to_string()),
+        "report": report,
+    }))
+}
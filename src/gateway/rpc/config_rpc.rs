@@ -1,8 +1,10 @@
 //! RPC handlers for configuration management.
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 
 use super::router::RpcContext;
 use super::types::RpcError;
@@ -294,6 +296,77 @@ pub async fn handle_schema_lookup(_ctx: Arc<RpcContext>, params: Value) -> Resul
     Ok(json!({ "path": path, "schema": current }))
 }
 
+// ---------------------------------------------------------------------------
+// Audit
+// ---------------------------------------------------------------------------
+
+/// Audit detail for a config RPC.  The raw TOML would put every credential
+/// in the log, so `content` is replaced by its hash and the key paths it
+/// changes, and a `patch` by its keys.
+pub(super) fn audit_detail(params: &Value) -> Value {
+    let mut detail = params.clone();
+    let Some(map) = detail.as_object_mut() else {
+        return detail;
+    };
+    if let Some(content) = map.remove("content") {
+        let content = content.as_str().unwrap_or_default();
+        let current = std::fs::read_to_string(config_file_path()).unwrap_or_default();
+        map.insert(
+            "content_sha256".into(),
+            json!(format!("{:x}", Sha256::digest(content.as_bytes()))),
+        );
+        map.insert(
+            "changed_keys".into(),
+            json!(changed_keys(&current, content)),
+        );
+    }
+    if let Some(patch) = map.remove("patch") {
+        let keys: Vec<&String> = patch
+            .as_object()
+            .map(|p| p.keys().collect())
+            .unwrap_or_default();
+        map.insert("patch_keys".into(), json!(keys));
+    }
+    detail
+}
+
+/// Dotted key paths whose values differ between two TOML documents.
+fn changed_keys(old: &str, new: &str) -> Vec<String> {
+    fn flatten(prefix: &str, value: toml::Value, out: &mut BTreeMap<String, toml::Value>) {
+        match value {
+            toml::Value::Table(table) => {
+                for (k, v) in table {
+                    let path = if prefix.is_empty() {
+                        k
+                    } else {
+                        format!("{}.{}", prefix, k)
+                    };
+                    flatten(&path, v, out);
+                }
+            }
+            leaf => {
+                out.insert(prefix.to_string(), leaf);
+            }
+        }
+    }
+    let parse = |text: &str| {
+        let mut out = BTreeMap::new();
+        if let Ok(value) = toml::from_str::<toml::Value>(text) {
+            flatten("", value, &mut out);
+        }
+        out
+    };
+    let (old, new) = (parse(old), parse(new));
+    let mut keys: Vec<String> = new
+        .iter()
+        .filter(|(k, v)| old.get(*k) != Some(*v))
+        .map(|(k, _)| k.clone())
+        .chain(old.keys().filter(|k| !new.contains_key(*k)).cloned())
+        .collect();
+    keys.sort();
+    keys
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -358,3 +431,25 @@ fn json_to_toml(v: &serde_json::Value) -> Result<toml::Value, RpcError> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_detail_keeps_config_values_out() {
+        let old = "[model]\nname = \"a\"\napi_key = \"sk-old\"\n[memory]\nenabled = true\n";
+        let new = "[model]\nname = \"a\"\napi_key = \"sk-new\"\n[serve]\nport = 1\n";
+        assert_eq!(
+            changed_keys(old, new),
+            vec!["memory.enabled", "model.api_key", "serve.port"]
+        );
+
+        let detail = audit_detail(&json!({ "content": new, "session_key": "s" }));
+        assert!(!detail.to_string().contains("sk-new"));
+        assert!(detail.get("content").is_none());
+        assert_eq!(detail["session_key"], "s");
+        let detail = audit_detail(&json!({ "patch": { "lark.app_secret": "x" } }));
+        assert_eq!(detail, json!({ "patch_keys": ["lark.app_secret"] }));
+    }
+}
@@ -0,0 +1,86 @@
+//! RPC handlers for the outbound delivery queue.
+//!
+//! Methods: delivery.list, delivery.dead, delivery.retry, delivery.discard
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use super::router::RpcContext;
+use super::types::RpcError;
+
+// ---------------------------------------------------------------------------
+// delivery.list — pending deliveries (awaiting first send or retry)
+// ---------------------------------------------------------------------------
+
+pub async fn handle_list(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let channel = params.get("channel").and_then(|v| v.as_str());
+    let pending: Vec<_> = ctx
+        .state
+        .channel
+        .delivery
+        .pending()
+        .await
+        .into_iter()
+        .filter(|d| channel.is_none_or(|c| d.channel == c))
+        .collect();
+    Ok(json!({ "count": pending.len(), "deliveries": pending }))
+}
+
+// ---------------------------------------------------------------------------
+// delivery.dead — dead-lettered deliveries
+// ---------------------------------------------------------------------------
+
+pub async fn handle_dead(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let channel = params.get("channel").and_then(|v| v.as_str());
+    let dead: Vec<_> = ctx
+        .state
+        .channel
+        .delivery
+        .dead_letters()
+        .await
+        .into_iter()
+        .filter(|d| channel.is_none_or(|c| d.channel == c))
+        .collect();
+    Ok(json!({ "count": dead.len(), "deliveries": dead }))
+}
+
+// ---------------------------------------------------------------------------
+// delivery.retry — move a dead-lettered delivery back to the queue
+// ---------------------------------------------------------------------------
+
+pub async fn handle_retry(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let id = params
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("missing 'id'"))?;
+    let requeued = ctx
+        .state
+        .channel
+        .delivery
+        .retry(id)
+        .await
+        .map_err(|e| RpcError::internal(e.to_string()))?;
+    if !requeued {
+        return Err(RpcError::not_found(format!(
+            "dead-lettered delivery '{}' not found",
+            id
+        )));
+    }
+    Ok(json!({ "ok": true, "id": id }))
+}
+
+// ---------------------------------------------------------------------------
+// delivery.discard — drop a pending or dead-lettered delivery
+// ---------------------------------------------------------------------------
+
+pub async fn handle_discard(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let id = params
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("missing 'id'"))?;
+    if !ctx.state.channel.delivery.discard(id).await {
+        return Err(RpcError::not_found(format!("delivery '{}' not found", id)));
+    }
+    Ok(json!({ "ok": true, "id": id }))
+}
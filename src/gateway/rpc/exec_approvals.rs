@@ -6,7 +6,10 @@ use serde_json::{json, Value};
 
 use super::router::RpcContext;
 use super::types::RpcError;
-use crate::gateway::exec_approvals::manager::{ApprovalDecision, ApprovalRequestPayload};
+use crate::gateway::exec_approvals::manager::{
+    ApprovalDecision, ApprovalOrigin, ApprovalRequestPayload,
+};
+use crate::gateway::exec_approvals::shell;
 use crate::gateway::presence::now_ms;
 
 pub async fn handle_approval_request(
@@ -27,26 +30,92 @@ pub async fn handle_approval_request(
         .get("node_id")
         .and_then(|v| v.as_str())
         .map(String::from);
+    // Conversation the command was triggered from; the prompt goes there.
+    let origin: Option<ApprovalOrigin> = match params.get("origin") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(
+            serde_json::from_value(v.clone())
+                .map_err(|e| RpcError::invalid_request(format!("invalid origin: {e}")))?,
+        ),
+    };
+
+    // Session the command runs in; "allow for session" decisions are scoped to it.
+    let session_key = params
+        .get("session_key")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let agent_id = params
+        .get("agent_id")
+        .and_then(|v| v.as_str())
+        .map(String::from);
 
     let request_id = uuid::Uuid::new_v4().to_string();
+    let payload = ApprovalRequestPayload {
+        request_id: request_id.clone(),
+        command,
+        args,
+        cwd,
+        node_id,
+        session_key,
+        agent_id,
+        created_at: now_ms(),
+        code: crate::channels::dm::generate_pairing_code(),
+        origin,
+    };
 
-    // Check policy first
+    // Check policy first, against the arguments as well as the program.
+    let command_line = payload.command_line();
     let config = ctx.state.channel.exec_approvals_config.read().await;
-    let policy_result =
-        crate::gateway::exec_approvals::policy::evaluate(&config, &command, node_id.as_deref());
+    let explanation = crate::gateway::exec_approvals::policy::explain(
+        &config,
+        &command_line,
+        payload.node_id.as_deref(),
+    );
     drop(config);
 
-    match policy_result {
+    match explanation.result {
         crate::gateway::exec_approvals::policy::PolicyResult::Allow => {
-            return Ok(json!({"decision": "allow", "request_id": request_id}));
+            ctx.state
+                .channel
+                .exec_approval_manager
+                .read()
+                .await
+                .record_decision(
+                    &payload,
+                    ApprovalDecision::Allow,
+                    "policy",
+                    &explanation.reason,
+                );
+            return Ok(
+                json!({"decision": "allow", "request_id": request_id, "reason": explanation.reason}),
+            );
         }
         crate::gateway::exec_approvals::policy::PolicyResult::Deny => {
-            return Ok(json!({"decision": "deny", "request_id": request_id}));
+            ctx.state
+                .channel
+                .exec_approval_manager
+                .read()
+                .await
+                .record_decision(
+                    &payload,
+                    ApprovalDecision::Deny,
+                    "policy",
+                    &explanation.reason,
+                );
+            return Ok(
+                json!({"decision": "deny", "request_id": request_id, "reason": explanation.reason}),
+            );
         }
         crate::gateway::exec_approvals::policy::PolicyResult::Ask => {
             // Check session allows
             let mgr = ctx.state.channel.exec_approval_manager.read().await;
-            if mgr.is_session_allowed(&command) {
+            if mgr.is_session_allowed(&command_line, &payload.scope()) {
+                mgr.record_decision(
+                    &payload,
+                    ApprovalDecision::Allow,
+                    "session_allow",
+                    "allowed for this session",
+                );
                 return Ok(
                     json!({"decision": "allow", "request_id": request_id, "source": "session"}),
                 );
@@ -55,14 +124,7 @@ pub async fn handle_approval_request(
         }
     }
 
-    let payload = ApprovalRequestPayload {
-        request_id: request_id.clone(),
-        command: command.clone(),
-        args,
-        cwd,
-        node_id,
-        created_at: now_ms(),
-    };
+    let prompt = crate::gateway::exec_approvals::chat::prompt(&payload, &explanation.reason);
 
     let _rx = ctx
         .state
@@ -72,18 +134,32 @@ pub async fn handle_approval_request(
         .await
         .create(payload);
 
+    // Ask in the originating chat as well, so the request can be decided
+    // without the dashboard.
+    let delivered = match prompt {
+        Some(prompt) => match ctx.state.channel.delivery.submit(prompt).await {
+            Ok(delivered) => delivered,
+            Err(e) => {
+                tracing::warn!(request_id = %request_id, error = %e, "failed to deliver exec approval prompt");
+                false
+            }
+        },
+        None => false,
+    };
+
     // Notify operators
     ctx.broadcaster
         .broadcast(
             "exec.approval.pending",
             json!({
                 "request_id": request_id,
-                "command": command,
+                "command": command_line,
+                "reason": explanation.reason,
             }),
         )
         .await;
 
-    Ok(json!({"decision": "pending", "request_id": request_id}))
+    Ok(json!({"decision": "pending", "request_id": request_id, "delivered": delivered}))
 }
 
 pub async fn handle_approval_resolve(
@@ -113,7 +189,7 @@ pub async fn handle_approval_resolve(
         .exec_approval_manager
         .write()
         .await
-        .resolve(request_id, decision);
+        .resolve(request_id, decision, &ctx.actor());
     if resolved {
         Ok(json!({"ok": true}))
     } else {
@@ -180,6 +256,8 @@ pub async fn handle_approvals_get(ctx: Arc<RpcContext>, _params: Value) -> Resul
         "mode": config.mode,
         "ask": config.ask,
         "allowlist": config.allowlist,
+        "denylist": config.denylist,
+        "approvers": config.approvers,
         "config_hash": config.config_hash,
         "pending": pending,
     }))
@@ -200,10 +278,23 @@ pub async fn handle_approvals_set(ctx: Arc<RpcContext>, params: Value) -> Result
     let new_allowlist = params
         .get("allowlist")
         .and_then(|v| serde_json::from_value(v.clone()).ok());
+    let new_denylist = params
+        .get("denylist")
+        .and_then(|v| serde_json::from_value(v.clone()).ok());
+    let new_approvers = params
+        .get("approvers")
+        .and_then(|v| serde_json::from_value(v.clone()).ok());
 
     let mut config = ctx.state.channel.exec_approvals_config.write().await;
     config
-        .cas_update(expected_hash, new_mode, new_ask, new_allowlist)
+        .cas_update(
+            expected_hash,
+            new_mode,
+            new_ask,
+            new_allowlist,
+            new_denylist,
+            new_approvers,
+        )
         .map_err(RpcError::invalid_request)?;
 
     Ok(json!({
@@ -212,6 +303,30 @@ pub async fn handle_approvals_set(ctx: Arc<RpcContext>, params: Value) -> Result
     }))
 }
 
+/// Dry-run a command against the policy and report which rule matched each
+/// part of it.
+pub async fn handle_approvals_explain(
+    ctx: Arc<RpcContext>,
+    params: Value,
+) -> Result<Value, RpcError> {
+    let command = params
+        .get("command")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("missing command"))?;
+    let args: Vec<String> = params
+        .get("args")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let line = std::iter::once(command.to_string())
+        .chain(args.iter().map(|a| shell::quote(a)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let node_id = params.get("node_id").and_then(|v| v.as_str());
+    let config = ctx.state.channel.exec_approvals_config.read().await;
+    let explanation = crate::gateway::exec_approvals::policy::explain(&config, &line, node_id);
+    Ok(serde_json::to_value(explanation).unwrap_or(Value::Null))
+}
+
 pub async fn handle_node_approvals_get(
     ctx: Arc<RpcContext>,
     params: Value,
@@ -245,3 +360,53 @@ pub async fn handle_node_approvals_set(
 
     Ok(json!({"ok": true}))
 }
+
+/// List unexpired "allow for session" decisions, optionally of one session.
+pub async fn handle_allows_list(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let session_key = params.get("session_key").and_then(|v| v.as_str());
+    let allows = ctx
+        .state
+        .channel
+        .exec_approval_manager
+        .read()
+        .await
+        .session_allows(session_key);
+    Ok(json!({ "allows": allows }))
+}
+
+/// Revoke a session allow by `id`, or every allow of `session_key`.
+pub async fn handle_allows_revoke(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let actor = ctx.actor();
+    let mut mgr = ctx.state.channel.exec_approval_manager.write().await;
+    if let Some(id) = params.get("id").and_then(|v| v.as_str()) {
+        return match mgr.revoke_allow(id, &actor) {
+            Some(_) => Ok(json!({"ok": true, "revoked": 1})),
+            None => Err(RpcError::not_found("session allow not found")),
+        };
+    }
+    let session_key = params
+        .get("session_key")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("missing id or session_key"))?;
+    let revoked = mgr.revoke_session(session_key, &actor);
+    Ok(json!({"ok": true, "revoked": revoked.len()}))
+}
+
+/// Read the approval audit log (filters: request_id, session_key, since, limit).
+pub async fn handle_approvals_audit(
+    ctx: Arc<RpcContext>,
+    params: Value,
+) -> Result<Value, RpcError> {
+    let query: crate::gateway::exec_approvals::audit::AuditQuery =
+        serde_json::from_value(params)
+            .map_err(|e| RpcError::invalid_request(format!("invalid query: {e}")))?;
+    let entries = ctx
+        .state
+        .channel
+        .exec_approval_manager
+        .read()
+        .await
+        .audit_log()
+        .read(&query);
+    Ok(json!({ "entries": entries }))
+}
@@ -6,6 +6,9 @@ use serde_json::{json, Value};
 
 use super::router::RpcContext;
 use super::types::RpcError;
+use crate::config::MemoryConfig;
+use crate::memory::profile::ProfileStore;
+use crate::memory::{transfer, LongTermMemory};
 
 // ---------------------------------------------------------------------------
 // memory.search
@@ -43,3 +46,160 @@ pub async fn handle_add_resource(ctx: Arc<RpcContext>, params: Value) -> Result<
 
     Ok(json!({"ok": true}))
 }
+
+// ---------------------------------------------------------------------------
+// memory.profile.*
+// ---------------------------------------------------------------------------
+
+fn profiles(ctx: &RpcContext) -> ProfileStore {
+    ProfileStore::open(&ctx.state.core.config)
+}
+
+fn user_id_param(params: &Value) -> Result<&str, RpcError> {
+    params["user_id"]
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| RpcError::invalid_request("missing 'user_id'"))
+}
+
+pub async fn handle_profile_list(ctx: Arc<RpcContext>, _params: Value) -> Result<Value, RpcError> {
+    let list = profiles(&ctx)
+        .list()
+        .await
+        .map_err(|e| RpcError::internal(format!("failed to list profiles: {}", e)))?;
+    Ok(json!({ "profiles": list }))
+}
+
+pub async fn handle_profile_get(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let user_id = user_id_param(&params)?;
+    let profile = profiles(&ctx)
+        .get(user_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("failed to read profile: {}", e)))?
+        .ok_or_else(|| RpcError::not_found(format!("no profile for '{}'", user_id)))?;
+    Ok(json!(profile))
+}
+
+pub async fn handle_profile_set(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let user_id = user_id_param(&params)?;
+    let content = params["content"]
+        .as_str()
+        .ok_or_else(|| RpcError::invalid_request("missing 'content'"))?;
+    let profile = profiles(&ctx)
+        .edit(user_id, content)
+        .await
+        .map_err(|e| RpcError::internal(format!("failed to save profile: {}", e)))?;
+    Ok(json!(profile))
+}
+
+pub async fn handle_profile_delete(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let user_id = user_id_param(&params)?;
+    let deleted = profiles(&ctx)
+        .delete(user_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("failed to delete profile: {}", e)))?;
+    Ok(json!({ "deleted": deleted }))
+}
+
+// ---------------------------------------------------------------------------
+// memory.export / memory.import / memory.migrate
+// ---------------------------------------------------------------------------
+
+/// Long-term memory directory of the `agent` param ("default" if absent).
+/// Only configured agents are accepted.
+fn agent_memory_dir(ctx: &RpcContext, params: &Value) -> Result<std::path::PathBuf, RpcError> {
+    let config = &ctx.state.core.config;
+    let agent = params["agent"].as_str().unwrap_or("default");
+    let known = agent == "default"
+        || config
+            .agents
+            .as_ref()
+            .is_some_and(|a| a.list.iter().any(|d| d.id == agent));
+    if !known {
+        return Err(RpcError::not_found(format!("unknown agent '{}'", agent)));
+    }
+    Ok(transfer::memory_dir(config, agent))
+}
+
+/// The memory in `dir` with `config`'s backend.  That is the gateway's live
+/// instance when it has one for it, so imports show up in recall right away;
+/// otherwise the store is loaded fresh.
+async fn open_ltm(
+    ctx: &RpcContext,
+    dir: std::path::PathBuf,
+    config: MemoryConfig,
+) -> Result<Arc<LongTermMemory>, RpcError> {
+    if let Some(ref shared) = ctx.state.agent.ltm {
+        if shared.base_dir() == dir && transfer::same_store(&config, &ctx.state.core.config.memory)
+        {
+            return Ok(shared.clone());
+        }
+    }
+    let ltm = LongTermMemory::new(dir, config);
+    ltm.load()
+        .await
+        .map_err(|e| RpcError::internal(format!("failed to load memories: {}", e)))?;
+    Ok(Arc::new(ltm))
+}
+
+pub async fn handle_export(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let dir = agent_memory_dir(&ctx, &params)?;
+    let ltm = open_ltm(&ctx, dir, ctx.state.core.config.memory.clone()).await?;
+    let records = ltm.export().await;
+    if params["format"].as_str() == Some("jsonl") {
+        return Ok(json!({ "jsonl": transfer::to_jsonl(&records), "count": records.len() }));
+    }
+    Ok(json!({ "records": records, "count": records.len() }))
+}
+
+pub async fn handle_import(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let records = if let Some(jsonl) = params["jsonl"].as_str() {
+        transfer::from_jsonl(jsonl).map_err(RpcError::invalid_request)?
+    } else if params["records"].is_array() {
+        serde_json::from_value(params["records"].clone())
+            .map_err(|e| RpcError::invalid_request(format!("invalid 'records': {}", e)))?
+    } else {
+        return Err(RpcError::invalid_request("missing 'jsonl' or 'records'"));
+    };
+    let dir = agent_memory_dir(&ctx, &params)?;
+    let ltm = open_ltm(&ctx, dir, ctx.state.core.config.memory.clone()).await?;
+    let report = ltm
+        .import(records)
+        .await
+        .map_err(|e| RpcError::internal(format!("import failed: {}", e)))?;
+    Ok(json!(report))
+}
+
+pub async fn handle_migrate(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let memory = &ctx.state.core.config.memory;
+    let to = params["to"]
+        .as_str()
+        .ok_or_else(|| RpcError::invalid_request("missing 'to'"))?;
+    let to_config = transfer::with_backend(memory, to).map_err(RpcError::invalid_request)?;
+    let from_config = match params["from"].as_str() {
+        Some(from) => transfer::with_backend(memory, from).map_err(RpcError::invalid_request)?,
+        None => memory.clone(),
+    };
+    if transfer::same_store(&from_config, &to_config) {
+        return Err(RpcError::invalid_request(format!(
+            "source and target are the same store ({})",
+            to
+        )));
+    }
+    // Both backends live in the agent's memory directory; the target is
+    // written in place next to the source.
+    let dir = agent_memory_dir(&ctx, &params)?;
+    let into_running = transfer::same_store(&to_config, memory);
+    let source = open_ltm(&ctx, dir.clone(), from_config).await?;
+    let report = source
+        .migrate_in_place(to_config)
+        .await
+        .map_err(|e| RpcError::internal(format!("migration failed: {}", e)))?;
+    // The gateway's own store just received the records; refresh its index.
+    if let Some(ref shared) = ctx.state.agent.ltm {
+        if into_running && shared.base_dir() == dir {
+            shared.load().await.ok();
+        }
+    }
+    Ok(json!(report))
+}
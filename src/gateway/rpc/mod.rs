@@ -5,12 +5,15 @@
 
 mod agent_files;
 mod agents;
+mod api_tokens_rpc;
+mod audit_rpc;
 mod bindings_rpc;
 mod broadcasts_rpc;
 mod channels;
 mod chat;
 mod config_rpc;
 mod debug_rpc;
+mod delivery_rpc;
 mod devices;
 mod dm_pairing;
 mod events;
@@ -270,6 +273,32 @@ pub fn register_all(router: &mut RpcRouter) {
         "usage.records",
         Box::new(|ctx, params| Box::pin(usage::handle_records(ctx, params))),
     );
+    router.register(
+        "usage.budgets.list",
+        Box::new(|ctx, params| Box::pin(usage::handle_budgets_list(ctx, params))),
+    );
+    router.register(
+        "usage.budgets.status",
+        Box::new(|ctx, params| Box::pin(usage::handle_budgets_status(ctx, params))),
+    );
+
+    // Outbound delivery queue
+    router.register(
+        "delivery.list",
+        Box::new(|ctx, params| Box::pin(delivery_rpc::handle_list(ctx, params))),
+    );
+    router.register(
+        "delivery.dead",
+        Box::new(|ctx, params| Box::pin(delivery_rpc::handle_dead(ctx, params))),
+    );
+    router.register(
+        "delivery.retry",
+        Box::new(|ctx, params| Box::pin(delivery_rpc::handle_retry(ctx, params))),
+    );
+    router.register(
+        "delivery.discard",
+        Box::new(|ctx, params| Box::pin(delivery_rpc::handle_discard(ctx, params))),
+    );
 
     // Logs
     router.register(
@@ -568,6 +597,22 @@ pub fn register_all(router: &mut RpcRouter) {
         "exec.approvals.set",
         Box::new(|ctx, params| Box::pin(exec_approvals::handle_approvals_set(ctx, params))),
     );
+    router.register(
+        "exec.approvals.explain",
+        Box::new(|ctx, params| Box::pin(exec_approvals::handle_approvals_explain(ctx, params))),
+    );
+    router.register(
+        "exec.approvals.allows.list",
+        Box::new(|ctx, params| Box::pin(exec_approvals::handle_allows_list(ctx, params))),
+    );
+    router.register(
+        "exec.approvals.allows.revoke",
+        Box::new(|ctx, params| Box::pin(exec_approvals::handle_allows_revoke(ctx, params))),
+    );
+    router.register(
+        "exec.approvals.audit",
+        Box::new(|ctx, params| Box::pin(exec_approvals::handle_approvals_audit(ctx, params))),
+    );
     router.register(
         "exec.approvals.node.get",
         Box::new(|ctx, params| Box::pin(exec_approvals::handle_node_approvals_get(ctx, params))),
@@ -613,6 +658,30 @@ pub fn register_all(router: &mut RpcRouter) {
         Box::new(|ctx, params| Box::pin(heartbeat_rpc::handle_set_heartbeats(ctx, params))),
     );
 
+    // API tokens
+    router.register(
+        "auth.tokens.list",
+        Box::new(|ctx, params| Box::pin(api_tokens_rpc::handle_list(ctx, params))),
+    );
+    router.register(
+        "auth.tokens.create",
+        Box::new(|ctx, params| Box::pin(api_tokens_rpc::handle_create(ctx, params))),
+    );
+    router.register(
+        "auth.tokens.revoke",
+        Box::new(|ctx, params| Box::pin(api_tokens_rpc::handle_revoke(ctx, params))),
+    );
+
+    // Audit log
+    router.register(
+        "audit.query",
+        Box::new(|ctx, params| Box::pin(audit_rpc::handle_query(ctx, params))),
+    );
+    router.register(
+        "audit.verify",
+        Box::new(|ctx, params| Box::pin(audit_rpc::handle_verify(ctx, params))),
+    );
+
     // Secrets
     router.register(
         "secrets.reload",
@@ -632,6 +701,34 @@ pub fn register_all(router: &mut RpcRouter) {
         "memory.add_resource",
         Box::new(|ctx, params| Box::pin(memory_rpc::handle_add_resource(ctx, params))),
     );
+    router.register(
+        "memory.profile.list",
+        Box::new(|ctx, params| Box::pin(memory_rpc::handle_profile_list(ctx, params))),
+    );
+    router.register(
+        "memory.profile.get",
+        Box::new(|ctx, params| Box::pin(memory_rpc::handle_profile_get(ctx, params))),
+    );
+    router.register(
+        "memory.profile.set",
+        Box::new(|ctx, params| Box::pin(memory_rpc::handle_profile_set(ctx, params))),
+    );
+    router.register(
+        "memory.profile.delete",
+        Box::new(|ctx, params| Box::pin(memory_rpc::handle_profile_delete(ctx, params))),
+    );
+    router.register(
+        "memory.export",
+        Box::new(|ctx, params| Box::pin(memory_rpc::handle_export(ctx, params))),
+    );
+    router.register(
+        "memory.import",
+        Box::new(|ctx, params| Box::pin(memory_rpc::handle_import(ctx, params))),
+    );
+    router.register(
+        "memory.migrate",
+        Box::new(|ctx, params| Box::pin(memory_rpc::handle_migrate(ctx, params))),
+    );
 
     // Misc
     router.register(
@@ -11,6 +11,7 @@ use tokio::sync::{mpsc, RwLock};
 
 use super::scopes::{self, Role};
 use super::types::{RpcError, ServerFrame};
+use crate::gateway::api_tokens::ApiTokenError;
 use crate::gateway::state::AppState;
 
 // ---------------------------------------------------------------------------
@@ -32,6 +33,22 @@ pub struct RpcContext {
     pub scopes: HashSet<String>,
     /// Broadcaster for pushing events to connected clients.
     pub broadcaster: Arc<Broadcaster>,
+    /// API token the connection authenticated with, re-checked per call.
+    pub api_token_id: Option<String>,
+}
+
+impl RpcContext {
+    /// Who is calling, for approval and audit records: `token:<id>`,
+    /// `rpc:<conn>` or `rpc:<client>:<conn>`.
+    pub fn actor(&self) -> String {
+        if let Some(ref id) = self.api_token_id {
+            format!("token:{}", id)
+        } else if self.client.id.is_empty() {
+            format!("rpc:{}", self.conn_id)
+        } else {
+            format!("rpc:{}:{}", self.client.id, self.conn_id)
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -73,7 +90,9 @@ impl RpcRouter {
     /// Dispatch a request to the appropriate handler.
     ///
     /// Performs scope checking *before* invoking the handler. Returns a
-    /// `ServerFrame::Response` in all cases.
+    /// `ServerFrame::Response` in all cases.  Privileged methods (see
+    /// [`scopes::is_audited`]) are recorded in the audit log, including
+    /// calls the scope check rejected.
     pub async fn dispatch(
         &self,
         ctx: Arc<RpcContext>,
@@ -81,19 +100,51 @@ impl RpcRouter {
         method: &str,
         params: Value,
     ) -> ServerFrame {
-        // 1. Scope check
-        if let Err(reason) = scopes::check_scope(method, ctx.role, &ctx.scopes) {
-            return ServerFrame::err(&id, RpcError::forbidden(reason));
+        let audit = scopes::is_audited(method).then(|| {
+            crate::audit::AuditRecord::new("rpc", method)
+                .with_actor(ctx.actor())
+                .with_session(
+                    params
+                        .get("session_key")
+                        .or_else(|| params.get("sessionKey"))
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                )
+                .with_detail(if method.starts_with("config.") {
+                    super::config_rpc::audit_detail(&params)
+                } else {
+                    params.clone()
+                })
+        });
+
+        // 1-2. Token and scope checks
+        if let Err(err) = authorize(&ctx, method) {
+            if let Some(record) = audit {
+                ctx.state
+                    .infra
+                    .audit
+                    .record(record.with_outcome(format!("forbidden: {}", err.message)));
+            }
+            return ServerFrame::err(&id, err);
         }
 
-        // 2. Look up handler
+        // 3. Look up handler
         let handler = match self.handlers.get(method) {
             Some(h) => h,
             None => return ServerFrame::err(&id, RpcError::method_not_found(method)),
         };
 
-        // 3. Invoke
-        match handler(ctx, params).await {
+        // 4. Invoke
+        let state = ctx.state.clone();
+        let result = handler(ctx, params).await;
+        if let Some(record) = audit {
+            let outcome = match result {
+                Ok(_) => "ok".to_string(),
+                Err(ref e) => format!("error: {}", e.message),
+            };
+            state.infra.audit.record(record.with_outcome(outcome));
+        }
+        match result {
             Ok(payload) => ServerFrame::ok(id, payload),
             Err(rpc_err) => ServerFrame::err(id, rpc_err),
         }
@@ -107,6 +158,23 @@ impl RpcRouter {
     }
 }
 
+/// Whether `ctx` may call `method`.  Also used for the `agent` and
+/// `chat.send` calls the WebSocket loop runs itself instead of dispatching.
+pub fn authorize(ctx: &RpcContext, method: &str) -> Result<(), RpcError> {
+    // API tokens may have been revoked or hit their rate limit since the
+    // connection was made.
+    if let (Some(token_id), Some(auth)) = (&ctx.api_token_id, &ctx.state.core.auth) {
+        if let Err(e) = auth.touch_api_token(token_id) {
+            return Err(if e == ApiTokenError::RateLimited {
+                RpcError::rate_limited(e.to_string())
+            } else {
+                RpcError::forbidden(e.to_string())
+            });
+        }
+    }
+    scopes::check_scope(method, ctx.role, &ctx.scopes).map_err(RpcError::forbidden)
+}
+
 impl Default for RpcRouter {
     fn default() -> Self {
         Self::new()
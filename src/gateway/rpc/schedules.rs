@@ -1,13 +1,22 @@
 //! RPC handlers for schedule (cron) management.
+//!
+//! Definitions are written to the `[[schedule]]` section of the config file
+//! and mirrored into the persistent [`CronStore`] the scheduler runs from, so
+//! they take effect (and survive restarts) without reloading config.
 
-use std::path::Path;
 use std::sync::Arc;
 
-use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use super::router::RpcContext;
 use super::types::RpcError;
+use crate::config::{ScheduleEntry, SynapseConfig};
+use crate::cron::{
+    parse_timezone, CronParser, CronRunLog, CronStore, DeliverTarget, MisfirePolicy,
+};
+
+/// Default page size for `cron.runs`.
+const DEFAULT_RUNS_LIMIT: usize = 50;
 
 fn config_file_path() -> String {
     if std::path::Path::new("synapse.toml").exists() {
@@ -25,68 +34,97 @@ async fn read_config_file() -> Result<(String, String), RpcError> {
     Ok((path, content))
 }
 
-fn build_schedule_toml(
-    name: &str,
-    prompt: &str,
-    cron: Option<&str>,
-    interval_secs: Option<u64>,
-    enabled: bool,
-    description: Option<&str>,
-) -> toml::Value {
-    let mut tbl = toml::map::Map::new();
-    tbl.insert("name".to_string(), toml::Value::String(name.to_string()));
-    tbl.insert(
-        "prompt".to_string(),
-        toml::Value::String(prompt.to_string()),
-    );
-    if let Some(c) = cron {
-        tbl.insert("cron".to_string(), toml::Value::String(c.to_string()));
-    }
-    if let Some(i) = interval_secs {
-        tbl.insert("interval_secs".to_string(), toml::Value::Integer(i as i64));
-    }
-    tbl.insert("enabled".to_string(), toml::Value::Boolean(enabled));
-    if let Some(d) = description {
-        tbl.insert(
-            "description".to_string(),
-            toml::Value::String(d.to_string()),
-        );
+fn build_schedule_toml(entry: &ScheduleEntry) -> Result<toml::Value, RpcError> {
+    let mut value = toml::Value::try_from(entry)
+        .map_err(|e| RpcError::internal(format!("serialize schedule: {}", e)))?;
+    // Keep config files free of defaults the user never set.
+    if let Some(tbl) = value.as_table_mut() {
+        if entry.jitter_secs == 0 {
+            tbl.remove("jitter_secs");
+        }
+        if entry.misfire == MisfirePolicy::default() {
+            tbl.remove("misfire");
+        }
     }
-    toml::Value::Table(tbl)
+    Ok(value)
 }
 
-// Schedule run history
-const SCHEDULE_RUNS_FILE: &str = "log/schedule_runs.json";
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ScheduleRunEntry {
-    id: String,
-    schedule_name: String,
-    started_at: String,
-    finished_at: Option<String>,
-    status: String,
-    result: Option<String>,
-    error: Option<String>,
-}
-
-async fn read_schedule_runs() -> Vec<ScheduleRunEntry> {
-    match tokio::fs::read_to_string(SCHEDULE_RUNS_FILE).await {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-        Err(_) => Vec::new(),
+/// Parse and validate the schedule fields shared by `cron.add` and `cron.update`.
+fn parse_schedule_params(
+    params: &Value,
+    config: &SynapseConfig,
+) -> Result<ScheduleEntry, RpcError> {
+    let name = params
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("missing 'name' parameter"))?;
+    let prompt = params
+        .get("prompt")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("missing 'prompt' parameter"))?;
+    let cron = params.get("cron").and_then(|v| v.as_str());
+    if let Some(expr) = cron {
+        if !CronParser::is_valid(expr) {
+            return Err(RpcError::invalid_request(format!(
+                "invalid cron expression '{}'",
+                expr
+            )));
+        }
     }
-}
-
-async fn append_schedule_run(entry: ScheduleRunEntry) {
-    let mut runs = read_schedule_runs().await;
-    runs.push(entry);
-    if let Some(parent) = Path::new(SCHEDULE_RUNS_FILE).parent() {
-        let _ = tokio::fs::create_dir_all(parent).await;
+    let timezone = params.get("timezone").and_then(|v| v.as_str());
+    if let Some(tz) = timezone {
+        if parse_timezone(tz).is_none() {
+            return Err(RpcError::invalid_request(format!(
+                "unknown timezone '{}'",
+                tz
+            )));
+        }
     }
-    let _ = tokio::fs::write(
-        SCHEDULE_RUNS_FILE,
-        serde_json::to_string_pretty(&runs).unwrap_or_default(),
-    )
-    .await;
+    let misfire = match params.get("misfire").and_then(|v| v.as_str()) {
+        Some(m) => MisfirePolicy::parse(m).ok_or_else(|| {
+            RpcError::invalid_request(format!(
+                "invalid 'misfire' '{}': expected skip, run_once or run_all",
+                m
+            ))
+        })?,
+        None => MisfirePolicy::default(),
+    };
+    let agent = params.get("agent").and_then(|v| v.as_str());
+    if let Some(id) = agent {
+        if !crate::agent::headless::agent_exists(config, id) {
+            return Err(RpcError::invalid_request(format!("unknown agent '{}'", id)));
+        }
+    }
+    let deliver = match params.get("deliver").filter(|v| !v.is_null()) {
+        Some(v) => Some(
+            serde_json::from_value::<DeliverTarget>(v.clone())
+                .map_err(|e| RpcError::invalid_request(format!("invalid 'deliver': {}", e)))?,
+        ),
+        None => None,
+    };
+
+    Ok(ScheduleEntry {
+        name: name.to_string(),
+        prompt: prompt.to_string(),
+        cron: cron.map(String::from),
+        interval_secs: params.get("interval_secs").and_then(|v| v.as_u64()),
+        enabled: params
+            .get("enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true),
+        description: params
+            .get("description")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        timezone: timezone.map(String::from),
+        jitter_secs: params
+            .get("jitter_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0),
+        misfire,
+        agent: agent.map(String::from),
+        deliver,
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -94,29 +132,59 @@ async fn append_schedule_run(entry: ScheduleRunEntry) {
 // ---------------------------------------------------------------------------
 
 pub async fn handle_list(ctx: Arc<RpcContext>, _params: Value) -> Result<Value, RpcError> {
-    let schedules: Vec<Value> = ctx
+    let store = CronStore::open(CronStore::default_path());
+    let entries = ctx
         .state
         .core
         .config
         .schedules
-        .as_ref()
-        .map(|entries| {
-            entries
-                .iter()
-                .map(|e| {
-                    json!({
-                        "name": e.name,
-                        "prompt": e.prompt,
-                        "cron": e.cron,
-                        "interval_secs": e.interval_secs,
-                        "enabled": e.enabled,
-                        "description": e.description,
-                    })
-                })
-                .collect()
-        })
+        .as_deref()
         .unwrap_or_default();
 
+    let mut schedules: Vec<Value> = entries
+        .iter()
+        .map(|e| {
+            let job = store.get(&e.name);
+            json!({
+                "name": e.name,
+                "prompt": e.prompt,
+                "cron": e.cron,
+                "interval_secs": e.interval_secs,
+                "enabled": e.enabled,
+                "description": e.description,
+                "timezone": e.timezone,
+                "jitter_secs": e.jitter_secs,
+                "misfire": e.misfire,
+                "agent": e.agent,
+                "deliver": e.deliver,
+                "last_run": job.as_ref().and_then(|j| j.last_run),
+                "next_run": job.as_ref().and_then(|j| j.next_run),
+            })
+        })
+        .collect();
+
+    // Jobs added at runtime that aren't (yet) in the loaded config.
+    for job in store.list() {
+        if entries.iter().any(|e| e.name == job.id) {
+            continue;
+        }
+        schedules.push(json!({
+            "name": job.id,
+            "prompt": job.message,
+            "cron": job.expression,
+            "interval_secs": null,
+            "enabled": job.enabled,
+            "description": null,
+            "timezone": job.timezone,
+            "jitter_secs": job.jitter_secs,
+            "misfire": job.misfire,
+            "agent": job.agent_id,
+            "deliver": job.deliver,
+            "last_run": job.last_run,
+            "next_run": job.next_run,
+        }));
+    }
+
     Ok(json!(schedules))
 }
 
@@ -124,28 +192,14 @@ pub async fn handle_list(ctx: Arc<RpcContext>, _params: Value) -> Result<Value,
 // cron.add
 // ---------------------------------------------------------------------------
 
-pub async fn handle_add(_ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
-    let name = params
-        .get("name")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| RpcError::invalid_request("missing 'name' parameter"))?;
-    let prompt = params
-        .get("prompt")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| RpcError::invalid_request("missing 'prompt' parameter"))?;
-    let cron = params.get("cron").and_then(|v| v.as_str());
-    let interval_secs = params.get("interval_secs").and_then(|v| v.as_u64());
-    let enabled = params
-        .get("enabled")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(true);
-    let description = params.get("description").and_then(|v| v.as_str());
+pub async fn handle_add(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let entry = parse_schedule_params(&params, &ctx.state.core.config)?;
 
     let (path, content) = read_config_file().await?;
     let mut doc: toml::Value =
         toml::from_str(&content).map_err(|e| RpcError::internal(format!("parse TOML: {}", e)))?;
 
-    let new_entry = build_schedule_toml(name, prompt, cron, interval_secs, enabled, description);
+    let new_entry = build_schedule_toml(&entry)?;
 
     let schedules = doc
         .as_table_mut()
@@ -162,38 +216,20 @@ pub async fn handle_add(_ctx: Arc<RpcContext>, params: Value) -> Result<Value, R
         .await
         .map_err(|e| RpcError::internal(format!("write: {}", e)))?;
 
+    crate::scheduler::upsert_entry(&CronStore::open(CronStore::default_path()), &entry, false);
+
     tracing::info!("schedule created via RPC");
 
-    Ok(json!({
-        "name": name,
-        "prompt": prompt,
-        "cron": cron,
-        "interval_secs": interval_secs,
-        "enabled": enabled,
-        "description": description,
-    }))
+    Ok(json!(entry))
 }
 
 // ---------------------------------------------------------------------------
 // cron.update
 // ---------------------------------------------------------------------------
 
-pub async fn handle_update(_ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
-    let name = params
-        .get("name")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| RpcError::invalid_request("missing 'name' parameter"))?;
-    let prompt = params
-        .get("prompt")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| RpcError::invalid_request("missing 'prompt' parameter"))?;
-    let cron = params.get("cron").and_then(|v| v.as_str());
-    let interval_secs = params.get("interval_secs").and_then(|v| v.as_u64());
-    let enabled = params
-        .get("enabled")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(true);
-    let description = params.get("description").and_then(|v| v.as_str());
+pub async fn handle_update(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let entry = parse_schedule_params(&params, &ctx.state.core.config)?;
+    let name = entry.name.as_str();
 
     let (path, content) = read_config_file().await?;
     let mut doc: toml::Value =
@@ -204,7 +240,7 @@ pub async fn handle_update(_ctx: Arc<RpcContext>, params: Value) -> Result<Value
             .iter()
             .position(|s| s.get("name").and_then(|n| n.as_str()) == Some(name))
         {
-            arr[pos] = build_schedule_toml(name, prompt, cron, interval_secs, enabled, description);
+            arr[pos] = build_schedule_toml(&entry)?;
         } else {
             return Err(RpcError::not_found(format!(
                 "schedule '{}' not found",
@@ -221,14 +257,15 @@ pub async fn handle_update(_ctx: Arc<RpcContext>, params: Value) -> Result<Value
         .await
         .map_err(|e| RpcError::internal(format!("write: {}", e)))?;
 
-    Ok(json!({
-        "name": name,
-        "prompt": prompt,
-        "cron": cron,
-        "interval_secs": interval_secs,
-        "enabled": enabled,
-        "description": description,
-    }))
+    let store = CronStore::open(CronStore::default_path());
+    if entry.cron.is_some() {
+        crate::scheduler::upsert_entry(&store, &entry, false);
+    } else {
+        // Switched to an interval schedule; the cron driver no longer owns it.
+        store.remove(name);
+    }
+
+    Ok(json!(entry))
 }
 
 // ---------------------------------------------------------------------------
@@ -262,6 +299,8 @@ pub async fn handle_remove(_ctx: Arc<RpcContext>, params: Value) -> Result<Value
         .await
         .map_err(|e| RpcError::internal(format!("write: {}", e)))?;
 
+    CronStore::open(CronStore::default_path()).remove(name);
+
     tracing::info!("schedule deleted via RPC");
 
     Ok(json!({ "ok": true }))
@@ -277,22 +316,21 @@ pub async fn handle_run(_ctx: Arc<RpcContext>, params: Value) -> Result<Value, R
         .and_then(|v| v.as_str())
         .ok_or_else(|| RpcError::invalid_request("missing 'name' parameter"))?;
 
-    let started_at = chrono::Utc::now().to_rfc3339();
-    let id = format!("run-{}-{}", name, chrono::Utc::now().timestamp_millis());
-    let finished_at = chrono::Utc::now().to_rfc3339();
-
-    append_schedule_run(ScheduleRunEntry {
-        id: id.clone(),
-        schedule_name: name.to_string(),
-        started_at,
-        finished_at: Some(finished_at),
-        status: "success".to_string(),
-        result: Some("Triggered via RPC".to_string()),
-        error: None,
-    })
-    .await;
-
-    Ok(json!({ "ok": true, "run_id": id }))
+    let store = CronStore::open(CronStore::default_path());
+    match store.get(name) {
+        Some(job) if !job.enabled => Err(RpcError::invalid_request(format!(
+            "schedule '{}' is disabled",
+            name
+        ))),
+        Some(_) => {
+            store.trigger(name);
+            Ok(json!({ "ok": true, "queued": true }))
+        }
+        None => Err(RpcError::not_found(format!(
+            "cron schedule '{}' not found",
+            name
+        ))),
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -304,17 +342,21 @@ pub async fn handle_runs(_ctx: Arc<RpcContext>, params: Value) -> Result<Value,
         .get("name")
         .and_then(|v| v.as_str())
         .ok_or_else(|| RpcError::invalid_request("missing 'name' parameter"))?;
+    let offset = params.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|l| l as usize)
+        .unwrap_or(DEFAULT_RUNS_LIMIT);
 
-    let all_runs = read_schedule_runs().await;
-    let runs: Vec<Value> = all_runs
-        .into_iter()
-        .filter(|r| r.schedule_name == name)
-        .rev()
-        .take(50)
-        .map(|r| serde_json::to_value(r).unwrap_or_default())
-        .collect();
+    let page = CronRunLog::new(CronRunLog::default_dir()).list(name, offset, limit);
 
-    Ok(json!(runs))
+    Ok(json!({
+        "total": page.total,
+        "offset": offset,
+        "limit": limit,
+        "runs": page.runs,
+    }))
 }
 
 // ---------------------------------------------------------------------------
@@ -15,6 +15,9 @@ pub enum Role {
     Operator,
     /// Remote node (machine-to-machine).
     Node,
+    /// Programmatic access with an API token; scopes are token scopes
+    /// such as `chat` or `sessions.read` (see [`check_token_scope`]).
+    ApiToken,
 }
 
 impl Default for Role {
@@ -38,12 +41,7 @@ const NODE_ROLE_METHODS: &[&str] = &[
 
 /// Methods available to any authenticated connection.
 const CHAT_METHODS: &[&str] = &[
-    "agent",
-    "chat.send",
-    "chat.stop",
     "chat.history",
-    "chat.abort",
-    "chat.inject",
     "agent.wait",
     "poll",
     "connect",
@@ -51,11 +49,21 @@ const CHAT_METHODS: &[&str] = &[
     "status",
     "ping",
     "system-presence",
+    "exec.approval.waitDecision",
+];
+
+/// Chat methods that run the agent or change a conversation: nodes and
+/// operators with `operator.write`.
+const CHAT_WRITE_METHODS: &[&str] = &[
+    "agent",
+    "chat.send",
+    "chat.stop",
+    "chat.abort",
+    "chat.inject",
     "system-event",
     "send",
     "wake",
     "exec.approval.request",
-    "exec.approval.waitDecision",
 ];
 
 /// Read-only operator methods.
@@ -85,6 +93,10 @@ const READ_METHODS: &[&str] = &[
     "usage.cost",
     "usage.aggregates",
     "usage.records",
+    "usage.budgets.list",
+    "usage.budgets.status",
+    "delivery.list",
+    "delivery.dead",
     "models.list",
     "tools.catalog",
     "workspace.list",
@@ -103,6 +115,9 @@ const READ_METHODS: &[&str] = &[
     "node.pair.list",
     "device.pair.list",
     "exec.approvals.get",
+    "exec.approvals.explain",
+    "exec.approvals.allows.list",
+    "exec.approvals.audit",
     "exec.approvals.node.get",
     "tts.status",
     "tts.providers",
@@ -155,6 +170,8 @@ const WRITE_METHODS: &[&str] = &[
     "secrets.reload",
     "secrets.resolve",
     "updates.run",
+    "delivery.retry",
+    "delivery.discard",
 ];
 
 /// Approval-related methods.
@@ -164,6 +181,7 @@ const APPROVAL_METHODS: &[&str] = &[
     "approval.deny",
     "exec.approval.resolve",
     "exec.approvals.set",
+    "exec.approvals.allows.revoke",
     "exec.approvals.node.set",
 ];
 
@@ -191,6 +209,10 @@ const PAIRING_METHODS: &[&str] = &[
 ///
 /// Returns `Ok(())` if allowed, `Err(reason)` if denied.
 pub fn check_scope(method: &str, role: Role, scopes: &HashSet<String>) -> Result<(), String> {
+    if role == Role::ApiToken {
+        return check_token_scope(method, scopes);
+    }
+
     // Node-only methods
     if NODE_ROLE_METHODS.contains(&method) {
         return if role == Role::Node {
@@ -205,6 +227,17 @@ pub fn check_scope(method: &str, role: Role, scopes: &HashSet<String>) -> Result
         return Ok(());
     }
 
+    // Chat methods that act — nodes, or operator.write
+    if CHAT_WRITE_METHODS.contains(&method) {
+        if role == Role::Node
+            || scopes.contains("operator.write")
+            || scopes.contains("operator.admin")
+        {
+            return Ok(());
+        }
+        return Err(format!("Method '{method}' requires scope operator.write"));
+    }
+
     // Read methods — require operator.read OR operator.write
     if READ_METHODS.contains(&method) {
         if scopes.contains("operator.read")
@@ -254,6 +287,107 @@ pub fn check_scope(method: &str, role: Role, scopes: &HashSet<String>) -> Result
     ))
 }
 
+/// Access level of an API token scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TokenLevel {
+    Read,
+    Write,
+    Admin,
+}
+
+impl TokenLevel {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "read" => Some(Self::Read),
+            "write" => Some(Self::Write),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `scope` is a valid API token scope: `*`, `chat`, or
+/// `<area>.<read|write|admin>` where the area is a method prefix
+/// (`sessions`, `cron`, `config`, ...) or `*`.
+pub fn is_valid_token_scope(scope: &str) -> bool {
+    if scope == "*" || scope == "chat" {
+        return true;
+    }
+    match scope.rsplit_once('.') {
+        Some((area, level)) => {
+            TokenLevel::parse(level).is_some()
+                && !area.is_empty()
+                && (area == "*"
+                    || area
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        }
+        None => false,
+    }
+}
+
+/// Whether token `scopes` grant `level` access to `area`.  A higher level
+/// includes the lower ones.
+pub fn token_allows<'a>(
+    scopes: impl IntoIterator<Item = &'a String>,
+    area: &str,
+    level: TokenLevel,
+) -> bool {
+    scopes.into_iter().any(|scope| {
+        scope == "*"
+            || scope.rsplit_once('.').is_some_and(|(a, l)| {
+                (a == area || a == "*") && TokenLevel::parse(l).is_some_and(|l| l >= level)
+            })
+    })
+}
+
+/// Check an API token's scopes for `method`.  Chat methods need the `chat`
+/// scope; other methods need `<area>.<level>` where the area is the first
+/// segment of the method name and the level follows the method's category:
+/// read methods need `read`, write and approval methods `write`, pairing
+/// and unlisted methods `admin`.  Node methods are never available.
+pub fn check_token_scope(method: &str, scopes: &HashSet<String>) -> Result<(), String> {
+    if NODE_ROLE_METHODS.contains(&method) {
+        return Err(format!("Method '{method}' requires Node role"));
+    }
+    if CHAT_METHODS.contains(&method) || CHAT_WRITE_METHODS.contains(&method) {
+        return if scopes.contains("chat") || scopes.contains("*") {
+            Ok(())
+        } else {
+            Err(format!("Method '{method}' requires token scope chat"))
+        };
+    }
+    let level = if READ_METHODS.contains(&method) {
+        TokenLevel::Read
+    } else if WRITE_METHODS.contains(&method) || APPROVAL_METHODS.contains(&method) {
+        TokenLevel::Write
+    } else {
+        TokenLevel::Admin
+    };
+    let area = method.split('.').next().unwrap_or(method);
+    if token_allows(scopes, area, level) {
+        Ok(())
+    } else {
+        let level = format!("{:?}", level).to_lowercase();
+        Err(format!(
+            "Method '{method}' requires token scope {area}.{level}"
+        ))
+    }
+}
+
+/// Whether calls to `method` go to the audit log: everything that can change
+/// state or needs elevated scopes, plus node pairing.  Chat and read methods
+/// are too frequent and change nothing an operator would audit.
+pub fn is_audited(method: &str) -> bool {
+    if method.starts_with("node.pair.") {
+        return true;
+    }
+    !(NODE_ROLE_METHODS.contains(&method)
+        || CHAT_METHODS.contains(&method)
+        || CHAT_WRITE_METHODS.contains(&method)
+        || READ_METHODS.contains(&method))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,6 +399,23 @@ mod tests {
         assert!(check_scope("agent", Role::Node, &scopes).is_ok());
     }
 
+    #[test]
+    fn viewers_cannot_run_the_agent() {
+        let viewer: HashSet<String> = crate::gateway::users::UserRole::Viewer
+            .scopes()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(check_scope("chat.history", Role::Operator, &viewer).is_ok());
+        assert!(check_scope("status", Role::Operator, &viewer).is_ok());
+        for method in ["chat.send", "agent", "chat.inject", "send", "wake"] {
+            assert!(check_scope(method, Role::Operator, &viewer).is_err());
+        }
+
+        let operator = HashSet::from(["operator.write".to_string()]);
+        assert!(check_scope("chat.send", Role::Operator, &operator).is_ok());
+    }
+
     #[test]
     fn node_methods_require_node_role() {
         let scopes = HashSet::new();
@@ -305,6 +456,7 @@ mod tests {
         assert!(check_scope("config.get", Role::Operator, &read).is_ok());
         assert!(check_scope("cron.list", Role::Operator, &read).is_ok());
         assert!(check_scope("usage.status", Role::Operator, &read).is_ok());
+        assert!(check_scope("usage.budgets.status", Role::Operator, &read).is_ok());
         assert!(check_scope("logs.tail", Role::Operator, &read).is_ok());
         assert!(check_scope("models.list", Role::Operator, &read).is_ok());
         assert!(check_scope("tools.catalog", Role::Operator, &read).is_ok());
@@ -330,4 +482,54 @@ mod tests {
         assert!(check_scope("sessions.delete", Role::Operator, &empty).is_err());
         assert!(check_scope("config.set", Role::Operator, &empty).is_err());
     }
+
+    #[test]
+    fn delivery_methods_have_correct_scopes() {
+        let read = HashSet::from(["operator.read".to_string()]);
+        let write = HashSet::from(["operator.write".to_string()]);
+
+        assert!(check_scope("delivery.list", Role::Operator, &read).is_ok());
+        assert!(check_scope("delivery.dead", Role::Operator, &read).is_ok());
+        assert!(check_scope("delivery.retry", Role::Operator, &read).is_err());
+        assert!(check_scope("delivery.retry", Role::Operator, &write).is_ok());
+        assert!(check_scope("delivery.discard", Role::Operator, &write).is_ok());
+    }
+
+    #[test]
+    fn privileged_methods_are_audited() {
+        assert!(is_audited("config.set"));
+        assert!(is_audited("config.apply"));
+        assert!(is_audited("node.pair.approve"));
+        assert!(is_audited("node.pair.request"));
+        assert!(!is_audited("config.get"));
+        assert!(!is_audited("chat.send"));
+        assert!(!is_audited("node.heartbeat"));
+    }
+
+    #[test]
+    fn api_token_scopes() {
+        let scopes: HashSet<String> = ["chat", "sessions.read", "cron.write", "config.admin"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let check = |m: &str| check_scope(m, Role::ApiToken, &scopes);
+        assert!(check("chat.send").is_ok());
+        assert!(check("sessions.list").is_ok());
+        assert!(check("sessions.delete").is_err());
+        assert!(check("cron.add").is_ok());
+        assert!(check("cron.list").is_ok());
+        assert!(check("config.set").is_ok());
+        assert!(check("audit.query").is_err());
+        assert!(check("node.heartbeat").is_err());
+
+        let none = HashSet::new();
+        assert!(check_scope("chat.send", Role::ApiToken, &none).is_err());
+        let all: HashSet<String> = ["*".to_string()].into();
+        assert!(check_scope("audit.verify", Role::ApiToken, &all).is_ok());
+
+        assert!(is_valid_token_scope("sessions.read"));
+        assert!(is_valid_token_scope("*.write"));
+        assert!(!is_valid_token_scope("sessions.delete"));
+        assert!(!is_valid_token_scope("operator.admin.x"));
+    }
 }
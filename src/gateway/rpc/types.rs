@@ -152,6 +152,17 @@ impl RpcError {
         }
     }
 
+    /// 429 — rate limited.
+    pub fn rate_limited(msg: impl Into<String>) -> Self {
+        Self {
+            code: 429,
+            message: msg.into(),
+            details: None,
+            retryable: true,
+            retry_after_ms: Some(60_000),
+        }
+    }
+
     /// 500 — internal server error.
     pub fn internal(msg: impl Into<String>) -> Self {
         Self {
@@ -277,6 +288,9 @@ pub struct AuthParams {
     /// Password (for password-based auth).
     #[serde(default)]
     pub password: Option<String>,
+    /// Account name for password-based auth; omit for the legacy password.
+    #[serde(default)]
+    pub username: Option<String>,
 }
 
 /// Device-level authentication (public-key signature).
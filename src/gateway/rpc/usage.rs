@@ -119,3 +119,37 @@ pub async fn handle_records(ctx: Arc<RpcContext>, params: Value) -> Result<Value
 
     serde_json::to_value(&records).map_err(|e| RpcError::internal(e.to_string()))
 }
+
+// ---------------------------------------------------------------------------
+// usage.budgets.list — configured spend budgets
+// ---------------------------------------------------------------------------
+
+pub async fn handle_budgets_list(ctx: Arc<RpcContext>, _params: Value) -> Result<Value, RpcError> {
+    Ok(json!({ "budgets": ctx.state.agent.budgets.summaries() }))
+}
+
+// ---------------------------------------------------------------------------
+// usage.budgets.status — spend against each budget this period
+// ---------------------------------------------------------------------------
+
+pub async fn handle_budgets_status(ctx: Arc<RpcContext>, params: Value) -> Result<Value, RpcError> {
+    let budget = params.get("budget").and_then(|v| v.as_str());
+    let key = params.get("key").and_then(|v| v.as_str());
+    let exceeded_only = params
+        .get("exceeded")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let now_ms = crate::gateway::presence::now_ms();
+    let statuses: Vec<_> = ctx
+        .state
+        .agent
+        .budgets
+        .status(now_ms)
+        .into_iter()
+        .filter(|s| budget.is_none_or(|b| s.budget == b))
+        .filter(|s| key.is_none_or(|k| s.key == k))
+        .filter(|s| !exceeded_only || s.exceeded)
+        .collect();
+    Ok(json!({ "budgets": statuses }))
+}
@@ -11,6 +11,7 @@ use synaptic::session::SessionManager;
 use tokio::sync::RwLock;
 
 use super::auth::AuthState;
+use super::budgets::Budgets;
 use super::canvas::CanvasEngine;
 use super::rpc::{Broadcaster, RpcRouter};
 use super::run_queue::AgentRunQueue;
@@ -19,7 +20,7 @@ use crate::agent;
 use crate::agent::context_engine::{ContextEngine, SharedContextEngine};
 use crate::channels::handler::AgentSession;
 use crate::config::SynapseConfig;
-use crate::gateway::messages::ChannelRegistry;
+use crate::gateway::messages::{ChannelRegistry, DeliveryService};
 use crate::gateway::rpc::wizard::WizardSession;
 use crate::session::SessionWriteLock;
 use synaptic::logging::LogBuffer;
@@ -67,11 +68,17 @@ pub struct AgentSubState {
     pub transient_mcp: Arc<RwLock<HashMap<String, TransientMcpServer>>>,
     pub cost_tracker: Arc<CostTrackingCallback>,
     pub usage_tracker: Arc<UsageTracker>,
+    /// Spend budgets, enforced on channel turns and scheduled/webhook runs.
+    pub budgets: Arc<Budgets>,
     #[allow(dead_code)]
     pub memory_provider: Arc<dyn MemoryProvider>,
     #[allow(dead_code)]
     pub context_engine: SharedContextEngine,
     pub agent_session: Arc<AgentSession>,
+    /// The default agent's long-term memory, shared with the memory plugin
+    /// (`None` when LTM is disabled).  Maintenance on that store goes through
+    /// it so the plugin's in-memory index stays current.
+    pub ltm: Option<Arc<crate::memory::LongTermMemory>>,
 }
 
 #[derive(Clone)]
@@ -105,6 +112,8 @@ pub struct ChannelSubState {
     pub approve_notifiers: Arc<crate::channels::dm::ApproveNotifierRegistry>,
     pub exec_approval_manager: Arc<RwLock<crate::gateway::exec_approvals::ExecApprovalManager>>,
     pub exec_approvals_config: Arc<RwLock<crate::gateway::exec_approvals::ExecApprovalsConfig>>,
+    /// Durable outbound delivery queue shared by all channel adapters.
+    pub delivery: Arc<DeliveryService>,
 }
 
 #[derive(Clone)]
@@ -116,6 +125,8 @@ pub struct InfraState {
     #[allow(dead_code)]
     pub canvas_engine: Arc<CanvasEngine>,
     pub plugin_registry: Arc<tokio::sync::RwLock<synaptic::plugin::PluginRegistry>>,
+    /// Hash-chained audit log of tool calls and privileged actions.
+    pub audit: Arc<crate::audit::AuditLog>,
     #[allow(dead_code)]
     pub bundle_skills_dirs: Vec<std::path::PathBuf>,
     #[allow(dead_code)]
@@ -147,6 +158,7 @@ struct AgentBundle {
     mcp_tools: Vec<Arc<dyn Tool>>,
     cost_tracker: Arc<CostTrackingCallback>,
     usage_tracker: Arc<UsageTracker>,
+    budgets: Arc<Budgets>,
     memory_provider: Arc<dyn MemoryProvider>,
     context_engine: SharedContextEngine,
 }
@@ -168,6 +180,9 @@ async fn build_agent_bundle(config: &SynapseConfig) -> crate::error::Result<Agen
     }
     usage_tracker.spawn_periodic_flush(std::time::Duration::from_secs(60));
 
+    let budgets = Arc::new(Budgets::from_config(config));
+    budgets.seed(&usage_tracker).await;
+
     // Memory provider will be set by memory plugin via PluginRegistry.memory_slot.
     // Use noop provider here — actual provider comes from infra bundle after plugin registration.
     let memory_provider: Arc<dyn MemoryProvider> =
@@ -180,6 +195,7 @@ async fn build_agent_bundle(config: &SynapseConfig) -> crate::error::Result<Agen
         mcp_tools,
         cost_tracker,
         usage_tracker,
+        budgets,
         memory_provider,
         context_engine,
     })
@@ -235,22 +251,32 @@ fn build_rpc_bundle() -> RpcBundle {
     }
 }
 
-/// Channel adapters: registry, manager, DM enforcer, approval notifiers.
+/// Channel adapters: registry, manager, DM enforcer, approval notifiers,
+/// and the outbound delivery queue (including its background retry worker).
 struct ChannelBundle {
     channel_registry: Arc<RwLock<ChannelRegistry>>,
+    delivery: Arc<DeliveryService>,
     channel_manager: Arc<super::channel_manager::ChannelAdapterManager>,
     dm_enforcer: Arc<crate::channels::dm::FileDmPolicyEnforcer>,
     approve_notifiers: Arc<crate::channels::dm::ApproveNotifierRegistry>,
 }
 
-fn build_channel_bundle() -> ChannelBundle {
+fn build_channel_bundle(config: &SynapseConfig) -> ChannelBundle {
+    let channel_registry = Arc::new(RwLock::new(ChannelRegistry::new()));
+    let delivery = Arc::new(DeliveryService::new(
+        &config.delivery,
+        channel_registry.clone(),
+    ));
+    delivery.spawn_worker();
+
     let pairing_dir = dirs::home_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join(".synapse")
         .join("pairing");
 
     ChannelBundle {
-        channel_registry: Arc::new(RwLock::new(ChannelRegistry::new())),
+        channel_registry,
+        delivery,
         channel_manager: Arc::new(super::channel_manager::ChannelAdapterManager::new()),
         dm_enforcer: Arc::new(crate::channels::dm::FileDmPolicyEnforcer::new(
             pairing_dir,
@@ -264,19 +290,29 @@ fn build_channel_bundle() -> ChannelBundle {
 /// Event bus and plugin registry (plugins are wired into the event bus).
 struct InfraBundle {
     event_bus: Arc<EventBus>,
+    audit: Arc<crate::audit::AuditLog>,
     plugin_registry: Arc<tokio::sync::RwLock<synaptic::plugin::PluginRegistry>>,
     /// Skills dirs contributed by plugin bundles (Claude/Codex/Cursor).
     bundle_skills_dirs: Vec<std::path::PathBuf>,
     /// Agent dirs contributed by plugin bundles.
     bundle_agent_dirs: Vec<std::path::PathBuf>,
+    /// The default agent's long-term memory.
+    ltm: Option<Arc<crate::memory::LongTermMemory>>,
 }
 
 async fn build_infra_bundle(
     config: &SynapseConfig,
+    model: &Arc<dyn ChatModel>,
     cost_tracker: &Arc<CostTrackingCallback>,
     usage_tracker: &Arc<UsageTracker>,
 ) -> InfraBundle {
     let event_bus = Arc::new(EventBus::new());
+    let audit = Arc::new(crate::audit::AuditLog::from_config(&config.audit));
+    event_bus.subscribe(
+        Arc::new(crate::audit::AuditSubscriber::new(audit.clone())),
+        0,
+        "audit",
+    );
     let plugin_registry = Arc::new(tokio::sync::RwLock::new(
         synaptic::plugin::PluginRegistry::new(event_bus.clone()),
     ));
@@ -298,6 +334,20 @@ async fn build_infra_bundle(
         ),
     ));
 
+    // The default agent's long-term memory, for the native memory plugin
+    // (memory tools, recall and user profiles).
+    let ltm = if config.memory.ltm_enabled {
+        let ltm = crate::memory::LongTermMemory::new(
+            std::path::PathBuf::from(config.sessions_dir()).join("long_term_memory"),
+            config.memory.clone(),
+        )
+        .with_model(model.clone());
+        ltm.load().await.ok();
+        Some(Arc::new(ltm))
+    } else {
+        None
+    };
+
     // Register slot-assigned plugins via factory registry (e.g., memory-viking)
     let factory_registry = crate::plugins::registry::default_registry();
     for (slot, plugin_name) in &config.plugins.slots {
@@ -320,6 +370,14 @@ async fn build_infra_bundle(
             continue;
         }
 
+        // memory-native needs Arc<LTM>, which can't pass through JSON config
+        if plugin_name == "memory-native" && slot == "memory" {
+            plugin_manager.add_builtin(Box::new(
+                crate::plugins::memory_native::NativeMemoryPlugin::new(ltm.clone()),
+            ));
+            continue;
+        }
+
         match factory_registry.create(plugin_name, plugin_config) {
             Some(plugin) => {
                 plugin_manager.add_builtin(plugin);
@@ -336,6 +394,13 @@ async fn build_infra_bundle(
         }
     }
 
+    // If no memory slot configured, default to native
+    if !config.plugins.slots.contains_key("memory") {
+        plugin_manager.add_builtin(Box::new(
+            crate::plugins::memory_native::NativeMemoryPlugin::new(ltm.clone()),
+        ));
+    }
+
     // Load state (disabled plugins) and register all builtins
     plugin_manager.load_state();
     if let Err(e) = plugin_manager.load_all().await {
@@ -356,9 +421,11 @@ async fn build_infra_bundle(
 
     InfraBundle {
         event_bus,
+        audit,
         plugin_registry,
         bundle_skills_dirs,
         bundle_agent_dirs,
+        ltm,
     }
 }
 
@@ -452,6 +519,7 @@ impl AppState {
         // ── Infrastructure (event bus, plugins) ─────────────────────────
         let infra_bundle = build_infra_bundle(
             config,
+            &agent_bundle.model,
             &agent_bundle.cost_tracker,
             &agent_bundle.usage_tracker,
         )
@@ -461,7 +529,7 @@ impl AppState {
         let rpc = build_rpc_bundle();
 
         // ── Channel adapters ────────────────────────────────────────────
-        let channels = build_channel_bundle();
+        let channels = build_channel_bundle(config);
 
         // ── Session management ──────────────────────────────────────────
         let session_mgr = crate::build_session_manager(config);
@@ -474,11 +542,18 @@ impl AppState {
             .map(|auth_config| Arc::new(AuthState::new(auth_config.clone())));
 
         // ── Exec approvals ──────────────────────────────────────────────
-        let exec_approval_manager = Arc::new(RwLock::new(
-            crate::gateway::exec_approvals::ExecApprovalManager::new(),
-        ));
-        let exec_approvals_config = Arc::new(RwLock::new(
-            crate::gateway::exec_approvals::ExecApprovalsConfig::load(),
+        let exec_config = crate::gateway::exec_approvals::ExecApprovalsConfig::load();
+        let mut exec_manager = crate::gateway::exec_approvals::ExecApprovalManager::load();
+        if let Some(ttl) = exec_config.session_allow_ttl_secs {
+            exec_manager = exec_manager.with_allow_ttl_ms(ttl * 1000);
+        }
+        let exec_approval_manager = Arc::new(RwLock::new(exec_manager));
+        let exec_approvals_config = Arc::new(RwLock::new(exec_config));
+        channels.delivery.register_interaction_handler(Arc::new(
+            crate::gateway::exec_approvals::chat::ChatApprovals::new(
+                exec_approval_manager.clone(),
+                exec_approvals_config.clone(),
+            ),
         ));
 
         // ── Transient MCP (shared between AgentSession and AgentSubState) ──
@@ -500,6 +575,7 @@ impl AppState {
                 agent_bundle.cost_tracker.clone(),
                 agent_bundle.usage_tracker.clone(),
             )
+            .with_budgets(agent_bundle.budgets.clone())
             .with_plugins(
                 infra_bundle.event_bus.clone(),
                 infra_bundle.plugin_registry.clone(),
@@ -528,9 +604,11 @@ impl AppState {
                 transient_mcp,
                 cost_tracker: agent_bundle.cost_tracker,
                 usage_tracker: agent_bundle.usage_tracker,
+                budgets: agent_bundle.budgets,
                 memory_provider,
                 context_engine: agent_bundle.context_engine,
                 agent_session,
+                ltm: infra_bundle.ltm,
             },
             session: SessionSubState {
                 sessions: Arc::new(session_mgr),
@@ -556,6 +634,7 @@ impl AppState {
                 approve_notifiers: channels.approve_notifiers,
                 exec_approval_manager,
                 exec_approvals_config,
+                delivery: channels.delivery,
             },
             infra: InfraState {
                 request_metrics: RequestMetrics::default(),
@@ -563,11 +642,43 @@ impl AppState {
                 event_bus: infra_bundle.event_bus,
                 canvas_engine: Arc::new(CanvasEngine::new()),
                 plugin_registry: infra_bundle.plugin_registry,
+                audit: infra_bundle.audit,
                 bundle_skills_dirs: infra_bundle.bundle_skills_dirs,
                 bundle_agent_dirs: infra_bundle.bundle_agent_dirs,
             },
         };
+        spawn_budget_alerts(&state);
 
         Ok(state)
     }
 }
+
+/// Forward budget alerts to dashboard clients (`usage.budget.alert`) and the
+/// audit log.
+fn spawn_budget_alerts(state: &AppState) {
+    if state.agent.budgets.is_empty() {
+        return;
+    }
+    let mut alerts = state.agent.budgets.subscribe();
+    let broadcaster = state.network.broadcaster.clone();
+    let audit = state.infra.audit.clone();
+    tokio::spawn(async move {
+        loop {
+            let alert = match alerts.recv().await {
+                Ok(alert) => alert,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(skipped = n, "budget alerts dropped");
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            let payload = serde_json::to_value(&alert).unwrap_or_default();
+            audit.record(
+                crate::audit::AuditRecord::new("usage", "budget.alert")
+                    .with_outcome(format!("{}%", alert.threshold))
+                    .with_detail(payload.clone()),
+            );
+            broadcaster.broadcast("usage.budget.alert", payload).await;
+        }
+    });
+}
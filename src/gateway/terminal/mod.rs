@@ -0,0 +1,410 @@
+//! Terminal WebSocket backend — an admin shell in a pseudo-terminal.
+//!
+//! The terminal is off unless `[terminal] enabled` is set, and refused while
+//! `[auth]` is disabled.  `/ws/terminal` authenticates itself, since browsers
+//! cannot set headers on a WebSocket: the session or API token comes from
+//! `Authorization: Bearer` or, from browsers, the `bearer.<token>` WebSocket
+//! subprotocol (offered next to `synapse.terminal`), never from the URL.
+//! Browser connections must come from the gateway's own origin.  Only admins
+//! (API tokens: `terminal.admin`) may connect.  With `[terminal] sandbox` the
+//! shell runs in the session's sandbox (`?session=`, `?agent=`) instead of on
+//! the host.
+//!
+//! Binary frames carry raw terminal bytes both ways.  Text frames from the
+//! client are keystrokes, or `{"type":"resize","cols":120,"rows":40}`.
+//!
+//! Every session is audited (`terminal.open` / `terminal.close`) and, with
+//! `[terminal] record`, its output is saved next to the audit log with the
+//! recording's hash sealed into the close entry, for `synapse audit replay`.
+
+#[cfg(unix)]
+mod pty;
+mod recording;
+
+use axum::extract::ws::{Message as WsMessage, WebSocket};
+use axum::extract::{Query, State, WebSocketUpgrade};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+
+use super::api_tokens;
+use super::rpc::scopes::TokenLevel;
+use super::state::AppState;
+use super::users::UserRole;
+use crate::audit::AuditRecord;
+use recording::Recording;
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/ws/terminal", get(terminal_handler))
+}
+
+/// Subprotocol the server accepts; browsers offer it together with
+/// `bearer.<token>`.
+const PROTOCOL: &str = "synapse.terminal";
+
+#[derive(Debug, Deserialize)]
+struct TerminalQuery {
+    /// Session whose sandbox to use (default: "main").
+    session: Option<String>,
+    /// Agent whose sandbox to use (default: "default").
+    agent: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+}
+
+/// Client control message.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Control {
+    Resize { cols: u16, rows: u16 },
+}
+
+/// Token offered as a `bearer.<token>` subprotocol.
+fn protocol_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::SEC_WEBSOCKET_PROTOCOL)?
+        .to_str()
+        .ok()?
+        .split(',')
+        .find_map(|p| p.trim().strip_prefix("bearer."))
+}
+
+/// Whether a browser request comes from the gateway's own origin.  Requests
+/// without `Origin` are not from a browser page and pass.
+fn same_origin(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let host = headers.get(header::HOST).and_then(|h| h.to_str().ok());
+    let origin_host = origin
+        .to_str()
+        .ok()
+        .and_then(|o| o.split_once("://"))
+        .map(|(_, rest)| rest.trim_end_matches('/'));
+    matches!((origin_host, host), (Some(o), Some(h)) if o.eq_ignore_ascii_case(h))
+}
+
+/// The actor opening the terminal, if they are an admin.
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<String, (StatusCode, String)> {
+    let auth = match &state.core.auth {
+        Some(auth) if auth.config.enabled => auth,
+        _ => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "the web terminal requires [auth] enabled = true".to_string(),
+            ))
+        }
+    };
+    if !same_origin(headers) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "cross-origin terminal connections are not allowed".to_string(),
+        ));
+    }
+    let token = match super::auth::bearer_token(headers) {
+        "" => protocol_token(headers).unwrap_or(""),
+        bearer => bearer,
+    };
+    if token.starts_with(api_tokens::TOKEN_PREFIX) {
+        let token = auth
+            .authenticate_api_token(token)
+            .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+        return if token.allows("terminal", TokenLevel::Admin) {
+            Ok(format!("token:{}", token.id))
+        } else {
+            Err((
+                StatusCode::FORBIDDEN,
+                format!("API token {} lacks scope terminal.admin", token.id),
+            ))
+        };
+    }
+    match auth.validate_token(token) {
+        Some(identity) if identity.role == UserRole::Admin => {
+            Ok(format!("user:{}", identity.username))
+        }
+        Some(identity) => Err((
+            StatusCode::FORBIDDEN,
+            format!("user {} is not an admin", identity.username),
+        )),
+        None => Err((
+            StatusCode::UNAUTHORIZED,
+            "Authentication required".to_string(),
+        )),
+    }
+}
+
+async fn terminal_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TerminalQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if !state.core.config.terminal.enabled {
+        return (StatusCode::NOT_FOUND, "Web terminal disabled").into_response();
+    }
+    match authorize(&state, &headers) {
+        Ok(actor) => ws
+            .protocols([PROTOCOL])
+            .on_upgrade(move |socket| handle_terminal(socket, state, actor, query)),
+        Err((status, error)) => {
+            if status == StatusCode::FORBIDDEN {
+                state.infra.audit.record(
+                    AuditRecord::new("terminal", "terminal.open")
+                        .with_outcome(format!("forbidden: {}", error)),
+                );
+            }
+            (status, error).into_response()
+        }
+    }
+}
+
+/// Program and arguments to run, and where they run (`host` or
+/// `sandbox:<runtime id>`).
+async fn shell_command(
+    state: &AppState,
+    query: &TerminalQuery,
+) -> Result<(String, Vec<String>, String), String> {
+    let config = &state.core.config.terminal;
+    if !config.sandbox {
+        let shell = config
+            .shell
+            .clone()
+            .or_else(|| std::env::var("SHELL").ok())
+            .unwrap_or_else(|| "/bin/bash".to_string());
+        return Ok((shell, Vec::new(), "host".to_string()));
+    }
+    sandbox_command(state, query).await
+}
+
+#[cfg(feature = "sandbox")]
+async fn sandbox_command(
+    state: &AppState,
+    query: &TerminalQuery,
+) -> Result<(String, Vec<String>, String), String> {
+    use std::sync::{Arc, OnceLock};
+
+    use crate::sandbox::orchestrator::{ResolvedBackend, SandboxOrchestrator};
+    use crate::sandbox::registry::SandboxPersistentRegistry;
+
+    /// Shared so terminals reuse the sandbox of their scope.
+    static ORCHESTRATOR: OnceLock<Arc<SandboxOrchestrator>> = OnceLock::new();
+
+    let sandbox_config = state
+        .core
+        .config
+        .sandbox
+        .as_ref()
+        .ok_or("[terminal] sandbox is set but [sandbox] is not configured")?;
+    let orchestrator = ORCHESTRATOR.get_or_init(|| {
+        Arc::new(SandboxOrchestrator::new(
+            Arc::new(synaptic::deep::sandbox::SandboxProviderRegistry::new()),
+            sandbox_config.clone(),
+            SandboxPersistentRegistry::new(SandboxPersistentRegistry::default_path()),
+        ))
+    });
+    let session = query.session.as_deref().unwrap_or("main");
+    let agent = query.agent.as_deref().unwrap_or("default");
+    match orchestrator
+        .resolve_backend(session, agent, None)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        // Never fall back to a host shell when a sandbox was asked for.
+        ResolvedBackend::Host => Err(format!(
+            "the sandbox policy runs session '{}' on the host; refusing a host shell",
+            session
+        )),
+        ResolvedBackend::Sandboxed(instance) => match instance.info.provider_id.as_str() {
+            "docker" => Ok((
+                "docker".to_string(),
+                vec![
+                    "exec".into(),
+                    "-it".into(),
+                    "-e".into(),
+                    "TERM=xterm-256color".into(),
+                    instance.runtime_id.clone(),
+                    "/bin/sh".into(),
+                    "-c".into(),
+                    "command -v bash >/dev/null && exec bash || exec sh".into(),
+                ],
+                format!("sandbox:{}", instance.runtime_id),
+            )),
+            other => Err(format!(
+                "sandbox provider '{}' does not support interactive terminals",
+                other
+            )),
+        },
+    }
+}
+
+#[cfg(not(feature = "sandbox"))]
+async fn sandbox_command(
+    _state: &AppState,
+    _query: &TerminalQuery,
+) -> Result<(String, Vec<String>, String), String> {
+    Err("[terminal] sandbox is set but this build has no sandbox support".into())
+}
+
+#[cfg(not(unix))]
+async fn handle_terminal(
+    mut socket: WebSocket,
+    _state: AppState,
+    _actor: String,
+    _query: TerminalQuery,
+) {
+    let _ = socket
+        .send(WsMessage::Text(
+            "The web terminal needs a Unix host.\r\n".into(),
+        ))
+        .await;
+}
+
+#[cfg(unix)]
+async fn handle_terminal(socket: WebSocket, state: AppState, actor: String, query: TerminalQuery) {
+    let (mut sender, mut receiver) = socket.split();
+    let id = format!("term-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let audit = state.infra.audit.clone();
+    let (cols, rows) = (query.cols.unwrap_or(80), query.rows.unwrap_or(24));
+    let open = AuditRecord::new("terminal", "terminal.open")
+        .with_actor(actor.clone())
+        .with_session(Some(id.clone()));
+
+    let spawned = match shell_command(&state, &query).await {
+        Ok((program, args, target)) => {
+            match pty::Pty::spawn(&program, &args, &[("TERM", "xterm-256color")], cols, rows) {
+                Ok((pty, child)) => Ok((pty, child, program, target)),
+                Err(e) => Err(format!("failed to spawn {}: {}", program, e)),
+            }
+        }
+        Err(e) => Err(e),
+    };
+    let (pty, mut child, program, target) = match spawned {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            audit.record(open.with_outcome(format!("error: {}", e)));
+            let _ = sender
+                .send(WsMessage::Text(format!("{}\r\n", e).into()))
+                .await;
+            return;
+        }
+    };
+
+    let mut recording = None;
+    if state.core.config.terminal.record {
+        if let Some(dir) = audit.path().and_then(|p| p.parent()) {
+            match Recording::create(&dir.join("terminal"), &id, cols, rows, &program) {
+                Ok(r) => recording = Some(r),
+                Err(e) => {
+                    tracing::warn!(terminal = %id, error = %e, "failed to start terminal recording")
+                }
+            }
+        }
+    }
+    audit.record(open.with_outcome("ok").with_detail(serde_json::json!({
+        "shell": program,
+        "target": target,
+        "cols": cols,
+        "rows": rows,
+        "recording": recording.as_ref().map(|r| r.path().display().to_string()),
+    })));
+    tracing::info!(terminal = %id, %actor, %target, "web terminal opened");
+
+    let started = std::time::Instant::now();
+    let mut bytes_out: u64 = 0;
+    let mut buf = [0u8; 4096];
+    loop {
+        tokio::select! {
+            read = pty.read(&mut buf) => {
+                let n = match read {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                bytes_out += n as u64;
+                if let Some(ref mut r) = recording {
+                    r.output(&buf[..n]);
+                }
+                if sender.send(WsMessage::Binary(buf[..n].to_vec().into())).await.is_err() {
+                    break;
+                }
+            }
+            msg = receiver.next() => {
+                let input = match msg {
+                    Some(Ok(WsMessage::Binary(data))) => data.to_vec(),
+                    Some(Ok(WsMessage::Text(text))) => {
+                        if let Ok(Control::Resize { cols, rows }) = serde_json::from_str(&text) {
+                            if let Err(e) = pty.resize(cols, rows) {
+                                tracing::debug!(terminal = %id, error = %e, "terminal resize failed");
+                            }
+                            if let Some(ref mut r) = recording {
+                                r.resize(cols, rows);
+                            }
+                            continue;
+                        }
+                        text.as_bytes().to_vec()
+                    }
+                    Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                if pty.write_all(&input).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = child.start_kill();
+    let status = child.wait().await.ok();
+    let (recording_path, recording_sha256) = match recording {
+        Some(r) => {
+            let path = r.path().display().to_string();
+            match r.finish() {
+                Ok(hash) => (Some(path), Some(hash)),
+                Err(e) => {
+                    tracing::warn!(terminal = %id, error = %e, "failed to save terminal recording");
+                    (Some(path), None)
+                }
+            }
+        }
+        None => (None, None),
+    };
+    audit.record(
+        AuditRecord::new("terminal", "terminal.close")
+            .with_actor(actor)
+            .with_session(Some(id.clone()))
+            .with_outcome("ok")
+            .with_detail(serde_json::json!({
+                "duration_ms": started.elapsed().as_millis() as u64,
+                "bytes_out": bytes_out,
+                "exit_code": status.and_then(|s| s.code()),
+                "recording": recording_path,
+                "recording_sha256": recording_sha256,
+            })),
+    );
+    tracing::info!(terminal = %id, "web terminal closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_tokens_from_subprotocols_and_checks_the_origin() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, "localhost:3000".parse().unwrap());
+        assert!(same_origin(&headers));
+        headers.insert(header::ORIGIN, "http://localhost:3000".parse().unwrap());
+        assert!(same_origin(&headers));
+        headers.insert(header::ORIGIN, "https://evil.example".parse().unwrap());
+        assert!(!same_origin(&headers));
+
+        assert_eq!(protocol_token(&headers), None);
+        headers.insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            "synapse.terminal, bearer.abc.def".parse().unwrap(),
+        );
+        assert_eq!(protocol_token(&headers), Some("abc.def"));
+    }
+}
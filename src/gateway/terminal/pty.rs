@@ -0,0 +1,179 @@
+//! A pseudo-terminal with a child process on its slave side.
+
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use tokio::io::unix::AsyncFd;
+use tokio::process::{Child, Command};
+
+/// The master side of a PTY, readable and writable without blocking.
+pub struct Pty {
+    fd: AsyncFd<OwnedFd>,
+}
+
+fn winsize(cols: u16, rows: u16) -> libc::winsize {
+    libc::winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+fn check(rc: libc::c_int) -> io::Result<libc::c_int> {
+    if rc == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(rc)
+    }
+}
+
+impl Pty {
+    /// Open a `cols`×`rows` PTY and run `program` on it as a session leader
+    /// with the PTY as its controlling terminal.
+    pub fn spawn(
+        program: &str,
+        args: &[String],
+        env: &[(&str, &str)],
+        cols: u16,
+        rows: u16,
+    ) -> io::Result<(Self, Child)> {
+        let mut master: libc::c_int = -1;
+        let mut slave: libc::c_int = -1;
+        let mut size = winsize(cols, rows);
+        // SAFETY: openpty writes two fds, which are immediately owned below.
+        check(unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut size,
+            )
+        })?;
+        // SAFETY: both fds were just opened and nothing else owns them.
+        let (master, slave) =
+            unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+        // SAFETY: plain fcntl calls on an fd we own.
+        unsafe {
+            check(libc::fcntl(
+                master.as_raw_fd(),
+                libc::F_SETFD,
+                libc::FD_CLOEXEC,
+            ))?;
+            let flags = check(libc::fcntl(master.as_raw_fd(), libc::F_GETFL))?;
+            check(libc::fcntl(
+                master.as_raw_fd(),
+                libc::F_SETFL,
+                flags | libc::O_NONBLOCK,
+            ))?;
+        }
+
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .envs(env.iter().copied())
+            .stdin(slave.try_clone()?)
+            .stdout(slave.try_clone()?)
+            .stderr(slave)
+            .kill_on_drop(true);
+        // SAFETY: only async-signal-safe calls between fork and exec.
+        unsafe {
+            cmd.pre_exec(|| {
+                check(libc::setsid())?;
+                check(libc::ioctl(0, libc::TIOCSCTTY as _, 0))?;
+                Ok(())
+            });
+        }
+        let child = cmd.spawn()?;
+        Ok((
+            Self {
+                fd: AsyncFd::new(master)?,
+            },
+            child,
+        ))
+    }
+
+    /// Read output.  Returns 0 once the child side has closed.
+    pub async fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let mut guard = self.fd.readable().await?;
+            let result = guard.try_io(|fd| {
+                // SAFETY: reads at most `buf.len()` bytes into `buf`.
+                let n = unsafe { libc::read(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+                if n < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(n as usize)
+                }
+            });
+            match result {
+                // Linux reports EIO once the last slave fd is closed.
+                Ok(Err(e)) if e.raw_os_error() == Some(libc::EIO) => return Ok(0),
+                Ok(result) => return result,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Write input.
+    pub async fn write_all(&self, mut data: &[u8]) -> io::Result<()> {
+        while !data.is_empty() {
+            let mut guard = self.fd.writable().await?;
+            let result = guard.try_io(|fd| {
+                // SAFETY: writes at most `data.len()` bytes from `data`.
+                let n = unsafe { libc::write(fd.as_raw_fd(), data.as_ptr().cast(), data.len()) };
+                if n < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(n as usize)
+                }
+            });
+            if let Ok(written) = result {
+                data = &data[written?..];
+            }
+        }
+        Ok(())
+    }
+
+    /// Change the window size; the child gets `SIGWINCH`.
+    pub fn resize(&self, cols: u16, rows: u16) -> io::Result<()> {
+        let size = winsize(cols, rows);
+        // SAFETY: TIOCSWINSZ reads a winsize from the pointer.
+        check(unsafe {
+            libc::ioctl(
+                self.fd.as_raw_fd(),
+                libc::TIOCSWINSZ as _,
+                &size as *const libc::winsize,
+            )
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn child_sees_a_tty_with_the_requested_size() {
+        let (pty, mut child) = Pty::spawn(
+            "/bin/sh",
+            &["-c".into(), "test -t 0 && stty size".into()],
+            &[],
+            100,
+            30,
+        )
+        .unwrap();
+        let mut output = Vec::new();
+        let mut buf = [0u8; 256];
+        loop {
+            let n = pty.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            output.extend_from_slice(&buf[..n]);
+        }
+        assert!(child.wait().await.unwrap().success());
+        assert_eq!(String::from_utf8_lossy(&output).trim(), "30 100");
+    }
+}
@@ -0,0 +1,141 @@
+//! Terminal session recordings in asciicast v2 format.
+//!
+//! A header line, then one `[seconds, "o", text]` line per output chunk and
+//! `[seconds, "r", "COLSxROWS"]` per resize.  Keystrokes are not recorded —
+//! they include passwords typed at prompts — but everything echoed is.  The
+//! SHA-256 of the file is returned by [`Recording::finish`] so the audit log
+//! can seal it.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use sha2::{Digest, Sha256};
+
+pub struct Recording {
+    path: PathBuf,
+    file: BufWriter<File>,
+    hasher: Sha256,
+    started: Instant,
+    /// Trailing bytes of an incomplete UTF-8 sequence.
+    partial: Vec<u8>,
+    /// First write error; later writes are skipped.
+    error: Option<io::Error>,
+}
+
+impl Recording {
+    /// Start `<dir>/<id>.cast`.
+    pub fn create(dir: &Path, id: &str, cols: u16, rows: u16, shell: &str) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.cast", id));
+        let file = File::create(&path)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+        }
+        let mut recording = Self {
+            path,
+            file: BufWriter::new(file),
+            hasher: Sha256::new(),
+            started: Instant::now(),
+            partial: Vec::new(),
+            error: None,
+        };
+        recording.write_line(&serde_json::json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": crate::gateway::users::now_secs(),
+            "env": { "SHELL": shell, "TERM": "xterm-256color" },
+        }));
+        Ok(recording)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write_line(&mut self, value: &serde_json::Value) {
+        if self.error.is_some() {
+            return;
+        }
+        let line = format!("{}\n", value);
+        self.hasher.update(line.as_bytes());
+        if let Err(e) = self.file.write_all(line.as_bytes()) {
+            self.error = Some(e);
+        }
+    }
+
+    fn event(&mut self, kind: &str, data: &str) {
+        let t = self.started.elapsed().as_secs_f64();
+        self.write_line(&serde_json::json!([t, kind, data]));
+    }
+
+    /// Record terminal output.  A UTF-8 sequence split across chunks is held
+    /// back until the rest arrives.
+    pub fn output(&mut self, data: &[u8]) {
+        self.partial.extend_from_slice(data);
+        let bytes = std::mem::take(&mut self.partial);
+        let (text, rest) = match std::str::from_utf8(&bytes) {
+            Ok(text) => (text.to_string(), &[][..]),
+            Err(e) if e.error_len().is_none() => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                (String::from_utf8_lossy(valid).into_owned(), rest)
+            }
+            Err(_) => (String::from_utf8_lossy(&bytes).into_owned(), &[][..]),
+        };
+        self.partial = rest.to_vec();
+        if !text.is_empty() {
+            self.event("o", &text);
+        }
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.event("r", &format!("{}x{}", cols, rows));
+    }
+
+    /// Flush the file and return its SHA-256 (hex).
+    pub fn finish(mut self) -> io::Result<String> {
+        if !self.partial.is_empty() {
+            let rest = String::from_utf8_lossy(&std::mem::take(&mut self.partial)).into_owned();
+            self.event("o", &rest);
+        }
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.file.flush()?;
+        Ok(format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_output_and_hashes_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut rec = Recording::create(dir.path(), "term-1", 80, 24, "/bin/bash").unwrap();
+        let path = rec.path().to_path_buf();
+        let snowman = "☃".as_bytes();
+        rec.output(b"hi ");
+        rec.output(&snowman[..1]);
+        rec.output(&snowman[1..]);
+        rec.resize(100, 40);
+        let hash = rec.finish().unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(format!("{:x}", Sha256::digest(text.as_bytes())), hash);
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[1][2], "hi ");
+        assert_eq!(lines[2][2], "☃");
+        assert_eq!(lines[3][1], "r");
+        assert_eq!(lines[3][2], "100x40");
+    }
+}
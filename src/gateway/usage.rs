@@ -452,7 +452,7 @@ impl UsageTracker {
         format!("{:04}-{:02}-{:02}", y, m, d)
     }
 
-    fn days_to_ymd(days: u64) -> (i64, u64, u64) {
+    pub(crate) fn days_to_ymd(days: u64) -> (i64, u64, u64) {
         // Algorithm from http://howardhinnant.github.io/date_algorithms.html
         let z = days as i64 + 719468;
         let era = if z >= 0 { z } else { z - 146096 } / 146097;
@@ -0,0 +1,268 @@
+//! Dashboard user accounts.
+//!
+//! Accounts live in `~/.synapse/auth/users.json` with argon2 password hashes
+//! and a role that maps onto the RPC scopes.  The gateway re-reads the file
+//! when it changes, so `synapse users` edits apply to a running gateway.
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+/// What a user may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// Everything, including config, secrets and user-facing admin methods.
+    Admin,
+    /// Read and write, approvals and pairing — no admin-only methods.
+    Operator,
+    /// Read-only dashboard access.
+    Viewer,
+}
+
+impl UserRole {
+    /// RPC scopes granted to this role (see `rpc::scopes`).
+    pub fn scopes(self) -> &'static [&'static str] {
+        match self {
+            Self::Admin => &["operator.admin"],
+            Self::Operator => &[
+                "operator.read",
+                "operator.write",
+                "operator.approvals",
+                "operator.pairing",
+            ],
+            Self::Viewer => &["operator.read"],
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::Operator => "operator",
+            Self::Viewer => "viewer",
+        }
+    }
+}
+
+impl std::str::FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "admin" => Ok(Self::Admin),
+            "operator" => Ok(Self::Operator),
+            "viewer" | "read-only" | "readonly" => Ok(Self::Viewer),
+            other => Err(format!(
+                "unknown role '{}' (expected admin, operator or viewer)",
+                other
+            )),
+        }
+    }
+}
+
+/// A dashboard account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserAccount {
+    pub username: String,
+    /// Argon2 PHC string.
+    pub password_hash: String,
+    pub role: UserRole,
+    /// Seconds since epoch.
+    pub created_at: u64,
+    /// Tokens issued before this time (seconds) are rejected; bumped when
+    /// the password changes.
+    #[serde(default)]
+    pub tokens_valid_after: u64,
+}
+
+/// The accounts file.
+pub struct UserStore {
+    path: PathBuf,
+    users: Vec<UserAccount>,
+    /// Modification time and size of the file when last read.
+    modified: Option<(SystemTime, u64)>,
+}
+
+impl UserStore {
+    /// Load accounts from `path`; a missing file means no accounts.
+    pub fn load(path: PathBuf) -> Self {
+        let mut store = Self {
+            path,
+            users: Vec::new(),
+            modified: None,
+        };
+        store.reload();
+        store
+    }
+
+    /// Default location: `~/.synapse/auth/users.json`.
+    pub fn default_path() -> PathBuf {
+        super::auth::default_auth_dir().join("users.json")
+    }
+
+    fn reload(&mut self) {
+        self.modified = file_stamp(&self.path);
+        self.users = std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+    }
+
+    /// Re-read the file if it changed since it was last read.
+    pub fn reload_if_changed(&mut self) {
+        if file_stamp(&self.path) != self.modified {
+            self.reload();
+        }
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(&self.users).map_err(|e| e.to_string())?;
+        write_private(&self.path, &json).map_err(|e| e.to_string())?;
+        self.modified = file_stamp(&self.path);
+        Ok(())
+    }
+
+    pub fn list(&self) -> &[UserAccount] {
+        &self.users
+    }
+
+    pub fn get(&self, username: &str) -> Option<&UserAccount> {
+        self.users.iter().find(|u| u.username == username)
+    }
+
+    /// Add an account.
+    pub fn add(&mut self, username: &str, password: &str, role: UserRole) -> Result<(), String> {
+        if username.is_empty()
+            || !username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@'))
+        {
+            return Err(format!("invalid username '{}'", username));
+        }
+        if self.get(username).is_some() {
+            return Err(format!("user '{}' already exists", username));
+        }
+        self.users.push(UserAccount {
+            username: username.to_string(),
+            password_hash: super::auth::AuthState::hash_password(password)?,
+            role,
+            created_at: now_secs(),
+            tokens_valid_after: 0,
+        });
+        self.save()
+    }
+
+    /// Remove an account; its tokens stop working.
+    pub fn remove(&mut self, username: &str) -> Result<(), String> {
+        let before = self.users.len();
+        self.users.retain(|u| u.username != username);
+        if self.users.len() == before {
+            return Err(format!("no user '{}'", username));
+        }
+        self.save()
+    }
+
+    /// Change a password, revoking the user's existing tokens.
+    pub fn set_password(&mut self, username: &str, password: &str) -> Result<(), String> {
+        let hash = super::auth::AuthState::hash_password(password)?;
+        let user = self
+            .users
+            .iter_mut()
+            .find(|u| u.username == username)
+            .ok_or_else(|| format!("no user '{}'", username))?;
+        user.password_hash = hash;
+        user.tokens_valid_after = now_secs();
+        self.save()
+    }
+
+    /// Change a role.
+    pub fn set_role(&mut self, username: &str, role: UserRole) -> Result<(), String> {
+        let user = self
+            .users
+            .iter_mut()
+            .find(|u| u.username == username)
+            .ok_or_else(|| format!("no user '{}'", username))?;
+        user.role = role;
+        self.save()
+    }
+
+    /// The account for `username` if `password` matches.
+    pub fn authenticate(&self, username: &str, password: &str) -> Option<&UserAccount> {
+        self.get(username)
+            .filter(|u| super::auth::verify_password_hash(&u.password_hash, password))
+    }
+}
+
+/// Modification time and size of `path`, to notice edits by other processes.
+pub(crate) fn file_stamp(path: &std::path::Path) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Replace `path` with `content`, readable by the owner only.  Written to a
+/// temp file and renamed, so readers never see a partial or world-readable
+/// file.
+pub(crate) fn write_private(path: &std::path::Path, content: &str) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = std::path::PathBuf::from(tmp);
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp)?;
+    std::io::Write::write_all(&mut file, content.as_bytes())?;
+    std::fs::rename(&tmp, path)
+}
+
+pub(crate) fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accounts_persist_and_authenticate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.json");
+        let mut store = UserStore::load(path.clone());
+        store.add("alice", "s3cret", UserRole::Admin).unwrap();
+        store.add("bob", "hunter2", UserRole::Viewer).unwrap();
+        assert!(store.add("alice", "x", UserRole::Viewer).is_err());
+        assert!(store.add("bad name", "x", UserRole::Viewer).is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let mut reloaded = UserStore::load(path.clone());
+        assert_eq!(reloaded.list().len(), 2);
+        assert!(reloaded.authenticate("alice", "s3cret").is_some());
+        assert!(reloaded.authenticate("alice", "wrong").is_none());
+        assert_eq!(
+            reloaded.authenticate("bob", "hunter2").map(|u| u.role),
+            Some(UserRole::Viewer)
+        );
+
+        store.set_password("bob", "correct horse").unwrap();
+        store.remove("alice").unwrap();
+        reloaded.reload_if_changed();
+        assert!(reloaded.get("alice").is_none());
+        assert!(reloaded.authenticate("bob", "correct horse").is_some());
+        assert!(reloaded.get("bob").unwrap().tokens_valid_after > 0);
+    }
+}
@@ -1,55 +1,429 @@
 //! Webhook endpoints — trigger agent runs via HTTP POST.
+//!
+//! Webhooks are declared as `[[webhook]]` entries in config.  A request to
+//! `POST /api/webhooks/{name}` is authenticated by its HMAC signature
+//! (GitHub or Stripe style) — or by gateway auth if the webhook has no
+//! secret — then the entry's prompt template is rendered from the JSON body
+//! and run through its agent.  The answer is returned to the caller, or with
+//! `async = true` the caller gets a run id to poll at
+//! `GET /api/webhooks/{name}/runs/{run_id}`.  Either way it can also be
+//! posted to a channel.
 
+use std::sync::Arc;
+
+use axum::body::Bytes;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, Method, StatusCode};
 use axum::response::Json;
-use axum::routing::post;
+use axum::routing::{get, post};
 use axum::Router;
-use serde::{Deserialize, Serialize};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::Sha256;
 
 use super::state::AppState;
+use crate::agent::headless::{self, RunSession};
+use crate::config::{WebhookConfig, WebhookSignature};
+use crate::cron::{CronRun, CronRunLog};
 
-#[derive(Deserialize)]
-pub struct WebhookPayload {
-    /// The prompt/message to send to the agent.
-    pub message: String,
-}
+type HmacSha256 = Hmac<Sha256>;
+
+/// Channel name webhook runs are attributed to.
+const WEBHOOK_CHANNEL: &str = "webhook";
+
+/// How far a Stripe-style signature timestamp may be from now.
+const STRIPE_TOLERANCE_SECS: i64 = 300;
 
 #[derive(Serialize)]
 pub struct WebhookResponse {
     pub status: String,
-    pub response: String,
+    pub run_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
 }
 
-pub fn routes() -> Router<AppState> {
+/// The trigger endpoint, which authenticates requests itself.
+pub fn public_routes() -> Router<AppState> {
     Router::new().route("/api/webhooks/{name}", post(handle_webhook))
 }
 
+/// Run lookups, behind gateway auth.
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/webhooks/{name}/runs/{run_id}", get(handle_get_run))
+}
+
+/// Run history for webhooks: `~/.synapse/webhooks/runs`.
+fn run_log() -> CronRunLog {
+    CronRunLog::new(
+        dirs::home_dir()
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join(".synapse")
+            .join("webhooks")
+            .join("runs"),
+    )
+}
+
 async fn handle_webhook(
     State(state): State<AppState>,
     Path(name): Path<String>,
-    Json(payload): Json<WebhookPayload>,
-) -> Result<Json<WebhookResponse>, (StatusCode, String)> {
-    tracing::info!(webhook = %name, "webhook triggered");
-
-    let messages = vec![
-        synaptic::core::Message::system(format!(
-            "You are Synapse, handling webhook '{}'. Respond concisely.",
-            name
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<WebhookResponse>), (StatusCode, String)> {
+    let hook = state
+        .core
+        .config
+        .webhooks
+        .iter()
+        .find(|w| w.name == name)
+        .cloned()
+        .ok_or((StatusCode::NOT_FOUND, format!("unknown webhook '{}'", name)))?;
+
+    authenticate(&state, &hook, &headers, &body).await?;
+
+    let payload: Value = serde_json::from_slice(&body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+    let prompt = render_template(&hook.prompt, &payload, &headers);
+    if prompt.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "rendered prompt is empty".into()));
+    }
+    let session_key = hook
+        .session_key
+        .as_deref()
+        .map(|t| render_template(t, &payload, &headers));
+
+    let mut run = CronRun::start(&hook.name);
+    run.id = format!("run-{}", uuid::Uuid::new_v4().simple());
+    let run_id = run.id.clone();
+    tracing::info!(webhook = %name, agent = %hook.agent, run_id = %run_id, "webhook triggered");
+
+    let log = Arc::new(run_log());
+    if hook.run_async {
+        if let Err(e) = log.record(&run) {
+            tracing::warn!(webhook = %name, error = %e, "failed to record webhook run");
+        }
+        tokio::spawn(async move {
+            let run = execute(&state, &hook, run, &prompt, session_key).await;
+            if let Err(e) = log.record(&run) {
+                tracing::warn!(webhook = %hook.name, error = %e, "failed to record webhook run");
+            }
+        });
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(WebhookResponse {
+                status: "accepted".to_string(),
+                run_id,
+                response: None,
+            }),
+        ));
+    }
+
+    let run = execute(&state, &hook, run, &prompt, session_key).await;
+    if let Err(e) = log.record(&run) {
+        tracing::warn!(webhook = %name, error = %e, "failed to record webhook run");
+    }
+    match run.error {
+        Some(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        None => Ok((
+            StatusCode::OK,
+            Json(WebhookResponse {
+                status: "ok".to_string(),
+                run_id,
+                response: run.result,
+            }),
         )),
-        synaptic::core::Message::human(&payload.message),
-    ];
-
-    let request = synaptic::core::ChatRequest::new(messages);
-    let response = state
-        .agent
-        .model
-        .chat(request)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-    Ok(Json(WebhookResponse {
-        status: "ok".to_string(),
-        response: response.message.content().to_string(),
-    }))
+    }
+}
+
+async fn handle_get_run(
+    Path((name, run_id)): Path<(String, String)>,
+) -> Result<Json<CronRun>, (StatusCode, String)> {
+    run_log()
+        .get(&name, &run_id)
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("unknown run '{}'", run_id)))
+}
+
+/// Check the request's signature, or gateway auth for unsigned webhooks.
+async fn authenticate(
+    state: &AppState,
+    hook: &WebhookConfig,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), (StatusCode, String)> {
+    if hook.secret.is_none() && hook.secret_env.is_none() {
+        let path = format!("/api/webhooks/{}", hook.name);
+        return super::auth::authorize(state, &Method::POST, &path, headers).map(|_| ());
+    }
+
+    let secret = crate::config::resolve_secret(
+        hook.secret.as_deref(),
+        hook.secret_env.as_deref(),
+        "webhook secret",
+    )
+    .map_err(|e| {
+        tracing::error!(webhook = %hook.name, error = %e, "webhook secret unavailable");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "webhook secret unavailable".to_string(),
+        )
+    })?;
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let valid = match hook.signature {
+        WebhookSignature::Github => header("x-hub-signature-256")
+            .is_some_and(|sig| verify_github(secret.as_bytes(), body, sig)),
+        WebhookSignature::Stripe => header("stripe-signature").is_some_and(|sig| {
+            verify_stripe(secret.as_bytes(), body, sig, chrono::Utc::now().timestamp())
+        }),
+    };
+    if valid {
+        Ok(())
+    } else {
+        tracing::warn!(webhook = %hook.name, "webhook signature rejected");
+        Err((StatusCode::UNAUTHORIZED, "invalid signature".into()))
+    }
+}
+
+/// Verify `X-Hub-Signature-256: sha256=<hex>` over the raw body.
+fn verify_github(secret: &[u8], body: &[u8], header: &str) -> bool {
+    header
+        .strip_prefix("sha256=")
+        .and_then(decode_hex)
+        .is_some_and(|sig| verify_hmac(secret, &[body], &sig))
+}
+
+/// Verify `Stripe-Signature: t=<ts>,v1=<hex>[,v1=<hex>...]` over
+/// `"<ts>.<body>"`, rejecting timestamps outside the tolerance window.
+fn verify_stripe(secret: &[u8], body: &[u8], header: &str, now: i64) -> bool {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", t)) => timestamp = Some(t),
+            Some(("v1", sig)) => signatures.extend(decode_hex(sig)),
+            _ => {}
+        }
+    }
+    let Some(ts) = timestamp else {
+        return false;
+    };
+    if ts
+        .parse::<i64>()
+        .map_or(true, |t| (now - t).abs() > STRIPE_TOLERANCE_SECS)
+    {
+        return false;
+    }
+    signatures
+        .iter()
+        .any(|sig| verify_hmac(secret, &[ts.as_bytes(), b".", body], sig))
+}
+
+/// Constant-time check of an HMAC-SHA256 over the concatenated `parts`.
+fn verify_hmac(secret: &[u8], parts: &[&[u8]], signature: &[u8]) -> bool {
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret) else {
+        return false;
+    };
+    for part in parts {
+        mac.update(part);
+    }
+    mac.verify_slice(signature).is_ok()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Fill `{{path}}` placeholders from the payload and headers.  Unknown
+/// paths render as empty strings.
+fn render_template(template: &str, payload: &Value, headers: &HeaderMap) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let path = rest[start + 2..start + 2 + len].trim();
+        out.push_str(&resolve_placeholder(path, payload, headers));
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn resolve_placeholder(path: &str, payload: &Value, headers: &HeaderMap) -> String {
+    if let Some(name) = path.strip_prefix("headers.") {
+        return headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+    }
+    let path = path.strip_prefix("body.").unwrap_or(path);
+    let value = if path == "body" {
+        Some(payload)
+    } else {
+        path.split('.').try_fold(payload, |v, key| match v {
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => v.get(key),
+        })
+    };
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(v @ (Value::Object(_) | Value::Array(_))) => {
+            serde_json::to_string_pretty(v).unwrap_or_default()
+        }
+        Some(v) => v.to_string(),
+    }
+}
+
+/// Run the webhook's agent and deliver the answer; returns the finished run.
+async fn execute(
+    state: &AppState,
+    hook: &WebhookConfig,
+    mut run: CronRun,
+    prompt: &str,
+    session_key: Option<String>,
+) -> CronRun {
+    let key = format!(
+        "agent:{}:{}:{}:{}",
+        hook.agent,
+        WEBHOOK_CHANNEL,
+        hook.name,
+        session_key.as_deref().unwrap_or(&run.id)
+    );
+    let session = RunSession {
+        key: key.clone(),
+        channel: WEBHOOK_CHANNEL,
+        display_name: format!("webhook {}", hook.name),
+        reuse: session_key.is_some(),
+    };
+    let sessions = &state.session.sessions;
+    let session_id = match session.open(sessions).await {
+        Ok(id) => id,
+        Err(e) => return failed(hook, run, e),
+    };
+    run.session_id = Some(session_id.clone());
+
+    let (content, usage) = match headless::run(
+        &state.core.config,
+        state.agent.model.clone(),
+        sessions,
+        &session_id,
+        WEBHOOK_CHANNEL,
+        &hook.agent,
+        prompt,
+        crate::agent::SessionKind::Cron,
+        // Webhook payloads come from outside any chat: agent-wide memories only.
+        headless::memory_scope(None, None, &key),
+        headless::budget_guard(
+            &state.agent.budgets,
+            &hook.agent,
+            WEBHOOK_CHANNEL,
+            None,
+            hook.deliver.as_ref(),
+        ),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return failed(hook, run, e),
+    };
+
+    if let Some(ref target) = hook.deliver {
+        match headless::deliver(&state.channel.delivery, target, &content).await {
+            Ok(id) => run.delivery_id = Some(id),
+            Err(e) => {
+                run.result = Some(content);
+                return failed(hook, run, e);
+            }
+        }
+    }
+
+    tracing::info!(webhook = %hook.name, agent = %hook.agent, session = %session_id, "webhook run completed");
+    run.succeed(content, usage)
+}
+
+fn failed(hook: &WebhookConfig, run: CronRun, error: String) -> CronRun {
+    tracing::error!(webhook = %hook.name, agent = %hook.agent, error = %error, "webhook run failed");
+    run.fail(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &[u8], msg: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret).unwrap();
+        mac.update(msg);
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    #[test]
+    fn github_signature() {
+        let body = br#"{"action":"completed"}"#;
+        let header = format!("sha256={}", sign(b"s3cret", body));
+        assert!(verify_github(b"s3cret", body, &header));
+        assert!(!verify_github(b"other", body, &header));
+        assert!(!verify_github(b"s3cret", b"{}", &header));
+        assert!(!verify_github(b"s3cret", body, "sha1=abcd"));
+        assert!(!verify_github(b"s3cret", body, "sha256=zz"));
+    }
+
+    #[test]
+    fn stripe_signature_and_tolerance() {
+        let body = br#"{"type":"invoice.paid"}"#;
+        let now = 1_700_000_000;
+        let sig = sign(
+            b"whsec",
+            format!("{}.{}", now, std::str::from_utf8(body).unwrap()).as_bytes(),
+        );
+        let header = format!("t={},v1=deadbeef,v1={}", now, sig);
+        assert!(verify_stripe(b"whsec", body, &header, now + 10));
+        assert!(!verify_stripe(
+            b"whsec",
+            body,
+            &header,
+            now + STRIPE_TOLERANCE_SECS + 1
+        ));
+        assert!(!verify_stripe(b"wrong", body, &header, now));
+        assert!(!verify_stripe(b"whsec", body, &format!("v1={}", sig), now));
+    }
+
+    #[test]
+    fn template_renders_body_paths_and_headers() {
+        let payload = serde_json::json!({
+            "repository": {"full_name": "acme/api"},
+            "jobs": [{"name": "test", "status": 1}],
+            "flag": true,
+        });
+        let mut headers = HeaderMap::new();
+        headers.insert("x-github-event", "workflow_run".parse().unwrap());
+
+        let out = render_template(
+            "{{headers.x-github-event}} on {{ repository.full_name }}: {{jobs.0.name}}={{body.jobs.0.status}} {{flag}}{{missing}}",
+            &payload,
+            &headers,
+        );
+        assert_eq!(out, "workflow_run on acme/api: test=1 true");
+
+        let whole = render_template("{{body}}", &payload, &headers);
+        assert!(whole.contains("\"full_name\": \"acme/api\""));
+        assert_eq!(
+            render_template("open {{ end", &payload, &headers),
+            "open {{ end"
+        );
+    }
 }
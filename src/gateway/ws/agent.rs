@@ -34,6 +34,7 @@ pub(super) async fn handle_v3_agent(
     session_key_str: &str,
     state: &AppState,
     conn_id: &str,
+    user: Option<&str>,
     request_id_rpc: &str,
     params: &Value,
     seq: &AtomicU64,
@@ -151,6 +152,7 @@ pub(super) async fn handle_v3_agent(
         content.clone(),
         conn_id,
     );
+    msg.sender.username = user.map(String::from);
 
     // Convert ws attachments to inbound message attachments
     if !ws_attachments.is_empty() {
@@ -4,16 +4,22 @@ use axum::extract::ws::{Message as WsMessage, WebSocket};
 use futures::stream::SplitSink;
 use futures::SinkExt;
 
+use crate::gateway::api_tokens;
 use crate::gateway::rpc::{
     AuthResult, ConnectParams, FeatureInfo, HelloOk, Role, RpcError, ServerFrame, ServerInfo,
     SnapshotInfo, StateVersion, GATEWAY_EVENTS, PROTOCOL_VERSION,
 };
 use crate::gateway::state::AppState;
+use crate::gateway::users::UserRole;
 
 /// Result of a successful WebSocket authentication.
 pub(super) struct WsAuthResult {
     pub role: Role,
     pub scopes: HashSet<String>,
+    /// Set for connections authenticated with an API token.
+    pub api_token_id: Option<String>,
+    /// Dashboard user the connection signed in as.
+    pub user: Option<String>,
 }
 
 /// Validate credentials from a v3 connect request.
@@ -29,20 +35,56 @@ pub(super) async fn authenticate(
 ) -> Option<WsAuthResult> {
     match &state.core.auth {
         Some(auth_state) if auth_state.config.enabled => {
+            // API tokens carry their own scopes.
+            let api_token = connect_params
+                .auth
+                .as_ref()
+                .and_then(|a| a.token.as_deref())
+                .filter(|t| t.starts_with(api_tokens::TOKEN_PREFIX));
+            if let Some(secret) = api_token {
+                return match auth_state.authenticate_api_token(secret) {
+                    Ok(token) => {
+                        tracing::debug!(%conn_id, token = %token.id, "v3 connect authenticated with API token");
+                        Some(WsAuthResult {
+                            role: Role::ApiToken,
+                            scopes: token.scopes.iter().cloned().collect(),
+                            api_token_id: Some(token.id),
+                            user: None,
+                        })
+                    }
+                    Err(e) => {
+                        let err_frame =
+                            ServerFrame::err(req_id, RpcError::forbidden(e.to_string()));
+                        let _ = sender
+                            .send(WsMessage::Text(
+                                serde_json::to_string(&err_frame).unwrap().into(),
+                            ))
+                            .await;
+                        tracing::warn!(%conn_id, error = %e, "v3 connect rejected: API token refused");
+                        None
+                    }
+                };
+            }
+
             // Auth is enabled — validate token or password
-            let authenticated = if let Some(ref auth_params) = connect_params.auth {
+            let identity = connect_params.auth.as_ref().and_then(|auth_params| {
                 if let Some(ref token) = auth_params.token {
-                    auth_state.is_valid_session(token).await
+                    auth_state.validate_token(token)
                 } else if let Some(ref password) = auth_params.password {
-                    auth_state.verify_password(password)
+                    auth_state.authenticate(auth_params.username.as_deref(), password)
                 } else {
-                    false
+                    None
                 }
-            } else {
-                false
-            };
+            });
 
-            if !authenticated {
+            let role = match connect_params.role.as_deref() {
+                Some("node") => Role::Node,
+                _ => Role::Operator,
+            };
+            // Read-only accounts cannot act as nodes.
+            let identity =
+                identity.filter(|i| role == Role::Operator || i.role != UserRole::Viewer);
+            let Some(identity) = identity else {
                 let err_frame =
                     ServerFrame::err(req_id, RpcError::forbidden("Authentication failed"));
                 let _ = sender
@@ -52,22 +94,25 @@ pub(super) async fn authenticate(
                     .await;
                 tracing::warn!(%conn_id, "v3 connect rejected: auth failed");
                 return None;
-            }
-
-            // Determine role and scopes from the connect request
-            let role = match connect_params.role.as_deref() {
-                Some("node") => Role::Node,
-                _ => Role::Operator,
             };
-            let mut granted_scopes: HashSet<String> =
-                connect_params.scopes.iter().cloned().collect();
-            // Default to operator.admin for authenticated operators
+
+            // Requested scopes are narrowed to what the user's role allows;
+            // with none requested the connection gets the role's scopes.
+            let mut granted_scopes: HashSet<String> = connect_params
+                .scopes
+                .iter()
+                .filter(|s| identity.allows_scope(s))
+                .cloned()
+                .collect();
             if role == Role::Operator && granted_scopes.is_empty() {
-                granted_scopes.insert("operator.admin".to_string());
+                granted_scopes = identity.scopes();
             }
+            tracing::debug!(%conn_id, user = %identity.username, role = identity.role.as_str(), "v3 connect authenticated");
             Some(WsAuthResult {
                 role,
                 scopes: granted_scopes,
+                api_token_id: None,
+                user: Some(identity.username),
             })
         }
         _ => {
@@ -80,7 +125,12 @@ pub(super) async fn authenticate(
             if scopes.is_empty() {
                 scopes.insert("operator.admin".to_string());
             }
-            Some(WsAuthResult { role, scopes })
+            Some(WsAuthResult {
+                role,
+                scopes,
+                api_token_id: None,
+                user: None,
+            })
         }
     }
 }
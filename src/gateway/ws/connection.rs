@@ -6,6 +6,7 @@ use axum::extract::ws::{Message as WsMessage, WebSocket};
 use futures::stream::{SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
 
+use crate::gateway::rpc::router::authorize;
 use crate::gateway::rpc::{
     ClientFrame, ConnectParams, RpcContext, RpcError, ServerFrame, PROTOCOL_VERSION,
 };
@@ -56,6 +57,7 @@ pub(super) async fn handle_v3_connection(
         };
     let role = auth_result.role;
     let scopes = auth_result.scopes;
+    let user = auth_result.user;
 
     // --- Register connection in broadcaster ---
     let mut event_rx = state.network.broadcaster.register(conn_id.clone()).await;
@@ -68,6 +70,7 @@ pub(super) async fn handle_v3_connection(
         role,
         scopes: scopes.clone(),
         broadcaster: state.network.broadcaster.clone(),
+        api_token_id: auth_result.api_token_id,
     });
 
     // --- Build and send hello-ok response ---
@@ -138,6 +141,16 @@ pub(super) async fn handle_v3_connection(
                 match frame {
                     ClientFrame::Request { id, method, params } => {
                         if method == "agent" || method == "chat.send" {
+                            if let Err(e) = authorize(&rpc_ctx, &method) {
+                                let err = ServerFrame::err(&id, e);
+                                let _ = sender
+                                    .send(WsMessage::Text(
+                                        serde_json::to_string(&err).unwrap().into(),
+                                    ))
+                                    .await;
+                                continue;
+                            }
+
                             // Extract sessionKey from params (default: "main")
                             let sk = params
                                 .get("sessionKey")
@@ -169,6 +182,7 @@ pub(super) async fn handle_v3_connection(
                                 &sk,
                                 &state,
                                 &conn_id,
+                                user.as_deref(),
                                 &id,
                                 &params,
                                 &seq,
@@ -2,13 +2,17 @@
 //!
 //! When enabled, the heartbeat runner periodically loads a prompt from
 //! `HEARTBEAT.md` (or a configured file) and runs the agent. Results can
-//! optionally be delivered to a target channel.
+//! optionally be delivered to a target channel.  With several instances,
+//! only the one holding the leader lease runs heartbeats.
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use serde::Deserialize;
 use tokio::sync::watch;
 
+use crate::leader::LeaderElection;
+
 /// Configuration for heartbeat-driven agent runs.
 #[derive(Debug, Clone, Deserialize)]
 #[allow(dead_code)]
@@ -144,11 +148,22 @@ pub fn is_within_active_hours(window: &str) -> bool {
 pub struct HeartbeatRunner {
     config: HeartbeatConfig,
     shutdown: watch::Receiver<bool>,
+    leader: Option<Arc<LeaderElection>>,
 }
 
 impl HeartbeatRunner {
     pub fn new(config: HeartbeatConfig, shutdown: watch::Receiver<bool>) -> Self {
-        Self { config, shutdown }
+        Self {
+            config,
+            shutdown,
+            leader: None,
+        }
+    }
+
+    /// Only run heartbeats while `leader` holds the lease.
+    pub fn with_leader(mut self, leader: Arc<LeaderElection>) -> Self {
+        self.leader = Some(leader);
+        self
     }
 
     /// Start the heartbeat loop. Returns a [`tokio::task::JoinHandle`].
@@ -180,6 +195,11 @@ impl HeartbeatRunner {
             loop {
                 tokio::select! {
                     _ = ticker.tick() => {
+                        if self.leader.as_ref().is_some_and(|l| !l.is_leader()) {
+                            tracing::debug!("heartbeat: not the leader, skipping");
+                            continue;
+                        }
+
                         // Check active hours gate
                         if let Some(ref window) = self.config.active_hours {
                             if !is_within_active_hours(window) {
@@ -0,0 +1,418 @@
+//! Lease-based leader election for multi-instance gateways.
+//!
+//! Singleton work — the cron driver, heartbeat runs, session maintenance and
+//! memory consolidation — must only happen on one instance.  With `gateway.leader_election` enabled,
+//! instances compete for a shared lease with a TTL.  The holder renews it
+//! every third of the TTL; if it stops renewing (crash, partition) any other
+//! instance takes over once the lease expires.  Each change of holder bumps a
+//! fencing token, so work started under a lease that has since been lost can
+//! be recognised and dropped.
+//!
+//! The lease is a JSON file guarded by `flock` (`gateway.leader_lease_url`,
+//! default `~/.synapse/leader.lease`), for instances on one host or on a
+//! shared filesystem with working locks.
+//!
+//! Without leader election the process is always the leader.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SynapseConfig;
+
+/// Lease TTL when `gateway.leader_lease_ttl_secs` is unset.
+const DEFAULT_TTL_SECS: u64 = 30;
+
+/// A lease record as stored by a backend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+    /// Instance id of the holder.
+    pub holder: String,
+    /// Fencing token, incremented every time the lease changes hands.
+    pub token: u64,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Lease {
+    fn is_live(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now
+    }
+}
+
+/// The record `holder` should write to take or renew the lease, or `None`
+/// if another instance holds a live one.
+fn claim(
+    current: Option<&Lease>,
+    holder: &str,
+    ttl: Duration,
+    now: DateTime<Utc>,
+) -> Option<Lease> {
+    let expires_at = now
+        + chrono::Duration::from_std(ttl)
+            .unwrap_or_else(|_| chrono::Duration::seconds(DEFAULT_TTL_SECS as i64));
+    match current {
+        Some(lease) if lease.is_live(now) && lease.holder == holder => Some(Lease {
+            expires_at,
+            ..lease.clone()
+        }),
+        Some(lease) if lease.is_live(now) => None,
+        _ => Some(Lease {
+            holder: holder.to_string(),
+            token: current.map_or(0, |l| l.token) + 1,
+            expires_at,
+        }),
+    }
+}
+
+/// The record to write when `holder` gives up the lease: expired, but
+/// keeping the token so the next holder's is higher.
+fn relinquish(current: Option<Lease>, holder: &str, now: DateTime<Utc>) -> Option<Lease> {
+    current
+        .filter(|l| l.holder == holder && l.is_live(now))
+        .map(|l| Lease {
+            expires_at: now,
+            ..l
+        })
+}
+
+/// Shared storage for the lease.
+#[async_trait]
+pub trait LeaseBackend: Send + Sync {
+    /// Take or renew the lease for `holder`.  Returns the lease in effect
+    /// afterwards, which belongs to another instance if it was taken.
+    async fn acquire(&self, holder: &str, ttl: Duration) -> Result<Lease, String>;
+
+    /// Give up the lease if `holder` has it.
+    async fn release(&self, holder: &str) -> Result<(), String>;
+}
+
+/// Lease kept in a JSON file, read and written under an exclusive `flock`
+/// on a sibling `.lock` file.
+pub struct FileLease {
+    path: PathBuf,
+}
+
+impl FileLease {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Default path: `~/.synapse/leader.lease`.
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".synapse")
+            .join("leader.lease")
+    }
+
+    /// Apply `update` to the current record while holding the lock, writing
+    /// back what it returns.  Returns the record in effect afterwards.
+    fn locked(
+        path: &std::path::Path,
+        update: impl FnOnce(Option<Lease>) -> Option<Lease>,
+    ) -> std::io::Result<Option<Lease>> {
+        let _lock = lock_file(&path.with_extension("lock"))?;
+        let current: Option<Lease> = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok());
+        match update(current.clone()) {
+            Some(next) if Some(&next) != current.as_ref() => {
+                let tmp = path.with_extension("lease.tmp");
+                std::fs::write(&tmp, serde_json::to_string(&next)?)?;
+                std::fs::rename(&tmp, path)?;
+                Ok(Some(next))
+            }
+            _ => Ok(current),
+        }
+    }
+
+    async fn update(
+        &self,
+        update: impl FnOnce(Option<Lease>) -> Option<Lease> + Send + 'static,
+    ) -> Result<Option<Lease>, String> {
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || Self::locked(&path, update))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("lease file {}: {}", self.path.display(), e))
+    }
+}
+
+#[async_trait]
+impl LeaseBackend for FileLease {
+    async fn acquire(&self, holder: &str, ttl: Duration) -> Result<Lease, String> {
+        let holder = holder.to_string();
+        self.update(move |current| claim(current.as_ref(), &holder, ttl, Utc::now()))
+            .await?
+            .ok_or_else(|| "lease file is empty".to_string())
+    }
+
+    async fn release(&self, holder: &str) -> Result<(), String> {
+        let holder = holder.to_string();
+        self.update(move |current| relinquish(current, &holder, Utc::now()))
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Open `path` and take an exclusive `flock` on it, released when the
+/// returned file is dropped.
+fn lock_file(path: &std::path::Path) -> std::io::Result<std::fs::File> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let lock = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    lock.lock()?;
+    Ok(lock)
+}
+
+/// This instance's view of the election.
+pub struct LeaderElection {
+    instance_id: String,
+    /// `None` when leader election is disabled.
+    backend: Option<Arc<dyn LeaseBackend>>,
+    ttl: Duration,
+    /// Last lease seen, whoever holds it.
+    lease: Mutex<Option<Lease>>,
+}
+
+impl LeaderElection {
+    /// An election without competitors: this process always leads.
+    pub fn single() -> Arc<Self> {
+        let instance_id = "local".to_string();
+        Arc::new(Self {
+            lease: Mutex::new(Some(Lease {
+                holder: instance_id.clone(),
+                token: 0,
+                expires_at: DateTime::<Utc>::MAX_UTC,
+            })),
+            instance_id,
+            backend: None,
+            ttl: Duration::from_secs(DEFAULT_TTL_SECS),
+        })
+    }
+
+    /// An election over `backend`.  Call [`LeaderElection::spawn`] to start
+    /// competing.
+    pub fn new(
+        instance_id: impl Into<String>,
+        backend: Arc<dyn LeaseBackend>,
+        ttl: Duration,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            instance_id: instance_id.into(),
+            backend: Some(backend),
+            ttl,
+            lease: Mutex::new(None),
+        })
+    }
+
+    /// Join the election configured under `[gateway]`, or lead alone if
+    /// `leader_election` is off.
+    pub fn start(config: &SynapseConfig) -> Arc<Self> {
+        let Some(gw) = config
+            .gateway
+            .as_ref()
+            .filter(|gw| gw.leader_election.unwrap_or(false))
+        else {
+            return Self::single();
+        };
+
+        let instance_id = gw.instance_id.clone().unwrap_or_else(default_instance_id);
+        let ttl = Duration::from_secs(gw.leader_lease_ttl_secs.unwrap_or(DEFAULT_TTL_SECS).max(3));
+        let backend: Arc<dyn LeaseBackend> = Arc::new(FileLease::new(
+            gw.leader_lease_url
+                .as_deref()
+                .map(PathBuf::from)
+                .unwrap_or_else(FileLease::default_path),
+        ));
+        tracing::info!(instance = %instance_id, ttl_secs = ttl.as_secs(), "leader election enabled");
+
+        let election = Self::new(instance_id, backend, ttl);
+        election.spawn();
+        election
+    }
+
+    /// Renew (or try to take) the lease every third of the TTL.
+    pub fn spawn(self: &Arc<Self>) {
+        let election = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(election.ttl / 3);
+            loop {
+                interval.tick().await;
+                election.renew().await;
+            }
+        });
+    }
+
+    /// One election round.  Returns whether this instance leads afterwards.
+    pub async fn renew(&self) -> bool {
+        let Some(ref backend) = self.backend else {
+            return true;
+        };
+        let was_leader = self.is_leader();
+        let lease = match backend.acquire(&self.instance_id, self.ttl).await {
+            Ok(lease) => lease,
+            Err(e) => {
+                // Keep what we have; the lease lapses on its own if this persists.
+                tracing::warn!(instance = %self.instance_id, error = %e, "leader lease renewal failed");
+                return self.is_leader();
+            }
+        };
+
+        let leads = lease.holder == self.instance_id;
+        let previous = self
+            .lease
+            .lock()
+            .expect("lock poisoned")
+            .replace(lease.clone());
+        if leads && !was_leader {
+            tracing::info!(instance = %self.instance_id, token = lease.token, "acquired leadership");
+        } else if !leads && was_leader {
+            tracing::warn!(instance = %self.instance_id, leader = %lease.holder, "lost leadership");
+        } else if !leads && previous.is_none_or(|p| p.holder != lease.holder) {
+            tracing::info!(instance = %self.instance_id, leader = %lease.holder, "following leader");
+        }
+        leads
+    }
+
+    /// Fencing token of the lease if this instance holds it and it hasn't
+    /// expired.  Work that must not overlap with another leader's should
+    /// check this again before committing its results.
+    pub fn token(&self) -> Option<u64> {
+        let lease = self.lease.lock().expect("lock poisoned");
+        lease
+            .as_ref()
+            .filter(|l| l.holder == self.instance_id && l.is_live(Utc::now()))
+            .map(|l| l.token)
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.token().is_some()
+    }
+
+    /// Give up the lease, e.g. on shutdown, so a standby takes over at once.
+    pub async fn release(&self) {
+        let Some(ref backend) = self.backend else {
+            return;
+        };
+        if !self.is_leader() {
+            return;
+        }
+        self.lease.lock().expect("lock poisoned").take();
+        match backend.release(&self.instance_id).await {
+            Ok(()) => tracing::info!(instance = %self.instance_id, "released leadership"),
+            Err(e) => {
+                tracing::warn!(instance = %self.instance_id, error = %e, "failed to release leader lease")
+            }
+        }
+    }
+}
+
+/// `<hostname>:<pid>`, unique per process even with several instances on
+/// one host.
+fn default_instance_id() -> String {
+    let host = std::process::Command::new("hostname")
+        .output()
+        .ok()
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "synapse".to_string());
+    format!("{}:{}", host, std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(n: i64) -> chrono::Duration {
+        chrono::Duration::seconds(n)
+    }
+
+    #[test]
+    fn claim_takes_renews_and_fences() {
+        let now = Utc::now();
+        let ttl = Duration::from_secs(30);
+
+        let a = claim(None, "a", ttl, now).unwrap();
+        assert_eq!((a.holder.as_str(), a.token), ("a", 1));
+
+        // Renewal extends the lease without changing the token.
+        let renewed = claim(Some(&a), "a", ttl, now + secs(10)).unwrap();
+        assert_eq!(renewed.token, 1);
+        assert!(renewed.expires_at > a.expires_at);
+
+        // A live lease can't be taken; an expired one can, with a new token.
+        assert!(claim(Some(&renewed), "b", ttl, now + secs(20)).is_none());
+        let b = claim(Some(&renewed), "b", ttl, now + secs(60)).unwrap();
+        assert_eq!((b.holder.as_str(), b.token), ("b", 2));
+
+        // Releasing expires the lease but keeps the token.
+        let released = relinquish(Some(b.clone()), "b", now + secs(61)).unwrap();
+        assert_eq!(released.token, 2);
+        assert!(relinquish(Some(b), "a", now + secs(61)).is_none());
+        assert_eq!(
+            claim(Some(&released), "a", ttl, now + secs(61))
+                .unwrap()
+                .token,
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn file_lease_fails_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend: Arc<dyn LeaseBackend> =
+            Arc::new(FileLease::new(dir.path().join("leader.lease")));
+        let ttl = Duration::from_secs(3);
+        let a = LeaderElection::new("a", backend.clone(), ttl);
+        let b = LeaderElection::new("b", backend.clone(), ttl);
+
+        assert!(a.renew().await);
+        assert!(!b.renew().await);
+        assert!(a.is_leader());
+        assert!(!b.is_leader());
+        let token = a.token().unwrap();
+
+        // `a` shuts down cleanly; `b` takes over with a higher token.
+        a.release().await;
+        assert!(!a.is_leader());
+        assert!(b.renew().await);
+        assert!(b.token().unwrap() > token);
+        assert!(!a.renew().await);
+    }
+
+    #[tokio::test]
+    async fn file_lease_expires_without_renewal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("leader.lease");
+        let backend = FileLease::new(path.clone());
+
+        // A crashed leader's lease, already past its TTL.
+        let stale = Lease {
+            holder: "crashed".to_string(),
+            token: 7,
+            expires_at: Utc::now() - secs(1),
+        };
+        std::fs::write(&path, serde_json::to_string(&stale).unwrap()).unwrap();
+
+        let lease = backend.acquire("b", Duration::from_secs(30)).await.unwrap();
+        assert_eq!((lease.holder.as_str(), lease.token), ("b", 8));
+    }
+
+    #[test]
+    fn single_instance_always_leads() {
+        let election = LeaderElection::single();
+        assert!(election.is_leader());
+        assert_eq!(election.token(), Some(0));
+    }
+}
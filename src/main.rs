@@ -1,6 +1,7 @@
 #[cfg(feature = "web")]
 mod acp;
 mod agent;
+mod audit;
 #[cfg(feature = "broadcast")]
 mod broadcast;
 mod cli;
@@ -14,6 +15,7 @@ mod heartbeat;
 mod hooks;
 mod hub;
 mod init;
+mod leader;
 mod memory;
 mod notify;
 mod plugins;
@@ -138,10 +140,26 @@ async fn run(cli: Cli) -> crate::error::Result<()> {
         );
     }
 
-    // Start scheduler if configured (runs in background)
-    let _scheduler = if config.schedules.as_ref().is_some_and(|s| !s.is_empty()) {
+    // Start scheduler if configured (runs in background). The gateway starts
+    // its own so scheduled results can go out through its channel adapters.
+    #[cfg(feature = "web")]
+    let gateway_schedules = matches!(cli.command, Some(Command::Serve { .. }));
+    #[cfg(not(feature = "web"))]
+    let gateway_schedules = false;
+    let run_schedules = !gateway_schedules && scheduler::has_jobs(&config);
+
+    // With several instances, only the lease holder runs singleton work
+    // (scheduled jobs, heartbeats, session maintenance). One-off commands
+    // that run none of it stay out of the election.
+    let leader = if gateway_schedules || run_schedules || config.heartbeat.enabled {
+        leader::LeaderElection::start(&config)
+    } else {
+        leader::LeaderElection::single()
+    };
+
+    let _scheduler = if run_schedules {
         let model = agent::build_model(&config, cli.model_override.as_deref())?;
-        match scheduler::start_scheduler(&config, model).await {
+        match scheduler::start_scheduler(&config, model, None, None, leader.clone()).await {
             Ok(s) => {
                 tracing::info!(
                     jobs = config.schedules.as_ref().map(|s| s.len()).unwrap_or(0),
@@ -161,7 +179,8 @@ async fn run(cli: Cli) -> crate::error::Result<()> {
     // Start heartbeat runner if enabled (runs in background)
     let _heartbeat = if config.heartbeat.enabled {
         let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
-        let runner = heartbeat::HeartbeatRunner::new(config.heartbeat.clone(), shutdown_rx);
+        let runner = heartbeat::HeartbeatRunner::new(config.heartbeat.clone(), shutdown_rx)
+            .with_leader(leader.clone());
         let handle = runner.start();
         tracing::info!(
             interval = %config.heartbeat.interval,
@@ -201,6 +220,42 @@ async fn run(cli: Cli) -> crate::error::Result<()> {
             Ok(())
         }
         #[cfg(feature = "web")]
+        Some(Command::Users {
+            action,
+            username,
+            role,
+            password,
+        }) => {
+            commands::users_cmd::run(
+                &action,
+                &commands::users_cmd::UsersArgs {
+                    username,
+                    role,
+                    password,
+                },
+            );
+            Ok(())
+        }
+        #[cfg(feature = "web")]
+        Some(Command::Tokens {
+            action,
+            target,
+            scopes,
+            expires_days,
+            rate_limit,
+        }) => {
+            commands::tokens_cmd::run(
+                &action,
+                &commands::tokens_cmd::TokensArgs {
+                    target,
+                    scopes,
+                    expires_days,
+                    rate_limit,
+                },
+            );
+            Ok(())
+        }
+        #[cfg(feature = "web")]
         Some(Command::Pairing {
             action,
             channel,
@@ -246,8 +301,14 @@ async fn run(cli: Cli) -> crate::error::Result<()> {
             let port = port
                 .or_else(|| config.serve.as_ref().and_then(|s| s.port))
                 .unwrap_or(3000);
-            gateway::run_server_with_log_buffer(&config, &host, port, Some(log_buffer.clone()))
-                .await
+            gateway::run_server_with_log_buffer(
+                &config,
+                &host,
+                port,
+                Some(log_buffer.clone()),
+                leader.clone(),
+            )
+            .await
         }
         #[cfg(any(
             feature = "bot-lark",
@@ -328,7 +389,13 @@ async fn run(cli: Cli) -> crate::error::Result<()> {
             action,
             query,
             limit,
-        }) => run_memory_command(&config, &action, query.as_deref(), limit).await,
+            agent,
+            from,
+            to,
+        }) => {
+            let backends = (from.as_deref(), to.as_deref());
+            run_memory_command(&config, &action, query.as_deref(), limit, &agent, backends).await
+        }
         #[cfg(feature = "web")]
         Some(Command::Connect {
             url,
@@ -360,6 +427,28 @@ async fn run(cli: Cli) -> crate::error::Result<()> {
         Some(Command::Sandbox { action }) => {
             sandbox::cli::handle_sandbox_cli(action, &config).await
         }
+        Some(Command::Audit {
+            action,
+            file,
+            category,
+            filter,
+            actor,
+            session,
+            limit,
+            json,
+        }) => commands::run_audit_command(
+            &config,
+            &action,
+            &commands::audit_cmd::AuditArgs {
+                file,
+                category,
+                filter,
+                actor,
+                session,
+                limit,
+                json,
+            },
+        ),
         Some(Command::Models { action, name }) => {
             commands::run_models_command(&config, &action, name.as_deref())
         }
@@ -404,10 +493,10 @@ async fn run_chat(
 
     // Long-term memory
     let sessions_dir = PathBuf::from(&config.sessions_dir());
-    let ltm = Arc::new(memory::LongTermMemory::new(
-        sessions_dir.join("long_term_memory"),
-        config.memory.clone(),
-    ));
+    let ltm = Arc::new(
+        memory::LongTermMemory::new(sessions_dir.join("long_term_memory"), config.memory.clone())
+            .with_model(model.clone()),
+    );
     ltm.load().await.ok();
 
     // Resolve or create session
@@ -476,17 +565,18 @@ async fn run_memory_command(
     action: &str,
     query: Option<&str>,
     limit: usize,
+    agent: &str,
+    (from, to): (Option<&str>, Option<&str>),
 ) -> crate::error::Result<()> {
-    let sessions_dir = PathBuf::from(&config.sessions_dir());
-    let ltm =
-        memory::LongTermMemory::new(sessions_dir.join("long_term_memory"), config.memory.clone());
+    let memory_dir = memory::transfer::memory_dir(config, agent);
+    let ltm = memory::LongTermMemory::new(memory_dir.clone(), config.memory.clone());
     ltm.load().await.ok();
 
     match action {
         "status" => {
             let count = ltm.count().await;
             let has_embeddings = ltm.uses_embeddings();
-            let db_path = sessions_dir.join("long_term_memory").join("vectors.db");
+            let db_path = memory_dir.join("vectors.db");
             let db_size = std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
 
             println!("{}", "--- Memory Status ---".bold());
@@ -531,10 +621,7 @@ async fn run_memory_command(
                     db_size as f64 / 1024.0
                 );
             }
-            let store_dir = sessions_dir
-                .join("long_term_memory")
-                .join("synapse")
-                .join("long_term_memory");
+            let store_dir = memory_dir.join("synapse").join("long_term_memory");
             if store_dir.exists() {
                 let file_count = std::fs::read_dir(&store_dir)
                     .map(|d| d.count())
@@ -588,12 +675,127 @@ async fn run_memory_command(
                 }
             }
         }
+        "ingest" => {
+            let uri = query.ok_or("usage: synapse memory ingest <path|file://uri|url>")?;
+            let docs = memory::ingest::read_resource(uri).await?;
+            let mut chunks = 0;
+            for doc in &docs {
+                let stored = ltm
+                    .ingest(&doc.uri, &doc.text, memory::MemoryTags::default())
+                    .await?;
+                if stored > 0 {
+                    println!("  {} ({} chunks)", doc.uri, stored);
+                } else {
+                    println!("  {} {}", doc.uri, "(unchanged)".dimmed());
+                }
+                chunks += stored;
+            }
+            println!(
+                "{} Ingested {} documents, {} chunks",
+                "memory:".green().bold(),
+                docs.len(),
+                chunks
+            );
+        }
+        "consolidate" => {
+            let model = agent::build_model(config, None)?;
+            let report = ltm.consolidate(Some(model.as_ref())).await;
+            println!(
+                "{} Merged {} duplicates, superseded {} contradicted memories",
+                "memory:".green().bold(),
+                report.merged,
+                report.superseded
+            );
+        }
+        "history" => {
+            let records = ltm.history(limit).await?;
+            if records.is_empty() {
+                println!("{}", "No memories have been superseded.".dimmed());
+            }
+            for (i, r) in records.iter().enumerate() {
+                let when = chrono::DateTime::from_timestamp(r.timestamp as i64, 0)
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                let reason = match r.reason {
+                    memory::consolidate::SupersedeReason::Duplicate => "merged",
+                    memory::consolidate::SupersedeReason::Contradiction => "superseded",
+                };
+                println!(
+                    "  {}. {} {} [{}] {}",
+                    i + 1,
+                    when.dimmed(),
+                    reason.yellow(),
+                    r.key.dimmed(),
+                    r.content.replace('\n', " ")
+                );
+                println!(
+                    "     {} {}",
+                    "→".dimmed(),
+                    r.superseded_by_content.replace('\n', " ")
+                );
+            }
+        }
+        "export" => {
+            let records = ltm.export().await;
+            let jsonl = memory::transfer::to_jsonl(&records);
+            match query {
+                Some(path) => {
+                    std::fs::write(path, jsonl)?;
+                    eprintln!(
+                        "{} Exported {} memories to {}",
+                        "memory:".green().bold(),
+                        records.len(),
+                        path
+                    );
+                }
+                None => print!("{}", jsonl),
+            }
+        }
+        "import" => {
+            let path = query.ok_or("usage: synapse memory import <file.jsonl>")?;
+            let records = memory::transfer::from_jsonl(&std::fs::read_to_string(path)?)?;
+            let report = ltm.import(records).await?;
+            println!(
+                "{} Imported {} memories ({} already present, {} re-embedded)",
+                "memory:".green().bold(),
+                report.imported,
+                report.skipped,
+                report.re_embedded
+            );
+        }
+        "migrate" => {
+            let to = to.ok_or("usage: synapse memory migrate --to <backend> [--from <backend>]")?;
+            let from_config = match from {
+                Some(spec) => memory::transfer::with_backend(&config.memory, spec)?,
+                None => config.memory.clone(),
+            };
+            let to_config = memory::transfer::with_backend(&config.memory, to)?;
+            if memory::transfer::same_store(&from_config, &to_config) {
+                return Err(format!("source and target are the same store ({})", to).into());
+            }
+            // Both backends live in the agent's memory directory; the target
+            // is written in place next to the source.
+            let source = memory::LongTermMemory::new(memory_dir.clone(), from_config);
+            source.load().await?;
+            let report = source.migrate_in_place(to_config).await?;
+            println!(
+                "{} Migrated {} memories to {} ({} already present, {} re-embedded)",
+                "memory:".green().bold(),
+                report.imported,
+                to,
+                report.skipped,
+                report.re_embedded
+            );
+            println!(
+                "  Set memory.ltm_backend (and ltm_backend_url) in the config to switch over."
+            );
+        }
         "clear" => {
             let count = ltm.clear_all().await?;
             println!("{} Cleared {} memories", "memory:".green().bold(), count);
         }
         _ => {
-            tracing::error!(action = %action, "Unknown memory action. Available: status, list, search, clear");
+            tracing::error!(action = %action, "Unknown memory action. Available: status, list, search, ingest, consolidate, history, export, import, migrate, clear");
         }
     }
     Ok(())
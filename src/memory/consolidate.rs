@@ -0,0 +1,202 @@
+//! Memory consolidation: keeping one current version of each fact.
+//!
+//! When a memory is saved, the most related existing memories with the same
+//! audience are looked up.  Near-duplicates (embedding similarity above
+//! `ltm_duplicate_threshold`) are merged into the new memory; if a model is
+//! available it is asked which of the remaining ones the new memory
+//! contradicts or makes obsolete ("lives in Berlin" vs. "moved to Lisbon"),
+//! and those are superseded.  The same pass runs periodically over the whole
+//! store to catch memories saved without a model.
+//!
+//! Nothing is lost silently: every replaced memory is recorded in the
+//! consolidation history with what replaced it and why.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use synaptic::core::ChatModel;
+
+use super::scope::MemoryTags;
+use super::LongTermMemory;
+use crate::config::SynapseConfig;
+
+/// Related memories considered per consolidated memory.
+pub(super) const CANDIDATES: usize = 5;
+
+/// Why a memory was replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupersedeReason {
+    /// Said the same thing as the newer memory.
+    Duplicate,
+    /// Contradicted or made obsolete by the newer memory.
+    Contradiction,
+}
+
+/// History record of a memory that was replaced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Supersession {
+    /// Key and content of the replaced memory.
+    pub key: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: MemoryTags,
+    /// Key and content of the memory that replaced it.
+    pub superseded_by: String,
+    pub superseded_by_content: String,
+    pub reason: SupersedeReason,
+    /// When it was replaced (unix seconds).
+    pub timestamp: u64,
+}
+
+/// Outcome of a consolidation pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ConsolidationReport {
+    pub merged: usize,
+    pub superseded: usize,
+}
+
+/// Cosine similarity of two embeddings.
+pub(super) fn cosine(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| *x as f64 * *y as f64).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    let denom = norm(a) * norm(b);
+    if denom == 0.0 {
+        0.0
+    } else {
+        dot / denom
+    }
+}
+
+/// Jaccard similarity of two keyword sets, used without real embeddings.
+pub(super) fn keyword_overlap(a: &[String], b: &[String]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let shared = a.iter().filter(|k| b.contains(k)).count();
+    let union = a.len() + b.iter().filter(|k| !a.contains(k)).count();
+    shared as f64 / union as f64
+}
+
+/// Prompt asking which `existing` memories `new` contradicts.
+pub(super) fn contradiction_prompt(new: &str, existing: &[&str]) -> String {
+    let listed: Vec<String> = existing
+        .iter()
+        .enumerate()
+        .map(|(i, m)| format!("{}. {}", i + 1, m))
+        .collect();
+    format!(
+        "A new fact was just saved to long-term memory:\n{}\n\n\
+         Existing memories:\n{}\n\n\
+         Which existing memories does the new fact contradict or make obsolete \
+         (e.g. an old address after a move, a changed preference)? Memories that \
+         merely add detail or are about something else are NOT obsolete.\n\
+         Reply with only the numbers, comma-separated, or NONE.",
+        new,
+        listed.join("\n")
+    )
+}
+
+/// Indices (0-based) of the memories named in the model's reply.
+pub(super) fn parse_contradictions(reply: &str, count: usize) -> Vec<usize> {
+    if reply.trim().eq_ignore_ascii_case("none") {
+        return Vec::new();
+    }
+    let mut picked: Vec<usize> = reply
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|n| n.parse::<usize>().ok())
+        .filter(|n| (1..=count).contains(n))
+        .map(|n| n - 1)
+        .collect();
+    picked.sort_unstable();
+    picked.dedup();
+    picked
+}
+
+/// Long-term memory directories: the default agent's and every configured
+/// agent's.
+fn memory_dirs(config: &SynapseConfig) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from(config.sessions_dir()).join("long_term_memory")];
+    if let Some(agents) = config.agents.as_ref() {
+        dirs.extend(
+            agents
+                .list
+                .iter()
+                .map(|d| crate::config::agent_memory_dir(&d.id)),
+        );
+    }
+    dirs
+}
+
+/// Consolidate every agent's long-term memory every `every`, on the leader
+/// only so instances sharing a store don't rewrite it concurrently.
+///
+/// `shared` is the gateway's live instance of the default agent's memory; it
+/// is consolidated in place so its index doesn't keep serving superseded
+/// memories.  Other agents' memories are opened fresh for each pass.
+pub fn spawn(
+    config: SynapseConfig,
+    model: Arc<dyn ChatModel>,
+    shared: Option<Arc<LongTermMemory>>,
+    leader: Arc<crate::leader::LeaderElection>,
+    every: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        // The first tick fires immediately; don't consolidate on startup.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if !leader.is_leader() {
+                continue;
+            }
+            for dir in memory_dirs(&config) {
+                if !dir.exists() {
+                    continue;
+                }
+                let ltm = match shared {
+                    Some(ref shared) if shared.base_dir() == dir.as_path() => shared.clone(),
+                    _ => Arc::new(LongTermMemory::new(dir.clone(), config.memory.clone())),
+                };
+                // Also picks up what other processes saved since the last pass.
+                if let Err(e) = ltm.load().await {
+                    tracing::warn!(path = %dir.display(), error = %e, "failed to load memories for consolidation");
+                    continue;
+                }
+                let report = ltm.consolidate(Some(model.as_ref())).await;
+                tracing::info!(
+                    path = %dir.display(),
+                    merged = report.merged,
+                    superseded = report.superseded,
+                    "memory consolidated"
+                );
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_model_verdicts() {
+        assert_eq!(parse_contradictions("NONE", 3), Vec::<usize>::new());
+        assert_eq!(parse_contradictions("2", 3), vec![1]);
+        assert_eq!(parse_contradictions("3, 1, 3", 3), vec![0, 2]);
+        // Out-of-range numbers are ignored.
+        assert_eq!(parse_contradictions("1, 7", 3), vec![0]);
+    }
+
+    #[test]
+    fn similarity_measures() {
+        assert!((cosine(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-9);
+        assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        let a = vec!["user".to_string(), "rust".to_string()];
+        let b = vec!["user".to_string(), "go".to_string()];
+        assert!((keyword_overlap(&a, &b) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(keyword_overlap(&a, &a), 1.0);
+    }
+}
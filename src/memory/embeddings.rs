@@ -1,6 +1,8 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
-use synaptic::core::Embeddings;
+use async_trait::async_trait;
+use synaptic::core::{Embeddings, SynapticError};
 use synaptic::embeddings::FakeEmbeddings;
 use synaptic::models::HttpBackend;
 use synaptic::ollama::{OllamaEmbeddings, OllamaEmbeddingsConfig};
@@ -124,3 +126,90 @@ fn build_ollama_embeddings(config: &MemoryConfig) -> (Arc<dyn Embeddings>, bool)
     let backend = Arc::new(HttpBackend::new());
     (Arc::new(OllamaEmbeddings::new(cfg, backend)), true)
 }
+
+/// Identifies the embedding space [`build_embeddings`] picks for `config`, so
+/// exported vectors are only reused by a store that embeds the same way.
+pub fn embedding_model_id(config: &MemoryConfig) -> String {
+    const KEYED: &[(&str, &str)] = &[
+        ("openai", "OPENAI_API_KEY"),
+        ("mistral", "MISTRAL_API_KEY"),
+        ("voyage", "VOYAGE_API_KEY"),
+        ("jina", "JINA_API_KEY"),
+        ("cohere", "COHERE_API_KEY"),
+        ("nomic", "NOMIC_API_KEY"),
+    ];
+    let has_key = |var: &str| std::env::var(var).is_ok_and(|k| !k.is_empty());
+    let ollama = || format!("ollama:{}", config.ollama_embedding_model);
+
+    match config.embedding_provider.as_str() {
+        "ollama" => ollama(),
+        "fake" => "fake".to_string(),
+        provider => match KEYED.iter().find(|(name, _)| *name == provider) {
+            Some((name, var)) if has_key(var) => name.to_string(),
+            Some(_) => "fake".to_string(),
+            None => KEYED
+                .iter()
+                .find(|(_, var)| has_key(var))
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_else(ollama),
+        },
+    }
+}
+
+/// Embeddings that answer from vectors seeded ahead of time and only call
+/// the provider for other texts.
+///
+/// Sits under the embedding cache so imported memories carrying vectors
+/// from the same model are cached without being embedded again.
+pub struct SeededEmbeddings {
+    inner: Arc<dyn Embeddings>,
+    seeds: RwLock<HashMap<String, Vec<f32>>>,
+}
+
+impl SeededEmbeddings {
+    pub fn new(inner: Arc<dyn Embeddings>) -> Self {
+        Self {
+            inner,
+            seeds: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn seed(&self, text: &str, vector: Vec<f32>) {
+        self.seeds.write().unwrap().insert(text.to_string(), vector);
+    }
+
+    pub fn clear(&self) {
+        self.seeds.write().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl Embeddings for SeededEmbeddings {
+    async fn embed_documents(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, SynapticError> {
+        let (mut vectors, missing): (Vec<Option<Vec<f32>>>, Vec<&str>) = {
+            let seeds = self.seeds.read().unwrap();
+            let vectors: Vec<_> = texts.iter().map(|t| seeds.get(*t).cloned()).collect();
+            let missing = texts
+                .iter()
+                .zip(&vectors)
+                .filter(|(_, v)| v.is_none())
+                .map(|(t, _)| *t)
+                .collect();
+            (vectors, missing)
+        };
+        if !missing.is_empty() {
+            let mut embedded = self.inner.embed_documents(&missing).await?.into_iter();
+            for slot in vectors.iter_mut().filter(|v| v.is_none()) {
+                *slot = embedded.next();
+            }
+        }
+        Ok(vectors.into_iter().map(Option::unwrap_or_default).collect())
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>, SynapticError> {
+        if let Some(v) = self.seeds.read().unwrap().get(text) {
+            return Ok(v.clone());
+        }
+        self.inner.embed_query(text).await
+    }
+}
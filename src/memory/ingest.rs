@@ -0,0 +1,246 @@
+//! Reading external documents for ingestion into long-term memory.
+//!
+//! A resource URI is a local path (`~` expanded), a `file://` URI or an
+//! `http(s)://` URL.  Directories are walked recursively for text-like files;
+//! PDFs are extracted with the same code as the `read_pdf` tool.
+
+use std::path::{Path, PathBuf};
+
+/// File extensions ingested when walking a directory.
+const TEXT_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "mdx", "txt", "text", "rst", "org", "adoc", "pdf", "html", "htm",
+];
+
+/// Files larger than this are skipped; URLs serving more are refused.
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+/// Time allowed for fetching a URL, body included.
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Text of one document, keyed by its canonical source URI.
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub uri: String,
+    pub text: String,
+}
+
+/// Read every document `uri` refers to.
+pub async fn read_resource(uri: &str) -> Result<Vec<Document>, String> {
+    let uri = uri.trim();
+    if uri.is_empty() {
+        return Err("empty resource uri".into());
+    }
+    if uri.starts_with("http://") || uri.starts_with("https://") {
+        return Ok(vec![fetch_url(uri).await?]);
+    }
+
+    let path = resolve_path(uri);
+    let files = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || collect_files(&path))
+            .await
+            .map_err(|e| format!("walk task failed: {}", e))??
+    };
+    if files.is_empty() {
+        return Err(format!("no ingestible files under {}", path.display()));
+    }
+
+    let mut docs = Vec::with_capacity(files.len());
+    for file in files {
+        match read_file(&file).await {
+            Ok(text) if !text.trim().is_empty() => docs.push(Document {
+                uri: format!("file://{}", file.display()),
+                text,
+            }),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(path = %file.display(), error = %e, "skipping resource"),
+        }
+    }
+    Ok(docs)
+}
+
+/// Local path for a `file://` URI or plain path.
+fn resolve_path(uri: &str) -> PathBuf {
+    let raw = uri.strip_prefix("file://").unwrap_or(uri);
+    let path = match raw.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(raw),
+    };
+    std::fs::canonicalize(&path).unwrap_or(path)
+}
+
+/// The file itself, or the ingestible files under a directory (sorted,
+/// hidden entries skipped).
+fn collect_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if meta.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(path = %dir.display(), error = %e, "skipping directory");
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let p = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(ft) = entry.file_type() else { continue };
+            if ft.is_dir() {
+                pending.push(p);
+            } else if ft.is_file()
+                && is_text_like(&p)
+                && entry.metadata().map(|m| m.len()).unwrap_or(0) <= MAX_FILE_BYTES
+            {
+                files.push(p);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn is_text_like(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| TEXT_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+async fn read_file(path: &Path) -> Result<String, String> {
+    match extension(path).as_str() {
+        "pdf" => crate::tools::extract_pdf_text(path.to_path_buf())
+            .await
+            .map_err(|e| e.to_string()),
+        ext => {
+            let bytes = tokio::fs::read(path).await.map_err(|e| e.to_string())?;
+            let text = String::from_utf8(bytes).map_err(|_| "not valid UTF-8".to_string())?;
+            Ok(if ext == "html" || ext == "htm" {
+                html_to_text(&text)
+            } else {
+                text
+            })
+        }
+    }
+}
+
+/// Fetch `url`, refusing bodies over [`MAX_FILE_BYTES`].
+async fn fetch_url(url: &str) -> Result<Document, String> {
+    let fail = |e: reqwest::Error| format!("fetch {}: {}", url, e);
+    let mut resp = reqwest::Client::new()
+        .get(url)
+        // Covers reading the body too, so a slow server can't hold the ingest.
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(fail)?
+        .error_for_status()
+        .map_err(fail)?;
+    let too_large = || format!("fetch {}: body exceeds {} bytes", url, MAX_FILE_BYTES);
+    if resp
+        .content_length()
+        .is_some_and(|len| len > MAX_FILE_BYTES)
+    {
+        return Err(too_large());
+    }
+    let is_html = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.contains("html"));
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(fail)? {
+        if (bytes.len() + chunk.len()) as u64 > MAX_FILE_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let body = String::from_utf8_lossy(&bytes).into_owned();
+    Ok(Document {
+        uri: url.to_string(),
+        text: if is_html { html_to_text(&body) } else { body },
+    })
+}
+
+/// Crude HTML to text: drops scripts, styles and tags, collapses blank lines.
+fn html_to_text(html: &str) -> String {
+    let mut out = String::with_capacity(html.len() / 2);
+    let lower = html.to_ascii_lowercase();
+    let mut i = 0;
+    while i < html.len() {
+        if html.as_bytes()[i] == b'<' {
+            let skip_to = ["script", "style"]
+                .iter()
+                .find(|t| lower[i + 1..].starts_with(*t))
+                .and_then(|t| lower[i..].find(&format!("</{}>", t)).map(|end| i + end));
+            let from = skip_to.unwrap_or(i);
+            match html[from..].find('>') {
+                Some(end) => {
+                    out.push(' ');
+                    i = from + end + 1;
+                }
+                None => break,
+            }
+        } else {
+            let next = html[i..].find('<').map(|n| i + n).unwrap_or(html.len());
+            out.push_str(&html[i..next]);
+            i = next;
+        }
+    }
+    let out = out
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    out.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_is_reduced_to_text() {
+        let html = "<html><head><style>p{}</style><script>var a = 1 < 2;</script></head>\
+                    <body><h1>Title</h1>\n<p>Fish &amp; chips</p></body></html>";
+        assert_eq!(html_to_text(html), "Title\nFish & chips");
+    }
+
+    #[tokio::test]
+    async fn walks_directories_for_text_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.md"), "# Notes\nalpha").unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/b.txt"), "beta").unwrap();
+        std::fs::write(dir.path().join("image.png"), [0u8, 1, 2]).unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(".git/HEAD.txt"), "ref").unwrap();
+
+        let uri = format!("file://{}", dir.path().display());
+        let docs = read_resource(&uri).await.unwrap();
+        let names: Vec<_> = docs
+            .iter()
+            .map(|d| d.uri.rsplit('/').next().unwrap().to_string())
+            .collect();
+        assert_eq!(names, vec!["a.md", "b.txt"]);
+        assert!(docs[0].uri.starts_with("file:///"));
+    }
+}
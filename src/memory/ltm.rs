@@ -1,7 +1,10 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use synaptic::core::{
     ChatModel, ChatRequest, Document, Embeddings, Message, SearchOptions, Store, SynapticError,
     VectorStore,
@@ -14,8 +17,12 @@ use tokio::sync::RwLock;
 
 use crate::config::MemoryConfig;
 
-use super::embeddings::build_embeddings;
+use super::consolidate::{self, ConsolidationReport, SupersedeReason, Supersession};
+use super::embeddings::{build_embeddings, embedding_model_id, SeededEmbeddings};
 use super::keywords::extract_keywords;
+use super::profile::{ProfileStore, MAX_PROFILES, PROFILE_NAMESPACE};
+use super::scope::{MemoryScope, MemoryTags};
+use super::transfer::{ImportReport, MemoryRecord};
 
 /// Build the LTM persistence store based on configuration.
 ///
@@ -97,8 +104,18 @@ pub fn build_ltm_store(config: &MemoryConfig, base_dir: &Path) -> Arc<dyn Store>
 }
 
 const NAMESPACE: &[&str] = &["synapse", "long_term_memory"];
+/// Chunks of ingested documents.  Kept apart from memories so they neither
+/// count against `ltm_max_entries` nor get pruned.
+const RESOURCE_NAMESPACE: &[&str] = &["synapse", "ltm_resources"];
+const MAX_RESOURCE_CHUNKS: usize = 100_000;
+/// Memories replaced by consolidation, kept as an audit trail.
+const HISTORY_NAMESPACE: &[&str] = &["synapse", "ltm_history"];
+const MAX_HISTORY: usize = 10_000;
 const CHUNK_SIZE: usize = 1600;
 const CHUNK_OVERLAP: usize = 320;
+/// Candidates fetched per requested result when recall is scoped, so that
+/// filtering out other conversations' memories still leaves enough hits.
+const SCOPED_OVERFETCH: usize = 4;
 
 /// A memory search result with source citation.
 #[derive(Debug, Clone)]
@@ -107,6 +124,33 @@ pub struct MemoryResult {
     pub content: String,
     pub source_key: String,
     pub evergreen: bool,
+    /// Where the text came from, for chunks of ingested documents.
+    pub source: Option<SourceRef>,
+    /// Relevance to the query, higher is better.
+    pub score: f64,
+    /// When the memory was stored (unix seconds, 0 if unknown).
+    pub timestamp: u64,
+    /// Owner, origin session and visibility.
+    pub tags: MemoryTags,
+}
+
+/// A ranked search candidate.
+struct Hit {
+    key: String,
+    content: String,
+    score: f64,
+}
+
+/// Location of an ingested chunk within its document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceRef {
+    /// `file://` URI or URL of the document.
+    pub uri: String,
+    /// Byte range of the chunk in the extracted text.
+    pub start: usize,
+    pub end: usize,
+    /// SHA-256 of the document text the chunk was cut from.
+    pub digest: String,
 }
 
 /// Long-term memory store with hybrid search, MMR diversity, and temporal decay.
@@ -120,6 +164,10 @@ pub struct LongTermMemory {
     entries: Arc<RwLock<Vec<MemoryEntry>>>,
     config: MemoryConfig,
     splitter: RecursiveCharacterTextSplitter,
+    /// Resolves contradictions when consolidating on save.
+    model: Option<Arc<dyn ChatModel>>,
+    /// Vectors supplied by an import, used instead of re-embedding.
+    seeds: Arc<SeededEmbeddings>,
 }
 
 #[derive(Debug, Clone)]
@@ -128,6 +176,19 @@ struct MemoryEntry {
     content: String,
     keywords: Vec<String>,
     evergreen: bool,
+    tags: MemoryTags,
+    source: Option<SourceRef>,
+    timestamp: u64,
+}
+
+impl MemoryEntry {
+    fn namespace(&self) -> &'static [&'static str] {
+        if self.source.is_some() {
+            RESOURCE_NAMESPACE
+        } else {
+            NAMESPACE
+        }
+    }
 }
 
 impl LongTermMemory {
@@ -139,11 +200,12 @@ impl LongTermMemory {
     /// Create with an explicitly injected store (for testing or custom backends).
     pub fn with_store(base_dir: PathBuf, config: MemoryConfig, store: Arc<dyn Store>) -> Self {
         let (raw_embeddings, has_real) = build_embeddings(&config);
+        let seeds = Arc::new(SeededEmbeddings::new(raw_embeddings.clone()));
 
         let embeddings: Arc<dyn Embeddings> = if has_real {
             let cache_store = Arc::new(FileStore::new(base_dir.join("embedding_cache")));
             Arc::new(CacheBackedEmbeddings::new(
-                raw_embeddings,
+                seeds.clone(),
                 cache_store,
                 "synapse_emb",
             ))
@@ -183,14 +245,41 @@ impl LongTermMemory {
             entries: Arc::new(RwLock::new(Vec::new())),
             config,
             splitter,
+            model: None,
+            seeds,
         }
     }
 
+    /// Use `model` to supersede contradicted memories on save.
+    pub fn with_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn model(&self) -> Option<Arc<dyn ChatModel>> {
+        self.model.clone()
+    }
+
+    /// Directory this memory is stored in.
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// User profiles kept in this memory's store.
+    pub fn profiles(&self) -> ProfileStore {
+        ProfileStore::new(self.store.clone())
+    }
+
     pub async fn load(&self) -> Result<(), SynapticError> {
-        let items = self
+        let mut items = self
             .store
             .search(NAMESPACE, None, self.config.ltm_max_entries)
             .await?;
+        items.extend(
+            self.store
+                .search(RESOURCE_NAMESPACE, None, MAX_RESOURCE_CHUNKS)
+                .await?,
+        );
         let mut entries = self.entries.write().await;
         entries.clear();
 
@@ -218,11 +307,24 @@ impl LongTermMemory {
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
             let keywords = extract_keywords(&content);
+            let tags = serde_json::from_value(item.value.clone()).unwrap_or_default();
+            let source = item
+                .value
+                .get("source")
+                .and_then(|v| serde_json::from_value(v.clone()).ok());
+            let timestamp = item
+                .value
+                .get("timestamp")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
             entries.push(MemoryEntry {
                 key: item.key.clone(),
                 content: content.clone(),
                 keywords,
                 evergreen,
+                tags,
+                source,
+                timestamp,
             });
 
             docs.push(Document::new(&item.key, &content));
@@ -239,15 +341,35 @@ impl LongTermMemory {
     }
 
     pub async fn remember(&self, content: &str) -> Result<(), SynapticError> {
-        self.remember_inner(content, false).await
+        self.remember_scoped(content, false, MemoryTags::default())
+            .await
     }
 
     #[allow(dead_code)]
     pub async fn remember_evergreen(&self, content: &str) -> Result<(), SynapticError> {
-        self.remember_inner(content, true).await
+        self.remember_scoped(content, true, MemoryTags::default())
+            .await
+    }
+
+    /// Store a memory with its provenance and visibility, consolidating it
+    /// with related memories (see [`consolidate`]).
+    pub async fn remember_scoped(
+        &self,
+        content: &str,
+        evergreen: bool,
+        tags: MemoryTags,
+    ) -> Result<(), SynapticError> {
+        self.remember_with(content, evergreen, tags, self.model.as_deref())
+            .await
     }
 
-    async fn remember_inner(&self, content: &str, evergreen: bool) -> Result<(), SynapticError> {
+    async fn remember_with(
+        &self,
+        content: &str,
+        evergreen: bool,
+        tags: MemoryTags,
+        model: Option<&dyn ChatModel>,
+    ) -> Result<(), SynapticError> {
         let chunks = if content.len() > CHUNK_SIZE * 2 {
             self.splitter.split_text(content)
         } else {
@@ -255,70 +377,179 @@ impl LongTermMemory {
         };
 
         for chunk in &chunks {
-            let key = format!("mem_{}", uuid::Uuid::new_v4());
-            let value = json!({
-                "content": chunk,
-                "timestamp": now_epoch(),
-                "evergreen": evergreen,
-            });
+            let entry = MemoryEntry {
+                key: format!("mem_{}", uuid::Uuid::new_v4()),
+                content: chunk.to_string(),
+                keywords: extract_keywords(chunk),
+                evergreen,
+                tags: tags.clone(),
+                source: None,
+                timestamp: now_epoch(),
+            };
+            let replaced = if self.config.ltm_consolidate {
+                self.find_superseded(&entry, model, false).await
+            } else {
+                Vec::new()
+            };
+            let entry = MemoryEntry {
+                // A merged duplicate keeps the old memory's evergreen flag.
+                evergreen: entry.evergreen
+                    || replaced
+                        .iter()
+                        .any(|(e, r)| *r == SupersedeReason::Duplicate && e.evergreen),
+                ..entry
+            };
+            self.insert(entry.clone()).await?;
+            self.supersede(&entry, &replaced).await;
+        }
+        self.prune().await?;
 
-            self.store.put(NAMESPACE, &key, value).await?;
-            self.hybrid_store
-                .put(NAMESPACE, &key, json!(chunk))
-                .await
-                .ok();
+        Ok(())
+    }
 
-            let doc = Document::new(&key, chunk);
+    /// Ingest a document's text as chunks cited back to `uri`.
+    ///
+    /// Chunks from an earlier version of the same document are replaced;
+    /// ingesting unchanged text is a no-op.  Returns the number of chunks
+    /// stored.
+    pub async fn ingest(
+        &self,
+        uri: &str,
+        text: &str,
+        tags: MemoryTags,
+    ) -> Result<usize, SynapticError> {
+        let digest = format!("{:x}", Sha256::digest(text.as_bytes()));
+        let stale: Vec<MemoryEntry> = {
+            let entries = self.entries.read().await;
+            let old: Vec<_> = entries
+                .iter()
+                .filter(|e| e.source.as_ref().is_some_and(|s| s.uri == uri))
+                .cloned()
+                .collect();
+            if !old.is_empty()
+                && old
+                    .iter()
+                    .all(|e| e.source.as_ref().is_some_and(|s| s.digest == digest))
             {
-                let vs = self.vector_store.write().await;
-                vs.add_documents(vec![doc], self.embeddings.as_ref())
-                    .await
-                    .ok();
+                return Ok(0);
             }
+            old
+        };
+        self.remove(&stale).await;
 
-            let keywords = extract_keywords(chunk);
-            let mut entries = self.entries.write().await;
-            entries.push(MemoryEntry {
-                key,
-                content: chunk.to_string(),
-                keywords,
-                evergreen,
-            });
-
-            let non_evergreen_count = entries.iter().filter(|e| !e.evergreen).count();
-            if entries.len() > self.config.ltm_max_entries && non_evergreen_count > 0 {
-                let excess = entries.len() - self.config.ltm_max_entries;
-                let mut removed = 0;
-                let mut to_remove = Vec::new();
-                for entry in entries.iter() {
-                    if removed >= excess {
-                        break;
-                    }
-                    if !entry.evergreen {
-                        to_remove.push(entry.key.clone());
-                        removed += 1;
-                    }
-                }
-                for key in &to_remove {
-                    self.store.delete(NAMESPACE, key).await.ok();
-                    self.hybrid_store.delete(NAMESPACE, key).await.ok();
-                    let vs = self.vector_store.write().await;
-                    vs.delete(&[key.as_str()]).await.ok();
-                }
-                entries.retain(|e| !to_remove.contains(&e.key));
+        let mut cursor = 0;
+        let mut stored = 0;
+        for chunk in self.splitter.split_text(text) {
+            if chunk.trim().is_empty() {
+                continue;
             }
+            let start = text[cursor..]
+                .find(&chunk)
+                .map(|i| cursor + i)
+                .unwrap_or(cursor);
+            let end = (start + chunk.len()).min(text.len());
+            // Chunks overlap, so the next one starts after this one's start.
+            if text[start..].starts_with(&chunk) {
+                cursor = start + chunk.chars().next().map_or(1, char::len_utf8);
+            }
+
+            let entry = MemoryEntry {
+                key: format!("res_{}", uuid::Uuid::new_v4()),
+                keywords: extract_keywords(&chunk),
+                content: chunk,
+                evergreen: false,
+                tags: tags.clone(),
+                source: Some(SourceRef {
+                    uri: uri.to_string(),
+                    start,
+                    end,
+                    digest: digest.clone(),
+                }),
+                timestamp: now_epoch(),
+            };
+            self.insert(entry).await?;
+            stored += 1;
+        }
+        tracing::info!(
+            uri,
+            chunks = stored,
+            replaced = stale.len(),
+            "resource ingested"
+        );
+        Ok(stored)
+    }
+
+    /// Persist `entry` and add it to the search indexes.
+    async fn insert(&self, entry: MemoryEntry) -> Result<(), SynapticError> {
+        let mut value = json!({
+            "content": entry.content,
+            "timestamp": entry.timestamp,
+            "evergreen": entry.evergreen,
+        });
+        if let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(&entry.tags) {
+            value.as_object_mut().unwrap().extend(fields);
+        }
+        if let Some(ref source) = entry.source {
+            value["source"] = json!(source);
         }
 
+        self.store.put(entry.namespace(), &entry.key, value).await?;
+        self.hybrid_store
+            .put(NAMESPACE, &entry.key, json!(entry.content))
+            .await
+            .ok();
+        {
+            let vs = self.vector_store.write().await;
+            vs.add_documents(
+                vec![Document::new(&entry.key, &entry.content)],
+                self.embeddings.as_ref(),
+            )
+            .await
+            .ok();
+        }
+        self.entries.write().await.push(entry);
         Ok(())
     }
 
+    /// Delete `stale` from the store and the search indexes.
+    async fn remove(&self, stale: &[MemoryEntry]) {
+        if stale.is_empty() {
+            return;
+        }
+        for entry in stale {
+            self.store.delete(entry.namespace(), &entry.key).await.ok();
+            self.hybrid_store.delete(NAMESPACE, &entry.key).await.ok();
+            let vs = self.vector_store.write().await;
+            vs.delete(&[entry.key.as_str()]).await.ok();
+        }
+        let mut entries = self.entries.write().await;
+        entries.retain(|e| !stale.iter().any(|s| s.key == e.key));
+    }
+
     pub async fn recall(&self, query: &str, limit: usize) -> Vec<String> {
-        let limit = if limit == 0 {
+        let limit = self.effective_limit(limit);
+        self.search_hits(query, limit)
+            .await
+            .into_iter()
+            .map(|h| h.content)
+            .collect()
+    }
+
+    fn effective_limit(&self, limit: usize) -> usize {
+        if limit == 0 {
             self.config.ltm_recall_limit
         } else {
             limit
-        };
+        }
+    }
 
+    /// Ranked candidates for `query` with their relevance scores.
+    ///
+    /// With real embeddings, candidates come from the hybrid store (BM25 +
+    /// vector similarity with temporal decay) and are re-ordered by MMR for
+    /// diversity, keeping their hybrid scores.  Without them, the score is
+    /// the fraction of query keywords matched, decayed by age.
+    async fn search_hits(&self, query: &str, limit: usize) -> Vec<Hit> {
         if self.has_real_embeddings {
             let decay_secs = self.config.ltm_decay_half_life_days * 86400;
             let candidate_count = limit * self.config.ltm_candidate_multiplier;
@@ -335,9 +566,13 @@ impl LongTermMemory {
                 .unwrap_or_default();
 
             if !hybrid_results.is_empty() {
+                let scores: HashMap<&str, f64> = hybrid_results
+                    .iter()
+                    .map(|item| (item.key.as_str(), item.score.unwrap_or(0.0)))
+                    .collect();
                 let fetch_k = hybrid_results.len().min(limit * 2);
                 let vs = self.vector_store.read().await;
-                match vs
+                if let Ok(docs) = vs
                     .mmr_search(
                         query,
                         limit,
@@ -347,17 +582,32 @@ impl LongTermMemory {
                     )
                     .await
                 {
-                    Ok(docs) if !docs.is_empty() => {
-                        return docs.into_iter().map(|d| d.content).collect();
-                    }
-                    _ => {
-                        return hybrid_results
-                            .into_iter()
-                            .take(limit)
-                            .filter_map(|item| item.value.as_str().map(|s| s.to_string()))
-                            .collect();
+                    let hits: Vec<Hit> = docs
+                        .into_iter()
+                        .filter_map(|d| {
+                            let score = *scores.get(d.id.as_str())?;
+                            Some(Hit {
+                                key: d.id,
+                                content: d.content,
+                                score,
+                            })
+                        })
+                        .collect();
+                    if !hits.is_empty() {
+                        return hits;
                     }
                 }
+                return hybrid_results
+                    .iter()
+                    .take(limit)
+                    .filter_map(|item| {
+                        Some(Hit {
+                            key: item.key.clone(),
+                            content: item.value.as_str()?.to_string(),
+                            score: item.score.unwrap_or(0.0),
+                        })
+                    })
+                    .collect();
             }
 
             let vs = self.vector_store.read().await;
@@ -371,10 +621,22 @@ impl LongTermMemory {
                 .await
             {
                 Ok(docs) if !docs.is_empty() => {
+                    let entries = self.entries.read().await;
+                    let now = now_epoch();
                     return docs
                         .into_iter()
                         .filter(|(_d, score)| *score >= min_score)
-                        .map(|(d, _score)| d.content)
+                        .map(|(d, score)| {
+                            let decay = entries
+                                .iter()
+                                .find(|e| e.key == d.id)
+                                .map_or(1.0, |e| self.decay(e, now));
+                            Hit {
+                                key: d.id,
+                                content: d.content,
+                                score: score as f64 * decay,
+                            }
+                        })
                         .collect();
                 }
                 Ok(_) => {}
@@ -387,31 +649,82 @@ impl LongTermMemory {
         self.recall_by_keywords(query, limit).await
     }
 
-    #[allow(dead_code)]
-    pub async fn recall_with_sources(&self, query: &str, limit: usize) -> Vec<MemoryResult> {
-        let limit = if limit == 0 {
-            self.config.ltm_recall_limit
-        } else {
+    /// Temporal decay factor for `entry`: halves every
+    /// `ltm_decay_half_life_days`.  Evergreen memories and ingested
+    /// documents don't decay.
+    fn decay(&self, entry: &MemoryEntry, now: u64) -> f64 {
+        let half_life = self.config.ltm_decay_half_life_days * 86400;
+        if entry.evergreen || entry.source.is_some() || half_life == 0 || entry.timestamp == 0 {
+            return 1.0;
+        }
+        let age = now.saturating_sub(entry.timestamp) as f64;
+        0.5f64.powf(age / half_life as f64)
+    }
+
+    /// Recall only memories the conversation in `scope` may see.
+    pub async fn recall_scoped(
+        &self,
+        query: &str,
+        limit: usize,
+        scope: &MemoryScope,
+    ) -> Vec<String> {
+        self.recall_with_sources(query, limit, scope)
+            .await
+            .into_iter()
+            .map(|r| r.content)
+            .collect()
+    }
+
+    /// Scoped recall with the score, key, timestamp and provenance of each
+    /// result.
+    pub async fn recall_with_sources(
+        &self,
+        query: &str,
+        limit: usize,
+        scope: &MemoryScope,
+    ) -> Vec<MemoryResult> {
+        let limit = self.effective_limit(limit);
+        let fetch = if scope.is_unrestricted() {
             limit
+        } else {
+            limit * SCOPED_OVERFETCH
         };
-
-        let contents = self.recall(query, limit).await;
+        let hits = self.search_hits(query, fetch).await;
         let entries = self.entries.read().await;
 
-        contents
-            .into_iter()
-            .map(|content| {
-                let entry = entries.iter().find(|e| e.content == content);
-                MemoryResult {
-                    source_key: entry.map(|e| e.key.clone()).unwrap_or_default(),
-                    evergreen: entry.is_some_and(|e| e.evergreen),
-                    content,
+        hits.into_iter()
+            .filter_map(|hit| {
+                let entry = entries.iter().find(|e| e.key == hit.key);
+                if let Some(e) = entry {
+                    if !scope.allows(&e.tags) {
+                        return None;
+                    }
+                } else if !scope.is_unrestricted() {
+                    // Unknown provenance: only operators see it.
+                    return None;
                 }
+                Some(MemoryResult {
+                    source_key: hit.key,
+                    evergreen: entry.is_some_and(|e| e.evergreen),
+                    source: entry.and_then(|e| e.source.clone()),
+                    score: hit.score,
+                    timestamp: entry.map_or(0, |e| e.timestamp),
+                    tags: entry.map(|e| e.tags.clone()).unwrap_or_default(),
+                    content: hit.content,
+                })
             })
+            .take(limit)
             .collect()
     }
 
-    pub async fn flush_before_compact(&self, discarding: &[Message], model: &dyn ChatModel) {
+    /// Extract memories worth keeping from messages about to be compacted
+    /// away.  They are saved with `scope`'s default visibility.
+    pub async fn flush_before_compact(
+        &self,
+        discarding: &[Message],
+        model: &dyn ChatModel,
+        scope: &MemoryScope,
+    ) {
         if discarding.is_empty() || !self.config.pre_compact_flush {
             return;
         }
@@ -452,10 +765,13 @@ impl LongTermMemory {
                 if text.trim() == "NONE" || text.is_empty() {
                     return;
                 }
+                let tags = scope.tags(scope.default_visibility()).unwrap_or_default();
                 for line in text.lines() {
                     let line = line.trim().trim_start_matches('-').trim();
                     if line.len() > 10 {
-                        self.remember(line).await.ok();
+                        self.remember_with(line, false, tags.clone(), Some(model))
+                            .await
+                            .ok();
                     }
                 }
             }
@@ -465,58 +781,353 @@ impl LongTermMemory {
         }
     }
 
-    #[allow(dead_code)]
-    pub async fn prune(&self) -> Result<usize, SynapticError> {
-        let mut entries = self.entries.write().await;
-        if entries.len() <= self.config.ltm_max_entries {
-            return Ok(0);
+    /// Related memories with the same audience that `entry` replaces:
+    /// near-duplicates, and those the model says it contradicts.
+    /// `older_only` restricts candidates to memories saved before it.
+    async fn find_superseded(
+        &self,
+        entry: &MemoryEntry,
+        model: Option<&dyn ChatModel>,
+        older_only: bool,
+    ) -> Vec<(MemoryEntry, SupersedeReason)> {
+        let hits = self
+            .search_hits(&entry.content, consolidate::CANDIDATES)
+            .await;
+        let candidates: Vec<MemoryEntry> = {
+            let entries = self.entries.read().await;
+            hits.iter()
+                .filter_map(|h| entries.iter().find(|e| e.key == h.key))
+                .filter(|e| {
+                    e.key != entry.key
+                        && e.source.is_none()
+                        && e.tags.same_audience(&entry.tags)
+                        && (!older_only || e.timestamp <= entry.timestamp)
+                })
+                .cloned()
+                .collect()
+        };
+        if candidates.is_empty() {
+            return Vec::new();
         }
 
-        let excess = entries.len() - self.config.ltm_max_entries;
-        let mut removed = 0;
-        let mut to_remove = Vec::new();
-        for entry in entries.iter() {
-            if removed >= excess {
-                break;
+        let similarities = self.similarities(entry, &candidates).await;
+        let threshold = self.config.ltm_duplicate_threshold as f64;
+        let (duplicates, related): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .zip(similarities)
+            .partition(|(_, similarity)| *similarity >= threshold);
+        let mut superseded: Vec<_> = duplicates
+            .into_iter()
+            .map(|(e, _)| (e, SupersedeReason::Duplicate))
+            .collect();
+
+        if let Some(model) = model.filter(|_| !related.is_empty()) {
+            let existing: Vec<&str> = related.iter().map(|(e, _)| e.content.as_str()).collect();
+            let prompt = consolidate::contradiction_prompt(&entry.content, &existing);
+            match model
+                .chat(ChatRequest::new(vec![Message::human(prompt)]))
+                .await
+            {
+                Ok(response) => {
+                    for i in
+                        consolidate::parse_contradictions(response.message.content(), related.len())
+                    {
+                        superseded.push((related[i].0.clone(), SupersedeReason::Contradiction));
+                    }
+                }
+                Err(e) => tracing::warn!(error = %e, "Memory contradiction check failed"),
             }
-            if !entry.evergreen {
-                to_remove.push(entry.key.clone());
-                removed += 1;
+        }
+        superseded
+    }
+
+    /// Similarity of `entry` to each candidate: embedding cosine, or keyword
+    /// overlap without real embeddings.
+    async fn similarities(&self, entry: &MemoryEntry, candidates: &[MemoryEntry]) -> Vec<f64> {
+        if self.has_real_embeddings {
+            let mut texts = vec![entry.content.as_str()];
+            texts.extend(candidates.iter().map(|c| c.content.as_str()));
+            match self.embeddings.embed_documents(&texts).await {
+                Ok(vectors) if vectors.len() == texts.len() => {
+                    return vectors[1..]
+                        .iter()
+                        .map(|v| consolidate::cosine(&vectors[0], v))
+                        .collect();
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::debug!(error = %e, "Embedding failed, comparing keywords");
+                }
             }
         }
+        candidates
+            .iter()
+            .map(|c| consolidate::keyword_overlap(&entry.keywords, &c.keywords))
+            .collect()
+    }
 
-        for key in &to_remove {
-            self.store.delete(NAMESPACE, key).await.ok();
-            self.hybrid_store.delete(NAMESPACE, key).await.ok();
-            let vs = self.vector_store.write().await;
-            vs.delete(&[key.as_str()]).await.ok();
+    /// Remove `replaced`, recording each in the consolidation history.
+    async fn supersede(&self, by: &MemoryEntry, replaced: &[(MemoryEntry, SupersedeReason)]) {
+        if replaced.is_empty() {
+            return;
         }
-        entries.retain(|e| !to_remove.contains(&e.key));
+        let now = now_epoch();
+        let mut removed = Vec::with_capacity(replaced.len());
+        for (old, reason) in replaced {
+            let record = Supersession {
+                key: old.key.clone(),
+                content: old.content.clone(),
+                tags: old.tags.clone(),
+                superseded_by: by.key.clone(),
+                superseded_by_content: by.content.clone(),
+                reason: *reason,
+                timestamp: now,
+            };
+            let key = format!("sup_{}", uuid::Uuid::new_v4());
+            // Never drop a memory without its history record.
+            if let Err(e) = self.store.put(HISTORY_NAMESPACE, &key, json!(record)).await {
+                tracing::warn!(key = %old.key, error = %e, "Failed to record superseded memory, keeping it");
+                continue;
+            }
+            tracing::info!(key = %old.key, by = %by.key, reason = ?reason, "Memory superseded");
+            removed.push(old.clone());
+        }
+        self.remove(&removed).await;
+    }
+
+    /// Consolidate the whole store: newest first, each memory replaces the
+    /// older duplicates and (with a model) older memories it contradicts.
+    pub async fn consolidate(&self, model: Option<&dyn ChatModel>) -> ConsolidationReport {
+        let mut memories: Vec<MemoryEntry> = self
+            .entries
+            .read()
+            .await
+            .iter()
+            .filter(|e| e.source.is_none())
+            .cloned()
+            .collect();
+        memories.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+        let mut report = ConsolidationReport::default();
+        for entry in memories {
+            // Superseded earlier in this pass.
+            if !self.entries.read().await.iter().any(|e| e.key == entry.key) {
+                continue;
+            }
+            let mut replaced = self.find_superseded(&entry, model, true).await;
+            // Don't let an ordinary memory swallow an evergreen duplicate.
+            replaced.retain(|(old, reason)| {
+                *reason != SupersedeReason::Duplicate || entry.evergreen || !old.evergreen
+            });
+            for (_, reason) in &replaced {
+                match reason {
+                    SupersedeReason::Duplicate => report.merged += 1,
+                    SupersedeReason::Contradiction => report.superseded += 1,
+                }
+            }
+            self.supersede(&entry, &replaced).await;
+        }
+        report
+    }
+
+    /// Every memory and document chunk, with its embedding when real
+    /// embeddings are in use.
+    pub async fn export(&self) -> Vec<MemoryRecord> {
+        let entries = self.entries.read().await.clone();
+        let mut vectors: Vec<Option<Vec<f32>>> = vec![None; entries.len()];
+        if self.has_real_embeddings && !entries.is_empty() {
+            let texts: Vec<&str> = entries.iter().map(|e| e.content.as_str()).collect();
+            match self.embeddings.embed_documents(&texts).await {
+                Ok(embedded) if embedded.len() == entries.len() => {
+                    vectors = embedded.into_iter().map(Some).collect();
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "exporting memories without embeddings"),
+            }
+        }
+        let model = embedding_model_id(&self.config);
+
+        entries
+            .into_iter()
+            .zip(vectors)
+            .map(|(e, embedding)| MemoryRecord {
+                key: e.key,
+                content: e.content,
+                keywords: e.keywords,
+                timestamp: e.timestamp,
+                evergreen: e.evergreen,
+                tags: e.tags,
+                source: e.source,
+                embedding_model: embedding.as_ref().map(|_| model.clone()),
+                embedding,
+            })
+            .collect()
+    }
+
+    /// Add exported records, keeping their keys, timestamps and provenance.
+    /// Keys already stored are skipped, so importing twice is harmless.
+    /// Vectors from the same embedding model are reused; other records are
+    /// embedded again.
+    pub async fn import(&self, records: Vec<MemoryRecord>) -> Result<ImportReport, SynapticError> {
+        let model = embedding_model_id(&self.config);
+        let mut known: std::collections::HashSet<String> = self
+            .entries
+            .read()
+            .await
+            .iter()
+            .map(|e| e.key.clone())
+            .collect();
+        let mut report = ImportReport::default();
+
+        for record in records {
+            if record.content.is_empty() || !known.insert(record.key.clone()) {
+                report.skipped += 1;
+                continue;
+            }
+            if self.has_real_embeddings {
+                match record.embedding {
+                    Some(vector) if record.embedding_model.as_deref() == Some(model.as_str()) => {
+                        self.seeds.seed(&record.content, vector)
+                    }
+                    _ => report.re_embedded += 1,
+                }
+            }
+            let keywords = if record.keywords.is_empty() {
+                extract_keywords(&record.content)
+            } else {
+                record.keywords
+            };
+            let inserted = self
+                .insert(MemoryEntry {
+                    key: record.key,
+                    content: record.content,
+                    keywords,
+                    evergreen: record.evergreen,
+                    tags: record.tags,
+                    source: record.source,
+                    timestamp: record.timestamp,
+                })
+                .await;
+            if let Err(e) = inserted {
+                self.seeds.clear();
+                return Err(e);
+            }
+            report.imported += 1;
+        }
+        self.seeds.clear();
+        tracing::info!(
+            imported = report.imported,
+            skipped = report.skipped,
+            re_embedded = report.re_embedded,
+            "memories imported"
+        );
+        Ok(report)
+    }
+
+    /// Copy everything to `target`, usually the same memory on another
+    /// backend: memories, document chunks, consolidation history and user
+    /// profiles.
+    pub async fn migrate_to(&self, target: &LongTermMemory) -> Result<ImportReport, SynapticError> {
+        let report = target.import(self.export().await).await?;
+        for (namespace, limit) in [
+            (HISTORY_NAMESPACE, MAX_HISTORY),
+            (PROFILE_NAMESPACE, MAX_PROFILES),
+        ] {
+            for item in self.store.search(namespace, None, limit).await? {
+                target.store.put(namespace, &item.key, item.value).await?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Migrate into the `to` backend for this same directory.
+    ///
+    /// Both stores may keep files here, so the target gets a scratch
+    /// directory of its own for its vector index and embedding cache,
+    /// removed afterwards.  The store itself is the one `to` selects.
+    pub async fn migrate_in_place(&self, to: MemoryConfig) -> Result<ImportReport, SynapticError> {
+        let scratch = self.base_dir.join(".migration");
+        let store = build_ltm_store(&to, &self.base_dir);
+        let target = LongTermMemory::with_store(scratch.clone(), to, store);
+        let report = match target.load().await {
+            Ok(()) => self.migrate_to(&target).await,
+            Err(e) => Err(e),
+        };
+        drop(target);
+        if let Err(e) = std::fs::remove_dir_all(&scratch) {
+            tracing::debug!(path = %scratch.display(), error = %e, "Failed to remove migration scratch directory");
+        }
+        report
+    }
+
+    /// Memories replaced by consolidation, most recent first.
+    pub async fn history(&self, limit: usize) -> Result<Vec<Supersession>, SynapticError> {
+        let items = self
+            .store
+            .search(HISTORY_NAMESPACE, None, MAX_HISTORY)
+            .await?;
+        let mut records: Vec<Supersession> = items
+            .into_iter()
+            .filter_map(|item| serde_json::from_value(item.value).ok())
+            .collect();
+        records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        records.truncate(limit);
+        Ok(records)
+    }
+
+    #[allow(dead_code)]
+    pub async fn prune(&self) -> Result<usize, SynapticError> {
+        // Ingested documents are managed by re-ingestion, not pruning.
+        let to_remove: Vec<MemoryEntry> = {
+            let entries = self.entries.read().await;
+            let memories = entries.iter().filter(|e| e.source.is_none()).count();
+            if memories <= self.config.ltm_max_entries {
+                return Ok(0);
+            }
+            let excess = memories - self.config.ltm_max_entries;
+            entries
+                .iter()
+                .filter(|e| e.source.is_none() && !e.evergreen)
+                .take(excess)
+                .cloned()
+                .collect()
+        };
+        self.remove(&to_remove).await;
 
         Ok(to_remove.len())
     }
 
-    async fn recall_by_keywords(&self, query: &str, limit: usize) -> Vec<String> {
+    async fn recall_by_keywords(&self, query: &str, limit: usize) -> Vec<Hit> {
         let query_keywords = extract_keywords(query);
+        if query_keywords.is_empty() {
+            return Vec::new();
+        }
         let entries = self.entries.read().await;
+        let now = now_epoch();
 
-        let mut scored: Vec<(usize, &MemoryEntry)> = entries
+        let mut scored: Vec<(f64, &MemoryEntry)> = entries
             .iter()
-            .map(|entry| {
-                let score = query_keywords
+            .filter_map(|entry| {
+                let matched = query_keywords
                     .iter()
                     .filter(|k| entry.keywords.contains(k))
                     .count();
-                (score, entry)
+                (matched > 0).then(|| {
+                    let relevance = matched as f64 / query_keywords.len() as f64;
+                    (relevance * self.decay(entry, now), entry)
+                })
             })
-            .filter(|(score, _)| *score > 0)
             .collect();
 
-        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
         scored
             .into_iter()
             .take(limit)
-            .map(|(_, entry)| entry.content.clone())
+            .map(|(score, entry)| Hit {
+                key: entry.key.clone(),
+                content: entry.content.clone(),
+                score,
+            })
             .collect()
     }
 
@@ -536,33 +1147,47 @@ impl LongTermMemory {
         self.entries.read().await.len()
     }
 
-    pub async fn forget(&self, keyword: &str) -> Result<usize, SynapticError> {
-        let keyword_lower = keyword.to_lowercase();
-        let mut entries = self.entries.write().await;
-        let mut removed = 0;
-
-        let to_remove: Vec<String> = entries
-            .iter()
-            .filter(|e| e.content.to_lowercase().contains(&keyword_lower))
-            .map(|e| e.key.clone())
-            .collect();
+    /// Number of memories visible to `scope`.
+    pub async fn count_in(&self, scope: &MemoryScope) -> usize {
+        let entries = self.entries.read().await;
+        entries.iter().filter(|e| scope.allows(&e.tags)).count()
+    }
 
-        for key in &to_remove {
-            self.store.delete(NAMESPACE, key).await.ok();
-            self.hybrid_store.delete(NAMESPACE, key).await.ok();
-            let vs = self.vector_store.write().await;
-            vs.delete(&[key.as_str()]).await.ok();
-            removed += 1;
-        }
+    pub async fn forget(&self, keyword: &str) -> Result<usize, SynapticError> {
+        self.forget_in(keyword, &MemoryScope::unrestricted()).await
+    }
 
-        entries.retain(|e| !e.content.to_lowercase().contains(&keyword_lower));
-        Ok(removed)
+    /// Delete memories containing `keyword` among those visible to `scope`.
+    pub async fn forget_in(
+        &self,
+        keyword: &str,
+        scope: &MemoryScope,
+    ) -> Result<usize, SynapticError> {
+        let keyword_lower = keyword.to_lowercase();
+        let to_remove: Vec<MemoryEntry> = {
+            let entries = self.entries.read().await;
+            entries
+                .iter()
+                .filter(|e| {
+                    e.content.to_lowercase().contains(&keyword_lower) && scope.allows(&e.tags)
+                })
+                .cloned()
+                .collect()
+        };
+        self.remove(&to_remove).await;
+        Ok(to_remove.len())
     }
 
     pub async fn list(&self) -> Vec<(String, String)> {
+        self.list_in(&MemoryScope::unrestricted()).await
+    }
+
+    /// Key and content of the memories visible to `scope`.
+    pub async fn list_in(&self, scope: &MemoryScope) -> Vec<(String, String)> {
         let entries = self.entries.read().await;
         entries
             .iter()
+            .filter(|e| scope.allows(&e.tags))
             .map(|e| (e.key.clone(), e.content.clone()))
             .collect()
     }
@@ -572,7 +1197,7 @@ impl LongTermMemory {
         let count = entries.len();
 
         for entry in entries.drain(..) {
-            self.store.delete(NAMESPACE, &entry.key).await.ok();
+            self.store.delete(entry.namespace(), &entry.key).await.ok();
             self.hybrid_store.delete(NAMESPACE, &entry.key).await.ok();
         }
 
@@ -645,3 +1270,294 @@ fn now_epoch() -> u64 {
         .unwrap_or_default()
         .as_secs()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(dir: &Path) -> LongTermMemory {
+        let config = MemoryConfig {
+            embedding_provider: "fake".into(),
+            ltm_consolidate: true,
+            ..Default::default()
+        };
+        LongTermMemory::with_store(dir.to_path_buf(), config, Arc::new(InMemoryStore::new()))
+    }
+
+    #[tokio::test]
+    async fn recall_is_filtered_by_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        let ltm = memory(dir.path());
+        let group = MemoryScope::from_session_key("agent:default:lark:grp:oc_1")
+            .with_sender(Some("alice".into()));
+        let bob = MemoryScope::from_session_key("agent:default:lark:dm:bob");
+
+        ltm.remember_scoped(
+            "alice deploys the billing service on fridays",
+            false,
+            group.tags(group.default_visibility()).unwrap(),
+        )
+        .await
+        .unwrap();
+        ltm.remember("the billing service runs on port 8080")
+            .await
+            .unwrap();
+
+        let in_group = ltm.recall_scoped("billing service", 5, &group).await;
+        assert_eq!(in_group.len(), 2);
+        let in_dm = ltm.recall_scoped("billing service", 5, &bob).await;
+        assert_eq!(in_dm, vec!["the billing service runs on port 8080"]);
+        assert_eq!(ltm.count_in(&bob).await, 1);
+
+        // Bob can't delete what he can't see.
+        assert_eq!(ltm.forget_in("alice", &bob).await.unwrap(), 0);
+        assert_eq!(ltm.count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn recall_reports_scores_and_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let ltm = memory(dir.path());
+        let alice = MemoryScope::from_session_key("agent:default:lark:dm:alice");
+        ltm.remember_scoped(
+            "the billing service runs on port 8080",
+            false,
+            alice.tags(crate::memory::Visibility::Agent).unwrap(),
+        )
+        .await
+        .unwrap();
+        ltm.remember("the billing team meets on mondays")
+            .await
+            .unwrap();
+
+        let hits = ltm
+            .recall_with_sources("billing service port", 5, &MemoryScope::unrestricted())
+            .await;
+        assert_eq!(hits.len(), 2);
+        assert!(hits[0].score > hits[1].score && hits[1].score > 0.0);
+        assert!(hits[0].score <= 1.0);
+        assert!(hits[0].timestamp > 0);
+        assert_eq!(
+            hits[0].tags.session_key.as_deref(),
+            Some("agent:default:lark:dm:alice")
+        );
+        let keys: Vec<_> = ltm.list().await.into_iter().map(|(k, _)| k).collect();
+        assert!(keys.contains(&hits[0].source_key));
+    }
+
+    /// Model that always gives the same verdict.
+    struct Verdict(&'static str);
+
+    #[async_trait::async_trait]
+    impl ChatModel for Verdict {
+        async fn chat(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<synaptic::core::ChatResponse, SynapticError> {
+            Ok(synaptic::core::ChatResponse {
+                message: Message::ai(self.0),
+                usage: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn duplicates_are_merged() {
+        let dir = tempfile::tempdir().unwrap();
+        let ltm = memory(dir.path());
+        ltm.remember_evergreen("the user prefers Rust")
+            .await
+            .unwrap();
+        ltm.remember("The user prefers rust.").await.unwrap();
+
+        assert_eq!(ltm.list().await[0].1, "The user prefers rust.");
+        assert_eq!(ltm.count().await, 1);
+        assert!(ltm.entries.read().await[0].evergreen);
+        let history = ltm.history(10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].reason, SupersedeReason::Duplicate);
+        assert_eq!(history[0].content, "the user prefers Rust");
+    }
+
+    #[tokio::test]
+    async fn consolidation_is_opt_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = MemoryConfig {
+            embedding_provider: "fake".into(),
+            ..Default::default()
+        };
+        let ltm =
+            LongTermMemory::with_store(dir.path().into(), config, Arc::new(InMemoryStore::new()));
+        ltm.remember("the user prefers Rust").await.unwrap();
+        ltm.remember("The user prefers rust.").await.unwrap();
+        assert_eq!(ltm.count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn contradicted_memories_are_superseded() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = MemoryConfig {
+            embedding_provider: "fake".into(),
+            ltm_consolidate: true,
+            ..Default::default()
+        };
+        let ltm =
+            LongTermMemory::with_store(dir.path().into(), config, Arc::new(InMemoryStore::new()))
+                .with_model(Arc::new(Verdict("1")));
+        ltm.remember("the user lives in Berlin").await.unwrap();
+        ltm.remember("the user moved to Lisbon").await.unwrap();
+
+        let contents: Vec<_> = ltm.list().await.into_iter().map(|(_, c)| c).collect();
+        assert_eq!(contents, vec!["the user moved to Lisbon"]);
+        let history = ltm.history(10).await.unwrap();
+        assert_eq!(history[0].reason, SupersedeReason::Contradiction);
+        assert_eq!(history[0].superseded_by_content, "the user moved to Lisbon");
+    }
+
+    #[tokio::test]
+    async fn consolidation_keeps_other_audiences_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let ltm = memory(dir.path());
+        let alice = MemoryScope::from_session_key("agent:default:lark:dm:alice");
+        let bob = MemoryScope::from_session_key("agent:default:lark:dm:bob");
+        for scope in [&alice, &bob] {
+            ltm.remember_scoped(
+                "prefers short answers",
+                false,
+                scope.tags(scope.default_visibility()).unwrap(),
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(ltm.count().await, 2);
+        assert_eq!(ltm.consolidate(None).await, ConsolidationReport::default());
+    }
+
+    #[tokio::test]
+    async fn tags_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn Store> = Arc::new(InMemoryStore::new());
+        let config = MemoryConfig {
+            embedding_provider: "fake".into(),
+            ..Default::default()
+        };
+        let alice = MemoryScope::from_session_key("agent:default:lark:dm:alice");
+        let ltm = LongTermMemory::with_store(dir.path().into(), config.clone(), store.clone());
+        ltm.remember_scoped(
+            "alice prefers short answers",
+            false,
+            alice.tags(alice.default_visibility()).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let reloaded = LongTermMemory::with_store(dir.path().into(), config, store);
+        reloaded.load().await.unwrap();
+        assert_eq!(reloaded.count_in(&alice).await, 1);
+        let bob = MemoryScope::from_session_key("agent:default:lark:dm:bob");
+        assert_eq!(reloaded.count_in(&bob).await, 0);
+    }
+
+    #[tokio::test]
+    async fn reingesting_a_changed_document_replaces_its_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let ltm = memory(dir.path());
+        let uri = "file:///notes/deploy.md";
+        let v1 = "Deploys happen from the release branch every tuesday.";
+
+        assert_eq!(ltm.ingest(uri, v1, MemoryTags::default()).await.unwrap(), 1);
+        assert_eq!(ltm.ingest(uri, v1, MemoryTags::default()).await.unwrap(), 0);
+        ltm.remember("deploys need two approvals").await.unwrap();
+
+        let v2 = "Deploys happen from main every thursday.";
+        assert_eq!(ltm.ingest(uri, v2, MemoryTags::default()).await.unwrap(), 1);
+        assert_eq!(ltm.count().await, 2);
+
+        let hits = ltm
+            .recall_with_sources("deploys thursday", 5, &MemoryScope::unrestricted())
+            .await;
+        let cited = hits.iter().find(|r| r.source.is_some()).unwrap();
+        assert_eq!(cited.content, v2);
+        let source = cited.source.as_ref().unwrap();
+        assert_eq!(source.uri, uri);
+        assert_eq!((source.start, source.end), (0, v2.len()));
+        assert!(!hits.iter().any(|r| r.content == v1));
+    }
+
+    #[tokio::test]
+    async fn ingested_chunks_are_not_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = MemoryConfig {
+            embedding_provider: "fake".into(),
+            ltm_max_entries: 1,
+            ..Default::default()
+        };
+        let ltm =
+            LongTermMemory::with_store(dir.path().into(), config, Arc::new(InMemoryStore::new()));
+        ltm.ingest("file:///a.md", "alpha document text", MemoryTags::default())
+            .await
+            .unwrap();
+        ltm.remember("first memory").await.unwrap();
+        ltm.remember("second memory").await.unwrap();
+
+        let contents: Vec<_> = ltm.list().await.into_iter().map(|(_, c)| c).collect();
+        assert_eq!(contents, vec!["alpha document text", "second memory"]);
+    }
+
+    #[tokio::test]
+    async fn export_import_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = memory(dir.path());
+        let alice = MemoryScope::from_session_key("agent:default:lark:dm:alice");
+        source
+            .remember_scoped(
+                "alice prefers dark mode",
+                true,
+                alice.tags(alice.default_visibility()).unwrap(),
+            )
+            .await
+            .unwrap();
+        source
+            .ingest(
+                "file:///notes.md",
+                "deploys happen on thursday",
+                MemoryTags::default(),
+            )
+            .await
+            .unwrap();
+        let exported = source.export().await;
+        assert_eq!(exported.len(), 2);
+        // Fake embeddings are never exported.
+        assert!(exported.iter().all(|r| r.embedding.is_none()));
+
+        let other = tempfile::tempdir().unwrap();
+        let target = memory(other.path());
+        let report = target.import(exported.clone()).await.unwrap();
+        assert_eq!((report.imported, report.skipped), (2, 0));
+        assert_eq!(target.export().await, exported);
+
+        let again = target.import(exported).await.unwrap();
+        assert_eq!((again.imported, again.skipped), (0, 2));
+        assert_eq!(target.count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn migrates_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = MemoryConfig {
+            embedding_provider: "fake".into(),
+            ..Default::default()
+        };
+        let source = LongTermMemory::new(dir.path().into(), config.clone());
+        source.remember("the user prefers Rust").await.unwrap();
+
+        let to = crate::memory::transfer::with_backend(&config, "sqlite").unwrap();
+        let report = source.migrate_in_place(to.clone()).await.unwrap();
+        assert_eq!(report.imported, 1);
+        assert!(!dir.path().join(".migration").exists());
+
+        let migrated = LongTermMemory::new(dir.path().into(), to);
+        migrated.load().await.unwrap();
+        assert_eq!(migrated.list().await[0].1, "the user prefers Rust");
+    }
+}
@@ -1,11 +1,17 @@
+pub mod consolidate;
 mod embeddings;
+pub mod ingest;
 mod keywords;
 mod ltm;
 pub mod native_provider;
+pub mod profile;
+mod scope;
+pub mod transfer;
 pub mod viking_provider;
 
-pub use self::ltm::LongTermMemory;
+pub use self::ltm::{build_ltm_store, LongTermMemory, SourceRef};
 #[allow(unused_imports)]
 pub use self::native_provider::NativeMemoryProvider;
+pub use self::scope::{MemoryScope, MemoryTags, Visibility};
 #[allow(unused_imports)]
 pub use self::viking_provider::{VikingConfig, VikingMemoryProvider};
@@ -1,10 +1,18 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use synaptic::core::SynapticError;
 use synaptic::memory::{CommitResult, MemoryProvider, MemoryResult};
 
-use crate::memory::LongTermMemory;
+use crate::memory::profile;
+use crate::memory::{LongTermMemory, MemoryScope, MemoryTags};
+
+/// Captured messages per session after which the user's profile is
+/// re-synthesized without waiting for a commit.
+const PROFILE_BATCH: usize = 20;
+/// Oldest captured messages are dropped past this.
+const MAX_CAPTURED: usize = 200;
 
 /// A [`MemoryProvider`] implementation backed by Synapse's native
 /// [`LongTermMemory`] store.
@@ -15,32 +23,110 @@ use crate::memory::LongTermMemory;
 #[allow(dead_code)]
 pub struct NativeMemoryProvider {
     ltm: Option<Arc<LongTermMemory>>,
+    /// Turns captured since the last commit, per session key, for profile
+    /// synthesis.
+    captured: Mutex<HashMap<String, Vec<(String, String)>>>,
 }
 
 #[allow(dead_code)]
 impl NativeMemoryProvider {
     pub fn new(ltm: Arc<LongTermMemory>) -> Self {
-        Self { ltm: Some(ltm) }
+        Self {
+            ltm: Some(ltm),
+            captured: Mutex::new(HashMap::new()),
+        }
     }
 
     /// Create a no-op provider that returns empty results for all queries.
     ///
     /// Used when no LTM is available (e.g. LTM is disabled in config).
     pub fn new_noop() -> Self {
-        Self { ltm: None }
+        Self {
+            ltm: None,
+            captured: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn recall_in(
+        &self,
+        query: &str,
+        limit: usize,
+        scope: &MemoryScope,
+    ) -> Result<Vec<MemoryResult>, SynapticError> {
+        let Some(ref ltm) = self.ltm else {
+            return Ok(Vec::new());
+        };
+        let results = ltm
+            .recall_with_sources(query, limit, scope)
+            .await
+            .into_iter()
+            .map(|r| {
+                let mut metadata = serde_json::json!({
+                    "key": r.source_key,
+                    "timestamp": r.timestamp,
+                    "evergreen": r.evergreen,
+                    "visibility": r.tags.visibility,
+                    "owner": r.tags.owner,
+                    "channel": r.tags.channel,
+                    "session_key": r.tags.session_key,
+                });
+                match r.source {
+                    // Ingested documents cite the document and byte range.
+                    Some(source) => {
+                        metadata["source"] = source.uri.clone().into();
+                        metadata["start"] = source.start.into();
+                        metadata["end"] = source.end.into();
+                        MemoryResult {
+                            uri: format!("{}#{}-{}", source.uri, source.start, source.end),
+                            content: r.content,
+                            score: r.score,
+                            category: Some("resource".to_string()),
+                            layer: Some("resource".to_string()),
+                            metadata,
+                        }
+                    }
+                    None => MemoryResult {
+                        uri: format!("ltm:{}", r.source_key),
+                        content: r.content,
+                        score: r.score,
+                        category: None,
+                        layer: Some("semantic".to_string()),
+                        metadata,
+                    },
+                }
+            })
+            .collect();
+        Ok(results)
     }
 }
 
 #[async_trait]
 impl MemoryProvider for NativeMemoryProvider {
-    /// Native LTM does not maintain a separate short-term message buffer, so
-    /// this is a no-op.
+    /// Capture a turn of a conversation with a single user for their profile.
+    /// Every `PROFILE_BATCH` messages the profile is synthesized right away.
     async fn add_message(
         &self,
-        _session_key: &str,
-        _role: &str,
-        _content: &str,
+        session_key: &str,
+        role: &str,
+        content: &str,
     ) -> Result<(), SynapticError> {
+        if self.ltm.is_none()
+            || profile::user_id(&MemoryScope::from_session_key(session_key)).is_none()
+        {
+            return Ok(());
+        }
+        let batch_full = {
+            let mut captured = self.captured.lock().unwrap();
+            let turns = captured.entry(session_key.to_string()).or_default();
+            turns.push((role.to_string(), content.to_string()));
+            if turns.len() > MAX_CAPTURED {
+                turns.drain(..turns.len() - MAX_CAPTURED);
+            }
+            turns.len() >= PROFILE_BATCH
+        };
+        if batch_full {
+            self.commit(session_key).await?;
+        }
         Ok(())
     }
 
@@ -57,52 +143,93 @@ impl MemoryProvider for NativeMemoryProvider {
 
     /// Retrieve the most relevant memories for `query` by delegating to the
     /// LTM hybrid-search / keyword-search pipeline.
+    ///
+    /// Recalls what the current conversation ([`MemoryScope::current`]) may
+    /// see.  Outside of one only agent-wide memories are returned: this feeds
+    /// auto-recall, which must not surface one user's private or group
+    /// memories in someone else's chat.
     async fn recall(&self, query: &str, limit: usize) -> Result<Vec<MemoryResult>, SynapticError> {
-        let Some(ref ltm) = self.ltm else {
-            return Ok(Vec::new());
-        };
-        let contents = ltm.recall(query, limit).await;
-        let results = contents
-            .into_iter()
-            .enumerate()
-            .map(|(i, content)| MemoryResult {
-                uri: format!("ltm:{}", i),
-                content,
-                score: 1.0,
-                category: None,
-                layer: Some("semantic".to_string()),
-                metadata: serde_json::Value::Null,
-            })
-            .collect();
-        Ok(results)
+        let scope = MemoryScope::current().unwrap_or_else(|| MemoryScope::session_only(""));
+        self.recall_in(query, limit, &scope).await
     }
 
-    /// Search memories, optionally scoped to a session.  The native LTM does
-    /// not distinguish sessions, so `session_key` is ignored.
+    /// Search the memories visible to the conversation `session_key` (see
+    /// [`MemoryScope::for_search`]); `None` searches all of them.
     async fn search(
         &self,
         query: &str,
-        _session_key: Option<&str>,
+        session_key: Option<&str>,
         limit: usize,
     ) -> Result<Vec<MemoryResult>, SynapticError> {
-        self.recall(query, limit).await
+        self.recall_in(query, limit, &MemoryScope::for_search(session_key))
+            .await
     }
 
-    /// Commit is a no-op for the native provider — Synapse manages its own
-    /// flush lifecycle (e.g. `flush_before_compact`).
-    async fn commit(&self, _session_key: &str) -> Result<CommitResult, SynapticError> {
+    /// Fold the turns captured for `session_key` into its user's profile.
+    ///
+    /// Memories themselves are extracted by Synapse's own flush lifecycle
+    /// (`flush_before_compact`), so only the profile is updated here.
+    async fn commit(&self, session_key: &str) -> Result<CommitResult, SynapticError> {
+        let turns = self
+            .captured
+            .lock()
+            .unwrap()
+            .remove(session_key)
+            .unwrap_or_default();
+        let (Some(ltm), Some(user_id)) = (
+            self.ltm.as_ref(),
+            profile::user_id(&MemoryScope::from_session_key(session_key)),
+        ) else {
+            return Ok(CommitResult::default());
+        };
+        let Some(model) = ltm.model() else {
+            tracing::debug!(session_key, "no model for profile synthesis, skipping");
+            return Ok(CommitResult::default());
+        };
+        if let Some(updated) = ltm
+            .profiles()
+            .synthesize(&user_id, &turns, model.as_ref())
+            .await?
+        {
+            tracing::info!(
+                user_id = %updated.user_id,
+                chars = updated.content.len(),
+                "user profile updated"
+            );
+        }
         Ok(CommitResult::default())
     }
 
-    /// The native LTM does not support arbitrary URI-based resource ingestion.
-    async fn add_resource(&self, _uri: &str) -> Result<(), SynapticError> {
-        Err(SynapticError::Tool(
-            "NativeMemoryProvider does not support resource ingestion".into(),
-        ))
+    /// Ingest a local file, directory, `file://` URI or URL: its text is
+    /// chunked, embedded and stored agent-wide with citations back to the
+    /// source.  Re-ingesting a changed document replaces its old chunks.
+    async fn add_resource(&self, uri: &str) -> Result<(), SynapticError> {
+        let Some(ref ltm) = self.ltm else {
+            return Err(SynapticError::Tool(
+                "long-term memory is disabled, nothing to ingest into".into(),
+            ));
+        };
+        let docs = crate::memory::ingest::read_resource(uri)
+            .await
+            .map_err(SynapticError::Tool)?;
+        for doc in docs {
+            ltm.ingest(&doc.uri, &doc.text, MemoryTags::default())
+                .await?;
+        }
+        Ok(())
     }
 
-    /// The native LTM does not maintain per-user profiles.
-    async fn get_profile(&self, _user_id: &str) -> Result<Option<String>, SynapticError> {
-        Ok(None)
+    /// The profile synthesized (or edited) for `user_id`
+    /// (`{channel}:{sender}`).
+    async fn get_profile(&self, user_id: &str) -> Result<Option<String>, SynapticError> {
+        let Some(ref ltm) = self.ltm else {
+            return Ok(None);
+        };
+        Ok(ltm
+            .profiles()
+            .get(user_id)
+            .await?
+            .map(|p| p.content)
+            .filter(|c| !c.is_empty()))
     }
 }
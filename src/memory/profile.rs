@@ -0,0 +1,271 @@
+//! Per-user profiles synthesized from conversations.
+//!
+//! Like `USER.md`, but maintained automatically and kept per sender: the
+//! native memory provider buffers each user's turns and, on commit, asks the
+//! model to fold them into that user's profile (preferences, facts, working
+//! context).  Profiles live in the LTM store, can be edited by operators
+//! through the `memory.profile.*` RPCs, and are injected into the system
+//! prompt of that user's sessions.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic::core::{ChatModel, ChatRequest, Message, Store, SynapticError};
+
+use super::scope::MemoryScope;
+
+pub(super) const PROFILE_NAMESPACE: &[&str] = &["synapse", "user_profiles"];
+pub(super) const MAX_PROFILES: usize = 100_000;
+/// Profiles are trimmed to this length so they stay cheap to inject.
+const MAX_PROFILE_CHARS: usize = 4000;
+/// Characters of each captured message shown to the model.
+const MAX_TURN_CHARS: usize = 500;
+
+/// A user's profile document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserProfile {
+    /// `{channel}:{sender}`, e.g. `lark:ou_123`.
+    pub user_id: String,
+    /// Markdown profile text.
+    pub content: String,
+    /// Last change (unix seconds).
+    pub updated_at: u64,
+    /// Whether the last change was an operator edit rather than synthesis.
+    #[serde(default)]
+    pub edited: bool,
+}
+
+/// The profile owner for a conversation: the DM peer, or the sender of a
+/// per-sender group session.  Shared group sessions have no single owner.
+pub fn user_id(scope: &MemoryScope) -> Option<String> {
+    let channel = scope.channel.as_deref()?;
+    // Everyone in a shared group reads the conversation, so the current
+    // sender doesn't own it; a per-sender group session has its peer set.
+    let user = if scope.group.is_some() {
+        scope.peer.as_deref()
+    } else {
+        scope.sender.as_deref().or(scope.peer.as_deref())
+    }?;
+    Some(format!("{}:{}", channel, user))
+}
+
+/// Profiles persisted in the LTM store.
+#[derive(Clone)]
+pub struct ProfileStore {
+    store: Arc<dyn Store>,
+}
+
+impl ProfileStore {
+    pub fn new(store: Arc<dyn Store>) -> Self {
+        Self { store }
+    }
+
+    /// The default agent's LTM store.
+    pub fn open(config: &crate::config::SynapseConfig) -> Self {
+        let dir = std::path::PathBuf::from(config.sessions_dir()).join("long_term_memory");
+        Self::new(super::build_ltm_store(&config.memory, &dir))
+    }
+
+    pub async fn get(&self, user_id: &str) -> Result<Option<UserProfile>, SynapticError> {
+        let item = self
+            .store
+            .get(PROFILE_NAMESPACE, &store_key(user_id))
+            .await?;
+        Ok(item.and_then(|i| serde_json::from_value(i.value).ok()))
+    }
+
+    pub async fn put(&self, profile: &UserProfile) -> Result<(), SynapticError> {
+        self.store
+            .put(
+                PROFILE_NAMESPACE,
+                &store_key(&profile.user_id),
+                json!(profile),
+            )
+            .await
+    }
+
+    /// Replace a profile's text by hand.
+    pub async fn edit(&self, user_id: &str, content: &str) -> Result<UserProfile, SynapticError> {
+        let profile = UserProfile {
+            user_id: user_id.to_string(),
+            content: truncate(content.trim()),
+            updated_at: now_epoch(),
+            edited: true,
+        };
+        self.put(&profile).await?;
+        Ok(profile)
+    }
+
+    /// Returns whether a profile existed.
+    pub async fn delete(&self, user_id: &str) -> Result<bool, SynapticError> {
+        let existed = self.get(user_id).await?.is_some();
+        if existed {
+            self.store
+                .delete(PROFILE_NAMESPACE, &store_key(user_id))
+                .await?;
+        }
+        Ok(existed)
+    }
+
+    /// All profiles, most recently updated first.
+    pub async fn list(&self) -> Result<Vec<UserProfile>, SynapticError> {
+        let items = self
+            .store
+            .search(PROFILE_NAMESPACE, None, MAX_PROFILES)
+            .await?;
+        let mut profiles: Vec<UserProfile> = items
+            .into_iter()
+            .filter_map(|i| serde_json::from_value(i.value).ok())
+            .collect();
+        profiles.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(profiles)
+    }
+
+    /// Fold `turns` (role, text) into `user_id`'s profile with `model`.
+    /// Returns the updated profile, or `None` if nothing new was learned.
+    pub async fn synthesize(
+        &self,
+        user_id: &str,
+        turns: &[(String, String)],
+        model: &dyn ChatModel,
+    ) -> Result<Option<UserProfile>, SynapticError> {
+        if turns.is_empty() {
+            return Ok(None);
+        }
+        let existing = self.get(user_id).await?;
+        let prompt = synthesis_prompt(existing.as_ref().map(|p| p.content.as_str()), turns);
+        let response = model
+            .chat(ChatRequest::new(vec![Message::human(prompt)]))
+            .await?;
+        let text = response.message.content().trim();
+        if text.is_empty() || text == "UNCHANGED" {
+            return Ok(None);
+        }
+        let profile = UserProfile {
+            user_id: user_id.to_string(),
+            content: truncate(text),
+            updated_at: now_epoch(),
+            edited: false,
+        };
+        self.put(&profile).await?;
+        Ok(Some(profile))
+    }
+}
+
+/// Store keys must be safe file names for the file backend.
+fn store_key(user_id: &str) -> String {
+    user_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_PROFILE_CHARS) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text.to_string(),
+    }
+}
+
+fn synthesis_prompt(existing: Option<&str>, turns: &[(String, String)]) -> String {
+    let mut conversation = String::new();
+    for (role, text) in turns {
+        let text = match text.char_indices().nth(MAX_TURN_CHARS) {
+            Some((end, _)) => format!("{}...", &text[..end]),
+            None => text.clone(),
+        };
+        conversation.push_str(&format!("{}: {}\n", role, text));
+    }
+    format!(
+        "You maintain a profile of a user for an AI assistant, written in Markdown with \
+         the sections \"Preferences\", \"Facts\" and \"Working context\".\n\n\
+         Current profile:\n{}\n\n\
+         Recent conversation:\n{}\n\
+         Update the profile with anything durable the conversation reveals about the \
+         user: how they like answers, who they are, what they are working on. Keep \
+         everything in the current profile that is still true (it may contain edits made \
+         by hand) and drop what the conversation shows is outdated. Don't record \
+         one-off requests or secrets. Keep it under {} characters.\n\
+         Reply with the full updated profile only, or UNCHANGED if there is nothing to add.",
+        existing.unwrap_or("(empty)"),
+        conversation,
+        MAX_PROFILE_CHARS
+    )
+}
+
+fn now_epoch() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use synaptic::store::InMemoryStore;
+
+    struct Reply(&'static str);
+
+    #[async_trait::async_trait]
+    impl ChatModel for Reply {
+        async fn chat(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<synaptic::core::ChatResponse, SynapticError> {
+            Ok(synaptic::core::ChatResponse {
+                message: Message::ai(self.0),
+                usage: None,
+            })
+        }
+    }
+
+    #[test]
+    fn profile_owner_comes_from_the_conversation() {
+        let dm = MemoryScope::from_session_key("agent:default:lark:dm:ou_1");
+        assert_eq!(user_id(&dm).as_deref(), Some("lark:ou_1"));
+        let per_sender = MemoryScope::from_session_key("agent:default:slack:grp:C1:sender:U2");
+        assert_eq!(user_id(&per_sender).as_deref(), Some("slack:U2"));
+        let shared = MemoryScope::from_session_key("agent:default:slack:grp:C1");
+        assert_eq!(user_id(&shared), None);
+        let shared = shared.with_sender(Some("U3".into()));
+        assert_eq!(user_id(&shared), None);
+    }
+
+    #[tokio::test]
+    async fn synthesis_updates_and_edits_override() {
+        let profiles = ProfileStore::new(Arc::new(InMemoryStore::new()));
+        let turns = vec![("user".to_string(), "I only write Rust".to_string())];
+
+        let updated = profiles
+            .synthesize("lark:ou_1", &turns, &Reply("## Preferences\n- Rust"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!updated.edited);
+        assert_eq!(
+            profiles.get("lark:ou_1").await.unwrap().unwrap().content,
+            "## Preferences\n- Rust"
+        );
+
+        let unchanged = profiles
+            .synthesize("lark:ou_1", &turns, &Reply("UNCHANGED"))
+            .await
+            .unwrap();
+        assert!(unchanged.is_none());
+
+        profiles.edit("lark:ou_1", "Prefers Go").await.unwrap();
+        let listed = profiles.list().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].edited);
+        assert!(profiles.delete("lark:ou_1").await.unwrap());
+        assert!(profiles.get("lark:ou_1").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,343 @@
+//! Who a memory belongs to and who may recall it.
+//!
+//! Every memory records where it was learned ([`MemoryTags`]) and how widely
+//! it may be shared ([`Visibility`]).  A recall is made on behalf of a
+//! conversation ([`MemoryScope`], derived from its session key) and only sees
+//! memories that conversation is allowed to see:
+//!
+//! - `private` — only DMs with the memory's owner
+//! - `group` — only the group chat the memory was learned in
+//! - `agent` — every conversation with the agent
+//!
+//! Memories saved before scoping existed have no tags and are agent-wide.
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+
+tokio::task_local! {
+    /// Scope of the conversation a provider search runs for, see
+    /// [`MemoryScope::within`].
+    static CURRENT: MemoryScope;
+}
+
+/// How widely a memory may be recalled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Only in DMs with the owner.
+    Private,
+    /// Only in the group chat it was learned in.
+    Group,
+    /// In every conversation with the agent.
+    #[default]
+    Agent,
+}
+
+impl Visibility {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "private" => Some(Self::Private),
+            "group" => Some(Self::Group),
+            "agent" => Some(Self::Agent),
+            _ => None,
+        }
+    }
+}
+
+/// Provenance and visibility stored with each memory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryTags {
+    /// Sender the memory was learned from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_key: Option<String>,
+    /// Group chat the memory was learned in, e.g. `lark:grp:oc_123`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+impl MemoryTags {
+    /// Whether memories tagged `self` and `other` are seen by the same
+    /// conversations, so one may replace the other.
+    pub fn same_audience(&self, other: &MemoryTags) -> bool {
+        self.visibility == other.visibility
+            && match self.visibility {
+                Visibility::Agent => true,
+                Visibility::Group => self.group == other.group,
+                Visibility::Private => self.owner == other.owner,
+            }
+    }
+}
+
+/// The conversation a recall or save is made for.
+///
+/// Built from the session key (see `channels::session_key`): DM keys name the
+/// peer, group keys name the chat.  An unrestricted scope (operator
+/// surfaces: CLI, dashboard) sees every memory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryScope {
+    #[serde(default)]
+    pub session_key: Option<String>,
+    #[serde(default)]
+    pub channel: Option<String>,
+    /// The DM peer, or the sender in per-sender group sessions.
+    #[serde(default)]
+    pub peer: Option<String>,
+    /// The group chat, for group sessions.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Sender of the current message; recorded as the owner of saved memories.
+    #[serde(default)]
+    pub sender: Option<String>,
+}
+
+impl MemoryScope {
+    /// Scope that sees everything.
+    pub fn unrestricted() -> Self {
+        Self::default()
+    }
+
+    /// Derive the scope from a session key of the form
+    /// `agent:{agent}:{channel}[:{account}]:{dm|grp}:{peer}[:topic:{t}][:sender:{s}]`.
+    ///
+    /// Keys without a DM or group part (e.g. `agent:default:main`) give a
+    /// scope that only sees agent-wide memories.
+    pub fn from_session_key(key: &str) -> Self {
+        let parts: Vec<&str> = key.split(':').collect();
+        let mut scope = Self {
+            session_key: Some(key.to_string()),
+            ..Default::default()
+        };
+        if parts.first() == Some(&"agent") && parts.len() > 2 {
+            scope.channel = Some(parts[2].to_string());
+        }
+        if let Some(i) = parts.iter().position(|p| *p == "dm") {
+            scope.peer = parts.get(i + 1).map(|s| s.to_string());
+        } else if let Some(i) = parts.iter().position(|p| *p == "grp") {
+            if let (Some(channel), Some(chat)) = (scope.channel.as_deref(), parts.get(i + 1)) {
+                scope.group = Some(format!("{}:grp:{}", channel, chat));
+            }
+            if let Some(j) = parts.iter().position(|p| *p == "sender") {
+                scope.peer = parts.get(j + 1).map(|s| s.to_string());
+            }
+        }
+        scope
+    }
+
+    /// Scope for a session whose key is not parsed for a peer or group, e.g.
+    /// a run key built from a template: it sees agent-wide memories only.
+    pub fn session_only(key: &str) -> Self {
+        Self {
+            session_key: Some(key.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Set the peer when it is known out of band, e.g. the signed-in
+    /// dashboard user, rather than named by the session key.  The peer is
+    /// also the sender.
+    pub fn with_peer(mut self, channel: &str, peer: String) -> Self {
+        self.channel = Some(channel.to_string());
+        self.sender = Some(peer.clone());
+        self.peer = Some(peer);
+        self
+    }
+
+    /// Run `f` on behalf of this conversation.  `MemoryProvider` calls only
+    /// carry a session key, which is not enough for scopes that don't come
+    /// from one ([`session_only`](Self::session_only),
+    /// [`with_peer`](Self::with_peer)); providers look the full scope up
+    /// with [`MemoryScope::current`].
+    pub async fn within<F: Future>(self, f: F) -> F::Output {
+        CURRENT.scope(self, f).await
+    }
+
+    /// The scope set by [`within`](Self::within), if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|s| s.clone()).ok()
+    }
+
+    /// Scope for a provider search made for `session_key`: the current scope
+    /// if it is that session's, otherwise the one the key itself names.
+    /// `None` searches everything.
+    pub fn for_search(session_key: Option<&str>) -> Self {
+        let Some(key) = session_key else {
+            return Self::unrestricted();
+        };
+        match Self::current() {
+            Some(scope) if scope.session_key.as_deref() == Some(key) => scope,
+            _ => Self::from_session_key(key),
+        }
+    }
+
+    /// Set the sender of the current message.
+    pub fn with_sender(mut self, sender: Option<String>) -> Self {
+        self.sender = sender;
+        self
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.session_key.is_none()
+    }
+
+    /// Whether this conversation may recall a memory tagged `tags`.
+    pub fn allows(&self, tags: &MemoryTags) -> bool {
+        if self.is_unrestricted() {
+            return true;
+        }
+        match tags.visibility {
+            Visibility::Agent => true,
+            Visibility::Group => self.group.is_some() && tags.group == self.group,
+            // Private memories never surface in a group chat.
+            Visibility::Private => {
+                self.group.is_none()
+                    && self.peer.is_some()
+                    && tags.owner == self.peer
+                    && tags.channel == self.channel
+            }
+        }
+    }
+
+    /// Visibility for memories saved here when none is asked for: the
+    /// narrowest audience that was part of the conversation.
+    pub fn default_visibility(&self) -> Visibility {
+        if self.group.is_some() {
+            Visibility::Group
+        } else if self.peer.is_some() {
+            Visibility::Private
+        } else {
+            Visibility::Agent
+        }
+    }
+
+    /// Tags for a memory saved in this conversation.
+    pub fn tags(&self, visibility: Visibility) -> Result<MemoryTags, String> {
+        let owner = self.sender.clone().or_else(|| self.peer.clone());
+        match visibility {
+            Visibility::Private if owner.is_none() => {
+                return Err("private memories need a known sender".into())
+            }
+            Visibility::Group if self.group.is_none() => {
+                return Err("group memories can only be saved in a group chat".into())
+            }
+            _ => {}
+        }
+        Ok(MemoryTags {
+            owner,
+            channel: self.channel.clone(),
+            session_key: self.session_key.clone(),
+            group: self.group.clone(),
+            visibility,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dm_and_group_keys() {
+        let dm = MemoryScope::from_session_key("agent:default:lark:dm:alice");
+        assert_eq!(dm.channel.as_deref(), Some("lark"));
+        assert_eq!(dm.peer.as_deref(), Some("alice"));
+        assert_eq!(dm.group, None);
+
+        let grp = MemoryScope::from_session_key("agent:default:lark:grp:oc_1:sender:bob");
+        assert_eq!(grp.group.as_deref(), Some("lark:grp:oc_1"));
+        assert_eq!(grp.peer.as_deref(), Some("bob"));
+
+        let main = MemoryScope::from_session_key("agent:default:main");
+        assert_eq!((main.peer, main.group), (None, None));
+    }
+
+    #[test]
+    fn group_memories_do_not_leak_into_dms() {
+        let group = MemoryScope::from_session_key("agent:default:lark:grp:oc_1")
+            .with_sender(Some("alice".into()));
+        let tags = group.tags(group.default_visibility()).unwrap();
+        assert_eq!(tags.visibility, Visibility::Group);
+        assert_eq!(tags.owner.as_deref(), Some("alice"));
+
+        assert!(group.allows(&tags));
+        assert!(!MemoryScope::from_session_key("agent:default:lark:dm:bob").allows(&tags));
+        assert!(!MemoryScope::from_session_key("agent:default:lark:dm:alice").allows(&tags));
+        assert!(!MemoryScope::from_session_key("agent:default:lark:grp:oc_2").allows(&tags));
+        assert!(MemoryScope::unrestricted().allows(&tags));
+    }
+
+    #[test]
+    fn private_memories_stay_with_their_owner() {
+        let alice = MemoryScope::from_session_key("agent:default:lark:dm:alice");
+        let tags = alice.tags(alice.default_visibility()).unwrap();
+        assert_eq!(tags.visibility, Visibility::Private);
+
+        assert!(alice.allows(&tags));
+        assert!(!MemoryScope::from_session_key("agent:default:lark:dm:bob").allows(&tags));
+        // Not even in a group Alice is part of.
+        assert!(
+            !MemoryScope::from_session_key("agent:default:lark:grp:oc_1:sender:alice")
+                .allows(&tags)
+        );
+    }
+
+    #[test]
+    fn private_memories_stay_on_their_channel() {
+        let lark = MemoryScope::from_session_key("agent:default:lark:dm:alice");
+        let tags = lark.tags(Visibility::Private).unwrap();
+
+        // Same id on another platform is not the same person.
+        assert!(!MemoryScope::from_session_key("agent:default:slack:dm:alice").allows(&tags));
+
+        let web =
+            MemoryScope::session_only("agent:default:main").with_peer("web", "user:alice".into());
+        let tags = web.tags(web.default_visibility()).unwrap();
+        assert_eq!(tags.visibility, Visibility::Private);
+        assert!(web.allows(&tags));
+        assert!(!MemoryScope::from_session_key("agent:default:lark:dm:user:alice").allows(&tags));
+    }
+
+    #[tokio::test]
+    async fn searches_use_the_current_scope_of_their_session() {
+        let web =
+            MemoryScope::session_only("agent:default:main").with_peer("web", "user:alice".into());
+        let seen = web
+            .clone()
+            .within(async { MemoryScope::for_search(Some("agent:default:main")) })
+            .await;
+        assert_eq!(seen, web);
+
+        // Another session's key is parsed as usual, and outside `within` too.
+        let seen = web
+            .within(async { MemoryScope::for_search(Some("agent:default:lark:dm:bob")) })
+            .await;
+        assert_eq!(seen.peer.as_deref(), Some("bob"));
+        assert_eq!(
+            MemoryScope::for_search(Some("agent:default:main")).peer,
+            None
+        );
+        assert!(MemoryScope::for_search(None).is_unrestricted());
+    }
+
+    #[test]
+    fn untagged_memories_are_agent_wide() {
+        let legacy: MemoryTags = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(legacy.visibility, Visibility::Agent);
+        assert!(MemoryScope::from_session_key("agent:default:lark:dm:bob").allows(&legacy));
+        assert!(MemoryScope::from_session_key("agent:default:main").allows(&legacy));
+    }
+
+    #[test]
+    fn rejects_visibility_the_conversation_cannot_have() {
+        let main = MemoryScope::from_session_key("agent:default:main");
+        assert!(main.tags(Visibility::Private).is_err());
+        assert!(main.tags(Visibility::Group).is_err());
+        assert_eq!(main.default_visibility(), Visibility::Agent);
+    }
+}
@@ -0,0 +1,171 @@
+//! Moving long-term memory between stores: JSONL export/import and backend
+//! migration.
+//!
+//! An export is one [`MemoryRecord`] per line.  Records carry their
+//! embedding and the id of the model that produced it; an importing store
+//! that embeds with the same model reuses the vector, any other store
+//! re-embeds the content.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::ltm::SourceRef;
+use super::scope::MemoryTags;
+use crate::config::{MemoryConfig, SynapseConfig};
+
+/// One exported memory or ingested document chunk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    pub key: String,
+    pub content: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// When the memory was stored (unix seconds).
+    #[serde(default)]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub evergreen: bool,
+    #[serde(default)]
+    pub tags: MemoryTags,
+    /// Set for chunks of ingested documents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+    /// Embedding model the vector came from (see `embedding_model_id`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+}
+
+/// Outcome of an import or migration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// Records whose key the store already had.
+    pub skipped: usize,
+    /// Imported records whose vectors could not be reused.
+    pub re_embedded: usize,
+}
+
+/// Serialize records as JSONL.
+pub fn to_jsonl(records: &[MemoryRecord]) -> String {
+    let mut out = String::new();
+    for record in records {
+        if let Ok(line) = serde_json::to_string(record) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Parse a JSONL export, skipping blank lines.
+pub fn from_jsonl(text: &str) -> Result<Vec<MemoryRecord>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// Long-term memory directory of `agent_id` ("default" for the main one).
+pub fn memory_dir(config: &SynapseConfig, agent_id: &str) -> PathBuf {
+    if agent_id == "default" {
+        PathBuf::from(config.sessions_dir()).join("long_term_memory")
+    } else {
+        crate::config::agent_memory_dir(agent_id)
+    }
+}
+
+/// `config` with its LTM backend replaced by `spec`: a backend name
+/// (`file`, `sqlite`, `postgres`, `redis`), a `postgres://` or `redis://`
+/// URL, or a path to a SQLite database.
+pub fn with_backend(config: &MemoryConfig, spec: &str) -> Result<MemoryConfig, String> {
+    let mut config = config.clone();
+    let (backend, url) = match spec.split_once("://") {
+        Some(("postgres" | "postgresql", _)) => ("postgres", Some(spec.to_string())),
+        Some(("redis" | "rediss", _)) => ("redis", Some(spec.to_string())),
+        Some(("sqlite", path)) => ("sqlite", Some(path.to_string())),
+        Some((scheme, _)) => {
+            return Err(format!("unsupported LTM backend url scheme '{}'", scheme))
+        }
+        None if spec.ends_with(".db") => ("sqlite", Some(spec.to_string())),
+        None => match spec {
+            "file" | "sqlite" | "postgres" | "pg" | "redis" => {
+                // Keep the configured URL only when it belongs to this backend.
+                let url = (config.ltm_backend == spec)
+                    .then(|| config.ltm_backend_url.clone())
+                    .flatten();
+                (spec, url)
+            }
+            _ => return Err(format!("unknown LTM backend '{}'", spec)),
+        },
+    };
+    config.ltm_backend = backend.to_string();
+    config.ltm_backend_url = url;
+    Ok(config)
+}
+
+/// Whether two configs point at the same LTM store (so migrating between
+/// them would copy a store onto itself).
+pub fn same_store(a: &MemoryConfig, b: &MemoryConfig) -> bool {
+    let backend = |c: &MemoryConfig| match c.ltm_backend.as_str() {
+        "pg" => "postgres".to_string(),
+        other => other.to_string(),
+    };
+    backend(a) == backend(b) && a.ltm_backend_url == b.ltm_backend_url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jsonl_round_trip() {
+        let record = MemoryRecord {
+            key: "mem_1".into(),
+            content: "the user prefers Rust".into(),
+            keywords: vec!["user".into(), "rust".into()],
+            timestamp: 1_700_000_000,
+            evergreen: true,
+            tags: MemoryTags::default(),
+            source: None,
+            embedding: Some(vec![0.5, -0.25]),
+            embedding_model: Some("openai".into()),
+        };
+        let text = to_jsonl(&[record.clone(), record.clone()]);
+        assert_eq!(text.lines().count(), 2);
+        assert_eq!(from_jsonl(&text).unwrap(), vec![record.clone(), record]);
+
+        let err = from_jsonl("{\"key\":\"a\",\"content\":\"x\"}\n\nnot json").unwrap_err();
+        assert!(err.starts_with("line 3:"), "{}", err);
+    }
+
+    #[test]
+    fn detects_the_same_store() {
+        let base = MemoryConfig::default();
+        let sqlite = with_backend(&base, "sqlite").unwrap();
+        assert!(same_store(&base, &with_backend(&base, "file").unwrap()));
+        assert!(!same_store(&base, &sqlite));
+        assert!(!same_store(
+            &sqlite,
+            &with_backend(&base, "other.db").unwrap()
+        ));
+    }
+
+    #[test]
+    fn backend_specs() {
+        let base = MemoryConfig::default();
+        let pg = with_backend(&base, "postgres://db/synapse").unwrap();
+        assert_eq!(pg.ltm_backend, "postgres");
+        assert_eq!(pg.ltm_backend_url.as_deref(), Some("postgres://db/synapse"));
+        let db = with_backend(&base, "/tmp/ltm.db").unwrap();
+        assert_eq!(
+            (db.ltm_backend.as_str(), db.ltm_backend_url.as_deref()),
+            ("sqlite", Some("/tmp/ltm.db"))
+        );
+        assert_eq!(with_backend(&base, "file").unwrap().ltm_backend, "file");
+        assert!(with_backend(&base, "mongo").is_err());
+    }
+}
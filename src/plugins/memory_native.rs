@@ -62,7 +62,8 @@ impl synaptic::plugin::Plugin for NativeMemoryPlugin {
             ));
             tracing::info!("memory-native: auto-recall enabled (embeddings available)");
         }
-        // No auto-capture for native — NativeMemoryProvider.add_message() is no-op
+        // No capture subscriber for native: the channel handler feeds turns to
+        // add_message() directly for user profile synthesis.
 
         Ok(())
     }
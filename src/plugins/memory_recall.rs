@@ -2,13 +2,44 @@
 //!
 //! Works with any MemoryProvider implementation (native LTM or Viking).
 //! Pattern: Interceptor (request mutation), not EventSubscriber (observation).
+//!
+//! The interceptor is registered once by the memory plugin and shared by
+//! every agent; the agent builder adds a [`RecallScope`] in front of it so
+//! each recall is made on behalf of the conversation (see
+//! [`MemoryScope::within`]).
 
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use synaptic::core::{Message, SynapticError};
+use synaptic::core::{Message, RunContext, SynapticError};
 use synaptic::memory::{MemoryProvider, MemoryResult};
-use synaptic::middleware::{Interceptor, ModelRequest};
+use synaptic::middleware::{Interceptor, ModelCaller, ModelRequest, ModelResponse};
+
+use crate::memory::MemoryScope;
+
+/// Runs the rest of the interceptor stack, auto-recall included, within one
+/// conversation's memory scope.
+pub struct RecallScope {
+    scope: MemoryScope,
+}
+
+impl RecallScope {
+    pub fn new(scope: MemoryScope) -> Self {
+        Self { scope }
+    }
+}
+
+#[async_trait]
+impl Interceptor for RecallScope {
+    async fn wrap_model_call(
+        &self,
+        request: ModelRequest,
+        ctx: &RunContext,
+        next: &dyn ModelCaller,
+    ) -> Result<ModelResponse, SynapticError> {
+        self.scope.clone().within(next.call(request, ctx)).await
+    }
+}
 
 pub struct MemoryRecallInterceptor {
     provider: Arc<dyn MemoryProvider>,
@@ -28,18 +59,34 @@ impl MemoryRecallInterceptor {
 
 #[async_trait]
 impl Interceptor for MemoryRecallInterceptor {
-    async fn before_model(&self, req: &mut ModelRequest) -> Result<(), SynapticError> {
+    /// Recall in `wrap_model_call` rather than `before_model` so it runs
+    /// inside the [`RecallScope`] set further up the stack.
+    async fn wrap_model_call(
+        &self,
+        mut request: ModelRequest,
+        ctx: &RunContext,
+        next: &dyn ModelCaller,
+    ) -> Result<ModelResponse, SynapticError> {
+        self.inject(&mut request).await;
+        next.call(request, ctx).await
+    }
+}
+
+impl MemoryRecallInterceptor {
+    /// Add the memories relevant to the last user message to the system
+    /// prompt.  Failures are logged and never block the call.
+    async fn inject(&self, req: &mut ModelRequest) {
         let query = extract_last_user_message(&req.messages);
 
         if should_skip_recall(&query) {
-            return Ok(());
+            return;
         }
 
         let results = match self.provider.recall(&query, self.limit).await {
             Ok(results) => results,
             Err(e) => {
                 tracing::warn!(error = %e, "memory recall failed, continuing without memories");
-                return Ok(()); // Don't block on memory failures
+                return;
             }
         };
 
@@ -49,7 +96,7 @@ impl Interceptor for MemoryRecallInterceptor {
             .collect();
 
         if results.is_empty() {
-            return Ok(());
+            return;
         }
 
         let recall_text = format_recall_results(&results);
@@ -64,7 +111,6 @@ impl Interceptor for MemoryRecallInterceptor {
             count = results.len(),
             "injected recalled memories into prompt"
         );
-        Ok(())
     }
 }
 
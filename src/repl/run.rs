@@ -214,7 +214,12 @@ pub async fn repl(
                             let to_discard = &rest[..discard_count];
 
                             if config.memory.pre_compact_flush {
-                                ltm.flush_before_compact(to_discard, model.as_ref()).await;
+                                ltm.flush_before_compact(
+                                    to_discard,
+                                    model.as_ref(),
+                                    &crate::memory::MemoryScope::unrestricted(),
+                                )
+                                .await;
                             }
 
                             let to_keep = rest[discard_count..].to_vec();
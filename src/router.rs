@@ -490,6 +490,9 @@ mod tests {
             agents: vec!["home".into(), "work".into()],
             strategy: BroadcastStrategy::Parallel,
             timeout_secs: 60,
+            synthesizer: None,
+            aggregation: Default::default(),
+            attach_answers: true,
         }];
         let router = BindingRouter::new(&agents, &[], &broadcasts);
 
@@ -1,138 +1,467 @@
 //! Cron/interval-based job scheduling.
 //!
-//! Reads `[[schedule]]` entries from config and registers them
-//! with the Synaptic scheduler.
+//! Reads `[[schedule]]` entries from config. Interval jobs are registered
+//! with the Synaptic scheduler; cron jobs are kept in the persistent
+//! [`CronStore`] (alongside jobs added via the `cron.*` RPCs) and driven here,
+//! so they are evaluated in a per-entry timezone with jitter, and a misfire
+//! policy can catch up on runs missed while the process was down.
 //!
-//! When `gateway.leader_election` is enabled, uses a simple file-lock based
-//! leader election so that only one instance runs scheduled jobs.
+//! Each run executes the entry's `agent` (from `agents.list`) as a full deep
+//! agent — with that agent's model, tools, workspace and long-term memory —
+//! in a fresh session of its own, then posts the result to the entry's
+//! `deliver` target through the outbound delivery queue.  Every run is
+//! recorded in the [`CronRunLog`].
+//!
+//! Jobs only run while this instance holds the [`LeaderElection`] lease, so
+//! with several gateways exactly one of them runs scheduled jobs.
 
-use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
+use chrono::Utc;
 use synaptic::core::ChatModel;
 use synaptic::scheduler::{Scheduler, SchedulerTask, TokioScheduler};
+use synaptic::session::SessionManager;
+
+use crate::agent::headless::{self, RunSession};
+use crate::config::{ScheduleEntry, SynapseConfig};
+use crate::cron::{
+    parse_timezone, CronJob, CronParser, CronRun, CronRunLog, CronStore, DeliverTarget, RunUsage,
+};
+use crate::gateway::budgets::Budgets;
+use crate::gateway::messages::{ChannelRegistry, DeliveryService};
+use crate::leader::LeaderElection;
+
+/// How often the cron driver checks the store for due jobs.
+const CRON_TICK: Duration = Duration::from_secs(1);
+
+/// Channel name scheduled runs are attributed to.
+const CRON_CHANNEL: &str = "cron";
+
+/// Delay before a failed one-shot job is tried again.
+const ONCE_RETRY_DELAY: chrono::Duration = chrono::Duration::minutes(1);
 
-use crate::config::SynapseConfig;
+/// Everything a scheduled run needs besides the job itself.
+struct JobContext {
+    config: Arc<SynapseConfig>,
+    /// Model for agents that don't override it.
+    model: Arc<dyn ChatModel>,
+    sessions: SessionManager,
+    delivery: Arc<DeliveryService>,
+    runs: Arc<CronRunLog>,
+    leader: Arc<LeaderElection>,
+    budgets: Arc<Budgets>,
+}
 
 /// A scheduled job that runs an agent with a predefined prompt.
 struct AgentTask {
-    model: Arc<dyn ChatModel>,
+    ctx: Arc<JobContext>,
+    name: String,
     prompt: String,
+    /// Agent id from `agents.list`, or "default".
+    agent: String,
+    deliver: Option<DeliverTarget>,
+    /// Session key of the chat the job was created from, and its creator;
+    /// the run recalls that chat's memories.
+    origin_session: Option<String>,
+    owner: Option<String>,
 }
 
-#[async_trait]
-impl SchedulerTask for AgentTask {
-    async fn run(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let messages = vec![
-            synaptic::core::Message::system("You are Synapse, executing a scheduled task."),
-            synaptic::core::Message::human(&self.prompt),
-        ];
-        let request = synaptic::core::ChatRequest::new(messages);
-        match self.model.chat(request).await {
-            Ok(response) => {
-                tracing::info!(result = %response.message.content(), "scheduler task completed");
-            }
-            Err(e) => {
-                tracing::error!(error = %e, "scheduler task failed");
-            }
+impl AgentTask {
+    fn for_entry(ctx: &Arc<JobContext>, entry: &ScheduleEntry) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            name: entry.name.clone(),
+            prompt: entry.prompt.clone(),
+            agent: entry.agent.clone().unwrap_or_else(|| "default".into()),
+            deliver: entry.deliver.clone(),
+            origin_session: None,
+            owner: None,
         }
-        Ok(())
     }
-}
 
-/// Path for the leader lock file.
-const LEADER_LOCK_FILE: &str = ".synapse_leader";
+    fn for_job(ctx: &Arc<JobContext>, job: &CronJob) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            name: job.id.clone(),
+            prompt: job.message.clone(),
+            agent: job.agent_id.clone(),
+            deliver: job.deliver.clone(),
+            origin_session: job.session_key.clone(),
+            owner: job.owner.clone(),
+        }
+    }
 
-/// Attempt to acquire leader status via a simple file lock.
-///
-/// Writes `instance_id` into `.synapse_leader`. If the file already exists
-/// and contains a different instance_id, this instance is not the leader.
-///
-/// Returns `true` if this instance is (or became) the leader.
-fn try_acquire_leader(instance_id: &str) -> bool {
-    let lock_path = PathBuf::from(LEADER_LOCK_FILE);
-
-    // Check if lock file exists and belongs to another instance
-    if lock_path.exists() {
-        if let Ok(existing) = fs::read_to_string(&lock_path) {
-            let existing = existing.trim();
-            if existing == instance_id {
-                return true; // We already hold the lock
+    /// Run the job once and return the finished run record.
+    async fn execute(&self) -> CronRun {
+        let mut run = CronRun::start(&self.name);
+        let token = self.ctx.leader.token();
+
+        let session_id = match self.create_session(&run.id).await {
+            Ok(id) => id,
+            Err(e) => return self.failed(run, e),
+        };
+        run.session_id = Some(session_id.clone());
+
+        let (content, usage) = match self.run_agent(&run.id, &session_id).await {
+            Ok(result) => result,
+            Err(e) => return self.failed(run, e),
+        };
+
+        // Another instance may have taken over while the agent was running;
+        // it owns the schedule now, so don't post a duplicate result.
+        if self.ctx.leader.token() != token {
+            run.result = Some(content);
+            return self.failed(run, "leadership lost during run".to_string());
+        }
+
+        if let Some(ref target) = self.deliver {
+            match headless::deliver(&self.ctx.delivery, target, &content).await {
+                Ok(id) => run.delivery_id = Some(id),
+                Err(e) => {
+                    run.result = Some(content);
+                    return self.failed(run, e);
+                }
             }
-            // Another instance holds the lock — not leader
-            tracing::info!(
-                leader = %existing,
-                instance = %instance_id,
-                "leader lock held by another instance, skipping scheduling"
-            );
-            return false;
         }
+
+        tracing::info!(
+            name = %self.name,
+            agent = %self.agent,
+            session = %session_id,
+            result = %content,
+            "scheduler task completed"
+        );
+        run.succeed(content, usage)
     }
 
-    // Try to create/overwrite the lock file
-    match fs::File::create(&lock_path).and_then(|mut f| f.write_all(instance_id.as_bytes())) {
-        Ok(()) => {
-            tracing::info!(instance = %instance_id, "acquired leader lock");
-            true
+    fn failed(&self, run: CronRun, error: String) -> CronRun {
+        tracing::error!(name = %self.name, agent = %self.agent, error = %error, "scheduler task failed");
+        run.fail(error)
+    }
+
+    /// Session key of one run: `agent:{agent}:cron:{run_id}`.
+    fn session_key(&self, run_id: &str) -> String {
+        format!("agent:{}:{}:{}", self.agent, CRON_CHANNEL, run_id)
+    }
+
+    /// Open a new session for one run.
+    async fn create_session(&self, run_id: &str) -> Result<String, String> {
+        RunSession {
+            key: self.session_key(run_id),
+            channel: CRON_CHANNEL,
+            display_name: self.name.clone(),
+            reuse: false,
         }
-        Err(e) => {
-            tracing::error!(error = %e, "failed to acquire leader lock");
-            false
+        .open(&self.ctx.sessions)
+        .await
+    }
+
+    /// Run the prompt through the job's deep agent, saving the conversation
+    /// to `session_id`.
+    async fn run_agent(
+        &self,
+        run_id: &str,
+        session_id: &str,
+    ) -> Result<(String, Option<RunUsage>), String> {
+        let scope = headless::memory_scope(
+            self.origin_session.as_deref(),
+            self.owner.clone(),
+            &self.session_key(run_id),
+        );
+        headless::run(
+            &self.ctx.config,
+            self.ctx.model.clone(),
+            &self.ctx.sessions,
+            session_id,
+            CRON_CHANNEL,
+            &self.agent,
+            &self.prompt,
+            crate::agent::SessionKind::Cron,
+            scope,
+            headless::budget_guard(
+                &self.ctx.budgets,
+                &self.agent,
+                CRON_CHANNEL,
+                self.owner.clone(),
+                self.deliver.as_ref(),
+            ),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl SchedulerTask for AgentTask {
+    async fn run(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.ctx.leader.is_leader() {
+            tracing::debug!(name = %self.name, "not the leader, skipping scheduled job");
+            return Ok(());
         }
+        let run = self.execute().await;
+        if let Err(e) = self.ctx.runs.record(&run) {
+            tracing::warn!(name = %self.name, error = %e, "failed to record scheduled run");
+        }
+        Ok(())
     }
 }
 
-/// Start the scheduler with jobs from config.
+/// Start the scheduler with jobs from config and the persistent cron store.
 ///
-/// If `gateway.leader_election` is enabled, only starts the scheduler if this
-/// instance acquires the leader lock (file-based).
+/// Results are posted through `delivery`, which should be the gateway's
+/// service so the channel adapters' senders are registered with it.  Without
+/// one, results are persisted to the delivery queue and sent the next time
+/// the gateway starts.
+///
+/// Runs are checked against and charged to `budgets`, which should be the
+/// gateway's so scheduled and chat spend share one ledger; without one, the
+/// configured budgets are tracked for this process only.
+///
+/// Jobs are registered on every instance but only run on the one holding
+/// `leader`'s lease; a standby picks them up as soon as it takes over.
 pub async fn start_scheduler(
     config: &SynapseConfig,
     model: Arc<dyn ChatModel>,
+    delivery: Option<Arc<DeliveryService>>,
+    budgets: Option<Arc<Budgets>>,
+    leader: Arc<LeaderElection>,
 ) -> crate::error::Result<Arc<TokioScheduler>> {
-    // Check leader election gate
-    if let Some(ref gw) = config.gateway {
-        if gw.leader_election.unwrap_or(false) {
-            let instance_id = gw.instance_id.as_deref().unwrap_or("default");
-
-            if !try_acquire_leader(instance_id) {
-                tracing::info!(
-                    instance = %instance_id,
-                    "skipping scheduler start, not the leader"
-                );
-                // Return an empty scheduler (no jobs registered)
-                return Ok(Arc::new(TokioScheduler::new()));
-            }
-        }
-    }
-
     let scheduler = Arc::new(TokioScheduler::new());
+    let delivery = delivery.unwrap_or_else(|| {
+        Arc::new(DeliveryService::new(
+            &config.delivery,
+            Arc::new(tokio::sync::RwLock::new(ChannelRegistry::new())),
+        ))
+    });
+    let ctx = Arc::new(JobContext {
+        config: Arc::new(config.clone()),
+        model,
+        sessions: crate::build_session_manager(config),
+        delivery,
+        runs: Arc::new(CronRunLog::new(CronRunLog::default_dir())),
+        leader,
+        budgets: budgets.unwrap_or_else(|| Arc::new(Budgets::from_config(config))),
+    });
+    let store = Arc::new(CronStore::open(CronStore::default_path()));
+    sync_config_jobs(&store, config);
 
     if let Some(schedules) = &config.schedules {
-        for entry in schedules {
-            let task = Box::new(AgentTask {
-                model: model.clone(),
-                prompt: entry.prompt.clone(),
-            });
-
-            let job_id = if let Some(ref cron) = entry.cron {
-                scheduler.schedule_cron(cron, &entry.name, task).await?
-            } else if let Some(secs) = entry.interval_secs {
-                scheduler
-                    .schedule_interval(Duration::from_secs(secs), &entry.name, task)
-                    .await?
-            } else {
+        for entry in schedules.iter().filter(|e| e.cron.is_none()) {
+            let Some(secs) = entry.interval_secs else {
                 tracing::warn!(name = %entry.name, "skipping schedule entry: no cron or interval_secs");
                 continue;
             };
-
+            let task = Box::new(AgentTask::for_entry(&ctx, entry));
+            let job_id = scheduler
+                .schedule_interval(Duration::from_secs(secs), &entry.name, task)
+                .await?;
             tracing::info!(name = %entry.name, job_id = %job_id, "registered scheduled job");
         }
     }
 
+    spawn_cron_driver(store, ctx);
+
     Ok(scheduler)
 }
+
+/// Whether there is anything for the scheduler to run: `[[schedule]]`
+/// entries in config, or jobs persisted in the cron store.
+pub fn has_jobs(config: &SynapseConfig) -> bool {
+    config.schedules.as_ref().is_some_and(|s| !s.is_empty())
+        || !CronStore::open(CronStore::default_path()).list().is_empty()
+}
+
+/// Build the [`CronJob`] for a cron `[[schedule]]` entry, or `None` for
+/// interval entries.
+pub(crate) fn cron_job_for(entry: &ScheduleEntry) -> Option<CronJob> {
+    let cron = entry.cron.as_deref()?;
+    let agent = entry.agent.as_deref().unwrap_or("default");
+    let mut job = CronJob::new(&entry.name, cron, agent, &entry.prompt)
+        .with_jitter_secs(entry.jitter_secs)
+        .with_misfire(entry.misfire);
+    if let Some(ref tz) = entry.timezone {
+        job = job.with_timezone(tz);
+    }
+    if let Some(ref target) = entry.deliver {
+        job = job.with_deliver(target.clone());
+    }
+    job.enabled = entry.enabled;
+    Some(job)
+}
+
+/// Upsert an entry's job into the store, keeping the persisted schedule
+/// state (`last_run`, `next_run`, ...) unless the schedule itself changed.
+pub(crate) fn upsert_entry(store: &CronStore, entry: &ScheduleEntry, from_config: bool) {
+    let Some(mut job) = cron_job_for(entry) else {
+        return;
+    };
+    job.from_config = from_config;
+    if let Some(existing) = store.get(&job.id) {
+        if existing.expression == job.expression && existing.timezone == job.timezone {
+            job.last_run = existing.last_run;
+            job.next_run = existing.next_run;
+            job.failure_count = existing.failure_count;
+            job.run_now = existing.run_now;
+        }
+        job.from_config |= existing.from_config;
+    }
+    store.upsert(job);
+}
+
+/// Mirror the cron `[[schedule]]` entries from config into the store, and
+/// drop jobs that came from config but have since been removed from it.
+fn sync_config_jobs(store: &CronStore, config: &SynapseConfig) {
+    let entries = config.schedules.as_deref().unwrap_or_default();
+    let mut names = HashSet::new();
+    for entry in entries {
+        let Some(ref cron) = entry.cron else {
+            continue;
+        };
+        if !CronParser::is_valid(cron) {
+            tracing::warn!(name = %entry.name, cron = %cron, "skipping schedule entry: invalid cron expression");
+            continue;
+        }
+        if let Some(tz) = entry
+            .timezone
+            .as_deref()
+            .filter(|tz| parse_timezone(tz).is_none())
+        {
+            tracing::warn!(name = %entry.name, timezone = %tz, "skipping schedule entry: unknown timezone");
+            continue;
+        }
+        upsert_entry(store, entry, true);
+        names.insert(entry.name.as_str());
+        tracing::info!(name = %entry.name, cron = %cron, "registered scheduled job");
+    }
+
+    for job in store.list() {
+        if job.from_config && !names.contains(job.id.as_str()) {
+            tracing::info!(name = %job.id, "schedule removed from config, dropping cron job");
+            store.remove(&job.id);
+        }
+    }
+}
+
+/// Drive every enabled job in the store until the process exits.
+fn spawn_cron_driver(store: Arc<CronStore>, ctx: Arc<JobContext>) {
+    tokio::spawn(async move {
+        // Resume persisted schedules: jobs whose `next_run` passed while we
+        // were down are misfires, handled by their policy on the first tick.
+        // `@reboot` jobs fire once per start.
+        let now = Utc::now();
+        for job in store.list() {
+            if job.next_run.is_none() || CronParser::is_reboot(&job.expression) {
+                let next = job.first_run(now);
+                store.update(&job.id, |j| j.next_run = next);
+            }
+        }
+
+        let running: Arc<Mutex<HashSet<String>>> = Arc::default();
+        let mut interval = tokio::time::interval(CRON_TICK);
+        loop {
+            interval.tick().await;
+            // Followers leave the schedule alone; when one takes over, jobs
+            // that came due in the meantime are handled as misfires.
+            if !ctx.leader.is_leader() {
+                continue;
+            }
+            store.reload_if_changed();
+            let now = Utc::now();
+
+            for job in store.enabled_jobs() {
+                if running.lock().expect("lock poisoned").contains(&job.id) {
+                    continue;
+                }
+                let due = if job.run_now {
+                    vec![now]
+                } else {
+                    match job.next_run {
+                        // Added since start-up (e.g. via `cron.add`).
+                        None => {
+                            if !CronParser::is_reboot(&job.expression) {
+                                let next = job.next_after(now);
+                                store.update(&job.id, |j| j.next_run = next);
+                            }
+                            continue;
+                        }
+                        Some(next) if next + job.jitter_for(next) > now => continue,
+                        Some(_) => job.due_runs(now),
+                    }
+                };
+
+                if job.once {
+                    // A skipped one-shot job is done; one that fires stays
+                    // in the store until it succeeds (see below).
+                    if due.is_empty() {
+                        store.remove(&job.id);
+                    } else {
+                        store.update(&job.id, |j| j.run_now = false);
+                    }
+                } else {
+                    let next = job.next_after(now);
+                    store.update(&job.id, |j| {
+                        j.next_run = next;
+                        j.run_now = false;
+                        if !due.is_empty() {
+                            j.last_run = Some(now);
+                        }
+                    });
+                }
+                if due.is_empty() {
+                    tracing::info!(name = %job.id, policy = ?job.misfire, "scheduled job missed its run time, skipping");
+                    continue;
+                }
+                if due.len() > 1
+                    || job
+                        .next_run
+                        .is_some_and(|n| now - n > chrono::Duration::minutes(1))
+                {
+                    tracing::info!(
+                        name = %job.id,
+                        runs = due.len(),
+                        policy = ?job.misfire,
+                        "scheduled job catching up on missed runs"
+                    );
+                }
+
+                running
+                    .lock()
+                    .expect("lock poisoned")
+                    .insert(job.id.clone());
+                let task = AgentTask::for_job(&ctx, &job);
+                let store = store.clone();
+                let running = running.clone();
+                let once = job.once;
+                tokio::spawn(async move {
+                    for scheduled_at in due {
+                        tracing::info!(name = %task.name, scheduled_at = %scheduled_at, "running scheduled job");
+                        let run = task.execute().await;
+                        let succeeded = run.error.is_none();
+                        if let Err(e) = task.ctx.runs.record(&run) {
+                            tracing::warn!(name = %task.name, error = %e, "failed to record scheduled run");
+                        }
+                        if once && succeeded {
+                            store.remove(&task.name);
+                            break;
+                        }
+                        let mut disabled = false;
+                        store.update(&task.name, |j| {
+                            disabled = j.record_outcome(succeeded);
+                            if once && !disabled {
+                                j.next_run = Some(Utc::now() + ONCE_RETRY_DELAY);
+                            }
+                        });
+                        if disabled {
+                            // Kept in the store so the failure stays visible.
+                            tracing::error!(name = %task.name, "scheduled job exceeded max retries, disabling");
+                            break;
+                        }
+                    }
+                    running.lock().expect("lock poisoned").remove(&task.name);
+                });
+            }
+        }
+    });
+}
@@ -51,6 +51,16 @@ pub fn channel_group_key(channel: &str, group_id: &str) -> String {
     format!("{}:group:{}", channel, group_id)
 }
 
+/// Build the storage key for one agent's session inside a broadcast group.
+/// "reviewer", "review", "agent:default:lark:group:c1" →
+/// "agent:reviewer:broadcast:review:lark:group:c1"
+pub fn broadcast_key(agent_id: &str, group: &str, session_key: &str) -> String {
+    to_store_key(
+        agent_id,
+        &format!("broadcast:{}:{}", group, to_request_key(session_key)),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +84,18 @@ mod tests {
         assert_eq!(to_request_key("not-a-store-key"), "not-a-store-key");
     }
 
+    #[test]
+    fn test_broadcast_key() {
+        assert_eq!(
+            broadcast_key("reviewer", "review", "agent:default:lark:group:c1"),
+            "agent:reviewer:broadcast:review:lark:group:c1"
+        );
+        assert_eq!(
+            agent_id_from_store_key(&broadcast_key("style", "review", "agent:default:main")),
+            "style"
+        );
+    }
+
     #[test]
     fn test_agent_id() {
         assert_eq!(agent_id_from_store_key("agent:default:main"), "default");
@@ -1,10 +1,15 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use synaptic::session::SessionManager;
 
+use crate::leader::LeaderElection;
+
 /// Session store maintenance: prune stale entries and cap total count.
-#[allow(dead_code)]
 pub struct SessionMaintenance {
     pub prune_after_days: u32, // Remove sessions older than N days (default 30)
     pub max_entries: usize,    // Keep max N sessions (default 500)
+    leader: Option<Arc<LeaderElection>>,
 }
 
 impl Default for SessionMaintenance {
@@ -12,12 +17,38 @@ impl Default for SessionMaintenance {
         Self {
             prune_after_days: 30,
             max_entries: 500,
+            leader: None,
         }
     }
 }
 
-#[allow(dead_code)]
 impl SessionMaintenance {
+    /// Only run maintenance while `leader` holds the lease, so instances
+    /// sharing a session store don't prune it concurrently.
+    pub fn with_leader(mut self, leader: Arc<LeaderElection>) -> Self {
+        self.leader = Some(leader);
+        self
+    }
+
+    /// Run maintenance every `every` in the background.
+    pub fn spawn(
+        self,
+        session_mgr: Arc<SessionManager>,
+        every: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                if self.leader.as_ref().is_some_and(|l| !l.is_leader()) {
+                    tracing::debug!("session maintenance: not the leader, skipping");
+                    continue;
+                }
+                self.run(&session_mgr).await;
+            }
+        })
+    }
+
     /// Run maintenance: prune stale + cap entries
     pub async fn run(&self, session_mgr: &SessionManager) -> MaintenanceResult {
         let mut result = MaintenanceResult::default();
@@ -60,10 +60,10 @@ pub async fn run_task(
 
     // Long-term memory (load before agent so we can share it)
     let sessions_dir = PathBuf::from(&config.sessions_dir());
-    let ltm = Arc::new(LongTermMemory::new(
-        sessions_dir.join("long_term_memory"),
-        config.memory.clone(),
-    ));
+    let ltm = Arc::new(
+        LongTermMemory::new(sessions_dir.join("long_term_memory"), config.memory.clone())
+            .with_model(routed_model.clone()),
+    );
     ltm.load().await.ok();
 
     // Initialize plugin system (memory plugin, services, bundles)
@@ -88,8 +88,11 @@ pub async fn run_task(
         Some(plugin_bundle.event_bus),
         Some(plugin_bundle.plugin_registry),
         None,
+        None,
+        None,
         agent::SessionKind::Full,
         &plugin_bundle.bundle_skills_dirs,
+        None,
     )
     .await?;
 
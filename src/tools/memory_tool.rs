@@ -2,6 +2,11 @@
 //!
 //! `memory_search` — semantic search over the configured memory provider.
 //! `memory_get` — list or count stored memories (LTM-specific).
+//!
+//! The tools are registered once by the memory plugin and shared by every
+//! agent.  For chat turns, [`scope_memory_tool`] wraps them so each call
+//! carries the conversation's [`MemoryScope`]; unwrapped tools (CLI,
+//! dashboard) see every memory.
 
 use std::sync::Arc;
 
@@ -10,7 +15,67 @@ use serde_json::{json, Value};
 use synaptic::core::{SynapticError, Tool};
 use synaptic::memory::MemoryProvider;
 
-use crate::memory::LongTermMemory;
+use crate::memory::{LongTermMemory, MemoryScope, Visibility};
+
+/// Reserved argument carrying the caller's scope.  It is not in any tool's
+/// schema and [`ScopedMemoryTool`] always overwrites it, so the model can't
+/// pick its own scope.
+const SCOPE_ARG: &str = "_scope";
+
+/// Tools that read [`SCOPE_ARG`].
+const SCOPED_TOOLS: &[&str] = &[
+    "memory_search",
+    "memory_save",
+    "memory_forget",
+    "memory_get",
+];
+
+/// Bind `tool` to `scope` if it is one of the memory tools; other tools are
+/// returned unchanged.
+pub fn scope_memory_tool(tool: Arc<dyn Tool>, scope: &MemoryScope) -> Arc<dyn Tool> {
+    if !SCOPED_TOOLS.contains(&tool.name()) {
+        return tool;
+    }
+    Arc::new(ScopedMemoryTool {
+        inner: tool,
+        scope: serde_json::to_value(scope).unwrap_or(Value::Null),
+    })
+}
+
+/// Memory tool bound to one conversation's scope.
+struct ScopedMemoryTool {
+    inner: Arc<dyn Tool>,
+    scope: Value,
+}
+
+#[async_trait]
+impl Tool for ScopedMemoryTool {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &'static str {
+        self.inner.description()
+    }
+
+    fn parameters(&self) -> Option<Value> {
+        self.inner.parameters()
+    }
+
+    async fn call(&self, mut args: Value) -> Result<Value, SynapticError> {
+        if let Some(obj) = args.as_object_mut() {
+            obj.insert(SCOPE_ARG.into(), self.scope.clone());
+        }
+        self.inner.call(args).await
+    }
+}
+
+/// The scope a memory tool call was made in.
+fn scope_of(args: &Value) -> MemoryScope {
+    args.get(SCOPE_ARG)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_else(MemoryScope::unrestricted)
+}
 
 /// Tool that allows the agent to search memory via the configured [`MemoryProvider`].
 pub struct MemorySearchTool {
@@ -62,7 +127,11 @@ impl Tool for MemorySearchTool {
 
         tracing::debug!("memory search");
 
-        let results = self.provider.recall(query, limit).await?;
+        let scope = scope_of(&args);
+        let session_key = scope.session_key.clone();
+        let results = scope
+            .within(self.provider.search(query, session_key.as_deref(), limit))
+            .await?;
 
         if results.is_empty() {
             Ok(json!("No relevant memories found."))
@@ -76,7 +145,14 @@ impl Tool for MemorySearchTool {
                         .as_deref()
                         .map(|l| format!(" [{}]", l))
                         .unwrap_or_default();
-                    format!("{}. [{}]{} {}", i + 1, r.uri, layer_tag, r.content)
+                    format!(
+                        "{}. [{}]{} (score: {:.2}) {}",
+                        i + 1,
+                        r.uri,
+                        layer_tag,
+                        r.score,
+                        r.content
+                    )
                 })
                 .collect();
             Ok(json!(formatted.join("\n")))
@@ -121,6 +197,11 @@ impl Tool for MemorySaveTool {
                     "type": "boolean",
                     "description": "If true, this memory is protected from automatic pruning. Use for critical facts like user preferences or important decisions. Default: false.",
                     "default": false
+                },
+                "visibility": {
+                    "type": "string",
+                    "enum": ["private", "group", "agent"],
+                    "description": "Who may recall this memory: 'private' (only DMs with the person who said it), 'group' (only this group chat) or 'agent' (every conversation). Defaults to private in DMs and group in group chats. Only use 'agent' for facts that are safe for everyone to see."
                 }
             },
             "required": ["content"]
@@ -138,16 +219,25 @@ impl Tool for MemorySaveTool {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
-        if evergreen {
-            self.ltm.remember_evergreen(content).await?;
-        } else {
-            self.ltm.remember(content).await?;
-        }
+        let scope = scope_of(&args);
+        let visibility = match args.get("visibility").and_then(|v| v.as_str()) {
+            Some(v) => Visibility::parse(v)
+                .ok_or_else(|| SynapticError::Tool(format!("unknown visibility '{}'", v)))?,
+            None => scope.default_visibility(),
+        };
+        let tags = scope.tags(visibility).map_err(SynapticError::Tool)?;
 
-        tracing::info!(evergreen, "memory saved via tool");
+        self.ltm.remember_scoped(content, evergreen, tags).await?;
+
+        tracing::info!(evergreen, ?visibility, "memory saved via tool");
         Ok(json!(format!(
-            "Memory saved{}.",
-            if evergreen { " (evergreen)" } else { "" }
+            "Memory saved{} ({}).",
+            if evergreen { " (evergreen)" } else { "" },
+            match visibility {
+                Visibility::Private => "private",
+                Visibility::Group => "this group",
+                Visibility::Agent => "agent-wide",
+            }
         )))
     }
 }
@@ -193,7 +283,7 @@ impl Tool for MemoryForgetTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| SynapticError::Tool("missing required parameter 'keyword'".into()))?;
 
-        let deleted = self.ltm.forget(keyword).await?;
+        let deleted = self.ltm.forget_in(keyword, &scope_of(&args)).await?;
         tracing::info!(keyword, deleted, "memories forgotten via tool");
         Ok(json!(format!("{} memories deleted.", deleted)))
     }
@@ -241,13 +331,14 @@ impl Tool for MemoryGetTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| SynapticError::Tool("missing required parameter 'action'".into()))?;
 
+        let scope = scope_of(&args);
         match action {
             "count" => {
-                let count = self.ltm.count().await;
+                let count = self.ltm.count_in(&scope).await;
                 Ok(json!(format!("{} memories stored.", count)))
             }
             "list" => {
-                let memories = self.ltm.list().await;
+                let memories = self.ltm.list_in(&scope).await;
                 if memories.is_empty() {
                     Ok(json!("No memories stored."))
                 } else {
@@ -273,3 +364,44 @@ impl Tool for MemoryGetTool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Echoes the scope it was called with.
+    struct ScopeEcho;
+
+    #[async_trait]
+    impl Tool for ScopeEcho {
+        fn name(&self) -> &'static str {
+            "memory_search"
+        }
+
+        fn description(&self) -> &'static str {
+            "echo"
+        }
+
+        fn parameters(&self) -> Option<Value> {
+            None
+        }
+
+        async fn call(&self, args: Value) -> Result<Value, SynapticError> {
+            Ok(serde_json::to_value(scope_of(&args)).unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn scoped_tool_overrides_model_supplied_scope() {
+        let scope = MemoryScope::from_session_key("agent:default:lark:dm:alice");
+        let tool = scope_memory_tool(Arc::new(ScopeEcho), &scope);
+        let forged = json!({ "query": "x", "_scope": { "session_key": null } });
+        let seen: MemoryScope = serde_json::from_value(tool.call(forged).await.unwrap()).unwrap();
+        assert_eq!(seen, scope);
+    }
+
+    #[test]
+    fn unscoped_calls_are_unrestricted() {
+        assert!(scope_of(&json!({ "query": "x" })).is_unrestricted());
+    }
+}
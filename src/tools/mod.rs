@@ -5,17 +5,21 @@ mod patch;
 mod pdf;
 pub mod platform_actions;
 pub mod pruning;
+mod schedule_tool;
 mod session_tool;
 
 pub use self::firecrawl::FirecrawlTool;
 #[allow(unused_imports)]
 pub use self::media_tool::{AnalyzeImageTool, TranscribeAudioTool};
-pub use self::memory_tool::{MemoryForgetTool, MemoryGetTool, MemorySaveTool, MemorySearchTool};
+pub use self::memory_tool::{
+    scope_memory_tool, MemoryForgetTool, MemoryGetTool, MemorySaveTool, MemorySearchTool,
+};
 pub use self::patch::ApplyPatchTool;
-pub use self::pdf::ReadPdfTool;
+pub use self::pdf::{extract_pdf_text, ReadPdfTool};
 #[allow(unused_imports)]
 pub use self::platform_actions::PlatformActionTool;
 pub use self::pruning::{prune_tool_results_with_options, PruningOptions};
+pub use self::schedule_tool::{schedule_tools, ScheduleOrigin};
 pub use self::session_tool::{
     SessionsHistoryTool, SessionsListTool, SessionsSendTool, SessionsSpawnTool,
 };
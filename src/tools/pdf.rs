@@ -10,6 +10,18 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use synaptic::core::{SynapticError, Tool};
 
+/// Extract the text of a PDF, pages separated by form feeds.
+///
+/// Uses pdf_extract directly (same as synaptic-pdf does internally).
+pub async fn extract_pdf_text(path: PathBuf) -> Result<String, SynapticError> {
+    tokio::task::spawn_blocking(move || {
+        pdf_extract::extract_text(&path)
+            .map_err(|e| SynapticError::Tool(format!("failed to extract PDF text: {}", e)))
+    })
+    .await
+    .map_err(|e| SynapticError::Tool(format!("PDF task failed: {}", e)))?
+}
+
 /// Tool that reads and extracts text from PDF files.
 pub struct ReadPdfTool {
     work_dir: PathBuf,
@@ -70,14 +82,7 @@ impl Tool for ReadPdfTool {
             )));
         }
 
-        // Use pdf_extract directly (same as synaptic-pdf does internally)
-        let path_clone = full_path.clone();
-        let text = tokio::task::spawn_blocking(move || {
-            pdf_extract::extract_text(&path_clone)
-                .map_err(|e| SynapticError::Tool(format!("failed to extract PDF text: {}", e)))
-        })
-        .await
-        .map_err(|e| SynapticError::Tool(format!("PDF task failed: {}", e)))??;
+        let text = extract_pdf_text(full_path).await?;
 
         // Handle optional page filter
         if let Some(page_num) = args.get("page").and_then(|v| v.as_u64()) {
@@ -0,0 +1,525 @@
+//! Agent tools for scheduling reminders and recurring tasks from chat.
+//!
+//! `schedule_create` — add a one-shot reminder or a recurring cron job.
+//! `schedule_list` — list the jobs created from the current chat.
+//! `schedule_cancel` — cancel one of those jobs.
+//!
+//! Jobs are written to the persistent [`CronStore`] the gateway scheduler
+//! runs from.  Each job is bound to the chat it was created in: it runs as
+//! the same agent, and its result is delivered back to that chat.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde_json::{json, Value};
+use synaptic::core::{SynapticError, Tool};
+
+use crate::config::ScheduleToolConfig;
+use crate::cron::{parse_timezone, CronJob, CronParser, CronStore, DeliverTarget, MisfirePolicy};
+
+/// The chat a schedule tool call comes from.
+#[derive(Debug, Clone)]
+pub struct ScheduleOrigin {
+    /// Session key of the chat.
+    pub session_key: String,
+    /// Agent handling the chat; scheduled runs use the same agent.
+    pub agent_id: String,
+    /// Sender of the message, for per-user limits.
+    pub user_id: Option<String>,
+    /// Where run results are posted.
+    pub deliver: DeliverTarget,
+}
+
+/// Build the `schedule_*` tools for a chat.
+pub fn schedule_tools(origin: ScheduleOrigin, limits: ScheduleToolConfig) -> Vec<Arc<dyn Tool>> {
+    tools_with_store(origin, limits, CronStore::default_path())
+}
+
+fn tools_with_store(
+    origin: ScheduleOrigin,
+    limits: ScheduleToolConfig,
+    store_path: PathBuf,
+) -> Vec<Arc<dyn Tool>> {
+    let scope = Arc::new(Scope {
+        origin,
+        limits,
+        store_path,
+    });
+    vec![
+        Arc::new(ScheduleCreateTool {
+            scope: scope.clone(),
+        }),
+        Arc::new(ScheduleListTool {
+            scope: scope.clone(),
+        }),
+        Arc::new(ScheduleCancelTool { scope }),
+    ]
+}
+
+/// State shared by the tools of one chat.
+struct Scope {
+    origin: ScheduleOrigin,
+    limits: ScheduleToolConfig,
+    store_path: PathBuf,
+}
+
+impl Scope {
+    fn store(&self) -> CronStore {
+        CronStore::open(&self.store_path)
+    }
+
+    /// Jobs created from this chat.
+    fn chat_jobs(&self, store: &CronStore) -> Vec<CronJob> {
+        store
+            .list()
+            .into_iter()
+            .filter(|j| j.session_key.as_deref() == Some(self.origin.session_key.as_str()))
+            .collect()
+    }
+
+    /// Who the per-user limit is counted against.
+    fn owner(&self) -> &str {
+        self.origin
+            .user_id
+            .as_deref()
+            .unwrap_or(&self.origin.session_key)
+    }
+}
+
+fn describe(job: &CronJob) -> String {
+    let when = job
+        .next_run
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| "not scheduled".to_string());
+    let schedule = if job.once {
+        "once".to_string()
+    } else {
+        format!("cron '{}'", job.expression)
+    };
+    let tz = job.timezone.as_deref().unwrap_or("UTC");
+    format!(
+        "- {} ({}, {}): next run {} — {}",
+        job.id, schedule, tz, when, job.message
+    )
+}
+
+/// Parse `at` as an RFC 3339 timestamp, or a local `YYYY-MM-DD HH:MM[:SS]`
+/// time in `tz`.
+fn parse_at(at: &str, tz: &Tz) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(at) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|fmt| NaiveDateTime::parse_from_str(at, fmt).ok())
+    .and_then(|naive| tz.from_local_datetime(&naive).earliest())
+    .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Whether any two consecutive runs of `expr` from `first` on are less than
+/// `min_secs` apart.  Irregular schedules (`0,1 9 * * *`) only show their
+/// shortest gap somewhere in the day, so this looks at a full day of runs
+/// and at least a few dozen of them.
+fn has_gap_below(expr: &str, tz: &Tz, first: DateTime<Utc>, min_secs: u64) -> bool {
+    const MIN_RUNS: usize = 32;
+    const MAX_RUNS: usize = 2000;
+    let horizon = first + chrono::Duration::days(1);
+    let mut prev = first;
+    for runs in 1..MAX_RUNS {
+        let Some(next) = CronParser::next_after_in(expr, prev, tz) else {
+            return false;
+        };
+        if ((next - prev).num_seconds().max(0) as u64) < min_secs {
+            return true;
+        }
+        if runs >= MIN_RUNS && next > horizon {
+            return false;
+        }
+        prev = next;
+    }
+    false
+}
+
+// ---------------------------------------------------------------------------
+// schedule_create
+// ---------------------------------------------------------------------------
+
+/// Tool that schedules a reminder or recurring task for the current chat.
+struct ScheduleCreateTool {
+    scope: Arc<Scope>,
+}
+
+#[async_trait]
+impl Tool for ScheduleCreateTool {
+    fn name(&self) -> &'static str {
+        "schedule_create"
+    }
+
+    fn description(&self) -> &'static str {
+        "Schedule a one-time reminder or a recurring task for this chat. When it fires, \
+         you are run with `prompt` and your reply is posted back here. Give exactly one of \
+         `at` (a specific time), `in_secs` (a delay) or `cron` (a recurring schedule)."
+    }
+
+    fn parameters(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "prompt": {
+                    "type": "string",
+                    "description": "Instruction to run when the job fires, e.g. 'Remind the user to call the bank'"
+                },
+                "at": {
+                    "type": "string",
+                    "description": "When to fire once: RFC 3339, or local 'YYYY-MM-DD HH:MM' in `timezone`"
+                },
+                "in_secs": {
+                    "type": "integer",
+                    "description": "Fire once after this many seconds"
+                },
+                "cron": {
+                    "type": "string",
+                    "description": "Recurring schedule as a cron expression, e.g. '0 9 * * MON'"
+                },
+                "timezone": {
+                    "type": "string",
+                    "description": "IANA timezone for `at` and `cron`, e.g. 'Asia/Shanghai' (default: UTC)"
+                }
+            },
+            "required": ["prompt"]
+        }))
+    }
+
+    async fn call(&self, args: Value) -> Result<Value, SynapticError> {
+        let scope = &self.scope;
+        let prompt = args
+            .get("prompt")
+            .and_then(|v| v.as_str())
+            .filter(|p| !p.trim().is_empty())
+            .ok_or_else(|| SynapticError::Tool("prompt is required".into()))?;
+        let at = args.get("at").and_then(|v| v.as_str());
+        let in_secs = args.get("in_secs").and_then(|v| v.as_u64());
+        let cron = args.get("cron").and_then(|v| v.as_str());
+        if [at.is_some(), in_secs.is_some(), cron.is_some()]
+            .iter()
+            .filter(|b| **b)
+            .count()
+            != 1
+        {
+            return Err(SynapticError::Tool(
+                "give exactly one of 'at', 'in_secs' or 'cron'".into(),
+            ));
+        }
+        let timezone = args.get("timezone").and_then(|v| v.as_str());
+        let tz = match timezone {
+            Some(name) => parse_timezone(name)
+                .ok_or_else(|| SynapticError::Tool(format!("unknown timezone '{}'", name)))?,
+            None => Tz::UTC,
+        };
+
+        let store = scope.store();
+        let owner = scope.owner();
+        let active = store
+            .list()
+            .iter()
+            .filter(|j| j.owner.as_deref() == Some(owner))
+            .count();
+        if active >= scope.limits.max_jobs_per_user {
+            return Err(SynapticError::Tool(format!(
+                "limit reached: at most {} scheduled jobs per user; cancel one first",
+                scope.limits.max_jobs_per_user
+            )));
+        }
+
+        let now = Utc::now();
+        let id = format!("chat-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let origin = &scope.origin;
+        let mut job = if let Some(expr) = cron {
+            if !CronParser::is_valid(expr) || CronParser::is_reboot(expr) {
+                return Err(SynapticError::Tool(format!(
+                    "invalid cron expression '{}'",
+                    expr
+                )));
+            }
+            let first = CronParser::next_after_in(expr, now, &tz);
+            if let Some(first) = first {
+                let min = scope.limits.min_interval_secs;
+                if has_gap_below(expr, &tz, first, min) {
+                    return Err(SynapticError::Tool(format!(
+                        "'{}' runs too often: recurring jobs must be at least {} seconds apart",
+                        expr, min
+                    )));
+                }
+            }
+            let mut job = CronJob::new(&id, expr, &origin.agent_id, prompt);
+            job.next_run = first;
+            job
+        } else {
+            let fire_at = match (at, in_secs) {
+                (Some(at), _) => parse_at(at, &tz).ok_or_else(|| {
+                    SynapticError::Tool(format!(
+                        "invalid 'at' '{}': expected RFC 3339 or 'YYYY-MM-DD HH:MM'",
+                        at
+                    ))
+                })?,
+                (None, Some(secs)) => i64::try_from(secs)
+                    .ok()
+                    .and_then(chrono::Duration::try_seconds)
+                    .and_then(|delay| now.checked_add_signed(delay))
+                    .ok_or_else(|| SynapticError::Tool("'in_secs' is too large".into()))?,
+                (None, None) => unreachable!("checked above"),
+            };
+            if fire_at <= now {
+                return Err(SynapticError::Tool(format!(
+                    "{} is in the past",
+                    fire_at.to_rfc3339()
+                )));
+            }
+            CronJob::once_at(&id, fire_at, &origin.agent_id, prompt)
+        };
+        if let Some(name) = timezone {
+            job = job.with_timezone(name);
+        }
+        job = job
+            .with_misfire(MisfirePolicy::RunOnce)
+            .with_deliver(origin.deliver.clone());
+        job.session_key = Some(origin.session_key.clone());
+        job.owner = Some(owner.to_string());
+
+        tracing::info!(
+            job_id = %job.id,
+            session_key = %origin.session_key,
+            once = job.once,
+            "schedule_create"
+        );
+        let summary = describe(&job);
+        store.upsert(job);
+        Ok(json!(format!("Scheduled:\n{}", summary)))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// schedule_list
+// ---------------------------------------------------------------------------
+
+/// Tool that lists the jobs scheduled from the current chat.
+struct ScheduleListTool {
+    scope: Arc<Scope>,
+}
+
+#[async_trait]
+impl Tool for ScheduleListTool {
+    fn name(&self) -> &'static str {
+        "schedule_list"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the reminders and recurring tasks scheduled from this chat."
+    }
+
+    fn parameters(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }))
+    }
+
+    async fn call(&self, _args: Value) -> Result<Value, SynapticError> {
+        let jobs = self.scope.chat_jobs(&self.scope.store());
+        if jobs.is_empty() {
+            return Ok(json!("Nothing is scheduled for this chat."));
+        }
+        let lines: Vec<String> = jobs.iter().map(describe).collect();
+        Ok(json!(lines.join("\n")))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// schedule_cancel
+// ---------------------------------------------------------------------------
+
+/// Tool that cancels a job scheduled from the current chat.
+struct ScheduleCancelTool {
+    scope: Arc<Scope>,
+}
+
+#[async_trait]
+impl Tool for ScheduleCancelTool {
+    fn name(&self) -> &'static str {
+        "schedule_cancel"
+    }
+
+    fn description(&self) -> &'static str {
+        "Cancel a reminder or recurring task scheduled from this chat, by its ID."
+    }
+
+    fn parameters(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Job ID, as shown by schedule_list"
+                }
+            },
+            "required": ["id"]
+        }))
+    }
+
+    async fn call(&self, args: Value) -> Result<Value, SynapticError> {
+        let id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SynapticError::Tool("id is required".into()))?;
+        let store = self.scope.store();
+        // Only jobs created from this chat can be cancelled from it.
+        if !self.scope.chat_jobs(&store).iter().any(|j| j.id == id) {
+            return Err(SynapticError::Tool(format!(
+                "no job '{}' scheduled from this chat",
+                id
+            )));
+        }
+        store.remove(id);
+        tracing::info!(job_id = %id, "schedule_cancel");
+        Ok(json!(format!("Cancelled {}.", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(session_key: &str, user: &str) -> ScheduleOrigin {
+        ScheduleOrigin {
+            session_key: session_key.into(),
+            agent_id: "default".into(),
+            user_id: Some(user.into()),
+            deliver: DeliverTarget {
+                channel: "lark".into(),
+                to: "chat:oc_1".into(),
+                account_id: None,
+                thread_id: None,
+            },
+        }
+    }
+
+    fn tools(dir: &tempfile::TempDir, origin: ScheduleOrigin) -> Vec<Arc<dyn Tool>> {
+        let limits = ScheduleToolConfig {
+            max_jobs_per_user: 2,
+            min_interval_secs: 300,
+        };
+        tools_with_store(origin, limits, dir.path().join("jobs.jsonl"))
+    }
+
+    #[tokio::test]
+    async fn create_once_binds_job_to_chat() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = tools(&dir, origin("lark:chat:oc_1", "ou_1"));
+        tools[0]
+            .call(json!({"prompt": "call the bank", "in_secs": 3600}))
+            .await
+            .unwrap();
+
+        let jobs = CronStore::open(dir.path().join("jobs.jsonl")).list();
+        assert_eq!(jobs.len(), 1);
+        let job = &jobs[0];
+        assert!(job.once);
+        assert_eq!(job.session_key.as_deref(), Some("lark:chat:oc_1"));
+        assert_eq!(job.owner.as_deref(), Some("ou_1"));
+        assert_eq!(job.deliver.as_ref().unwrap().to, "chat:oc_1");
+        assert!(job.next_run.unwrap() > Utc::now());
+    }
+
+    #[tokio::test]
+    async fn create_validates_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let create = &tools(&dir, origin("s", "u"))[0];
+        // None or several of at / in_secs / cron.
+        assert!(create.call(json!({"prompt": "x"})).await.is_err());
+        assert!(create
+            .call(json!({"prompt": "x", "in_secs": 5, "cron": "0 9 * * *"}))
+            .await
+            .is_err());
+        // Too frequent.
+        assert!(create
+            .call(json!({"prompt": "x", "cron": "* * * * *"}))
+            .await
+            .is_err());
+        // Too frequent only at one point in the day.
+        assert!(create
+            .call(json!({"prompt": "x", "cron": "0,1 9 * * *"}))
+            .await
+            .is_err());
+        // A delay past the end of time.
+        assert!(create
+            .call(json!({"prompt": "x", "in_secs": u64::MAX}))
+            .await
+            .is_err());
+        // In the past.
+        assert!(create
+            .call(json!({"prompt": "x", "at": "2000-01-01T00:00:00Z"}))
+            .await
+            .is_err());
+        assert!(create
+            .call(json!({"prompt": "x", "cron": "0 9 * * MON", "timezone": "Asia/Shanghai"}))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn per_user_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let create = &tools(&dir, origin("s", "u"))[0];
+        for _ in 0..2 {
+            create
+                .call(json!({"prompt": "x", "in_secs": 60}))
+                .await
+                .unwrap();
+        }
+        let err = create
+            .call(json!({"prompt": "x", "in_secs": 60}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("limit"));
+    }
+
+    #[tokio::test]
+    async fn list_and_cancel_are_scoped_to_the_chat() {
+        let dir = tempfile::tempdir().unwrap();
+        let mine = tools(&dir, origin("chat-a", "u1"));
+        let theirs = tools(&dir, origin("chat-b", "u2"));
+        theirs[0]
+            .call(json!({"prompt": "theirs", "in_secs": 60}))
+            .await
+            .unwrap();
+
+        let listed = mine[1].call(json!({})).await.unwrap();
+        assert_eq!(listed, json!("Nothing is scheduled for this chat."));
+
+        let id = CronStore::open(dir.path().join("jobs.jsonl")).list()[0]
+            .id
+            .clone();
+        assert!(mine[2].call(json!({"id": id})).await.is_err());
+        assert!(theirs[2].call(json!({"id": id})).await.is_ok());
+        assert!(CronStore::open(dir.path().join("jobs.jsonl"))
+            .list()
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_at_accepts_local_times() {
+        let tz: Tz = "Asia/Shanghai".parse().unwrap();
+        let at = parse_at("2030-01-02 10:00", &tz).unwrap();
+        assert_eq!(at.to_rfc3339(), "2030-01-02T02:00:00+00:00");
+        assert!(parse_at("tomorrow", &tz).is_none());
+    }
+}